@@ -0,0 +1,202 @@
+use std::{
+    env,
+    fmt::{self, Debug, Formatter},
+    sync::OnceLock,
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use log::warn;
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
+
+use crate::headers::{HostScope, http_host};
+
+/// The prefix of the environment variables holding the credentials for a single host, which
+/// ends with the host's name in upper case with anything other than letters and digits replaced
+/// by underscores, e.g. `REFMAN_AUTH_DATA_EXAMPLE_ORG` for `data.example.org`.
+const AUTH_ENV_PREFIX: &str = "REFMAN_AUTH_";
+
+/// The credentials provided on the command line, along with the hosts they're sent to in place of
+/// any configured for those hosts in the environment. They're only ever held in memory, so they
+/// can't end up in a registry.
+static CREDENTIALS: OnceLock<(Credentials, HostScope)> = OnceLock::new();
+
+/// Credentials for HTTP servers that only serve files to authenticated users, sent in the
+/// `Authorization` header of every request for a file on those servers, whether it's being
+/// checked or downloaded.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Credentials {
+    /// A token sent as `Authorization: Bearer <token>`
+    Bearer(String),
+    /// A username and password sent with HTTP Basic authentication
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+}
+
+impl Credentials {
+    /// Reads credentials for HTTP Basic authentication in the `user:password` form `curl`
+    /// accepts. Everything after the first colon is the password, and leaving out the colon
+    /// sends no password at all.
+    #[must_use]
+    pub fn basic(user_and_password: &str) -> Self {
+        match user_and_password.split_once(':') {
+            Some((username, password)) => Credentials::Basic {
+                username: username.to_string(),
+                password: Some(password.to_string()),
+            },
+            None => Credentials::Basic {
+                username: user_and_password.to_string(),
+                password: None,
+            },
+        }
+    }
+
+    /// Reads credentials in the same form as an `Authorization` header, i.e. `Bearer <token>` or
+    /// `Basic <user:password>`, returning `None` for any other scheme.
+    fn from_header_form(value: &str) -> Option<Self> {
+        let (scheme, credentials) = value.trim().split_once(' ')?;
+        let credentials = credentials.trim();
+        if scheme.eq_ignore_ascii_case("bearer") {
+            Some(Credentials::Bearer(credentials.to_string()))
+        } else if scheme.eq_ignore_ascii_case("basic") {
+            Some(Credentials::basic(credentials))
+        } else {
+            None
+        }
+    }
+
+    /// The value of the `Authorization` header that sends these credentials, which is marked as
+    /// sensitive so that it's never logged.
+    fn header_value(&self) -> Option<HeaderValue> {
+        let value = match self {
+            Credentials::Bearer(token) => format!("Bearer {token}"),
+            Credentials::Basic { username, password } => {
+                let pair = format!("{username}:{}", password.as_deref().unwrap_or_default());
+                format!("Basic {}", STANDARD.encode(pair))
+            },
+        };
+        let mut value = HeaderValue::from_str(&value)
+            .inspect_err(|_| warn!("The provided credentials can't be sent in an HTTP header"))
+            .ok()?;
+        value.set_sensitive(true);
+        Some(value)
+    }
+}
+
+impl Debug for Credentials {
+    /// Leaves the secrets themselves out, so that credentials can't leak into logs.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Credentials::Bearer(_) => write!(f, "Bearer(<redacted>)"),
+            Credentials::Basic { username, .. } => {
+                write!(
+                    f,
+                    "Basic {{ username: {username:?}, password: <redacted> }}"
+                )
+            },
+        }
+    }
+}
+
+/// Sets the credentials to send to the hosts in `scope` for the rest of the run, in place of any
+/// configured for them in the environment. Only the first credentials set are used.
+pub fn set_credentials(credentials: Credentials, scope: HostScope) {
+    if CREDENTIALS.set((credentials, scope)).is_err() {
+        warn!("Credentials were already provided for this run, so the new ones will be ignored.");
+    }
+}
+
+/// Returns the credentials to send with requests for `url`: the ones set for the run, if its host
+/// is in their scope, or otherwise the ones in the `REFMAN_AUTH_<HOST>` environment variable for
+/// its host.
+pub(crate) fn credentials_for(url: &str) -> Option<Credentials> {
+    if let Some((credentials, scope)) = CREDENTIALS.get() {
+        if scope.contains(url) {
+            return Some(credentials.clone());
+        }
+    }
+
+    let host = http_host(url)?;
+    let value = env::var(host_env_var(&host)).ok()?;
+    let credentials = Credentials::from_header_form(&value);
+    if credentials.is_none() {
+        warn!(
+            "The credentials for {host} in the environment aren't in the form `Bearer <token>` or `Basic <user:password>`, so none will be sent."
+        );
+    }
+    credentials
+}
+
+/// Returns the headers that authenticate requests for `url`, which are empty when it has no
+/// credentials.
+pub(crate) fn auth_headers(url: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(value) = credentials_for(url).and_then(|credentials| credentials.header_value()) {
+        headers.insert(AUTHORIZATION, value);
+    }
+    headers
+}
+
+/// Returns the name of the environment variable that holds the credentials for `host`.
+fn host_env_var(host: &str) -> String {
+    let host: String = host
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{AUTH_ENV_PREFIX}{host}")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_host_env_var() {
+        assert_eq!(
+            host_env_var("data.example-lab.org"),
+            "REFMAN_AUTH_DATA_EXAMPLE_LAB_ORG"
+        );
+        assert_eq!(host_env_var("10.0.0.7"), "REFMAN_AUTH_10_0_0_7");
+    }
+
+    #[test]
+    fn test_credentials_header() {
+        assert_eq!(
+            Credentials::from_header_form("Bearer abc123"),
+            Some(Credentials::Bearer("abc123".to_string()))
+        );
+        let basic = Credentials::from_header_form("basic alice:s3cret:too").unwrap();
+        assert_eq!(
+            basic,
+            Credentials::Basic {
+                username: "alice".to_string(),
+                password: Some("s3cret:too".to_string()),
+            }
+        );
+        assert_eq!(Credentials::from_header_form("Digest abc"), None);
+        assert_eq!(Credentials::from_header_form("abc123"), None);
+
+        let value = Credentials::basic("alice:secret").header_value().unwrap();
+        assert_eq!(value, "Basic YWxpY2U6c2VjcmV0");
+        assert!(value.is_sensitive());
+        assert_eq!(
+            Credentials::Bearer("abc123".to_string())
+                .header_value()
+                .unwrap(),
+            "Bearer abc123"
+        );
+
+        // secrets stay out of debug output
+        let debugged = format!("{basic:?} {:?}", Credentials::Bearer("abc123".to_string()));
+        assert!(!debugged.contains("s3cret") && !debugged.contains("abc123"));
+    }
+}
@@ -1,6 +1,15 @@
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+
+use crate::{
+    DEFAULT_CHECK_TIMEOUT, DEFAULT_DOWNLOAD_JOBS, DEFAULT_LINK_CHECK_JOBS, DEFAULT_RETRIES,
+    HashAlgorithm, ImportConflict,
+};
+
+use crate::data::{ByteRange, FileFormat};
+use crate::headers::HttpHeader;
 
 pub const INFO: &str = r"
 
@@ -28,6 +37,17 @@ pub struct Cli {
     #[command(flatten)]
     pub verbose: clap_verbosity_flag::Verbosity,
 
+    /// Name of a profile in refman's config file to take the registry settings from, e.g. to switch
+    /// between projects' registries without passing `--registry` every time. `--registry` and `--global`
+    /// take precedence over the profile, which takes precedence over the defaults at the top of the config file.
+    #[arg(long, global = true, required = false)]
+    pub profile: Option<String>,
+
+    /// Print logs without colors. Colors are also left out when the `NO_COLOR` environment variable is set
+    /// or when stderr isn't a terminal, e.g. when it's redirected to a log file.
+    #[arg(long, global = true, required = false)]
+    pub no_color: bool,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -38,8 +58,24 @@ pub struct Cli {
 /// - `Init`: Creates a new reference registry without registering any datasets yet
 /// - `Register`: Add a new dataset entry to the registry with an associated label
 /// - `Remove`: Delete an existing dataset from the registry by its label
+/// - `Rename`: Change a dataset's label without losing its files' download state
+/// - `Import`: Fold the datasets from another registry into this one
+/// - `Relink`: Point a registered file at a new URL without losing its download state
+/// - `Rehash`: Record SHA-256 checksums for files downloaded before they were recorded
 /// - `List`: Show all datasets currently in the registry
+/// - `Status`: Summarize how many of each dataset's files are downloaded and validated
+/// - `Info`: Show the registry's metadata, like its title and maintainers
+/// - `Export`: Print the registry in another format, like JSON
+/// - `Diff`: Compare the registry to another, showing added, removed, and changed datasets
+/// - `SetMeta`: Change the registry's title or description
+/// - `VerifyRegistry`: Check that the registry is internally consistent, e.g. as a CI gate
+/// - `Validate`: Parse every downloaded file again to check that it's still intact
+/// - `VerifyUrls`: Check that every registered URL still resolves, without downloading anything
 /// - `Download`: Fetch registered dataset files to the local filesystem
+/// - `Update`: Download files again that have changed upstream since they were downloaded
+/// - `QuickDownload`: Fetch, validate, and hash files straight from URLs, optionally registering them
+/// - `Edit`: Open the registry in the user's `$EDITOR`, checking the edits before saving them
+/// - `Completions`: Print a completion script for the requested shell
 ///
 /// Each command takes various arguments to customize its behavior, like whether to use
 /// a global vs project-local registry, custom file paths, etc. Most commands require
@@ -49,6 +85,7 @@ pub struct Cli {
 /// registered dataset, including download URLs for supported file formats like FASTA,
 /// Genbank, GFF, etc.
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum Commands {
     #[clap(
         about = "Initialize a registry for the current project without registering any datasets.",
@@ -63,9 +100,23 @@ pub enum Commands {
         #[arg(short, long, required = false)]
         description: Option<String>,
 
-        /// Optional file path (absolute or relative) to the refman registry file.
+        /// Who maintains the registry, e.g. a name or email address. Can be provided more than once.
+        #[arg(short, long, required = false)]
+        maintainer: Vec<String>,
+
+        /// Optional homepage where users of a shared registry can learn more or get in touch
+        #[arg(long, required = false)]
+        homepage: Option<String>,
+
+        /// Optional base64-encoded minisign public key used to verify the signatures of any dataset that
+        /// doesn't provide its own key
+        #[arg(long, required = false)]
+        public_key: Option<String>,
+
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
         #[arg(short, long, required = false)]
-        registry: Option<String>,
+        registry: Option<PathBuf>,
 
         /// Whether to use a global registry as opposed to a project-specific registry
         #[arg(short, long, required = false)]
@@ -79,8 +130,14 @@ pub enum Commands {
     Register {
         /// Shorthand label for a dataset to register with refman. Once registered, this shorthand can be used
         /// to download and manage reference datasets in the future.
-        #[arg(index = 1, required = true)]
-        label: String,
+        #[arg(index = 1, required_unless_present_any = ["label_from_url", "from_file"])]
+        label: Option<String>,
+
+        /// Derive the label from the name of the first registered file, without its extensions, e.g.
+        /// `Homo_sapiens.GRCh38.dna` for `Homo_sapiens.GRCh38.dna.fa.gz`. If that label is taken, a counter is
+        /// appended to keep it unique.
+        #[arg(long, required = false, conflicts_with_all = ["label", "update_only"])]
+        label_from_url: bool,
 
         /// URL to simple reference sequence in FASTA format
         #[arg(long, required = false)]
@@ -106,17 +163,242 @@ pub enum Commands {
         #[arg(long, required = false)]
         bed: Option<String>,
 
+        /// URL to reference variant data in Variant Call Format (VCF)
+        #[arg(long, required = false)]
+        vcf: Option<String>,
+
         /// URL to reference a TAR archive of arbitrary data
         #[arg(long, required = false)]
         tar: Option<String>,
 
-        /// Optional file path (absolute or relative) to the refman registry file.
+        /// NCBI assembly accession, e.g. `GCF_000001405.40` or `GCA_000001405.29`, whose genomic FASTA and any
+        /// GFF or GTF annotations will be looked up and registered. Explicitly provided URLs take precedence.
+        #[arg(long, required = false)]
+        ncbi_assembly: Option<String>,
+
+        /// URL of a JSON document describing the dataset's files, e.g. from a provider's API. The URLs selected
+        /// with `--jsonpath` are registered under the formats inferred from their extensions. Explicitly
+        /// provided URLs take precedence.
+        #[arg(long, required = false, requires = "jsonpath")]
+        from_json: Option<String>,
+
+        /// `JSONPath` expression selecting the file URLs in the document from `--from-json`, e.g.
+        /// '$.files[*].url'
+        #[arg(long, required = false, requires = "from_json")]
+        jsonpath: Option<String>,
+
+        /// Register the download links currently copied to the clipboard under the formats inferred from their
+        /// extensions. Explicitly provided URLs take precedence.
+        #[arg(long, required = false, conflicts_with = "from_json")]
+        from_clipboard: bool,
+
+        /// Register many datasets at once from a tab-separated file, or a comma-separated one if its name ends in
+        /// `.csv`. Each row lists a label followed by FASTA, Genbank, GFA, GFF, GTF, BED, and optionally TAR and VCF
+        /// URLs, with blank columns for missing files; a header row starting with `label` is skipped. URLs are
+        /// checked concurrently, rows that can't be registered are reported without stopping the others, and the
+        /// registry is written once at the end.
+        #[arg(
+            long,
+            required = false,
+            value_name = "PATH",
+            conflicts_with_all = [
+                "label", "label_from_url", "fasta", "genbank", "gfa", "gtf", "gff", "bed", "vcf", "tar",
+                "ncbi_assembly", "from_json", "from_clipboard", "fasta_sig", "genbank_sig", "gfa_sig", "gtf_sig",
+                "gff_sig", "bed_sig", "vcf_sig", "tar_sig", "fasta_records", "genbank_records", "gtf_records",
+                "gff_records", "vcf_records", "fasta_md5", "genbank_md5", "gfa_md5", "gtf_md5", "gff_md5", "bed_md5",
+                "vcf_md5", "tar_md5", "hash_algo", "fasta_range", "public_key", "dest"
+            ]
+        )]
+        from_file: Option<PathBuf>,
+
+        /// Abort the whole batch at the first row of `--from-file` that is malformed or can't be registered, leaving
+        /// the registry as it was. By default, such rows are reported and skipped, and the others are registered.
+        #[arg(long, required = false, requires = "from_file")]
+        fail_fast: bool,
+
+        /// URL to a minisign signature of the FASTA file, to be verified after it is downloaded
+        #[arg(long, required = false)]
+        fasta_sig: Option<String>,
+
+        /// URL to a minisign signature of the Genbank file, to be verified after it is downloaded
+        #[arg(long, required = false)]
+        genbank_sig: Option<String>,
+
+        /// URL to a minisign signature of the GFA file, to be verified after it is downloaded
+        #[arg(long, required = false)]
+        gfa_sig: Option<String>,
+
+        /// URL to a minisign signature of the GTF file, to be verified after it is downloaded
+        #[arg(long, required = false)]
+        gtf_sig: Option<String>,
+
+        /// URL to a minisign signature of the GFF file, to be verified after it is downloaded
+        #[arg(long, required = false)]
+        gff_sig: Option<String>,
+
+        /// URL to a minisign signature of the BED file, to be verified after it is downloaded
+        #[arg(long, required = false)]
+        bed_sig: Option<String>,
+
+        /// URL to a minisign signature of the VCF file, to be verified after it is downloaded
+        #[arg(long, required = false)]
+        vcf_sig: Option<String>,
+
+        /// URL to a minisign signature of the TAR file, to be verified after it is downloaded
+        #[arg(long, required = false)]
+        tar_sig: Option<String>,
+
+        /// Number of sequences the FASTA file is expected to contain, which validation will check after it is
+        /// downloaded
+        #[arg(long, required = false)]
+        fasta_records: Option<usize>,
+
+        /// Number of records the Genbank file is expected to contain, which validation will check after it is
+        /// downloaded
+        #[arg(long, required = false)]
+        genbank_records: Option<usize>,
+
+        /// Number of features the GTF file is expected to contain, which validation will check after it is
+        /// downloaded
+        #[arg(long, required = false)]
+        gtf_records: Option<usize>,
+
+        /// Number of features the GFF file is expected to contain, which validation will check after it is
+        /// downloaded
+        #[arg(long, required = false)]
+        gff_records: Option<usize>,
+
+        /// Number of variant records the VCF file is expected to contain, which validation will check after it is
+        /// downloaded
+        #[arg(long, required = false)]
+        vcf_records: Option<usize>,
+
+        /// Checksum published for the FASTA file, which the download will be verified against
+        #[arg(long, required = false)]
+        fasta_md5: Option<String>,
+
+        /// Checksum published for the Genbank file, which the download will be verified against
+        #[arg(long, required = false)]
+        genbank_md5: Option<String>,
+
+        /// Checksum published for the GFA file, which the download will be verified against
+        #[arg(long, required = false)]
+        gfa_md5: Option<String>,
+
+        /// Checksum published for the GTF file, which the download will be verified against
+        #[arg(long, required = false)]
+        gtf_md5: Option<String>,
+
+        /// Checksum published for the GFF file, which the download will be verified against
+        #[arg(long, required = false)]
+        gff_md5: Option<String>,
+
+        /// Checksum published for the BED file, which the download will be verified against
+        #[arg(long, required = false)]
+        bed_md5: Option<String>,
+
+        /// Checksum published for the VCF file, which the download will be verified against
+        #[arg(long, required = false)]
+        vcf_md5: Option<String>,
+
+        /// Checksum published for the TAR file, which the download will be verified against
+        #[arg(long, required = false)]
+        tar_md5: Option<String>,
+
+        /// Algorithm the checksums provided with `--fasta-md5` and the like were computed with, e.g. `sha256` for
+        /// providers that publish SHA-256 manifests. The checksums are stored with their algorithm, and downloads are
+        /// verified with it.
+        #[arg(long, required = false, value_enum, default_value_t = HashAlgorithm::Md5)]
+        hash_algo: HashAlgorithm,
+
+        /// Range of bytes to download from the FASTA file instead of the whole file, e.g. one chromosome's
+        /// record, written as inclusive zero-based offsets like `1000-1999` or `1000-`. The offsets of each record in
+        /// an uncompressed FASTA can be worked out from its `.fai` index.
+        #[arg(long, required = false)]
+        fasta_range: Option<ByteRange>,
+
+        /// Base64-encoded minisign public key to verify this dataset's signatures with, i.e. the second line of a
+        /// `minisign.pub` file. Takes precedence over any public key set for the whole registry.
+        #[arg(long, required = false)]
+        public_key: Option<String>,
+
+        /// Directory this dataset's files should always be downloaded into, e.g. a scratch volume for especially
+        /// large files, overriding the destination provided to `refman download`.
+        #[arg(long, required = false)]
+        dest: Option<PathBuf>,
+
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
         #[arg(short, long, required = false)]
-        registry: Option<String>,
+        registry: Option<PathBuf>,
 
         /// Whether to use a global registry as opposed to a project-specific registry
         #[arg(short, long, required = false)]
         global: bool,
+
+        /// Refuse to register a dataset that uses the same URL for more than one format, instead of only
+        /// warning about it.
+        #[arg(long, required = false)]
+        strict: bool,
+
+        /// Only update a dataset that is already registered, failing instead of creating a new dataset if the
+        /// label isn't found, e.g. because of a typo.
+        #[arg(long, required = false, conflicts_with = "create_only")]
+        update_only: bool,
+
+        /// Only create a new dataset, failing instead of updating an existing dataset if the label is already
+        /// registered.
+        #[arg(long, required = false)]
+        create_only: bool,
+
+        /// Only check that the URLs of sequence files, i.e. FASTA and Genbank files, are accessible, recording
+        /// the URLs of annotations and other files without checking them. This speeds up registration when
+        /// annotation servers are slow or flaky; the unchecked URLs are still checked when they're downloaded.
+        #[arg(long, required = false)]
+        check_only_sequences: bool,
+
+        /// Don't check that any of the URLs are accessible, only that they're well-formed http or https URLs,
+        /// e.g. to register datasets without a network connection or behind a firewall that blocks the checks.
+        /// The URLs are still checked when their files are downloaded.
+        #[arg(long, visible_alias = "no-check", required = false, conflicts_with_all = ["check_only_sequences", "check_timeout"])]
+        offline: bool,
+
+        /// Give up on checking a URL if its host hasn't answered after SECONDS seconds, failing registration with
+        /// an error that names the URL instead of waiting indefinitely on a host that's down. The URLs of a
+        /// dataset are checked at the same time, so this also bounds how long all of the checks take.
+        #[arg(long, required = false, value_name = "SECONDS", default_value_t = DEFAULT_CHECK_TIMEOUT.as_secs(), value_parser = clap::value_parser!(u64).range(1..))]
+        check_timeout: u64,
+
+        /// Token to send as `Authorization: Bearer TOKEN` when checking the URLs, for servers that only share
+        /// files with authenticated users. It's only sent to the hosts of the URLs provided on the command line or
+        /// in the batch file, not to servers like NCBI's that files are looked up on. Credentials are only used
+        /// for this run and never saved in the registry; they can instead be set for one host with e.g.
+        /// `REFMAN_AUTH_DATA_EXAMPLE_ORG="Bearer TOKEN"`.
+        #[arg(
+            long,
+            required = false,
+            value_name = "TOKEN",
+            conflicts_with = "auth_basic"
+        )]
+        auth_bearer: Option<String>,
+
+        /// Username and password, as `USER:PASS`, to send with HTTP Basic authentication when checking the URLs,
+        /// only to the hosts of the URLs provided like `--auth-bearer`. Credentials are only used for this run and
+        /// never saved in the registry; they can instead be set for one host with e.g.
+        /// `REFMAN_AUTH_DATA_EXAMPLE_ORG="Basic USER:PASS"`.
+        #[arg(long, required = false, value_name = "USER:PASS")]
+        auth_basic: Option<String>,
+
+        /// Header to send with HTTP requests to the hosts of the URLs provided, like `--auth-bearer`, as
+        /// `NAME: VALUE`, e.g. an API key a provider requires. Can be provided more than once. A `User-Agent`, e.g.
+        /// one that identifies you as NCBI asks, is sent to every host instead of refman's `refman/<version>`.
+        #[arg(
+            short = 'H',
+            long = "header",
+            required = false,
+            value_name = "NAME: VALUE"
+        )]
+        headers: Vec<HttpHeader>,
     },
 
     #[clap(
@@ -129,9 +411,133 @@ pub enum Commands {
         #[arg(index = 1, required = true)]
         label: String,
 
-        /// Optional file path (absolute or relative) to the refman registry file.
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
+        #[arg(short, long, required = false)]
+        global: bool,
+    },
+
+    #[clap(
+        about = "Change the label of a registered dataset, keeping its files' download and validation state.",
+        visible_aliases = &["mv"],
+    )]
+    Rename {
+        /// Label the dataset is currently registered under
+        #[arg(index = 1, required = true)]
+        old_label: String,
+
+        /// New label for the dataset, which must not already be registered
+        #[arg(index = 2, required = true)]
+        new_label: String,
+
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
+        #[arg(short, long, required = false)]
+        global: bool,
+    },
+
+    #[clap(
+        about = "Fold the datasets from another refman registry, e.g. one shared by a collaborator, into this one.",
+        visible_aliases = &["merge"],
+    )]
+    Import {
+        /// Path (absolute or relative) to the registry file to import datasets from, or to a directory holding a
+        /// `refman.toml`.
+        #[arg(index = 1, required = true)]
+        source_path: PathBuf,
+
+        /// What to do with an imported dataset whose label is already registered: keep the registered dataset
+        /// (`skip`), replace it with the imported one (`overwrite`), or add the imported files and metadata to it
+        /// (`merge-fields`), preferring the imported file wherever both register a format under different URLs.
+        #[arg(long, required = false, value_enum, default_value_t = ImportConflict::Skip)]
+        on_conflict: ImportConflict,
+
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
+        #[arg(short, long, required = false)]
+        global: bool,
+    },
+
+    #[clap(
+        about = "Point a registered file at a new URL, keeping its download and validation state.",
+        visible_aliases = &["rl"],
+    )]
+    Relink {
+        /// Label string for a registered dataset
+        #[arg(index = 1, required = true)]
+        label: String,
+
+        /// New URL for the dataset's FASTA file
+        #[arg(long, required = false)]
+        fasta: Option<String>,
+
+        /// New URL for the dataset's Genbank file
+        #[arg(long, required = false)]
+        genbank: Option<String>,
+
+        /// New URL for the dataset's GFA file
+        #[arg(long, required = false)]
+        gfa: Option<String>,
+
+        /// New URL for the dataset's GTF file
+        #[arg(long, required = false)]
+        gtf: Option<String>,
+
+        /// New URL for the dataset's GFF file
+        #[arg(long, required = false)]
+        gff: Option<String>,
+
+        /// New URL for the dataset's BED file
+        #[arg(long, required = false)]
+        bed: Option<String>,
+
+        /// New URL for the dataset's VCF file
+        #[arg(long, required = false)]
+        vcf: Option<String>,
+
+        /// New URL for the dataset's TAR archive
+        #[arg(long, required = false)]
+        tar: Option<String>,
+
+        /// Stream and hash each new URL to make sure it serves the same content as the file previously
+        /// downloaded, refusing to relink it otherwise.
+        #[arg(long, required = false)]
+        verify: bool,
+
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
+        #[arg(short, long, required = false)]
+        global: bool,
+    },
+
+    #[clap(
+        about = "Record SHA-256 checksums for downloaded files that only have MD5 checksums, without downloading them again."
+    )]
+    Rehash {
+        /// Label string for a registered dataset to rehash, defaulting to all datasets
+        #[arg(index = 1, required = false)]
+        label: Option<String>,
+
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
         #[arg(short, long, required = false)]
-        registry: Option<String>,
+        registry: Option<PathBuf>,
 
         /// Whether to use a global registry as opposed to a project-specific registry
         #[arg(short, long, required = false)]
@@ -147,9 +553,207 @@ pub enum Commands {
         #[arg(index = 1, required = false)]
         label: Option<String>,
 
-        /// Optional file path (absolute or relative) to the refman registry file.
+        /// Print only the number of registered datasets, or the number matching the label if one is provided,
+        /// e.g. for use in scripts
+        #[arg(short, long, required = false, conflicts_with = "format")]
+        count: bool,
+
+        /// How to print the datasets. `toml` prints a registry containing only the listed datasets, which can be
+        /// saved as a new `refman.toml`.
+        #[arg(short, long, required = false, value_enum, default_value_t = ListFormat::Table)]
+        format: ListFormat,
+
+        /// Print the datasets as a JSON array instead of a table, with each file's download status, whether it
+        /// was validated, and its recorded checksums, e.g. to pipe into `jq`. Nothing else is printed to stdout.
+        #[arg(long, required = false, conflicts_with_all = ["count", "format"])]
+        json: bool,
+
+        /// Print only the label of each registered dataset, one per line and without any formatting, e.g. for
+        /// shell completions or scripts that loop over every dataset
+        #[arg(long, required = false, conflicts_with_all = ["label", "count", "format", "json"])]
+        labels_only: bool,
+
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
+        #[arg(short, long, required = false)]
+        global: bool,
+    },
+
+    #[clap(
+        about = "Summarize how many of each dataset's files have been downloaded and validated, and how long ago they were last validated.",
+        visible_aliases = &["st"]
+    )]
+    Status {
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
+        #[arg(short, long, required = false)]
+        global: bool,
+    },
+
+    #[clap(
+        about = "Show the refman registry's metadata, including who maintains it and how to reach them.",
+        visible_aliases = &["about"]
+    )]
+    Info {
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
         #[arg(short, long, required = false)]
-        registry: Option<String>,
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
+        #[arg(short, long, required = false)]
+        global: bool,
+    },
+
+    #[clap(
+        about = "Print the refman registry to stdout in another format, e.g. JSON for pipelines that don't read TOML."
+    )]
+    Export {
+        /// Format to export the registry in. In either format, each registered file has a `status` field saying
+        /// whether it is `not_yet_downloaded`, `downloaded`, or `failed`.
+        #[arg(short, long, required = false, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
+        #[arg(short, long, required = false)]
+        global: bool,
+    },
+
+    #[clap(
+        about = "Compare the registry to another, printing the datasets added or removed and every URL that changed."
+    )]
+    Diff {
+        /// Path (absolute or relative) to the registry file to compare against, or to a directory holding a
+        /// `refman.toml`. Its URLs are shown as the new ones.
+        #[arg(index = 1, required = true)]
+        other_path: PathBuf,
+
+        /// Print the differences as JSON, with the added and removed labels and the old and new URL of every
+        /// changed file, e.g. to pipe into `jq`.
+        #[arg(long, required = false)]
+        json: bool,
+
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
+        #[arg(short, long, required = false)]
+        global: bool,
+    },
+
+    #[clap(
+        about = "Change the refman registry's title or description without touching its datasets."
+    )]
+    SetMeta {
+        /// New project title. Pass an empty string to clear it.
+        #[arg(short, long, required_unless_present = "description")]
+        title: Option<String>,
+
+        /// New project description. Pass an empty string to clear it.
+        #[arg(short, long, required_unless_present = "title")]
+        description: Option<String>,
+
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
+        #[arg(short, long, required = false)]
+        global: bool,
+    },
+
+    #[clap(
+        about = "Check that the refman registry is internally consistent, reporting every problem found and exiting with an error if there are any.",
+        visible_aliases = &["verify", "check"]
+    )]
+    VerifyRegistry {
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
+        #[arg(short, long, required = false)]
+        global: bool,
+    },
+
+    #[clap(
+        about = "Parse every downloaded file again in its registered format, exiting with an error if any fail validation."
+    )]
+    Validate {
+        /// Validate with at most N threads at once, e.g. to limit resource usage on a shared node. Every core is
+        /// used by default.
+        #[arg(long, required = false, value_name = "N")]
+        validate_threads: Option<usize>,
+
+        /// Parse every file, even those that still match the checksums recorded when they were last validated,
+        /// which are otherwise only hashed, for a thorough check.
+        #[arg(long, required = false)]
+        force_revalidate: bool,
+
+        /// Validate only the first N records of each FASTA, Genbank, GFF, GTF, or VCF file instead of parsing the
+        /// whole file. Much faster for very large references, but a weaker check, so the registry records which
+        /// files were only sampled. Only leading records are checked; there's no way to sample the last ones.
+        #[arg(long, required = false, value_name = "N")]
+        sample: Option<usize>,
+
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
+        #[arg(short, long, required = false)]
+        global: bool,
+    },
+
+    #[clap(
+        about = "Check that every registered URL still leads to its file, without downloading anything, exiting with an error if any are broken.",
+        visible_aliases = &["check-urls", "links"]
+    )]
+    VerifyUrls {
+        /// Labels of the registered datasets whose URLs should be checked, given as a comma-separated list or one
+        /// after the other. Every dataset's URLs are checked when none are given.
+        #[arg(
+            index = 1,
+            required = false,
+            value_delimiter = ',',
+            value_name = "LABELS"
+        )]
+        labels: Vec<String>,
+
+        /// Check the URLs of every dataset in the registry. This is the default when no labels are given.
+        #[arg(short, long, required = false, conflicts_with = "labels")]
+        all: bool,
+
+        /// Maximum number of URLs to check at once
+        #[arg(short, long, required = false, value_name = "N", default_value_t = DEFAULT_LINK_CHECK_JOBS)]
+        jobs: usize,
+
+        /// Give up on a URL if its host hasn't answered after SECONDS seconds, reporting it as timed out
+        #[arg(long, required = false, value_name = "SECONDS", default_value_t = DEFAULT_CHECK_TIMEOUT.as_secs(), value_parser = clap::value_parser!(u64).range(1..))]
+        timeout: u64,
+
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
 
         /// Whether to use a global registry as opposed to a project-specific registry
         #[arg(short, long, required = false)]
@@ -161,24 +765,447 @@ pub enum Commands {
         visible_aliases = &["d", "dl", "down", "get", "g", "f", "fetch", "pull", "p"]
     )]
     Download {
-        /// Label string for a registered dataset
-        #[arg(index = 1, required = false)]
-        label: Option<String>,
+        /// Labels of the registered datasets to download, given as a comma-separated list or one after the
+        /// other, e.g. `hg38,mm39`. Every dataset is downloaded when none are given.
+        #[arg(
+            index = 1,
+            required = false,
+            value_delimiter = ',',
+            value_name = "LABELS"
+        )]
+        labels: Vec<String>,
 
-        /// Destination directory for downloaded files, defaulting to the current working directory.
+        /// Destination directory for downloaded files, defaulting to the `destination` in refman's config file, or
+        /// else the current working directory.
         #[arg(short, long, required = false)]
         dest: Option<PathBuf>,
 
-        /// Optional file path (absolute or relative) to the refman registry file.
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
         #[arg(short, long, required = false)]
-        registry: Option<String>,
+        registry: Option<PathBuf>,
 
         /// Whether to use a global registry as opposed to a project-specific registry
         #[arg(short, long, required = false)]
         global: bool,
 
         /// Whether to download all datasets in the registry
+        #[arg(short, long, required = false, conflicts_with = "labels")]
+        all: bool,
+
+        /// Only download files of these formats, given as a comma-separated list, e.g. `--only fasta,gff`. Files of
+        /// other formats are left as they are.
+        #[arg(
+            long,
+            required = false,
+            value_enum,
+            value_delimiter = ',',
+            value_name = "FORMATS"
+        )]
+        only: Vec<FileFormat>,
+
+        /// Don't download files of these formats, given as a comma-separated list, e.g. `--except genbank`. Files of
+        /// these formats are left as they are.
+        #[arg(
+            long,
+            required = false,
+            value_enum,
+            value_delimiter = ',',
+            value_name = "FORMATS"
+        )]
+        except: Vec<FileFormat>,
+
+        /// Abort the whole download, cancelling any transfers in flight, at the first file that fails to
+        /// download. By default, downloads are best-effort and failures are logged and skipped.
+        #[arg(long, required = false)]
+        fail_fast: bool,
+
+        /// Keep files that download successfully but fail format validation, recording them in the registry
+        /// as unvalidated so they can be inspected. By default, a validation failure is an error.
+        #[arg(long, required = false)]
+        continue_on_validation_error: bool,
+
+        /// Stream each file and record its checksum in the registry without writing the file to disk, e.g. to
+        /// pin the expected checksum of a large reference that you don't want to keep.
+        #[arg(long, required = false)]
+        checksum_only: bool,
+
+        /// Instead of downloading anything, record copies of registered files that were put in place some other
+        /// way, e.g. transferred from another machine. PATH is either a directory holding the copies, which is
+        /// searched recursively, or a manifest listing one path per line, like the output of `md5sum`. Copies
+        /// are matched to registered files by checksum, so the registry must already record their checksums.
+        #[arg(
+            long,
+            required = false,
+            value_name = "PATH",
+            conflicts_with = "checksum_only"
+        )]
+        resume_from_manifest: Option<PathBuf>,
+
+        /// Print the files that would be downloaded or skipped, where each would be saved, and how large each
+        /// is according to its server, followed by the total size, without downloading or writing anything.
+        #[arg(
+            long,
+            required = false,
+            conflicts_with_all = ["checksum_only", "resume_from_manifest"]
+        )]
+        dry_run: bool,
+
+        /// Validate only the first N records of each downloaded FASTA, Genbank, GFF, GTF, or VCF file instead of
+        /// parsing the whole file. Much faster for very large references, but a weaker check, so the registry
+        /// records that the file was only sampled.
+        #[arg(long, required = false, value_name = "N")]
+        sample: Option<usize>,
+
+        /// Try again to download files that have failed to download several times in a row, which are otherwise
+        /// skipped.
+        #[arg(long, required = false)]
+        retry_failed: bool,
+
+        /// URL to POST a JSON event to when each dataset's download completes or fails, e.g. to let a workflow
+        /// system know that a reference is available. Failing to notify the webhook doesn't fail the download.
+        #[arg(long, required = false)]
+        webhook: Option<String>,
+
+        /// Bearer token to authenticate with the webhook, if it requires one
+        #[arg(long, required = false, requires = "webhook")]
+        webhook_token: Option<String>,
+
+        /// Overwrite files in the destination that have the same names as the files being downloaded, even if
+        /// refman didn't download them. By default, refman asks first, or warns when run non-interactively.
+        #[arg(long, required = false, conflicts_with = "no_clobber")]
+        overwrite: bool,
+
+        /// Never overwrite files in the destination that refman didn't download; skip downloading the files
+        /// that would replace them instead.
+        #[arg(long, required = false)]
+        no_clobber: bool,
+
+        /// Download each file into subdirectories of the destination that mirror its URL's path, e.g.
+        /// `pub/release-110/fasta/genome.fa`, to keep version or organization information from the provider's
+        /// layout on disk.
+        #[arg(long, required = false, overrides_with = "flat")]
+        mirror_structure: bool,
+
+        /// Download every file directly into the destination, regardless of its URL's path. This is the
+        /// default, and overrides an earlier `--mirror-structure`.
+        #[arg(long, required = false, overrides_with = "mirror_structure")]
+        flat: bool,
+
+        /// Download each dataset's files into a subdirectory of the destination named after its label, e.g.
+        /// `GRCh38/genome.fasta`, so that datasets whose files share a name don't overwrite each other. Datasets
+        /// registered with their own destination are still downloaded there.
+        #[arg(long, required = false)]
+        per_label_dirs: bool,
+
+        /// Unpack each tarball (`.tar`, `.tar.gz`, or `.tgz`) that downloads and validates into a directory next to
+        /// it named after the archive, e.g. `bundle/` for `bundle.tar.gz`. The tarball itself is kept.
+        #[arg(long, required = false, conflicts_with = "checksum_only")]
+        extract: bool,
+
+        /// Drop the first N components of each path in a tarball when extracting it, like `tar --strip-components`,
+        /// e.g. `1` to unpack the contents of the directory many providers wrap their archives in rather than the
+        /// directory itself. The paths recorded for the extracted files are the stripped ones.
+        #[arg(
+            long,
+            required = false,
+            requires = "extract",
+            value_name = "N",
+            default_value_t = 0
+        )]
+        strip_components: usize,
+
+        /// Follow at most N redirects from each URL when checking and downloading files. A URL that redirects
+        /// more times than this fails to download.
+        #[arg(long, required = false, value_name = "N")]
+        max_redirects: Option<usize>,
+
+        /// Refuse to follow redirects to a different host than the one a URL names, so that a reference can't
+        /// silently be downloaded from somewhere else.
+        #[arg(long, required = false)]
+        no_cross_host_redirect: bool,
+
+        /// How to summarize the run once the downloads finish. `json` prints each file's label, format, URL,
+        /// local path, size, hash, and whether it was downloaded, skipped, or failed to stdout, e.g. for CI logs.
+        #[arg(long, required = false, value_enum, default_value_t = SummaryFormat::Text)]
+        summary_format: SummaryFormat,
+
+        /// Checksum to compare on later runs to decide whether each downloaded file is still intact. Both MD5 and
+        /// SHA-256 checksums are recorded either way; `sha256` is stronger and matches providers that publish
+        /// SHA-256 manifests.
+        #[arg(long, required = false, value_enum, default_value_t = HashAlgorithm::Md5)]
+        hash_algo: HashAlgorithm,
+
+        /// URL of a checksum index published alongside the files, like an `MD5SUMS` or `SHA256SUMS` file in
+        /// the output format of `md5sum` or `sha256sum`. Files whose published checksums differ from the ones
+        /// recorded are downloaded again, and unchanged files are skipped without being requested.
+        #[arg(
+            long,
+            required = false,
+            value_name = "URL",
+            conflicts_with = "checksum_only"
+        )]
+        remote_index: Option<String>,
+
+        /// Algorithm the checksums in the `--remote-index` were computed with
+        #[arg(long, required = false, value_enum, default_value_t = HashAlgorithm::Md5, requires = "remote_index")]
+        index_algo: HashAlgorithm,
+
+        /// Maximum number of files to download at once across all datasets, which keeps large registries from
+        /// opening so many connections that providers rate-limit or ban them.
+        #[arg(short, long, required = false, value_name = "N", default_value_t = DEFAULT_DOWNLOAD_JOBS)]
+        jobs: usize,
+
+        /// Maximum number of idle connections to keep open per host, which keeps `refman` polite when many
+        /// files come from the same server. Unlimited by default.
+        #[arg(long, required = false)]
+        max_idle_per_host: Option<usize>,
+
+        /// Number of seconds an idle connection is kept alive for reuse before it is closed (default: 90).
+        #[arg(long, required = false)]
+        idle_timeout: Option<u64>,
+
+        /// Interval in seconds between TCP keep-alive probes on open connections. Disabled by default.
+        #[arg(long, required = false)]
+        keepalive: Option<u64>,
+
+        /// Number of times to try a failed request for a file again, waiting twice as long before each retry as
+        /// before the last, up to a minute. `--retries 0` tries each file once, e.g. to fail fast in CI.
+        #[arg(long, required = false, value_name = "N", default_value_t = DEFAULT_RETRIES)]
+        retries: u32,
+
+        /// Number of seconds any one HTTP request may take, including streaming the whole file, before it is
+        /// abandoned and retried, so set it generously for large files. Requests have no time limit by default.
+        #[arg(long, required = false, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+        timeout: Option<u64>,
+
+        /// Log each file's progress every SECONDS seconds while it downloads, with the bytes downloaded so far and
+        /// the rate, so that the logs of batch jobs without a terminal show long downloads moving. Heartbeats are
+        /// logged at the info level, so they're shown with `-vv`.
+        #[arg(long, required = false, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+        heartbeat: Option<u64>,
+
+        /// Token to send as `Authorization: Bearer TOKEN` with HTTP requests for files, for servers that only share
+        /// files with authenticated users. It's only sent to the hosts of the datasets being downloaded, and of
+        /// the `--remote-index`. Credentials are only used for this run and never saved in the registry; to send
+        /// them to a single host instead, set e.g. `REFMAN_AUTH_DATA_EXAMPLE_ORG="Bearer TOKEN"`.
+        #[arg(
+            long,
+            required = false,
+            value_name = "TOKEN",
+            conflicts_with = "auth_basic"
+        )]
+        auth_bearer: Option<String>,
+
+        /// Username and password, as `USER:PASS`, to send with HTTP Basic authentication with HTTP requests for
+        /// files, only to the hosts of the datasets being downloaded like `--auth-bearer`. Credentials are only
+        /// used for this run and never saved in the registry; to send them to a single host instead, set e.g.
+        /// `REFMAN_AUTH_DATA_EXAMPLE_ORG="Basic USER:PASS"`.
+        #[arg(long, required = false, value_name = "USER:PASS")]
+        auth_basic: Option<String>,
+
+        /// Header to send with HTTP requests to the hosts of the datasets being downloaded, like `--auth-bearer`,
+        /// as `NAME: VALUE`, e.g. an API key a provider requires. Can be provided more than once. A `User-Agent`,
+        /// e.g. one that identifies you as NCBI asks, is sent to every host instead of refman's `refman/<version>`.
+        #[arg(
+            short = 'H',
+            long = "header",
+            required = false,
+            value_name = "NAME: VALUE"
+        )]
+        headers: Vec<HttpHeader>,
+    },
+
+    #[clap(
+        about = "Download files again that have changed upstream since they were downloaded, leaving the rest untouched.",
+        visible_aliases = &["refresh"]
+    )]
+    Update {
+        /// Label string for a registered dataset
+        #[arg(index = 1, required_unless_present = "all")]
+        label: Option<String>,
+
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
         #[arg(short, long, required = false)]
+        global: bool,
+
+        /// Whether to update all datasets in the registry
+        #[arg(short, long, required = false, conflicts_with = "label")]
         all: bool,
+
+        /// Abort the whole update at the first file that can't be checked, downloaded, or validated. By default,
+        /// failures are logged and those files are left as they were.
+        #[arg(long, required = false)]
+        fail_fast: bool,
+    },
+
+    #[clap(
+        about = "Download, validate, and hash one or more files straight from their URLs, without registering them first.",
+        visible_aliases = &["qd", "quick"]
+    )]
+    QuickDownload {
+        /// URLs of the files to download. Each file's format is inferred from its extension, and at most one file
+        /// per format can be provided.
+        #[arg(required = true, num_args = 1..)]
+        urls: Vec<String>,
+
+        /// Label for the dataset made up of the provided files, used if it is saved to a registry.
+        #[arg(short, long, default_value = "quick-download")]
+        label: String,
+
+        /// Destination directory for downloaded files, defaulting to the `destination` in refman's config file, or
+        /// else the current working directory.
+        #[arg(short, long, required = false)]
+        dest: Option<PathBuf>,
+
+        /// Register the downloaded dataset in the refman registry. By default, nothing is written besides the
+        /// downloaded files themselves.
+        #[arg(short, long, required = false)]
+        save: bool,
+
+        /// Optional path (absolute or relative) to the refman registry file, or to a directory holding a
+        /// `refman.toml`, used with `--save`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to save to a global registry as opposed to a project-specific registry, used with `--save`.
+        #[arg(short, long, required = false)]
+        global: bool,
+
+        /// Abort the whole download, cancelling any transfers in flight, at the first file that fails to
+        /// download. By default, downloads are best-effort and failures are logged and skipped.
+        #[arg(long, required = false)]
+        fail_fast: bool,
     },
+
+    #[clap(
+        about = "Open the refman registry in your $EDITOR, checking your edits before they are saved.",
+        visible_aliases = &["e"]
+    )]
+    Edit {
+        /// Optional path (absolute or relative) to the refman registry file, e.g. `refs.toml`, or to a directory
+        /// holding a `refman.toml`.
+        #[arg(short, long, required = false)]
+        registry: Option<PathBuf>,
+
+        /// Whether to use a global registry as opposed to a project-specific registry
+        #[arg(short, long, required = false)]
+        global: bool,
+    },
+
+    #[clap(
+        about = "Print a shell completion script for refman to stdout, e.g. `refman completions zsh > ~/.zfunc/_refman`.",
+        long_about = "Print a shell completion script for refman to stdout, e.g. `refman completions zsh > ~/.zfunc/_refman`.\n\nThe script completes refman's subcommands and options. Registered dataset labels change as datasets are registered and removed, so they aren't part of it; completion functions can list them instead with `refman list --labels-only`, which prints one label per line."
+    )]
+    Completions {
+        /// The shell to generate completions for
+        #[arg(index = 1, required = true, value_enum)]
+        shell: Shell,
+    },
+}
+
+impl Commands {
+    /// Returns the arguments that choose which registry the command operates on, for commands
+    /// that operate on one.
+    pub fn registry_args_mut(&mut self) -> Option<(&mut Option<PathBuf>, &mut bool)> {
+        match self {
+            Commands::Init {
+                registry, global, ..
+            }
+            | Commands::Register {
+                registry, global, ..
+            }
+            | Commands::Remove {
+                registry, global, ..
+            }
+            | Commands::Rename {
+                registry, global, ..
+            }
+            | Commands::Import {
+                registry, global, ..
+            }
+            | Commands::Relink {
+                registry, global, ..
+            }
+            | Commands::Rehash {
+                registry, global, ..
+            }
+            | Commands::List {
+                registry, global, ..
+            }
+            | Commands::Status { registry, global }
+            | Commands::Info { registry, global }
+            | Commands::Export {
+                registry, global, ..
+            }
+            | Commands::Diff {
+                registry, global, ..
+            }
+            | Commands::SetMeta {
+                registry, global, ..
+            }
+            | Commands::VerifyRegistry { registry, global }
+            | Commands::Validate {
+                registry, global, ..
+            }
+            | Commands::VerifyUrls {
+                registry, global, ..
+            }
+            | Commands::Download {
+                registry, global, ..
+            }
+            | Commands::Update {
+                registry, global, ..
+            }
+            | Commands::QuickDownload {
+                registry, global, ..
+            }
+            | Commands::Edit { registry, global } => Some((registry, global)),
+            Commands::Completions { .. } => None,
+        }
+    }
+
+    /// Returns the argument that chooses where the command downloads files to, for commands that
+    /// download files.
+    pub fn dest_mut(&mut self) -> Option<&mut Option<PathBuf>> {
+        match self {
+            Commands::Download { dest, .. } | Commands::QuickDownload { dest, .. } => Some(dest),
+            _ => None,
+        }
+    }
+}
+
+/// The ways `refman list` can print registered datasets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ListFormat {
+    /// A human-readable table
+    #[default]
+    Table,
+    /// A registry file holding only the listed datasets
+    Toml,
+}
+
+/// The formats `refman export` can print the registry in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ExportFormat {
+    /// JSON, with each file's download status tagged
+    #[default]
+    Json,
+    /// YAML, with the same structure as JSON
+    Yaml,
+}
+
+/// The ways `refman download` can summarize a finished run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum SummaryFormat {
+    /// A human-readable message on stderr
+    #[default]
+    Text,
+    /// A JSON record of every file in the run, printed to stdout
+    Json,
 }
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use arboard::Clipboard;
+use log::debug;
+
+use crate::{
+    EntryError,
+    data::{FileFormat, classify_urls},
+};
+
+/// Reads URLs from the system clipboard and infers the format of each from its extension.
+///
+/// This is a shortcut for interactive use, e.g. right after copying a download link from a
+/// provider's website. The clipboard's text is split on whitespace, so several links can be
+/// copied at once, and every piece that looks like a URL is classified with
+/// [`FileFormat::from_uri`]. As with any other source of URLs, ones whose format can't be
+/// inferred or whose format was already found are skipped with a warning.
+///
+/// # Errors
+///
+/// Returns `EntryError::ClipboardUnavailable` if the clipboard can't be read, e.g. in a headless
+/// environment without a display server, and `EntryError::NoUrlsInClipboard` if it holds no URLs
+/// in a format refman recognizes.
+pub fn clipboard_urls() -> Result<BTreeMap<FileFormat, String>, EntryError> {
+    let text = Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|e| EntryError::ClipboardUnavailable(e.to_string()))?;
+
+    let files = urls_in_text(&text);
+    if files.is_empty() {
+        return Err(EntryError::NoUrlsInClipboard);
+    }
+    debug!("Found {} file(s) to register in the clipboard", files.len());
+
+    Ok(files)
+}
+
+/// Picks out the URLs among the whitespace-separated pieces of some text and classifies them by
+/// format.
+fn urls_in_text(text: &str) -> BTreeMap<FileFormat, String> {
+    classify_urls(
+        text.split_whitespace()
+            .filter(|piece| piece.contains("://")),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_urls_in_text() {
+        let text = "Genome: https://example.com/genome.fa.gz\n\
+                    https://example.com/genes.gtf https://example.com/README";
+        let files = urls_in_text(text);
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(
+            files.get(&FileFormat::Fasta).map(String::as_str),
+            Some("https://example.com/genome.fa.gz")
+        );
+        assert_eq!(
+            files.get(&FileFormat::Gtf).map(String::as_str),
+            Some("https://example.com/genes.gtf")
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use std::{collections::BTreeMap, env, fs, path::PathBuf};
+
+use log::debug;
+use serde::Deserialize;
+
+use crate::RegistryError;
+
+/// refman's own settings, as opposed to the contents of any one registry.
+///
+/// These are read from `config.toml` in refman's config directory, e.g.
+/// `~/.config/refman/config.toml` on Linux, from `config.toml` in `$REFMAN_HOME` if there's one
+/// there, or from the file named by the `REFMAN_CONFIG` environment variable if it's set. The
+/// config holds the defaults refman falls back to when the command line doesn't say otherwise,
+/// as well as named profiles:
+///
+/// ```toml
+/// default_global = true
+/// destination = "~/refs"
+///
+/// [profiles.projectA]
+/// registry = "~/projects/projectA"
+///
+/// [profiles.shared]
+/// global = true
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Config {
+    /// Whether commands use the global registry when neither `--registry` nor `--global` is
+    /// provided. Passing `--registry .` still uses the registry in the current directory.
+    #[serde(default)]
+    pub default_global: bool,
+
+    /// The directory holding the registry commands use when neither `--registry` nor `--global`
+    /// is provided, or the registry file itself. A leading `~/` is expanded to the user's home
+    /// directory. This takes precedence over `default_global`.
+    pub registry: Option<String>,
+
+    /// The directory files are downloaded into when `--dest` isn't provided. A leading `~/` is
+    /// expanded to the user's home directory.
+    pub destination: Option<String>,
+
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// A named set of registry settings that can be selected with `--profile` instead of passing
+/// the same flags to every command.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Profile {
+    /// The directory holding the profile's registry, like `--registry`. A leading `~/` is
+    /// expanded to the user's home directory.
+    pub registry: Option<String>,
+
+    /// Whether the profile uses the global registry, like `--global`.
+    #[serde(default)]
+    pub global: bool,
+}
+
+impl Config {
+    /// Returns where refman's config file is expected to be: the file named by `REFMAN_CONFIG`,
+    /// then `config.toml` in `REFMAN_HOME` if it exists, then `config.toml` in the platform's
+    /// config directory. Returns `None` if none of these are available.
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        env::var_os("REFMAN_CONFIG")
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("REFMAN_HOME")
+                    .map(|home| PathBuf::from(home).join("config.toml"))
+                    .filter(|path| path.is_file())
+            })
+            .or_else(|| dirs::config_dir().map(|dir| dir.join("refman").join("config.toml")))
+    }
+
+    /// Reads refman's config file, falling back to an empty config if there isn't one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RegistryError::InvalidConfig` if the config file exists but can't be read or
+    /// parsed.
+    pub fn load() -> Result<Self, RegistryError> {
+        let Some(path) = Self::path().filter(|path| path.exists()) else {
+            debug!("No refman config file was found, so the default settings will be used.");
+            return Ok(Self::default());
+        };
+
+        fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| toml::from_str(&contents).map_err(|e| e.to_string()))
+            .map_err(|e| RegistryError::InvalidConfig(path.display().to_string(), e))
+    }
+
+    /// Looks up the profile with the provided name.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RegistryError::UnknownProfile` if the config doesn't define the profile.
+    pub fn profile(&self, name: &str) -> Result<&Profile, RegistryError> {
+        self.profiles.get(name).ok_or_else(|| {
+            let path = Self::path().map_or_else(
+                || "refman's config file".to_string(),
+                |path| path.display().to_string(),
+            );
+            RegistryError::UnknownProfile(name.to_string(), path)
+        })
+    }
+
+    /// Fills in a command's registry arguments from the config's defaults, unless a registry was
+    /// already chosen with `--registry`, `--global`, or a profile, which take precedence over
+    /// the defaults.
+    pub fn apply(&self, registry: &mut Option<PathBuf>, global: &mut bool) {
+        if registry.is_some() || *global {
+            return;
+        }
+
+        *registry = self.registry.as_deref().map(expand_home);
+        *global = registry.is_none() && self.default_global;
+    }
+
+    /// Fills in a command's download destination from the config's default, unless one was
+    /// already chosen with `--dest`.
+    pub fn apply_destination(&self, dest: &mut Option<PathBuf>) {
+        if dest.is_none() {
+            *dest = self.destination.as_deref().map(expand_home);
+        }
+    }
+}
+
+impl Profile {
+    /// Fills in a command's registry arguments from the profile, unless a registry was already
+    /// chosen with `--registry` or `--global`, which take precedence over the profile.
+    pub fn apply(&self, registry: &mut Option<PathBuf>, global: &mut bool) {
+        if registry.is_some() || *global {
+            return;
+        }
+
+        *registry = self.registry.as_deref().map(expand_home);
+        *global = self.global;
+    }
+}
+
+/// Expands a leading `~/` in a path to the user's home directory, as a shell would. The home
+/// directory is joined as a path, so it needn't be valid UTF-8.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn test_profile_apply() {
+        let config: Config = toml::from_str(
+            r#"
+            [profiles.projectA]
+            registry = "/data/projectA"
+
+            [profiles.shared]
+            global = true
+            "#,
+        )
+        .unwrap();
+        let profile = config.profile("projectA").unwrap();
+
+        let (mut registry, mut global) = (None, false);
+        profile.apply(&mut registry, &mut global);
+        assert_eq!(registry.as_deref(), Some(Path::new("/data/projectA")));
+        assert!(!global);
+
+        // flags on the command line win over the profile
+        let (mut registry, mut global) = (Some(PathBuf::from("elsewhere")), false);
+        profile.apply(&mut registry, &mut global);
+        assert_eq!(registry.as_deref(), Some(Path::new("elsewhere")));
+        let (mut registry, mut global) = (None, true);
+        profile.apply(&mut registry, &mut global);
+        assert!(registry.is_none());
+
+        let (mut registry, mut global) = (None, false);
+        config
+            .profile("shared")
+            .unwrap()
+            .apply(&mut registry, &mut global);
+        assert!(registry.is_none());
+        assert!(global);
+
+        assert!(matches!(
+            config.profile("projectB"),
+            Err(RegistryError::UnknownProfile(name, _)) if name == "projectB"
+        ));
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config: Config = toml::from_str(
+            r#"
+            default_global = true
+            destination = "/data/refs"
+
+            [profiles.projectA]
+            registry = "/data/projectA"
+            "#,
+        )
+        .unwrap();
+
+        let (mut registry, mut global) = (None, false);
+        config.apply(&mut registry, &mut global);
+        assert!(registry.is_none());
+        assert!(global);
+
+        // a profile, like the command line, wins over the defaults
+        let (mut registry, mut global) = (None, false);
+        config
+            .profile("projectA")
+            .unwrap()
+            .apply(&mut registry, &mut global);
+        config.apply(&mut registry, &mut global);
+        assert_eq!(registry.as_deref(), Some(Path::new("/data/projectA")));
+        assert!(!global);
+
+        let mut dest = None;
+        config.apply_destination(&mut dest);
+        assert_eq!(dest.as_deref(), Some(Path::new("/data/refs")));
+        let mut dest = Some(PathBuf::from("elsewhere"));
+        config.apply_destination(&mut dest);
+        assert_eq!(dest.as_deref(), Some(Path::new("elsewhere")));
+
+        // a config without any defaults leaves the command line's settings alone
+        let (mut registry, mut global, mut dest) = (None, false, None);
+        let empty = Config::default();
+        empty.apply(&mut registry, &mut global);
+        empty.apply_destination(&mut dest);
+        assert!(registry.is_none() && !global && dest.is_none());
+    }
+}
@@ -1,15 +1,27 @@
 use std::{
+    collections::{BTreeMap, HashSet},
     fmt::{self, Display},
-    path::{Path, PathBuf},
+    path::{self, Component, Path, PathBuf},
+    str::FromStr,
+    time::Duration,
 };
 
-use log::debug;
+use futures::future::try_join_all;
+use jiff::Timestamp;
+use log::{debug, info, warn};
+use minisign_verify::PublicKey;
 use serde::{Deserialize, Serialize};
+use tokio::time;
+use url::Url;
 
 use crate::{
     EntryError, ValidationError,
-    downloads::check_url,
-    validate::{UnvalidatedFile, ValidatedFile, hash_valid_download},
+    downloads::{
+        CacheValidators, DEFAULT_CHECK_TIMEOUT, check_url, download_path, uri_to_filename,
+    },
+    validate::{
+        HashAlgorithm, UnvalidatedFile, ValidatedFile, hash_download_digests, verify_signature,
+    },
 };
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -17,6 +29,52 @@ use crate::{
 pub enum DownloadStatus {
     NotYetDownloaded(String),
     Downloaded(ValidatedFile),
+    /// A file that could not be downloaded, recorded so that persistent failures are visible
+    /// across runs rather than being retried from scratch with no memory of them
+    Failed {
+        url: String,
+        last_error: String,
+        attempts: u32,
+        last_attempt: Timestamp,
+    },
+}
+
+/// The form a [`DownloadStatus`] takes in exports of the registry, where a `status` field names
+/// the state of each file so that other tools can tell them apart. Registry files keep the
+/// untagged form.
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum TaggedStatus<'a> {
+    NotYetDownloaded {
+        url: &'a str,
+    },
+    Downloaded(&'a ValidatedFile),
+    Failed {
+        url: &'a str,
+        last_error: &'a str,
+        attempts: u32,
+        last_attempt: Timestamp,
+    },
+}
+
+impl<'a> From<&'a DownloadStatus> for TaggedStatus<'a> {
+    fn from(status: &'a DownloadStatus) -> Self {
+        match status {
+            DownloadStatus::NotYetDownloaded(url) => TaggedStatus::NotYetDownloaded { url },
+            DownloadStatus::Downloaded(validated_file) => TaggedStatus::Downloaded(validated_file),
+            DownloadStatus::Failed {
+                url,
+                last_error,
+                attempts,
+                last_attempt,
+            } => TaggedStatus::Failed {
+                url,
+                last_error,
+                attempts: *attempts,
+                last_attempt: *last_attempt,
+            },
+        }
+    }
 }
 
 impl Default for DownloadStatus {
@@ -34,6 +92,17 @@ impl Display for DownloadStatus {
             DownloadStatus::Downloaded(validated_file) => {
                 write!(f, "Downloaded: {validated_file}")
             },
+            DownloadStatus::Failed {
+                url,
+                last_error,
+                attempts,
+                last_attempt,
+            } => {
+                write!(
+                    f,
+                    "Failed {attempts}x: {url} (last attempted {last_attempt}: {last_error})"
+                )
+            },
         }
     }
 }
@@ -52,7 +121,7 @@ impl DownloadStatus {
     #[must_use]
     pub fn url(&self) -> &str {
         match self {
-            DownloadStatus::NotYetDownloaded(url) => url,
+            DownloadStatus::NotYetDownloaded(url) | DownloadStatus::Failed { url, .. } => url,
             DownloadStatus::Downloaded(validated_file) => &validated_file.uri,
         }
     }
@@ -60,38 +129,342 @@ impl DownloadStatus {
     #[must_use]
     pub fn url_owned(&self) -> String {
         match self {
-            DownloadStatus::NotYetDownloaded(url) => url.to_owned(),
+            DownloadStatus::NotYetDownloaded(url) | DownloadStatus::Failed { url, .. } => {
+                url.to_owned()
+            },
             DownloadStatus::Downloaded(validated_file) => validated_file.uri.clone(),
         }
     }
 
+    /// Whether the file has been downloaded and kept locally. Files that were only checksummed,
+    /// e.g. with `refman download --checksum-only`, have no local path and are not considered
+    /// downloaded.
     #[must_use]
     pub fn is_downloaded(&self) -> bool {
         match self {
-            DownloadStatus::NotYetDownloaded(_) => false,
-            DownloadStatus::Downloaded(_) => true,
+            DownloadStatus::NotYetDownloaded(_) | DownloadStatus::Failed { .. } => false,
+            DownloadStatus::Downloaded(validated_file) => {
+                !validated_file.local_path.as_os_str().is_empty()
+            },
         }
     }
 
     #[must_use]
     pub fn is_validated(&self) -> bool {
         match self {
-            DownloadStatus::NotYetDownloaded(_) => false,
+            DownloadStatus::NotYetDownloaded(_) | DownloadStatus::Failed { .. } => false,
             DownloadStatus::Downloaded(validated_file) => validated_file.validated,
         }
     }
+
+    /// How many times in a row the file has failed to download, which is zero unless its most
+    /// recent download failed.
+    #[must_use]
+    pub fn failed_attempts(&self) -> u32 {
+        match self {
+            DownloadStatus::Failed { attempts, .. } => *attempts,
+            _ => 0,
+        }
+    }
+
+    /// Whether this file and `other` are registered with the same URL, whatever the state of
+    /// their downloads.
+    #[must_use]
+    pub fn same_source(&self, other: &DownloadStatus) -> bool {
+        self.url() == other.url()
+    }
+
+    /// Whether this file and `other` have both been downloaded with matching checksums, i.e. hold
+    /// the same content even if they came from different URLs. SHA-256 checksums are compared
+    /// when both files have one and MD5 checksums otherwise, so files without a checksum of the
+    /// same kind are never considered the same.
+    #[must_use]
+    pub fn same_content(&self, other: &DownloadStatus) -> bool {
+        let (DownloadStatus::Downloaded(file), DownloadStatus::Downloaded(other_file)) =
+            (self, other)
+        else {
+            return false;
+        };
+        [HashAlgorithm::Sha256, HashAlgorithm::Md5]
+            .into_iter()
+            .find_map(|algorithm| file.checksum(algorithm).zip(other_file.checksum(algorithm)))
+            .is_some_and(|(checksum, other_checksum)| checksum.eq_ignore_ascii_case(other_checksum))
+    }
+}
+
+/// The file formats that `refman` knows how to register, download, and validate.
+///
+/// Each format corresponds to one of the file fields on [`RefDataset`]. Besides naming those
+/// fields, this type knows how to recognize a format from the file extensions conventionally
+/// used for it, which lets `refman` work out where a bare URL or path belongs.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum FileFormat {
+    Fasta,
+    Genbank,
+    Gfa,
+    Gff,
+    Gtf,
+    Bed,
+    Vcf,
+    Tar,
+}
+
+impl Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FileFormat::Fasta => "FASTA",
+            FileFormat::Genbank => "Genbank",
+            FileFormat::Gfa => "GFA",
+            FileFormat::Gff => "GFF",
+            FileFormat::Gtf => "GTF",
+            FileFormat::Bed => "BED",
+            FileFormat::Vcf => "VCF",
+            FileFormat::Tar => "TAR",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FileFormat {
+    /// Infers a file's format from the extension at the end of a URL or local path.
+    ///
+    /// Query strings and fragments are ignored, extensions are matched case-insensitively, and
+    /// a trailing `.gz` or `.bgz` compression extension is looked past, so that e.g.
+    /// `Homo_sapiens.GRCh38.110.gff3.gz?download=1` is recognized as GFF. Tarballs are
+    /// recognized from `.tar`, `.tar.gz`, and `.tgz`.
+    ///
+    /// Returns `None` if the extension is missing or not one `refman` recognizes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use refman::data::FileFormat;
+    ///
+    /// assert_eq!(FileFormat::from_uri("https://example.com/genes.gff3.gz"), Some(FileFormat::Gff));
+    /// assert_eq!(FileFormat::from_uri("genes.gtf"), Some(FileFormat::Gtf));
+    /// assert_eq!(FileFormat::from_uri("https://example.com/download"), None);
+    /// ```
+    #[must_use]
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        // drop any query string or fragment, and then keep only the final path segment
+        let path = uri.split(['?', '#']).next().unwrap_or_default();
+        let filename = path
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        // look past any compression extension to the format extension underneath
+        let uncompressed = filename
+            .strip_suffix(".gz")
+            .or_else(|| filename.strip_suffix(".bgz"))
+            .unwrap_or(&filename);
+        let extension = Path::new(uncompressed).extension()?.to_str()?;
+
+        match extension {
+            "fasta" | "fa" | "fna" | "faa" | "fas" => Some(FileFormat::Fasta),
+            "gb" | "gbk" | "gbff" | "genbank" => Some(FileFormat::Genbank),
+            "gfa" => Some(FileFormat::Gfa),
+            "gff" | "gff3" => Some(FileFormat::Gff),
+            "gtf" => Some(FileFormat::Gtf),
+            "bed" => Some(FileFormat::Bed),
+            "vcf" => Some(FileFormat::Vcf),
+            "tar" | "tgz" => Some(FileFormat::Tar),
+            _ => None,
+        }
+    }
+}
+
+/// Derives a dataset label from the name of the file at the end of a URL or local path, for
+/// registering files without coming up with a label.
+///
+/// The label is the filename's stem, i.e. without any compression extension and the format
+/// extension underneath, so `https://example.com/pub/Homo_sapiens.GRCh38.dna.fa.gz` becomes
+/// `Homo_sapiens.GRCh38.dna`. Characters other than letters, digits, `.`, `-`, and `_` are
+/// replaced with underscores so that the label is easy to type and use as a registry key.
+///
+/// # Errors
+///
+/// Returns `EntryError::NoLabelInUrl` if the URL doesn't end with a filename, or the filename
+/// doesn't leave anything to use as a label.
+pub fn label_from_url(uri: &str) -> Result<String, EntryError> {
+    let filename = match Url::parse(uri) {
+        Ok(url) => uri_to_filename(&url).ok().map(str::to_string),
+        Err(_) => Path::new(uri)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+    }
+    .ok_or_else(|| EntryError::NoLabelInUrl(uri.to_string()))?;
+
+    let path = Path::new(&filename);
+    let compressed = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz") || ext.eq_ignore_ascii_case("bgz"));
+    let uncompressed = if compressed {
+        path.file_stem().map(Path::new)
+    } else {
+        Some(path)
+    };
+    let stem = uncompressed
+        .and_then(Path::file_stem)
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+
+    let label: String = stem
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let label = label.trim_matches(['.', '-', '_']);
+    if label.is_empty() {
+        return Err(EntryError::NoLabelInUrl(uri.to_string()));
+    }
+
+    Ok(label.to_string())
+}
+
+/// A range of bytes to download from a file instead of the whole file, e.g. a single chromosome
+/// out of a large uncompressed FASTA whose offsets are known from its `.fai` index.
+///
+/// Ranges are written like HTTP byte ranges, with inclusive, zero-based offsets: `1000-1999` is
+/// the thousand bytes starting at offset 1000, and `1000-` is everything from offset 1000 on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: Option<u64>,
+}
+
+impl Display for ByteRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.end {
+            Some(end) => write!(f, "{}-{end}", self.start),
+            None => write!(f, "{}-", self.start),
+        }
+    }
+}
+
+impl FromStr for ByteRange {
+    type Err = EntryError;
+
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let invalid = || EntryError::InvalidByteRange(range.to_string());
+        let (start, end) = range.trim().split_once('-').ok_or_else(invalid)?;
+        let start = start.parse().map_err(|_| invalid())?;
+        let end = match end {
+            "" => None,
+            end => Some(end.parse().map_err(|_| invalid())?),
+        };
+        if end.is_some_and(|end| end < start) {
+            return Err(invalid());
+        }
+
+        Ok(ByteRange { start, end })
+    }
+}
+
+impl TryFrom<String> for ByteRange {
+    type Error = EntryError;
+
+    fn try_from(range: String) -> Result<Self, Self::Error> {
+        range.parse()
+    }
+}
+
+impl From<ByteRange> for String {
+    fn from(range: ByteRange) -> Self {
+        range.to_string()
+    }
+}
+
+/// Infers the format of each of the provided URLs from its extension, keeping the first URL found
+/// for each format. URLs whose format can't be inferred with [`FileFormat::from_uri`], or whose
+/// format an earlier URL already provided, are skipped with a warning.
+pub(crate) fn classify_urls<'a>(
+    urls: impl IntoIterator<Item = &'a str>,
+) -> BTreeMap<FileFormat, String> {
+    let mut files = BTreeMap::new();
+    for url in urls {
+        let Some(format) = FileFormat::from_uri(url) else {
+            warn!("Skipping {url}, as its file format could not be inferred from its extension.");
+            continue;
+        };
+        if let Some(previous) = files.get(&format) {
+            warn!("Skipping {url}, as the {format} file {previous} was already found.");
+            continue;
+        }
+        files.insert(format, url.to_string());
+    }
+
+    files
+}
+
+/// Checks that `url` is accessible like [`check_url`], giving up once the check has taken longer
+/// than `timeout`, if provided, so that a host that never answers can't stall registration.
+async fn check_url_within(url: &str, timeout: Option<Duration>) -> Result<(), EntryError> {
+    let Some(timeout) = timeout else {
+        check_url(url).await?;
+        return Ok(());
+    };
+    match time::timeout(timeout, check_url(url)).await {
+        Ok(checked) => checked.map(|_| ()).map_err(EntryError::from),
+        Err(_) => Err(EntryError::CheckTimedOut(
+            url.to_string(),
+            timeout.as_secs_f64(),
+        )),
+    }
+}
+
+/// Whether `url` parses as a URL that uses the http://, https://, ftp://, or s3:// protocols, without
+/// checking that anything exists there.
+pub(crate) fn is_well_formed_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https" | "ftp" | "s3"))
+}
+
+/// Turns a dataset's label into the name of the directory its files are downloaded into with
+/// per-label directories, replacing path separators and any other characters that would let the
+/// label name something other than a single directory, like `..`, with underscores.
+fn label_dir_name(label: &str) -> String {
+    let name: String = label
+        .chars()
+        .map(|c| if path::is_separator(c) { '_' } else { c })
+        .collect();
+    let mut components = Path::new(&name).components();
+    if let (Some(Component::Normal(_)), None) = (components.next(), components.next()) {
+        name
+    } else {
+        name.replace(['.', ':'], "_")
+    }
 }
 
 /// A structure that manages various types of data associated with a single biological reference dataset.
 /// A reference dataset typically consists of sequence files (like FASTA or Genbank)
-/// and optional annotation files (like GFF, GTF, or BED) that provide additional layers of genomic
+/// and optional annotation files (like GFF, GTF, BED, or VCF) that provide additional layers of genomic
 /// information.
 ///
 /// The structure enforces important data integrity rules:
 /// - Every dataset must have a unique label for identification
 /// - At least one file must be associated with a label
-/// - Annotation files (GFF, GTF, BED) can only be included if there's an associated sequence file
-///   (FASTA or Genbank) present
+/// - Annotation files (GFF, GTF, BED, VCF) can only be included if there's an associated sequence
+///   file (FASTA or Genbank) present
 ///
 /// Each field represents a different file format commonly used in bioinformatics:
 /// - FASTA: Raw sequence data
@@ -100,6 +473,7 @@ impl DownloadStatus {
 /// - GFF: General Feature Format for genomic features
 /// - GTF: Gene Transfer Format (a refined version of GFF)
 /// - BED: Browser Extensible Data format for genomic intervals
+/// - VCF: Variant Call Format for known variants against the sequence
 /// - TAR: A tar-archive ("tarball") of arbitrary files
 ///
 /// Files are stored as optional strings, typically representing paths or identifiers to the actual
@@ -115,7 +489,76 @@ pub struct RefDataset {
     pub gff: Option<DownloadStatus>,
     pub gtf: Option<DownloadStatus>,
     pub bed: Option<DownloadStatus>,
+    pub vcf: Option<DownloadStatus>,
     pub tar: Option<DownloadStatus>,
+    /// URLs of minisign signatures for any of the dataset's files, keyed by the format of the
+    /// file they sign
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub signatures: BTreeMap<FileFormat, String>,
+    /// The base64-encoded minisign public key used to verify this dataset's signatures, taking
+    /// precedence over any key set for the whole registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    /// Ranges of bytes to download from some of the dataset's files instead of the whole files,
+    /// keyed by format
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub byte_ranges: BTreeMap<FileFormat, ByteRange>,
+    /// The number of records each of the dataset's files is expected to contain, keyed by format,
+    /// which validation checks files against
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub expected_records: BTreeMap<FileFormat, usize>,
+    /// Checksums published for some of the dataset's files, keyed by format, which downloads are
+    /// verified against. Registries written before other algorithms were supported store them as
+    /// `expected_md5`.
+    #[serde(
+        default,
+        alias = "expected_md5",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub expected_checksums: BTreeMap<FileFormat, String>,
+    /// The algorithm the `expected_checksums` were computed with
+    #[serde(default, skip_serializing_if = "HashAlgorithm::is_md5")]
+    pub checksum_algorithm: HashAlgorithm,
+    /// A directory this dataset's files are always downloaded into, overriding the destination
+    /// requested for a download
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dest: Option<PathBuf>,
+    /// The files unpacked from the dataset's tarball when it was last extracted, laid out as
+    /// they were written, i.e. after any leading components were stripped from their paths
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extracted: Vec<PathBuf>,
+    /// Which of the dataset's URLs were checked when it was created, so that registering it
+    /// checks the same ones
+    #[serde(skip)]
+    pub url_checks: UrlChecks,
+}
+
+/// Which of a dataset's URLs are checked for accessibility when it's registered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UrlChecks {
+    /// Check every URL. This is the default.
+    #[default]
+    All,
+    /// Only check the URLs of sequence files, i.e. FASTA and Genbank files, recording the others
+    /// without checking them, e.g. when annotation servers are slow or flaky. The unchecked URLs
+    /// are still checked when their files are downloaded.
+    SequencesOnly,
+    /// Don't check any URLs, only that they're well-formed http or https URLs, e.g. to register
+    /// datasets without a network connection or behind a firewall that blocks the checks. The
+    /// URLs are still checked when their files are downloaded.
+    Offline,
+}
+
+impl UrlChecks {
+    /// Whether the URL of a file in `format` is checked under this policy.
+    #[must_use]
+    pub fn checks(self, format: FileFormat) -> bool {
+        match self {
+            Self::All => true,
+            Self::SequencesOnly => matches!(format, FileFormat::Fasta | FileFormat::Genbank),
+            Self::Offline => false,
+        }
+    }
 }
 
 impl RefDataset {
@@ -124,9 +567,10 @@ impl RefDataset {
     /// This method creates a new [`RefDataset`] instance after validating that certain
     /// critical invariants are maintained:
     /// - Every dataset must have a non-empty label for identification
-    /// - At least one file (FASTA, Genbank, GFA, GFF, GTF, or BED) must be associated with a label
-    /// - Annotation files (GFF, GTF, BED) can only be included if there's an associated sequence file
-    ///   (FASTA or Genbank) present
+    /// - At least one file (FASTA, Genbank, GFA, GFF, GTF, BED, VCF, or TAR) must be associated
+    ///   with a label
+    /// - Annotation files (GFF, GTF, BED, VCF) can only be included if there's an associated
+    ///   sequence file (FASTA or Genbank) present
     /// - All provided file URLs must be valid and accessible
     ///
     /// # Arguments
@@ -138,6 +582,7 @@ impl RefDataset {
     /// * `gff` - Optional URL to a GFF format annotation file
     /// * `gtf` - Optional URL to a GTF format annotation file
     /// * `bed` - Optional URL to a BED format annotation file
+    /// * `vcf` - Optional URL to a VCF format variant file
     /// # `tar` - Optional URL to a tar archive of arbitrary files
     ///
     /// # Returns
@@ -152,11 +597,13 @@ impl RefDataset {
     /// - No files are provided with the label (`EntryError::LabelButNoFiles`)
     /// - Annotation files are provided without sequence files (`EntryError::AnnotationsButNoSequence`)
     /// - Any provided URL is invalid or inaccessible
+    /// - Checking any provided URL takes longer than [`DEFAULT_CHECK_TIMEOUT`]
+    ///   (`EntryError::CheckTimedOut`)
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// use your_crate::RefDataset;
+    /// use refman::RefDataset;
     ///
     /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
     /// let dataset = RefDataset::try_new(
@@ -167,12 +614,13 @@ impl RefDataset {
     ///     Some("https://example.com/hg38.gff".to_string()),
     ///     None,
     ///     None,
+    ///     None,
     ///     None
     /// ).await?;
     /// # Ok(())
     /// # }
     /// ```
-    #[allow(clippy::similar_names, clippy::too_many_arguments)]
+    #[allow(clippy::too_many_arguments)]
     pub async fn try_new(
         label: String,
         fasta: Option<String>,
@@ -181,91 +629,153 @@ impl RefDataset {
         gff: Option<String>,
         gtf: Option<String>,
         bed: Option<String>,
+        vcf: Option<String>,
+        tar: Option<String>,
+    ) -> Result<Self, EntryError> {
+        Self::try_new_with_checks(
+            label,
+            fasta,
+            genbank,
+            gfa,
+            gff,
+            gtf,
+            bed,
+            vcf,
+            tar,
+            UrlChecks::All,
+            Some(DEFAULT_CHECK_TIMEOUT),
+        )
+        .await
+    }
+
+    /// Creates a new reference dataset like [`RefDataset::try_new`], but only checks the URLs
+    /// that `url_checks` asks for, giving up on any check that takes longer than `check_timeout`.
+    /// Registering the dataset afterwards checks the same URLs.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`RefDataset::try_new`], other than for URLs that aren't
+    /// checked, as well as `EntryError::CheckTimedOut` if a check takes longer than
+    /// `check_timeout`. With [`UrlChecks::Offline`], returns `EntryError::MalformedUrl` if a URL
+    /// doesn't parse or doesn't use the http://, https://, ftp://, or s3:// protocols.
+    #[allow(
+        clippy::similar_names,
+        clippy::too_many_arguments,
+        clippy::single_match_else
+    )]
+    pub async fn try_new_with_checks(
+        label: String,
+        fasta: Option<String>,
+        genbank: Option<String>,
+        gfa: Option<String>,
+        gff: Option<String>,
+        gtf: Option<String>,
+        bed: Option<String>,
+        vcf: Option<String>,
         tar: Option<String>,
+        url_checks: UrlChecks,
+        check_timeout: Option<Duration>,
     ) -> Result<Self, EntryError> {
-        match (&fasta, &genbank, &gfa, &gff, &gtf, &bed, &tar) {
+        match (&fasta, &genbank, &gfa, &gff, &gtf, &bed, &vcf, &tar) {
             // This is the case when no files are provided, but a label is (label is the only argument to this function
             // that is not an Option<String>)
-            (None, None, None, None, None, None, None) => Err(EntryError::LabelButNoFiles),
+            (None, None, None, None, None, None, None, None) => Err(EntryError::LabelButNoFiles),
 
             // If none of the above conditions are met, we're all good! Return an instance of the `RefDataset` struct
             // with validated combinations of fields.
             _ => {
-                // check each of the possible files, if provided by the user. If all are successful, initialize each
-                // file name wrapped in a `DownloadStatus` `NotYetDownloaded` variant, which preserves backwards
-                // compatibility with the `refman.toml` format and controls the valid ways state can be updated in the
-                // `refman` register-download-validate workflow. We'll just use variable shadowing here instead of
-                // binding new variables.
-                let fasta = if let Some(url_to_check) = fasta {
-                    let _ = check_url(&url_to_check).await?;
-                    let status = DownloadStatus::new(url_to_check);
-                    Some(status)
-                } else {
-                    None
-                };
-                let genbank = if let Some(url_to_check) = genbank {
-                    let _ = check_url(&url_to_check).await?;
-                    let status = DownloadStatus::new(url_to_check);
-                    Some(status)
-                } else {
-                    None
-                };
-                let gfa = if let Some(url_to_check) = gfa {
-                    let _ = check_url(&url_to_check).await?;
-                    let status = DownloadStatus::new(url_to_check);
-                    Some(status)
-                } else {
-                    None
-                };
-                let gff = if let Some(url_to_check) = gff {
-                    let _ = check_url(&url_to_check).await?;
-                    let status = DownloadStatus::new(url_to_check);
-                    Some(status)
-                } else {
-                    None
-                };
-                let gtf = if let Some(url_to_check) = gtf {
-                    let _ = check_url(&url_to_check).await?;
-                    let status = DownloadStatus::new(url_to_check);
-                    Some(status)
-                } else {
-                    None
-                };
-                let bed = if let Some(url_to_check) = bed {
-                    let _ = check_url(&url_to_check).await?;
-                    let status = DownloadStatus::new(url_to_check);
-                    Some(status)
-                } else {
-                    None
-                };
-                let tar = if let Some(url_to_check) = tar {
-                    let _ = check_url(&url_to_check).await?;
-                    let status = DownloadStatus::new(url_to_check);
-                    Some(status)
-                } else {
-                    None
-                };
+                // check each of the possible files, if provided by the user, all at once so that one slow host
+                // doesn't hold up the others. If all are successful, initialize each file name wrapped in a
+                // `DownloadStatus` `NotYetDownloaded` variant, which preserves backwards compatibility with the
+                // `refman.toml` format and controls the valid ways state can be updated in the `refman`
+                // register-download-validate workflow.
+                let urls = [
+                    (FileFormat::Fasta, &fasta),
+                    (FileFormat::Genbank, &genbank),
+                    (FileFormat::Gfa, &gfa),
+                    (FileFormat::Gff, &gff),
+                    (FileFormat::Gtf, &gtf),
+                    (FileFormat::Bed, &bed),
+                    (FileFormat::Vcf, &vcf),
+                    (FileFormat::Tar, &tar),
+                ];
+
+                // URLs that won't be checked at all should at least look like something we can download
+                if url_checks == UrlChecks::Offline {
+                    if let Some(malformed) = urls
+                        .iter()
+                        .filter_map(|(_, url)| url.as_deref())
+                        .find(|url| !is_well_formed_url(url))
+                    {
+                        return Err(EntryError::MalformedUrl(label, malformed.to_string()));
+                    }
+                }
+
+                let urls_to_check = urls
+                    .into_iter()
+                    .filter(|(format, _)| url_checks.checks(*format))
+                    .filter_map(|(_, url)| url.as_deref());
+                try_join_all(urls_to_check.map(|url| check_url_within(url, check_timeout))).await?;
 
                 // If all provided URLs are valid, set up an instance of a registry
-                Ok(Self {
+                let dataset = Self {
                     label,
-                    fasta,
-                    genbank,
-                    gfa,
-                    gff,
-                    gtf,
-                    bed,
-                    tar,
-                })
+                    fasta: fasta.map(DownloadStatus::new),
+                    genbank: genbank.map(DownloadStatus::new),
+                    gfa: gfa.map(DownloadStatus::new),
+                    gff: gff.map(DownloadStatus::new),
+                    gtf: gtf.map(DownloadStatus::new),
+                    bed: bed.map(DownloadStatus::new),
+                    vcf: vcf.map(DownloadStatus::new),
+                    tar: tar.map(DownloadStatus::new),
+                    url_checks,
+                    ..Self::default()
+                };
+                dataset.warn_duplicate_urls();
+
+                Ok(dataset)
             },
         }
     }
 
+    /// Checks that this dataset satisfies the structural rules every registry entry must follow,
+    /// regardless of how it got into the registry.
+    ///
+    /// [`RefDataset::try_new`] enforces these rules for datasets registered through `refman`, but
+    /// a registry that has been edited by hand may contain entries that never passed through it.
+    /// This method makes the same guarantees checkable after the fact: the dataset must have a
+    /// non-empty label, and it must have at least one file associated with that label.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::EmptyLabel` if the label is empty or only whitespace, or
+    /// `EntryError::LabelButNoFiles` if no files are registered for the dataset.
+    pub fn validate_structure(&self) -> Result<(), EntryError> {
+        if self.label.trim().is_empty() {
+            return Err(EntryError::EmptyLabel);
+        }
+
+        match self {
+            RefDataset {
+                fasta: None,
+                genbank: None,
+                gfa: None,
+                gff: None,
+                gtf: None,
+                bed: None,
+                vcf: None,
+                tar: None,
+                ..
+            } => Err(EntryError::LabelButNoFiles),
+            _ => Ok(()),
+        }
+    }
+
     pub(crate) fn get_fasta_download(&self, target_dir: &Path) -> Option<UnvalidatedFile> {
         // resolve state for each of the files
         match &self.fasta {
             Some(file) => match file {
-                DownloadStatus::NotYetDownloaded(uri) => {
+                DownloadStatus::NotYetDownloaded(uri) | DownloadStatus::Failed { url: uri, .. } => {
                     let unvalidated = UnvalidatedFile::Fasta {
                         uri: uri.clone(),
                         local_path: PathBuf::new(),
@@ -291,19 +801,19 @@ impl RefDataset {
                     }
 
                     // make sure there's a hash we can use to checksum
-                    let Some(old_hash) = &validated_file.hash else {
+                    let Some(old_hash) = validated_file.recorded_checksum() else {
                         debug!("The file was never hashed, so it will be re-downloaded");
                         return None;
                     };
 
                     // make sure the file exists and still matches the hash. Otherwise, re-download.
-                    let Ok(new_hash) = hash_valid_download(old_path) else {
+                    let Ok(new_hash) = validated_file.algorithm.hash_file(old_path) else {
                         debug!(
                             "The checksum failed because the file could not be accessed, so it will be redownloaded"
                         );
                         return None;
                     };
-                    if old_path.exists() && old_hash.eq(&new_hash) {
+                    if old_path.exists() && old_hash == new_hash {
                         debug!(
                             "The path previously recorded for the download, {:?}, existed and it passed the checksum, so it will not be re-downloaded",
                             old_path,
@@ -327,9 +837,9 @@ impl RefDataset {
     pub(crate) fn get_genbank_download(&self, target_dir: &Path) -> Option<UnvalidatedFile> {
         match &self.genbank {
             Some(file) => match file {
-                DownloadStatus::NotYetDownloaded(uri) => {
+                DownloadStatus::NotYetDownloaded(uri) | DownloadStatus::Failed { url: uri, .. } => {
                     let unvalidated = UnvalidatedFile::Genbank {
-                        uri: uri.to_string(),
+                        uri: uri.clone(),
                         local_path: PathBuf::new(),
                     };
                     Some(unvalidated)
@@ -352,19 +862,19 @@ impl RefDataset {
                     }
 
                     // make sure there's a hash we can use to checksum
-                    let Some(old_hash) = &validated_file.hash else {
+                    let Some(old_hash) = validated_file.recorded_checksum() else {
                         debug!("The file was never hashed, so it will be re-downloaded");
                         return None;
                     };
 
                     // make sure the file exists and still matches the hash. Otherwise, re-download.
-                    let Ok(new_hash) = hash_valid_download(old_path) else {
+                    let Ok(new_hash) = validated_file.algorithm.hash_file(old_path) else {
                         debug!(
                             "The checksum failed because the file could not be accessed, so it will be redownloaded"
                         );
                         return None;
                     };
-                    if old_path.exists() && old_hash.eq(&new_hash) {
+                    if old_path.exists() && old_hash == new_hash {
                         debug!(
                             "The path previously recorded for the download, {:?}, existed and it passed the checksum, so it will not be re-downloaded",
                             old_path,
@@ -388,9 +898,9 @@ impl RefDataset {
     pub(crate) fn get_gfa_download(&self, target_dir: &Path) -> Option<UnvalidatedFile> {
         match &self.gfa {
             Some(file) => match file {
-                DownloadStatus::NotYetDownloaded(uri) => {
+                DownloadStatus::NotYetDownloaded(uri) | DownloadStatus::Failed { url: uri, .. } => {
                     let unvalidated = UnvalidatedFile::Gfa {
-                        uri: uri.to_string(),
+                        uri: uri.clone(),
                         local_path: PathBuf::new(),
                     };
                     Some(unvalidated)
@@ -413,19 +923,19 @@ impl RefDataset {
                     }
 
                     // make sure there's a hash we can use to checksum
-                    let Some(old_hash) = &validated_file.hash else {
+                    let Some(old_hash) = validated_file.recorded_checksum() else {
                         debug!("The file was never hashed, so it will be re-downloaded");
                         return None;
                     };
 
                     // make sure the file exists and still matches the hash. Otherwise, re-download.
-                    let Ok(new_hash) = hash_valid_download(old_path) else {
+                    let Ok(new_hash) = validated_file.algorithm.hash_file(old_path) else {
                         debug!(
                             "The checksum failed because the file could not be accessed, so it will be redownloaded"
                         );
                         return None;
                     };
-                    if old_path.exists() && old_hash.eq(&new_hash) {
+                    if old_path.exists() && old_hash == new_hash {
                         debug!(
                             "The path previously recorded for the download, {:?}, existed and it passed the checksum, so it will not be re-downloaded",
                             old_path,
@@ -449,9 +959,9 @@ impl RefDataset {
     pub(crate) fn get_gff_download(&self, target_dir: &Path) -> Option<UnvalidatedFile> {
         match &self.gff {
             Some(file) => match file {
-                DownloadStatus::NotYetDownloaded(uri) => {
+                DownloadStatus::NotYetDownloaded(uri) | DownloadStatus::Failed { url: uri, .. } => {
                     let unvalidated = UnvalidatedFile::Gff {
-                        uri: uri.to_string(),
+                        uri: uri.clone(),
                         local_path: PathBuf::new(),
                     };
                     Some(unvalidated)
@@ -474,19 +984,19 @@ impl RefDataset {
                     }
 
                     // make sure there's a hash we can use to checksum
-                    let Some(old_hash) = &validated_file.hash else {
+                    let Some(old_hash) = validated_file.recorded_checksum() else {
                         debug!("The file was never hashed, so it will be re-downloaded");
                         return None;
                     };
 
                     // make sure the file exists and still matches the hash. Otherwise, re-download.
-                    let Ok(new_hash) = hash_valid_download(old_path) else {
+                    let Ok(new_hash) = validated_file.algorithm.hash_file(old_path) else {
                         debug!(
                             "The checksum failed because the file could not be accessed, so it will be redownloaded"
                         );
                         return None;
                     };
-                    if old_path.exists() && old_hash.eq(&new_hash) {
+                    if old_path.exists() && old_hash == new_hash {
                         debug!(
                             "The path previously recorded for the download, {:?}, existed and it passed the checksum, so it will not be re-downloaded",
                             old_path,
@@ -510,9 +1020,9 @@ impl RefDataset {
     pub(crate) fn get_gtf_download(&self, target_dir: &Path) -> Option<UnvalidatedFile> {
         match &self.gtf {
             Some(file) => match file {
-                DownloadStatus::NotYetDownloaded(uri) => {
+                DownloadStatus::NotYetDownloaded(uri) | DownloadStatus::Failed { url: uri, .. } => {
                     let unvalidated = UnvalidatedFile::Gtf {
-                        uri: uri.to_string(),
+                        uri: uri.clone(),
                         local_path: PathBuf::new(),
                     };
                     Some(unvalidated)
@@ -535,19 +1045,19 @@ impl RefDataset {
                     }
 
                     // make sure there's a hash we can use to checksum
-                    let Some(old_hash) = &validated_file.hash else {
+                    let Some(old_hash) = validated_file.recorded_checksum() else {
                         debug!("The file was never hashed, so it will be re-downloaded");
                         return None;
                     };
 
                     // make sure the file exists and still matches the hash. Otherwise, re-download.
-                    let Ok(new_hash) = hash_valid_download(old_path) else {
+                    let Ok(new_hash) = validated_file.algorithm.hash_file(old_path) else {
                         debug!(
                             "The checksum failed because the file could not be accessed, so it will be redownloaded"
                         );
                         return None;
                     };
-                    if old_path.exists() && old_hash.eq(&new_hash) {
+                    if old_path.exists() && old_hash == new_hash {
                         debug!(
                             "The path previously recorded for the download, {:?}, existed and it passed the checksum, so it will not be re-downloaded",
                             old_path,
@@ -571,9 +1081,9 @@ impl RefDataset {
     pub(crate) fn get_bed_download(&self, target_dir: &Path) -> Option<UnvalidatedFile> {
         match &self.bed {
             Some(file) => match file {
-                DownloadStatus::NotYetDownloaded(uri) => {
+                DownloadStatus::NotYetDownloaded(uri) | DownloadStatus::Failed { url: uri, .. } => {
                     let unvalidated = UnvalidatedFile::Bed {
-                        uri: uri.to_string(),
+                        uri: uri.clone(),
                         local_path: PathBuf::new(),
                     };
                     Some(unvalidated)
@@ -596,19 +1106,19 @@ impl RefDataset {
                     }
 
                     // make sure there's a hash we can use to checksum
-                    let Some(old_hash) = &validated_file.hash else {
+                    let Some(old_hash) = validated_file.recorded_checksum() else {
                         debug!("The file was never hashed, so it will be re-downloaded");
                         return None;
                     };
 
                     // make sure the file exists and still matches the hash. Otherwise, re-download.
-                    let Ok(new_hash) = hash_valid_download(old_path) else {
+                    let Ok(new_hash) = validated_file.algorithm.hash_file(old_path) else {
                         debug!(
                             "The checksum failed because the file could not be accessed, so it will be redownloaded"
                         );
                         return None;
                     };
-                    if old_path.exists() && old_hash.eq(&new_hash) {
+                    if old_path.exists() && old_hash == new_hash {
                         debug!(
                             "The path previously recorded for the download, {:?}, existed and it passed the checksum, so it will not be re-downloaded",
                             old_path,
@@ -628,12 +1138,74 @@ impl RefDataset {
             None => None,
         }
     }
+
+    pub(crate) fn get_vcf_download(&self, target_dir: &Path) -> Option<UnvalidatedFile> {
+        match &self.vcf {
+            Some(file) => match file {
+                DownloadStatus::NotYetDownloaded(uri) | DownloadStatus::Failed { url: uri, .. } => {
+                    let unvalidated = UnvalidatedFile::Vcf {
+                        uri: uri.clone(),
+                        local_path: PathBuf::new(),
+                    };
+                    Some(unvalidated)
+                },
+                DownloadStatus::Downloaded(validated_file) => {
+                    debug!(
+                        "Deciding whether to re-download the previously downloaded file at {:?}...",
+                        validated_file
+                    );
+
+                    // pull in the previously downloaded file path
+                    let old_path = &validated_file.local_path;
+
+                    // make sure the old file still exists. If not, it should be downloaded.
+                    if !old_path.exists() || !old_path.starts_with(target_dir) {
+                        return Some(UnvalidatedFile::Vcf {
+                            uri: validated_file.uri.clone(),
+                            local_path: PathBuf::new(),
+                        });
+                    }
+
+                    // make sure there's a hash we can use to checksum
+                    let Some(old_hash) = validated_file.recorded_checksum() else {
+                        debug!("The file was never hashed, so it will be re-downloaded");
+                        return None;
+                    };
+
+                    // make sure the file exists and still matches the hash. Otherwise, re-download.
+                    let Ok(new_hash) = validated_file.algorithm.hash_file(old_path) else {
+                        debug!(
+                            "The checksum failed because the file could not be accessed, so it will be redownloaded"
+                        );
+                        return None;
+                    };
+                    if old_path.exists() && old_hash == new_hash {
+                        debug!(
+                            "The path previously recorded for the download, {:?}, existed and it passed the checksum, so it will not be re-downloaded",
+                            old_path,
+                        );
+                        return None;
+                    }
+
+                    // if we've made it this far, the file should be redownloaded. Clear the
+                    // local path and fill the URI into an UnvalidatedFile variant
+                    let unvalidated = UnvalidatedFile::Vcf {
+                        uri: validated_file.uri.clone(),
+                        local_path: PathBuf::new(),
+                    };
+                    Some(unvalidated)
+                },
+            },
+            None => None,
+        }
+    }
+
     pub(crate) fn get_tar_download(&self, target_dir: &Path) -> Option<UnvalidatedFile> {
         match &self.tar {
             Some(file) => match file {
-                DownloadStatus::NotYetDownloaded(uri) => {
+                DownloadStatus::NotYetDownloaded(uri) | DownloadStatus::Failed { url: uri, .. } => {
                     let unvalidated = UnvalidatedFile::Tar {
-                        uri: uri.to_string(),
+                        uri: uri.clone(),
                         local_path: PathBuf::new(),
                     };
                     Some(unvalidated)
@@ -656,19 +1228,19 @@ impl RefDataset {
                     }
 
                     // make sure there's a hash we can use to checksum
-                    let Some(old_hash) = &validated_file.hash else {
+                    let Some(old_hash) = validated_file.recorded_checksum() else {
                         debug!("The file was never hashed, so it will be re-downloaded");
                         return None;
                     };
 
                     // make sure the file exists and still matches the hash. Otherwise, re-download.
-                    let Ok(new_hash) = hash_valid_download(old_path) else {
+                    let Ok(new_hash) = validated_file.algorithm.hash_file(old_path) else {
                         debug!(
                             "The checksum failed because the file could not be accessed, so it will be redownloaded"
                         );
                         return None;
                     };
-                    if old_path.exists() && old_hash.eq(&new_hash) {
+                    if old_path.exists() && old_hash == new_hash {
                         debug!(
                             "The path previously recorded for the download, {:?}, existed and it passed the checksum, so it will not be re-downloaded",
                             old_path,
@@ -696,7 +1268,7 @@ impl RefDataset {
     /// is a key part of the refman register-download-validate workflow, transitioning files
     /// from the `NotYetDownloaded` to `Downloaded` state.
     ///
-    /// The method handles all supported file types (FASTA, Genbank, GFA, GFF, GTF, BED)
+    /// The method handles all supported file types (FASTA, Genbank, GFA, GFF, GTF, BED, VCF)
     /// and updates the respective field in the dataset with validated file information,
     /// including hash values and local paths.
     ///
@@ -718,8 +1290,8 @@ impl RefDataset {
     ///
     /// # Examples
     ///
-    /// ```rust,no_run
-    /// use your_crate::{RefDataset, UnvalidatedFile};
+    /// ```rust,ignore
+    /// use refman::RefDataset;
     /// use std::path::PathBuf;
     ///
     /// let mut dataset = RefDataset::default();
@@ -733,51 +1305,1286 @@ impl RefDataset {
         &mut self,
         downloaded_file: &UnvalidatedFile,
     ) -> Result<(), ValidationError> {
-        match downloaded_file {
-            UnvalidatedFile::Fasta { .. } => {
-                let validated = downloaded_file.try_validate()?;
-                let updated_status = DownloadStatus::new_downloaded(validated);
+        self.update_with_sampled_download(downloaded_file, None)
+    }
 
-                self.fasta = Some(updated_status);
-            },
-            UnvalidatedFile::Genbank { .. } => {
-                let validated = downloaded_file.try_validate()?;
-                let updated_status = DownloadStatus::new_downloaded(validated);
+    /// Updates the state of the dataset with a downloaded file like
+    /// [`RefDataset::update_with_download`], but when `sample` is provided, only validates that
+    /// many records from the start of the file. See [`UnvalidatedFile::try_validate_sample`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ValidationError` if the sampled records fail validation or the file hash
+    /// cannot be computed.
+    pub fn update_with_sampled_download(
+        &mut self,
+        downloaded_file: &UnvalidatedFile,
+        sample: Option<usize>,
+    ) -> Result<(), ValidationError> {
+        let (validated, records) = downloaded_file.validate_and_count(sample)?;
+        let format = downloaded_file.format();
+        if let Some(&expected) = self.expected_records.get(&format) {
+            match records {
+                Some(found) if found != expected => {
+                    return Err(ValidationError::RecordCountMismatch(
+                        format.to_string(),
+                        validated.local_path.display().to_string(),
+                        expected,
+                        found,
+                    ));
+                },
+                Some(_) => {},
+                None => debug!(
+                    "The records in {} weren't all counted, so they weren't checked against the {expected} expected.",
+                    validated.local_path.display()
+                ),
+            }
+        }
+        self.check_expected_checksum(format, &validated)?;
+        *self.file_field_mut(format) = Some(DownloadStatus::new_downloaded(validated));
 
-                self.genbank = Some(updated_status);
-            },
-            UnvalidatedFile::Gfa { .. } => {
-                let validated = downloaded_file.try_validate()?;
-                let updated_status = DownloadStatus::new_downloaded(validated);
+        Ok(())
+    }
 
-                self.gfa = Some(updated_status);
-            },
-            UnvalidatedFile::Gff { .. } => {
-                let validated = downloaded_file.try_validate()?;
-                let updated_status = DownloadStatus::new_downloaded(validated);
+    /// Sets which checksum is compared to decide whether the downloaded file registered under
+    /// `format` is still intact, e.g. after downloading it with `--hash-algo`. Files that haven't
+    /// been downloaded are left as they are.
+    pub(crate) fn set_hash_algorithm(&mut self, format: FileFormat, algorithm: HashAlgorithm) {
+        if let Some(DownloadStatus::Downloaded(file)) = self.file_field_mut(format) {
+            file.algorithm = algorithm;
+        }
+    }
 
-                self.gff = Some(updated_status);
-            },
-            UnvalidatedFile::Gtf { .. } => {
-                let validated = downloaded_file.try_validate()?;
-                let updated_status = DownloadStatus::new_downloaded(validated);
+    /// Records that the downloaded file registered under `format` passed validation at
+    /// `timestamp`, having been sampled at `sampled_records` records, or checked in full if
+    /// that's `None`.
+    pub(crate) fn record_validation(
+        &mut self,
+        format: FileFormat,
+        timestamp: Timestamp,
+        sampled_records: Option<usize>,
+    ) {
+        if let Some(DownloadStatus::Downloaded(file)) = self.file_field_mut(format) {
+            file.last_validated = Some(timestamp);
+            file.sampled_records = sampled_records;
+        }
+    }
 
-                self.gtf = Some(updated_status);
-            },
-            UnvalidatedFile::Bed { .. } => {
-                let validated = downloaded_file.try_validate()?;
-                let updated_status = DownloadStatus::new_downloaded(validated);
+    /// Records the headers the server sent with the downloaded file registered under `format`,
+    /// so that `refman update` can later ask whether it changed.
+    pub(crate) fn set_cache_validators(&mut self, format: FileFormat, validators: CacheValidators) {
+        if let Some(DownloadStatus::Downloaded(file)) = self.file_field_mut(format) {
+            file.etag = validators.etag;
+            file.last_modified = validators.last_modified;
+        }
+    }
 
-                self.bed = Some(updated_status);
-            },
-            UnvalidatedFile::Tar { .. } => {
-                let validated = downloaded_file.try_validate()?;
-                let updated_status = DownloadStatus::new_downloaded(validated);
+    /// Updates the state of the dataset with a downloaded file that is being kept despite not
+    /// passing validation.
+    ///
+    /// The file's field is set to a `Downloaded` status whose [`ValidatedFile`] has `validated`
+    /// set to `false`, so that `DownloadStatus::is_validated` continues to report the file as
+    /// unvalidated. See [`RefDataset::update_with_download`] for the strict counterpart.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ValidationError::InaccessibleFile` if the downloaded file cannot be hashed, and
+    /// `ValidationError::ChecksumMismatch` if it doesn't match a checksum registered for it, since
+    /// a file that isn't what the provider published can't be kept even when it's unvalidated.
+    pub fn update_with_unvalidated_download(
+        &mut self,
+        downloaded_file: &UnvalidatedFile,
+    ) -> Result<(), ValidationError> {
+        let unvalidated = downloaded_file.record_without_validation()?;
+        self.check_expected_checksum(downloaded_file.format(), &unvalidated)?;
+        *self.file_field_mut(downloaded_file.format()) =
+            Some(DownloadStatus::new_downloaded(unvalidated));
+
+        Ok(())
+    }
+
+    /// Carries over the signatures, public key, expected record counts and checksums, byte ranges,
+    /// and destination provided in `other`, replacing any this dataset has for the same formats.
+    pub(crate) fn merge_metadata(&mut self, other: &RefDataset) {
+        self.signatures.extend(other.signatures.clone());
+        if other.public_key.is_some() {
+            self.public_key.clone_from(&other.public_key);
+        }
+        self.expected_records.extend(other.expected_records.clone());
+        if !other.expected_checksums.is_empty() {
+            // checksums computed with different algorithms can't be mixed
+            if other.checksum_algorithm != self.checksum_algorithm {
+                self.expected_checksums.clear();
+                self.checksum_algorithm = other.checksum_algorithm;
+            }
+            self.expected_checksums
+                .extend(other.expected_checksums.clone());
+        }
+        self.byte_ranges.extend(other.byte_ranges.clone());
+        if other.dest.is_some() {
+            self.dest.clone_from(&other.dest);
+        }
+    }
 
-                self.tar = Some(updated_status);
+    /// Merges `other`, a dataset with the same label from another registry, into this one, field
+    /// by field.
+    ///
+    /// Metadata is carried over as in [`RefDataset::merge_metadata`], and every file in `other`
+    /// is added to this dataset. When both register a file of the same format under different
+    /// URLs, `other`'s file replaces this dataset's, and the replacement is logged. Files with the
+    /// same URL in both keep this dataset's download and validation state, since it describes
+    /// the local copy.
+    pub(crate) fn merge_from(&mut self, other: &RefDataset) {
+        self.merge_metadata(other);
+
+        for (format, incoming) in other.file_fields() {
+            let Some(incoming) = incoming else {
+                continue;
+            };
+            let field = self.file_field_mut(format);
+            match field {
+                Some(existing) if existing.url() == incoming.url() => continue,
+                Some(existing) => info!(
+                    "Replacing the {format} file of '{}', {}, with {}",
+                    other.label,
+                    existing.url(),
+                    incoming.url()
+                ),
+                None => {},
+            }
+            *field = Some(incoming.clone());
+        }
+        self.warn_duplicate_urls();
+    }
+
+    /// Points the dataset's file of the provided format at a new URL, keeping any record of the
+    /// file having been downloaded and validated.
+    ///
+    /// This is for files whose provider has moved them to a new URL without changing their
+    /// contents, so the local copy is still good and doesn't need to be downloaded again. Files
+    /// that haven't been downloaded, or that failed to download, are simply pointed at the new
+    /// URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::FormatNotRegistered` if the dataset has no file of the provided
+    /// format.
+    pub fn relink(&mut self, format: FileFormat, new_url: &str) -> Result<(), EntryError> {
+        let label = self.label.clone();
+        match self.file_field_mut(format) {
+            None => Err(EntryError::FormatNotRegistered(label, format.to_string())),
+            Some(DownloadStatus::Downloaded(file)) => {
+                file.uri = new_url.to_string();
+                Ok(())
+            },
+            Some(status) => {
+                *status = DownloadStatus::new(new_url.to_string());
+                Ok(())
             },
         }
+    }
 
-        Ok(())
+    /// Records SHA-256 checksums for the dataset's downloaded files that only have an MD5 checksum,
+    /// hashing the local copies rather than downloading them again, and returns how many were
+    /// rehashed.
+    ///
+    /// The MD5 checksums are left in place. Files that are missing on disk, can't be read, or no
+    /// longer match their MD5 checksum are skipped with a warning, since their SHA-256 checksum
+    /// wouldn't describe the file that was downloaded.
+    pub fn rehash(&mut self) -> usize {
+        let formats: Vec<FileFormat> = self
+            .file_fields()
+            .into_iter()
+            .map(|(format, _)| format)
+            .collect();
+        let mut rehashed = 0;
+        for format in formats {
+            let Some(DownloadStatus::Downloaded(file)) = self.file_field_mut(format) else {
+                continue;
+            };
+            if file.sha256.is_some() {
+                continue;
+            }
+            if file.local_path.as_os_str().is_empty() || !file.local_path.exists() {
+                warn!(
+                    "The {format} file downloaded from {} is no longer on disk, so it can't be rehashed.",
+                    file.uri
+                );
+                continue;
+            }
+            let (md5, sha256) = match hash_download_digests(&file.local_path) {
+                Ok(digests) => digests,
+                Err(error) => {
+                    warn!("Unable to rehash {}: {error}", file.local_path.display());
+                    continue;
+                },
+            };
+            if file.hash.as_ref().is_some_and(|hash| *hash != md5) {
+                warn!(
+                    "{} has changed since it was downloaded, so it won't be rehashed. Download it again to update its checksums.",
+                    file.local_path.display()
+                );
+                continue;
+            }
+            file.sha256 = Some(sha256);
+            rehashed += 1;
+        }
+
+        rehashed
+    }
+
+    /// Records that a file failed to download, keeping count of how many times in a row it has
+    /// failed. Files registered under the same URL for other formats are marked as failed too,
+    /// since they're only downloaded once.
+    pub(crate) fn record_failed_download(&mut self, file: &UnvalidatedFile, error: &str) {
+        let mut formats: Vec<FileFormat> = self
+            .files_sharing_url(file)
+            .iter()
+            .map(UnvalidatedFile::format)
+            .collect();
+        formats.insert(0, file.format());
+        for format in formats {
+            let field = self.file_field_mut(format);
+            let attempts = field.as_ref().map_or(0, DownloadStatus::failed_attempts) + 1;
+            *field = Some(DownloadStatus::Failed {
+                url: file.url().to_string(),
+                last_error: error.to_string(),
+                attempts,
+                last_attempt: Timestamp::now(),
+            });
+        }
+    }
+
+    /// Returns how many times in a row the dataset's file of the provided format has failed to
+    /// download. See [`DownloadStatus::failed_attempts`].
+    #[must_use]
+    pub fn failed_attempts(&self, format: FileFormat) -> u32 {
+        self.file_fields()
+            .into_iter()
+            .find(|(field_format, _)| *field_format == format)
+            .and_then(|(_, status)| status)
+            .map_or(0, DownloadStatus::failed_attempts)
+    }
+
+    /// Attaches the number of records some of the dataset's files are expected to contain, keyed by
+    /// format, so that validating a download also checks that it has exactly that many records.
+    ///
+    /// This catches truncated or mixed-up files that still parse. Record counts are only checked
+    /// for FASTA, Genbank, GFF, and GTF files, and aren't checked when only a sample of a file's
+    /// records is validated.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::RecordCountWithoutFile` if a count is provided for a format with no
+    /// registered file.
+    pub fn with_expected_records(
+        mut self,
+        expected_records: BTreeMap<FileFormat, usize>,
+    ) -> Result<Self, EntryError> {
+        for format in expected_records.keys() {
+            if self
+                .file_fields()
+                .iter()
+                .all(|(registered, status)| registered != format || status.is_none())
+            {
+                return Err(EntryError::RecordCountWithoutFile(format.to_string()));
+            }
+        }
+        self.expected_records.extend(expected_records);
+
+        Ok(self)
+    }
+
+    /// Attaches the checksums that providers publish for some of the dataset's files, keyed by
+    /// format and computed with `algorithm`, so that each download is verified against the
+    /// published checksum rather than just trusting whatever came over the wire. Checksums
+    /// attached earlier with a different algorithm are replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::InvalidChecksum` if a checksum isn't the right number of hexadecimal
+    /// digits for `algorithm`, and `EntryError::ChecksumWithoutFile` if a checksum is provided for
+    /// a format with no registered file.
+    pub fn with_expected_checksums(
+        mut self,
+        expected_checksums: BTreeMap<FileFormat, String>,
+        algorithm: HashAlgorithm,
+    ) -> Result<Self, EntryError> {
+        for (format, checksum) in &expected_checksums {
+            if checksum.len() != algorithm.hex_digits()
+                || !checksum.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                return Err(EntryError::InvalidChecksum(
+                    checksum.clone(),
+                    algorithm.to_string(),
+                    algorithm.hex_digits(),
+                ));
+            }
+            if self
+                .file_fields()
+                .iter()
+                .all(|(registered, status)| registered != format || status.is_none())
+            {
+                return Err(EntryError::ChecksumWithoutFile(format.to_string()));
+            }
+        }
+        if algorithm != self.checksum_algorithm {
+            self.expected_checksums.clear();
+            self.checksum_algorithm = algorithm;
+        }
+        self.expected_checksums.extend(
+            expected_checksums
+                .into_iter()
+                .map(|(format, checksum)| (format, checksum.to_ascii_lowercase())),
+        );
+
+        Ok(self)
+    }
+
+    /// Checks a downloaded file against the checksum registered for its format, if there is one.
+    fn check_expected_checksum(
+        &self,
+        format: FileFormat,
+        downloaded: &ValidatedFile,
+    ) -> Result<(), ValidationError> {
+        let Some(expected) = self.expected_checksums.get(&format) else {
+            return Ok(());
+        };
+        let found = downloaded
+            .checksum(self.checksum_algorithm)
+            .unwrap_or_default();
+        if found == expected {
+            return Ok(());
+        }
+
+        Err(ValidationError::ChecksumMismatch(
+            format.to_string(),
+            downloaded.local_path.display().to_string(),
+            self.checksum_algorithm.to_string(),
+            expected.clone(),
+            found.to_string(),
+        ))
+    }
+
+    /// Attaches ranges of bytes to download from some of the dataset's files, keyed by format, so
+    /// that only those parts of the files are downloaded, validated, and hashed.
+    ///
+    /// Ranges are requested with HTTP `Range` headers, so the files' servers must support them.
+    /// A range should line up with whole records, e.g. a FASTA header through the end of its
+    /// sequence, for the partial file to pass validation.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::ByteRangeWithoutFile` if a range is provided for a format with no
+    /// registered file.
+    pub fn with_byte_ranges(
+        mut self,
+        byte_ranges: BTreeMap<FileFormat, ByteRange>,
+    ) -> Result<Self, EntryError> {
+        for format in byte_ranges.keys() {
+            if self
+                .file_fields()
+                .iter()
+                .all(|(registered, status)| registered != format || status.is_none())
+            {
+                return Err(EntryError::ByteRangeWithoutFile(format.to_string()));
+            }
+        }
+        self.byte_ranges.extend(byte_ranges);
+
+        Ok(self)
+    }
+
+    /// Sets a directory that this dataset's files should always be downloaded into, regardless of
+    /// the destination requested for a download.
+    #[must_use]
+    pub fn with_dest(mut self, dest: Option<PathBuf>) -> Self {
+        if dest.is_some() {
+            self.dest = dest;
+        }
+        self
+    }
+
+    /// Returns the directory this dataset's files should be downloaded into: its own destination
+    /// if one was registered, or `default` otherwise. With `per_label`, files that would land in
+    /// `default` go into a subdirectory of it named after the dataset's label instead, so that
+    /// datasets whose files share a name don't overwrite each other.
+    #[must_use]
+    pub fn download_dir(&self, default: &Path, per_label: bool) -> PathBuf {
+        match &self.dest {
+            Some(dest) => dest.clone(),
+            None if per_label => default.join(label_dir_name(&self.label)),
+            None => default.to_path_buf(),
+        }
+    }
+
+    /// Attaches minisign signatures for some of the dataset's files, along with an optional public
+    /// key to verify them with.
+    ///
+    /// Each signature is keyed by the format of the file it signs, and is downloaded alongside
+    /// that file and verified before the file is validated. Verification only happens when a
+    /// public key is available, either the one provided here or one set for the whole registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::SignatureWithoutFile` if a signature is provided for a format with no
+    /// registered file, `EntryError::InvalidPublicKey` if the public key can't be decoded, or
+    /// an error if a signature URL is invalid or inaccessible.
+    pub async fn with_signatures(
+        mut self,
+        signatures: BTreeMap<FileFormat, String>,
+        public_key: Option<String>,
+    ) -> Result<Self, EntryError> {
+        for (format, url) in &signatures {
+            if self
+                .file_fields()
+                .iter()
+                .all(|(registered, status)| registered != format || status.is_none())
+            {
+                return Err(EntryError::SignatureWithoutFile(format.to_string()));
+            }
+            let _ = check_url(url).await?;
+        }
+        if let Some(key) = &public_key {
+            PublicKey::from_base64(key).map_err(|_| EntryError::InvalidPublicKey(key.clone()))?;
+        }
+
+        self.signatures.extend(signatures);
+        if public_key.is_some() {
+            self.public_key = public_key;
+        }
+
+        Ok(self)
+    }
+
+    /// Verifies the signature registered for `file`, if there is one, against the dataset's
+    /// public key or else the provided registry-wide key. The signature is expected to have been
+    /// downloaded into the same directory as the file.
+    ///
+    /// Files without a registered signature pass trivially. A signature without any public key to
+    /// check it against is skipped with a warning.
+    pub(crate) fn verify_signature(
+        &self,
+        file: &UnvalidatedFile,
+        registry_key: Option<&str>,
+    ) -> Result<(), ValidationError> {
+        let Some(signature_url) = self.signatures.get(&file.format()) else {
+            return Ok(());
+        };
+        let Some(public_key) = self.public_key.as_deref().or(registry_key) else {
+            warn!(
+                "A signature is registered for {}, but no public key was configured to verify it with, so it will not be checked.",
+                file.url()
+            );
+            return Ok(());
+        };
+
+        let local_path = file.get_path();
+        let signature_path = local_path
+            .parent()
+            .and_then(|dir| download_path(signature_url, dir, false))
+            .ok_or_else(|| {
+                ValidationError::SignatureInvalid(
+                    local_path.display().to_string(),
+                    format!("the signature at {signature_url} was not downloaded"),
+                )
+            })?;
+
+        verify_signature(local_path, &signature_path, public_key)
+    }
+
+    /// Returns each file field of the dataset alongside the format it holds.
+    pub(crate) fn file_fields(&self) -> [(FileFormat, Option<&DownloadStatus>); 8] {
+        [
+            (FileFormat::Fasta, self.fasta.as_ref()),
+            (FileFormat::Genbank, self.genbank.as_ref()),
+            (FileFormat::Gfa, self.gfa.as_ref()),
+            (FileFormat::Gff, self.gff.as_ref()),
+            (FileFormat::Gtf, self.gtf.as_ref()),
+            (FileFormat::Bed, self.bed.as_ref()),
+            (FileFormat::Vcf, self.vcf.as_ref()),
+            (FileFormat::Tar, self.tar.as_ref()),
+        ]
+    }
+
+    /// Returns the URLs of the dataset's files and of their signatures.
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        self.file_fields()
+            .into_iter()
+            .filter_map(|(_, status)| status.map(DownloadStatus::url))
+            .chain(self.signatures.values().map(String::as_str))
+    }
+
+    /// The number of files registered for the dataset, across all formats.
+    #[must_use]
+    pub fn file_count(&self) -> usize {
+        self.file_fields()
+            .iter()
+            .filter(|(_, status)| status.is_some())
+            .count()
+    }
+
+    /// The number of bytes the dataset's downloaded files take up on disk, as recorded when they
+    /// were downloaded, or `None` if no downloaded file has a recorded size. Files registered
+    /// under several formats with the same URL share one local copy, so they're only counted
+    /// once.
+    #[must_use]
+    pub fn downloaded_size(&self) -> Option<u64> {
+        let mut counted = HashSet::new();
+        self.file_fields()
+            .into_iter()
+            .filter_map(|(_, status)| status.filter(|status| status.is_downloaded()))
+            .filter_map(|status| match status {
+                DownloadStatus::Downloaded(file) => Some(file),
+                _ => None,
+            })
+            .filter(|file| counted.insert(&file.local_path))
+            .filter_map(|file| file.size_bytes)
+            .reduce(|total, size| total + size)
+    }
+
+    /// Whether the dataset has a sequence file, i.e. a FASTA or Genbank file, registered.
+    #[must_use]
+    pub fn has_sequence(&self) -> bool {
+        self.fasta.is_some() || self.genbank.is_some()
+    }
+
+    /// Converts the dataset to JSON for exporting, with each file's download status tagged with
+    /// a `status` field, e.g. `"status": "downloaded"`, instead of the untagged form used in
+    /// registry files.
+    pub(crate) fn to_export_json(&self) -> serde_json::Result<serde_json::Value> {
+        let mut exported = serde_json::to_value(self)?;
+        for (format, status) in self.file_fields() {
+            let Some(status) = status else {
+                continue;
+            };
+            if let serde_json::Value::String(key) = serde_json::to_value(format)? {
+                exported[key] = serde_json::to_value(TaggedStatus::from(status))?;
+            }
+        }
+
+        Ok(exported)
+    }
+
+    /// Whether the dataset has an annotation file, i.e. a GFF, GTF, BED, or VCF file, registered.
+    #[must_use]
+    pub fn has_annotation(&self) -> bool {
+        self.gff.is_some() || self.gtf.is_some() || self.bed.is_some() || self.vcf.is_some()
+    }
+
+    /// Returns every URL that is registered under more than one format in this dataset, in the
+    /// order the formats are listed in the registry.
+    ///
+    /// The same URL registered as, say, both FASTA and GFF is almost always a copy-paste mistake,
+    /// since a single file can't be valid in both formats.
+    #[must_use]
+    pub fn duplicate_urls(&self) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        let mut duplicates = Vec::new();
+        for (_, status) in self.file_fields() {
+            let Some(url) = status.map(DownloadStatus::url) else {
+                continue;
+            };
+            if !seen.insert(url) && !duplicates.contains(&url) {
+                duplicates.push(url);
+            }
+        }
+        duplicates
+    }
+
+    /// Checks that no URL is registered under more than one format in this dataset. See
+    /// [`RefDataset::duplicate_urls`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::DuplicateUrl` for the first URL that is registered more than once.
+    pub fn check_duplicate_urls(&self) -> Result<(), EntryError> {
+        match self.duplicate_urls().first() {
+            Some(url) => Err(EntryError::DuplicateUrl(
+                self.label.clone(),
+                (*url).to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Logs a warning for each URL that is registered under more than one format in this dataset.
+    pub(crate) fn warn_duplicate_urls(&self) {
+        for url in self.duplicate_urls() {
+            warn!(
+                "The dataset '{}' registers the URL {url} for more than one format, which is usually a mistake. It will only be downloaded once.",
+                self.label
+            );
+        }
+    }
+
+    /// Returns the files in this dataset, other than `file` itself, that are registered with the
+    /// same URL as `file`, pointing at `file`'s local path so that a single download can be
+    /// recorded for all of them.
+    pub(crate) fn files_sharing_url(&self, file: &UnvalidatedFile) -> Vec<UnvalidatedFile> {
+        self.file_fields()
+            .into_iter()
+            .filter(|(format, status)| {
+                *format != file.format() && status.is_some_and(|status| status.url() == file.url())
+            })
+            .map(|(format, _)| {
+                UnvalidatedFile::new(
+                    format,
+                    file.url().to_string(),
+                    file.get_path().to_path_buf(),
+                )
+            })
+            .collect()
+    }
+
+    /// Records a checksum computed for the remote file registered under `format`, without the
+    /// file itself having been kept.
+    ///
+    /// If the field already records a download with the same URL and checksum, it is left as is.
+    /// Otherwise, it is set to a `Downloaded` status with an empty `local_path`, no validation,
+    /// and the new checksum, which the next regular download of the file will replace.
+    pub(crate) fn update_with_checksum(&mut self, format: FileFormat, uri: &str, hash: String) {
+        let field = self.file_field_mut(format);
+        if let Some(DownloadStatus::Downloaded(existing)) = field {
+            if existing.uri == uri && existing.hash.as_deref() == Some(hash.as_str()) {
+                debug!("The checksum for {uri} is unchanged, so its registry entry is left as is.");
+                return;
+            }
+        }
+
+        let checksum_record = ValidatedFile {
+            uri: uri.to_string(),
+            local_path: PathBuf::new(),
+            validated: false,
+            hash: Some(hash),
+            last_validated: None,
+            sampled_records: None,
+            sha256: None,
+            algorithm: HashAlgorithm::default(),
+            etag: None,
+            last_modified: None,
+            size_bytes: None,
+        };
+        *field = Some(DownloadStatus::new_downloaded(checksum_record));
+    }
+
+    /// Records that a copy of the file registered under `format` was placed at `local_path` by
+    /// some means other than a refman download, e.g. transferred from another machine.
+    ///
+    /// Only files that were previously downloaded or checksummed are updated, since the copy is
+    /// expected to have been matched to them by checksum; everything else recorded about them,
+    /// like whether they were validated, carries over to the copy.
+    pub(crate) fn update_with_local_copy(&mut self, format: FileFormat, local_path: &Path) {
+        if let Some(DownloadStatus::Downloaded(file)) = self.file_field_mut(format) {
+            file.local_path = local_path.to_path_buf();
+        }
+    }
+
+    /// Returns a mutable reference to the field that stores files of the provided format.
+    fn file_field_mut(&mut self, format: FileFormat) -> &mut Option<DownloadStatus> {
+        match format {
+            FileFormat::Fasta => &mut self.fasta,
+            FileFormat::Genbank => &mut self.genbank,
+            FileFormat::Gfa => &mut self.gfa,
+            FileFormat::Gff => &mut self.gff,
+            FileFormat::Gtf => &mut self.gtf,
+            FileFormat::Bed => &mut self.bed,
+            FileFormat::Vcf => &mut self.vcf,
+            FileFormat::Tar => &mut self.tar,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use std::{fs, net::TcpListener};
+
+    use super::*;
+    use crate::validate::hash_valid_download;
+
+    #[test]
+    fn test_downloaded_size() {
+        let downloaded = |url: &str, local_path: &str, size_bytes: Option<u64>| {
+            Some(DownloadStatus::new_downloaded(ValidatedFile {
+                uri: url.to_string(),
+                local_path: PathBuf::from(local_path),
+                size_bytes,
+                ..ValidatedFile::default()
+            }))
+        };
+        let mut dataset = RefDataset {
+            label: "genome".to_string(),
+            fasta: downloaded(
+                "https://example.com/genome.fa",
+                "refs/genome.fa",
+                Some(1000),
+            ),
+            // registered under two formats, but downloaded once
+            gff: downloaded("https://example.com/genes.gff", "refs/genes.gff", Some(200)),
+            gtf: downloaded("https://example.com/genes.gff", "refs/genes.gff", Some(200)),
+            // only checksummed, so it takes up no space
+            bed: downloaded("https://example.com/regions.bed", "", Some(30)),
+            ..RefDataset::default()
+        };
+        assert_eq!(dataset.downloaded_size(), Some(1200));
+
+        // files recorded before sizes were don't count
+        dataset.fasta = downloaded("https://example.com/genome.fa", "refs/genome.fa", None);
+        assert_eq!(dataset.downloaded_size(), Some(200));
+        dataset.gff = None;
+        dataset.gtf = None;
+        assert_eq!(dataset.downloaded_size(), None);
+
+        // and registries written before sizes were recorded still read
+        let file: ValidatedFile = toml::from_str(
+            "uri = \"https://example.com/genome.fa\"\nlocal_path = \"refs/genome.fa\"\nvalidated = true\n",
+        )
+        .unwrap();
+        assert_eq!(file.size_bytes, None);
+    }
+
+    #[test]
+    fn test_per_label_download_dir() {
+        let target_dir = Path::new("refs");
+        let dataset = RefDataset {
+            label: "GRCh38".to_string(),
+            ..RefDataset::default()
+        };
+        assert_eq!(dataset.download_dir(target_dir, false), target_dir);
+        assert_eq!(
+            dataset.download_dir(target_dir, true),
+            target_dir.join("GRCh38")
+        );
+
+        // labels can't lead outside of the destination
+        for label in ["..", "../etc", "/etc", "a/b"] {
+            let dataset = RefDataset {
+                label: label.to_string(),
+                ..RefDataset::default()
+            };
+            let dir = dataset.download_dir(target_dir, true);
+            assert_eq!(dir.parent(), Some(target_dir), "{label}");
+            assert!(
+                matches!(dir.components().next_back(), Some(Component::Normal(_))),
+                "{label}"
+            );
+        }
+
+        // a dataset's own destination is used as is
+        let dataset = dataset.with_dest(Some(PathBuf::from("scratch")));
+        assert_eq!(dataset.download_dir(target_dir, true), Path::new("scratch"));
+    }
+
+    #[test]
+    fn test_gff_extensions() {
+        for uri in [
+            "https://ftp.ensembl.org/pub/release-110/gff3/homo_sapiens/Homo_sapiens.GRCh38.110.gff3.gz",
+            "Homo_sapiens.GRCh38.110.gff3",
+            "annotation.gff",
+            "annotation.GFF.gz",
+            "https://example.com/annotation.gff3?contentDisposition=attachment",
+        ] {
+            assert_eq!(FileFormat::from_uri(uri), Some(FileFormat::Gff), "{uri}");
+        }
+    }
+
+    #[test]
+    fn test_gtf_extensions() {
+        for uri in [
+            "https://ftp.ensembl.org/pub/release-110/gtf/homo_sapiens/Homo_sapiens.GRCh38.110.gtf.gz",
+            "genes.gtf",
+        ] {
+            assert_eq!(FileFormat::from_uri(uri), Some(FileFormat::Gtf), "{uri}");
+        }
+    }
+
+    #[test]
+    fn test_other_extensions() {
+        assert_eq!(
+            FileFormat::from_uri("MN908947.3.fasta"),
+            Some(FileFormat::Fasta)
+        );
+        assert_eq!(
+            FileFormat::from_uri("genome.fa.gz"),
+            Some(FileFormat::Fasta)
+        );
+        assert_eq!(
+            FileFormat::from_uri("MN908947.3.gbk"),
+            Some(FileFormat::Genbank)
+        );
+        assert_eq!(FileFormat::from_uri("pangenome.gfa"), Some(FileFormat::Gfa));
+        assert_eq!(FileFormat::from_uri("primers.bed"), Some(FileFormat::Bed));
+        assert_eq!(FileFormat::from_uri("dbsnp.vcf.gz"), Some(FileFormat::Vcf));
+        assert_eq!(FileFormat::from_uri("bundle.tar.gz"), Some(FileFormat::Tar));
+        assert_eq!(FileFormat::from_uri("bundle.tgz"), Some(FileFormat::Tar));
+        assert_eq!(FileFormat::from_uri("https://example.com/download"), None);
+        assert_eq!(FileFormat::from_uri("notes.txt"), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_timeout_names_url() {
+        // a host that accepts connections but never answers them
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/genome.fasta", listener.local_addr().unwrap());
+
+        let error = RefDataset::try_new_with_checks(
+            "genome".to_string(),
+            Some(url.clone()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            UrlChecks::All,
+            Some(Duration::from_millis(200)),
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(&error, EntryError::CheckTimedOut(timed_out, _) if *timed_out == url),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_offline_checks_only_url_shape() {
+        let offline = |fasta: &str| {
+            RefDataset::try_new_with_checks(
+                "genome".to_string(),
+                Some(fasta.to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                UrlChecks::Offline,
+                None,
+            )
+        };
+
+        // nothing answers at this host, but nothing asks it either
+        let dataset = offline("https://refman.invalid/genome.fasta")
+            .await
+            .unwrap();
+        assert_eq!(
+            dataset.fasta,
+            Some(DownloadStatus::NotYetDownloaded(
+                "https://refman.invalid/genome.fasta".to_string()
+            ))
+        );
+
+        for malformed in [
+            "sftp://example.com/genome.fasta",
+            "genome.fasta",
+            "https://",
+        ] {
+            assert!(
+                matches!(
+                    offline(malformed).await,
+                    Err(EntryError::MalformedUrl(_, url)) if url == malformed
+                ),
+                "{malformed}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_same_source_and_content() {
+        let downloaded = |url: &str, hash: &str, sha256: Option<&str>| {
+            DownloadStatus::new_downloaded(ValidatedFile {
+                uri: url.to_string(),
+                hash: Some(hash.to_string()),
+                sha256: sha256.map(str::to_string),
+                ..ValidatedFile::default()
+            })
+        };
+        let url = "https://example.com/genome.fasta";
+        let mirror = "https://mirror.example.com/genome.fasta";
+
+        let pending = DownloadStatus::new(url.to_string());
+        let original = downloaded(url, "aaaa", Some("1111"));
+        assert!(pending.same_source(&original));
+        assert!(!pending.same_content(&original));
+
+        // a mirror of the same file is a different source with the same content
+        let mirrored = downloaded(mirror, "AAAA", None);
+        assert!(!original.same_source(&mirrored));
+        assert!(original.same_content(&mirrored));
+
+        // SHA-256 checksums take precedence when both files have one
+        let changed = downloaded(mirror, "aaaa", Some("2222"));
+        assert!(!original.same_content(&changed));
+        assert!(!downloaded(url, "aaaa", None).same_content(&downloaded(url, "bbbb", None)));
+    }
+
+    #[test]
+    fn test_duplicate_urls() {
+        let url = "https://example.com/genome.fasta";
+        let dataset = RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new(url.to_string())),
+            gff: Some(DownloadStatus::new(url.to_string())),
+            bed: Some(DownloadStatus::new(
+                "https://example.com/regions.bed".to_string(),
+            )),
+            ..RefDataset::default()
+        };
+        assert_eq!(dataset.duplicate_urls(), vec![url]);
+        assert!(matches!(
+            dataset.check_duplicate_urls(),
+            Err(EntryError::DuplicateUrl(label, dup)) if label == "genome" && dup == url
+        ));
+
+        let downloaded = UnvalidatedFile::Fasta {
+            uri: url.to_string(),
+            local_path: PathBuf::from("genome.fasta"),
+        };
+        let sharing = dataset.files_sharing_url(&downloaded);
+        assert_eq!(sharing.len(), 1);
+        assert_eq!(sharing[0].format(), FileFormat::Gff);
+        assert_eq!(sharing[0].get_path(), Path::new("genome.fasta"));
+    }
+
+    #[test]
+    fn test_update_with_checksum() {
+        let url = "https://example.com/genome.fasta";
+        let mut dataset = RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new(url.to_string())),
+            ..RefDataset::default()
+        };
+
+        dataset.update_with_checksum(FileFormat::Fasta, url, "abc123".to_string());
+        let fasta = dataset.fasta.as_ref().unwrap();
+        assert!(!fasta.is_downloaded());
+        assert!(!fasta.is_validated());
+        assert!(
+            matches!(fasta, DownloadStatus::Downloaded(file) if file.hash.as_deref() == Some("abc123"))
+        );
+    }
+
+    #[test]
+    fn test_redownload_with_hash_algorithm() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("genome.fasta");
+        fs::write(&path, ">seq\nACGT\n").unwrap();
+        let sha256 = HashAlgorithm::Sha256.hash_file(&path).unwrap();
+
+        // only the checksum for the recorded algorithm is compared
+        let mut dataset = RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new_downloaded(ValidatedFile {
+                uri: "https://example.com/genome.fasta".to_string(),
+                local_path: path.clone(),
+                validated: true,
+                hash: Some("stale".to_string()),
+                sha256: Some(sha256),
+                ..ValidatedFile::default()
+            })),
+            ..RefDataset::default()
+        };
+        assert!(dataset.get_fasta_download(dir.path()).is_some());
+        dataset.set_hash_algorithm(FileFormat::Fasta, HashAlgorithm::Sha256);
+        assert!(dataset.get_fasta_download(dir.path()).is_none());
+
+        // registries from before the algorithm was recorded use MD5
+        let reparsed: RefDataset = toml::from_str(
+            &toml::to_string(&dataset)
+                .unwrap()
+                .replace("algorithm = \"sha256\"\n", ""),
+        )
+        .unwrap();
+        assert!(matches!(
+            reparsed.fasta,
+            Some(DownloadStatus::Downloaded(ValidatedFile {
+                algorithm: HashAlgorithm::Md5,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_relink() {
+        let mut dataset = RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new_downloaded(ValidatedFile {
+                uri: "https://old.example.com/genome.fasta".to_string(),
+                local_path: PathBuf::from("genome.fasta"),
+                validated: true,
+                hash: Some("abc123".to_string()),
+                ..ValidatedFile::default()
+            })),
+            ..RefDataset::default()
+        };
+
+        let new_url = "https://new.example.com/genome.fasta";
+        dataset.relink(FileFormat::Fasta, new_url).unwrap();
+        let Some(DownloadStatus::Downloaded(file)) = &dataset.fasta else {
+            panic!("relinking should keep the download state");
+        };
+        assert_eq!(file.uri, new_url);
+        assert_eq!(file.hash.as_deref(), Some("abc123"));
+        assert!(file.validated);
+
+        assert!(matches!(
+            dataset.relink(FileFormat::Gff, new_url),
+            Err(EntryError::FormatNotRegistered(..))
+        ));
+    }
+
+    #[test]
+    fn test_rehash() {
+        let dir = tempfile::tempdir().unwrap();
+        let fasta = dir.path().join("genome.fasta");
+        let gff = dir.path().join("genes.gff");
+        fs::write(&fasta, ">seq\nACGT\n").unwrap();
+        fs::write(&gff, "##gff-version 3\n").unwrap();
+        let downloaded = |path: &Path| {
+            DownloadStatus::new_downloaded(ValidatedFile {
+                uri: format!("https://example.com/{}", path.display()),
+                local_path: path.to_path_buf(),
+                validated: true,
+                hash: hash_valid_download(path).ok(),
+                ..ValidatedFile::default()
+            })
+        };
+        let mut dataset = RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(downloaded(&fasta)),
+            gff: Some(downloaded(&gff)),
+            bed: Some(downloaded(&dir.path().join("missing.bed"))),
+            ..RefDataset::default()
+        };
+        fs::write(&gff, "##gff-version 3\nchanged\n").unwrap();
+
+        assert_eq!(dataset.rehash(), 1);
+        let Some(DownloadStatus::Downloaded(file)) = &dataset.fasta else {
+            panic!("rehashing should keep the download state");
+        };
+        assert_eq!(
+            file.sha256.as_deref(),
+            Some("23eef005d2e50e51848fb810c8c2da768c78df0c226378c68d9168c21618eba2")
+        );
+        assert!(file.hash.is_some());
+        let Some(DownloadStatus::Downloaded(file)) = &dataset.gff else {
+            panic!("rehashing should keep the download state");
+        };
+        assert!(file.sha256.is_none());
+
+        // files that were already rehashed are left alone
+        assert_eq!(dataset.rehash(), 0);
+    }
+
+    #[test]
+    fn test_label_from_url() {
+        assert_eq!(
+            label_from_url("https://example.com/pub/Homo_sapiens.GRCh38.dna.fa.gz?download=1")
+                .unwrap(),
+            "Homo_sapiens.GRCh38.dna"
+        );
+        assert_eq!(
+            label_from_url("https://example.com/my%20genome.gbk").unwrap(),
+            "my_20genome"
+        );
+        assert_eq!(label_from_url("refs/sample.tar.gz").unwrap(), "sample");
+        assert!(matches!(
+            label_from_url("https://example.com/files/"),
+            Err(EntryError::NoLabelInUrl(_))
+        ));
+    }
+
+    #[test]
+    fn test_byte_range() {
+        let range: ByteRange = "1000-1999".parse().unwrap();
+        assert_eq!(
+            range,
+            ByteRange {
+                start: 1000,
+                end: Some(1999)
+            }
+        );
+        assert_eq!(range.to_string(), "1000-1999");
+
+        let open_ended: ByteRange = "1000-".parse().unwrap();
+        assert_eq!(open_ended.end, None);
+        assert_eq!(open_ended.to_string(), "1000-");
+
+        for invalid in ["1000", "-1000", "1999-1000", "a-b"] {
+            assert!(matches!(
+                invalid.parse::<ByteRange>(),
+                Err(EntryError::InvalidByteRange(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_expected_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("genome.fa");
+        fs::write(&path, ">chr1\nACGT\n>chr2\nTTGA\n").unwrap();
+        let url = "https://example.com/genome.fa".to_string();
+        let file = UnvalidatedFile::new(FileFormat::Fasta, url.clone(), path);
+        let dataset = |records: usize| {
+            RefDataset {
+                label: "genome".to_string(),
+                fasta: Some(DownloadStatus::new(url.clone())),
+                ..RefDataset::default()
+            }
+            .with_expected_records(BTreeMap::from([(FileFormat::Fasta, records)]))
+            .unwrap()
+        };
+
+        assert!(dataset(2).update_with_download(&file).is_ok());
+        assert!(matches!(
+            dataset(3).update_with_download(&file),
+            Err(ValidationError::RecordCountMismatch(_, _, 3, 2))
+        ));
+        // a sample can't say how many records the whole file has
+        assert!(
+            dataset(3)
+                .update_with_sampled_download(&file, Some(1))
+                .is_ok()
+        );
+
+        assert!(matches!(
+            dataset(2).with_expected_records(BTreeMap::from([(FileFormat::Gff, 10)])),
+            Err(EntryError::RecordCountWithoutFile(_))
+        ));
+    }
+
+    #[test]
+    fn test_export_json_tags_statuses() {
+        let dataset = RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new_downloaded(ValidatedFile {
+                uri: "https://example.com/genome.fasta".to_string(),
+                hash: Some("abc".to_string()),
+                ..ValidatedFile::default()
+            })),
+            gff: Some(DownloadStatus::new(
+                "https://example.com/genes.gff".to_string(),
+            )),
+            ..RefDataset::default()
+        };
+
+        let exported = dataset.to_export_json().unwrap();
+        assert_eq!(exported["fasta"]["status"], "downloaded");
+        assert_eq!(exported["fasta"]["hash"], "abc");
+        assert_eq!(exported["gff"]["status"], "not_yet_downloaded");
+        assert_eq!(exported["gff"]["url"], "https://example.com/genes.gff");
+        assert!(exported["gtf"].is_null());
+
+        // the registry file keeps the untagged form
+        let toml = toml::to_string(&dataset).unwrap();
+        assert!(toml.contains("gff = \"https://example.com/genes.gff\""));
+    }
+
+    #[test]
+    fn test_expected_checksums() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("genome.fa");
+        fs::write(&path, ">chr1\nACGT\n").unwrap();
+        let md5 = hash_valid_download(&path).unwrap();
+        let sha256 = HashAlgorithm::Sha256.hash_file(&path).unwrap();
+        let url = "https://example.com/genome.fa".to_string();
+        let file = UnvalidatedFile::new(FileFormat::Fasta, url.clone(), path);
+        let dataset = |checksum: &str, algorithm: HashAlgorithm| {
+            RefDataset {
+                label: "genome".to_string(),
+                fasta: Some(DownloadStatus::new(url.clone())),
+                ..RefDataset::default()
+            }
+            .with_expected_checksums(
+                BTreeMap::from([(FileFormat::Fasta, checksum.to_string())]),
+                algorithm,
+            )
+        };
+
+        assert!(
+            dataset(&md5.to_ascii_uppercase(), HashAlgorithm::Md5)
+                .unwrap()
+                .update_with_download(&file)
+                .is_ok()
+        );
+        assert!(
+            dataset(&sha256, HashAlgorithm::Sha256)
+                .unwrap()
+                .update_with_download(&file)
+                .is_ok()
+        );
+        let mut mismatched =
+            dataset("0123456789abcdef0123456789abcdef", HashAlgorithm::Md5).unwrap();
+        assert!(matches!(
+            mismatched.update_with_download(&file),
+            Err(ValidationError::ChecksumMismatch(..))
+        ));
+        // the checksum is checked even for files kept without validation
+        assert!(matches!(
+            mismatched.update_with_unvalidated_download(&file),
+            Err(ValidationError::ChecksumMismatch(..))
+        ));
+        // an MD5 checksum can't pass for a SHA-256 one
+        assert!(matches!(
+            dataset(&md5, HashAlgorithm::Sha256),
+            Err(EntryError::InvalidChecksum(..))
+        ));
+
+        assert!(matches!(
+            dataset("abc123", HashAlgorithm::Md5),
+            Err(EntryError::InvalidChecksum(..))
+        ));
+        assert!(matches!(
+            dataset(&md5, HashAlgorithm::Md5)
+                .unwrap()
+                .with_expected_checksums(
+                    BTreeMap::from([(FileFormat::Gff, md5.clone())]),
+                    HashAlgorithm::Md5
+                ),
+            Err(EntryError::ChecksumWithoutFile(_))
+        ));
+
+        // the algorithm is stored with the checksums, and older registries' MD5 checksums still load
+        let registered = dataset(&sha256, HashAlgorithm::Sha256).unwrap();
+        let toml = toml::to_string(&registered).unwrap();
+        assert!(toml.contains("checksum_algorithm = \"sha256\""));
+        let reloaded: RefDataset = toml::from_str(&toml).unwrap();
+        assert_eq!(reloaded.checksum_algorithm, HashAlgorithm::Sha256);
+        assert_eq!(reloaded.expected_checksums, registered.expected_checksums);
+        let legacy: RefDataset = toml::from_str(&format!(
+            "label = \"genome\"\n\n[expected_md5]\nfasta = \"{md5}\"\n"
+        ))
+        .unwrap();
+        assert_eq!(legacy.checksum_algorithm, HashAlgorithm::Md5);
+        assert_eq!(
+            legacy.expected_checksums.get(&FileFormat::Fasta),
+            Some(&md5)
+        );
+    }
+
+    #[test]
+    fn test_record_failed_download() {
+        let url = "https://example.com/genome.fasta";
+        let mut dataset = RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new(url.to_string())),
+            ..RefDataset::default()
+        };
+        let file = UnvalidatedFile::new(FileFormat::Fasta, url.to_string(), PathBuf::new());
+
+        dataset.record_failed_download(&file, "404 Not Found");
+        dataset.record_failed_download(&file, "404 Not Found");
+        assert_eq!(dataset.failed_attempts(FileFormat::Fasta), 2);
+        assert_eq!(dataset.fasta.as_ref().unwrap().url(), url);
+
+        // failures survive a round trip through the registry, and a failed file is still downloaded
+        let reparsed: RefDataset = toml::from_str(&toml::to_string(&dataset).unwrap()).unwrap();
+        assert_eq!(reparsed.fasta, dataset.fasta);
+        assert!(reparsed.get_fasta_download(Path::new(".")).is_some());
     }
 }
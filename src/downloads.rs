@@ -1,19 +1,342 @@
-use std::{iter::Iterator, path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    future::Future,
+    io,
+    iter::{self, Iterator},
+    path::{Component, Path, PathBuf},
+    pin::pin,
+    result::Result as StdResult,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use color_eyre::{eyre::eyre, Result};
-use futures::StreamExt;
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, eyre},
+};
+use futures::{Stream, StreamExt, stream};
+use indicatif::{HumanBytes, HumanDuration, MultiProgress, ProgressBar, ProgressStyle};
 use log::{debug, error, info, warn};
-use lychee_lib::{CacheStatus, Status};
-use reqwest::Client;
+use lychee_lib::{CacheStatus, ClientBuilder, DEFAULT_MAX_REDIRECTS, ErrorKind, Status};
+use md5::Context;
+use reqwest::{
+    Client, StatusCode,
+    header::{
+        CONTENT_DISPOSITION, CONTENT_RANGE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE,
+        LAST_MODIFIED, RANGE,
+    },
+    redirect::Policy,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
 use tokio::{
-    fs::{self, File},
-    io::AsyncWriteExt,
+    fs::{self, File, OpenOptions},
+    io::{AsyncReadExt, AsyncWriteExt},
     time,
 };
 use url::Url;
 
-use crate::validate::UnvalidatedFile;
+use crate::{
+    data::{ByteRange, FileFormat},
+    ftp::{self, FtpTransfer},
+    headers::{client_builder, link_check_headers, with_headers},
+    s3::{self, S3Transfer},
+    validate::{HashAlgorithm, UnvalidatedFile, ValidatedFile, file_size},
+};
+
+/// Settings controlling how `refman` behaves while downloading the files in a registry.
+///
+/// `DownloadOptions` bundles together the knobs that apply to a whole download run rather
+/// than to any one dataset, so that they can be threaded through
+/// [`Project::download_dataset`](crate::project::Project::download_dataset) as a single
+/// argument. The `Default` implementation reproduces `refman`'s long-standing behavior, so
+/// callers only need to set the fields they care about:
+///
+/// ```
+/// use refman::DownloadOptions;
+///
+/// let options = DownloadOptions {
+///     fail_fast: true,
+///     ..DownloadOptions::default()
+/// };
+/// assert!(options.fail_fast);
+/// ```
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct DownloadOptions {
+    /// When `true`, the first file that fails to download aborts the entire operation,
+    /// cancelling any downloads still in flight and returning the error. When `false` (the
+    /// default), downloads are best-effort: failures are logged and the remaining files are
+    /// still downloaded and recorded in the registry.
+    pub fail_fast: bool,
+
+    /// When `true`, a file that downloads successfully but fails format validation is kept on
+    /// disk and recorded in the registry as downloaded but not validated, with the validation
+    /// error logged. When `false` (the default), a validation failure is returned as an error
+    /// and none of the downloads for that run are recorded.
+    pub continue_on_validation_error: bool,
+
+    /// When `true`, files are streamed from their URLs and hashed as their bytes arrive, but
+    /// nothing is written to disk. The hashes are recorded in the registry so that they can be
+    /// used to verify the files later, which is useful for large references that are checked but
+    /// not kept. Defaults to `false`.
+    pub checksum_only: bool,
+
+    /// What to do when a file about to be downloaded already exists in the destination
+    /// directory but wasn't put there by a previous `refman` download. Defaults to
+    /// [`ExistingFiles::Warn`].
+    pub existing_files: ExistingFiles,
+
+    /// The maximum number of idle connections to keep open to any one host, so that many files
+    /// pulled from the same server reuse a handful of connections instead of opening dozens.
+    /// `None` uses reqwest's default, which places no limit on idle connections.
+    pub max_idle_per_host: Option<usize>,
+
+    /// How long an idle pooled connection is kept alive before it is closed. `None` uses
+    /// reqwest's default of 90 seconds.
+    pub idle_timeout: Option<Duration>,
+
+    /// The interval at which TCP keep-alive probes are sent on open connections. `None`, the
+    /// default, leaves TCP keep-alive disabled.
+    pub tcp_keepalive: Option<Duration>,
+
+    /// When set, validate only this many records from the start of each downloaded file rather
+    /// than parsing the whole thing, which is much faster for very large references but a weaker
+    /// check. Files validated this way record that they were sampled.
+    pub validation_sample: Option<usize>,
+
+    /// When `true`, files that have repeatedly failed to download in previous runs are tried
+    /// again. When `false` (the default), files that failed several times in a row are skipped
+    /// with a warning, so that chronically broken sources don't slow down every run.
+    pub retry_failed: bool,
+
+    /// A URL to `POST` a JSON event to when each dataset's download completes or fails, so that
+    /// workflow systems can react to references becoming available without polling. Failing to
+    /// notify the webhook is logged but never fails the download.
+    pub webhook: Option<String>,
+
+    /// A bearer token to authenticate with the webhook, if it requires one.
+    pub webhook_token: Option<String>,
+
+    /// When `true`, each file is downloaded into subdirectories of the destination that mirror
+    /// the directories in its URL's path, e.g. `pub/release-110/fasta/genome.fa`, preserving
+    /// any version or organization information the provider encodes there. When `false` (the
+    /// default), every file lands directly in the destination directory.
+    pub mirror_structure: bool,
+
+    /// When `true`, each dataset's files are downloaded into a subdirectory of the destination
+    /// named after its label, e.g. `GRCh38/genome.fasta`, so that datasets whose files share a
+    /// name don't overwrite each other. Datasets registered with their own destination still use
+    /// it as is. Defaults to `false`.
+    pub per_label_dirs: bool,
+
+    /// Limits on the redirects followed when checking and downloading files. By default,
+    /// redirects are followed wherever they lead, up to the usual limits.
+    pub redirects: RedirectPolicy,
+
+    /// When `true`, a JSON summary of every file in the run is printed to stdout once the
+    /// downloads finish, giving each file's label, format, URL, local path, size, hash, and
+    /// whether it was downloaded, skipped, or failed. When `false` (the default), only the
+    /// human-readable progress messages are shown.
+    pub json_summary: bool,
+
+    /// The most files to download at once across all datasets, so that large registries don't
+    /// open hundreds of connections and get rate-limited or banned by providers like NCBI or
+    /// Ensembl. `None` uses the default of [`DEFAULT_DOWNLOAD_JOBS`].
+    pub jobs: Option<usize>,
+
+    /// Which checksum is compared on later runs to decide whether each downloaded file is still
+    /// intact. Both MD5 and SHA-256 checksums are always recorded; this picks the one that
+    /// counts. Defaults to MD5 for compatibility with older registries.
+    pub hash_algorithm: HashAlgorithm,
+
+    /// The URL of a provider's published index of checksums, e.g. an `MD5SUMS` file, to compare
+    /// against the checksums recorded in the registry. Downloaded files whose published checksums
+    /// differ are downloaded again, and files whose checksums match are skipped without being
+    /// requested. Files the index doesn't list are handled as usual.
+    pub remote_index: Option<String>,
+
+    /// The algorithm the checksums in [`DownloadOptions::remote_index`] were computed with.
+    pub index_algorithm: HashAlgorithm,
+
+    /// How often to log the progress of each file still downloading, giving the number of bytes
+    /// downloaded so far and the rate, so that logs of batch jobs without a terminal show that
+    /// long downloads are still moving. `None` (the default) logs no heartbeat.
+    pub heartbeat: Option<Duration>,
+
+    /// Which of each dataset's files to download, e.g. only the annotations of a dataset whose
+    /// FASTA is too large to want. By default, files of every format are downloaded.
+    pub formats: FormatSelection,
+
+    /// How many times a failed request for a file is tried again, waiting twice as long before
+    /// each retry as before the last, up to [`MAX_RETRY_BACKOFF`]. `Some(0)` tries each file
+    /// once, without waiting. `None` uses the default of [`DEFAULT_RETRIES`].
+    pub retries: Option<u32>,
+
+    /// The most time any one HTTP request may take, including streaming the whole file, before
+    /// it's abandoned as failed and retried, so set it generously for large files. `None`, the
+    /// default, leaves requests without a time limit.
+    pub timeout: Option<Duration>,
+
+    /// When `true`, each tarball that downloads and validates is unpacked into a directory next
+    /// to it named after the archive, e.g. `bundle/` for `bundle.tar.gz`. The tarball itself is
+    /// kept and recorded as usual. Defaults to `false`.
+    pub extract: bool,
+
+    /// How many leading components to drop from the path of each entry of a tarball being
+    /// extracted, like `tar --strip-components`, e.g. `1` to unpack the contents of the directory
+    /// many providers wrap their archives in rather than the directory itself. Defaults to `0`.
+    pub strip_components: usize,
+}
+
+/// How many files are downloaded at once when [`DownloadOptions::jobs`] isn't set.
+pub const DEFAULT_DOWNLOAD_JOBS: usize = 4;
+
+/// How many URLs `refman verify-urls` checks at once when it isn't told otherwise.
+pub const DEFAULT_LINK_CHECK_JOBS: usize = 8;
+
+/// How many times a failed request is tried again when [`DownloadOptions::retries`] isn't set,
+/// for 5 attempts in all.
+pub const DEFAULT_RETRIES: u32 = 4;
+
+/// The longest a failed request waits before it's tried again, however many retries came before.
+pub const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How long checking that a URL is accessible may take when registering a dataset, before the
+/// host is given up on.
+pub const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Which file formats a download run covers, so that some of a dataset's files can be
+/// downloaded without the others.
+///
+/// Files of formats left out are neither downloaded nor touched on disk, and keep whatever state
+/// the registry records for them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormatSelection {
+    /// The only formats to download. An empty list, the default, allows every format.
+    pub only: Vec<FileFormat>,
+
+    /// Formats not to download, even if [`FormatSelection::only`] lists them.
+    pub except: Vec<FileFormat>,
+}
+
+impl FormatSelection {
+    /// Whether files of `format` are downloaded.
+    #[must_use]
+    pub fn includes(&self, format: FileFormat) -> bool {
+        (self.only.is_empty() || self.only.contains(&format)) && !self.except.contains(&format)
+    }
+}
+
+/// Limits on the redirects `refman` follows on the way to a file.
+///
+/// A registered URL that redirects may end up somewhere other than the provider it names, so
+/// security-conscious users can cap how many redirects are followed or refuse redirects to other
+/// hosts altogether. A redirect that breaks these limits stops the download with an error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RedirectPolicy {
+    /// The most redirects to follow from any one URL. `None` uses the default limits of 5
+    /// redirects when a URL is checked and 10 when it is downloaded.
+    pub max_redirects: Option<usize>,
+
+    /// When `true`, redirects to a different host than the one the URL names are refused. Since
+    /// the link checker can't be told to refuse them, they are caught when the file is downloaded.
+    pub same_host_only: bool,
+}
+
+impl RedirectPolicy {
+    /// The number of redirects reqwest follows by default.
+    const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+    /// Builds the redirect policy for reqwest clients that download files.
+    fn client_policy(self) -> Policy {
+        if self == Self::default() {
+            return Policy::default();
+        }
+
+        let max_redirects = self.max_redirects.unwrap_or(Self::DEFAULT_MAX_REDIRECTS);
+        let same_host_only = self.same_host_only;
+        Policy::custom(move |attempt| {
+            let followed = attempt.previous().len();
+            if followed > max_redirects {
+                let message = format!(
+                    "{} redirected more than {max_redirects} times",
+                    attempt.previous()[0]
+                );
+                return attempt.error(message);
+            }
+            if same_host_only && attempt.previous()[0].host_str() != attempt.url().host_str() {
+                let message = format!(
+                    "{} redirected to {}, which is on a different host",
+                    attempt.previous()[0],
+                    attempt.url()
+                );
+                return attempt.error(message);
+            }
+            attempt.follow()
+        })
+    }
+}
+
+/// How to handle files in a download's destination directory that have the same names as the
+/// files being downloaded, but that `refman` has no record of downloading.
+///
+/// Files that `refman` downloaded itself are recorded in the registry, and are either left alone
+/// because they still pass their checksums or are expected to be replaced. Any other file with
+/// the same name is probably unrelated, e.g. in a shared directory, so overwriting it may lose
+/// data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExistingFiles {
+    /// Log a warning for each unrecorded file, then overwrite it. This is the default, and
+    /// matches how `refman` behaved before it checked for existing files.
+    #[default]
+    Warn,
+    /// Overwrite unrecorded files without warning.
+    Overwrite,
+    /// Leave unrecorded files untouched and skip downloading the files that would replace them.
+    Skip,
+}
+
+impl DownloadOptions {
+    /// Returns how many files may be downloaded at once, which is always at least one.
+    #[must_use]
+    pub fn download_jobs(&self) -> usize {
+        self.jobs.unwrap_or(DEFAULT_DOWNLOAD_JOBS).max(1)
+    }
+
+    /// Returns how many times a failed request for a file is tried again.
+    #[must_use]
+    pub fn retry_count(&self) -> u32 {
+        self.retries.unwrap_or(DEFAULT_RETRIES)
+    }
+
+    /// Builds the HTTP client that is shared between every download in a run, applying any
+    /// connection pooling, keep-alive, and timeout settings from these options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reqwest is unable to initialize the client, e.g. because the
+    /// system's TLS backend cannot be loaded.
+    pub fn build_client(&self) -> Result<Client> {
+        let mut builder = client_builder();
+        if let Some(max_idle) = self.max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        if let Some(idle_timeout) = self.idle_timeout {
+            builder = builder.pool_idle_timeout(idle_timeout);
+        }
+        if let Some(keepalive) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(keepalive);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        builder = builder.redirect(self.redirects.client_policy());
+
+        Ok(builder.build()?)
+    }
+}
 
 /// A helper function for downloading files with retry attempts built in.
 ///
@@ -26,10 +349,19 @@ use crate::validate::UnvalidatedFile;
 /// * `url` - A string slice containing the URL to download from
 /// * `client` - A reqwest HTTP client instance to make the request with
 /// * `target_dir` - A Path reference specifying where to save the downloaded file
+/// * `range` - An optional range of bytes to download instead of the whole file
+/// * `mirror_structure` - Whether to place the file under subdirectories of `target_dir` that
+///   mirror the directories in the URL's path, rather than directly in `target_dir`
+/// * `redirects` - Limits on the redirects followed on the way to the file, which should match
+///   the redirect policy `client` was built with
+/// * `heartbeat` - How often to log the download's progress while it runs, if at all
+/// * `retries` - How many times to try the request again if it fails
 ///
 /// # Returns
 ///
-/// Returns a Result containing () on success, or an error if the download fails after retries
+/// Returns the downloaded file with its local path filled in, along with the headers the server
+/// sent that can later tell whether the file changed, or an error if the download fails after
+/// retries
 ///
 /// # Errors
 ///
@@ -43,119 +375,1043 @@ use crate::validate::UnvalidatedFile;
 /// # Details
 ///
 /// The function implements:
-/// - Automatic retries with exponential backoff
+/// - Automatic retries with an exponential backoff, capped at [`MAX_RETRY_BACKOFF`]
 /// - Streaming downloads to handle large files
 /// - Progress tracking via log messages
-/// - Filename extraction from URLs
+/// - Filename extraction from `Content-Disposition` headers or URLs
 /// - HTTP status code handling
 /// - Error recovery and retry logic
+#[allow(clippy::too_many_arguments)]
 pub async fn request_dataset(
     file_to_request: UnvalidatedFile,
     client: Client,
     target_dir: Arc<PathBuf>,
     multi_progbar: Arc<MultiProgress>,
-) -> Result<UnvalidatedFile> {
+    range: Option<ByteRange>,
+    mirror_structure: bool,
+    redirects: RedirectPolicy,
+    heartbeat: Option<Duration>,
+    retries: u32,
+) -> Result<(UnvalidatedFile, CacheValidators)> {
+    // registered local paths and `file://` URLs are copied into the target directory rather than requested
+    if let Some(source) = local_source(file_to_request.url()) {
+        let copied = copy_local_file(&source, &target_dir).await?;
+        return Ok((file_to_request.set_path(copied), CacheValidators::default()));
+    }
+
+    let file_dir = if mirror_structure {
+        let url = Url::parse(file_to_request.url())?;
+        mirrored_dir(&url, &target_dir)
+    } else {
+        target_dir.to_path_buf()
+    };
+    let StreamedDownload {
+        path, validators, ..
+    } = stream_download(
+        file_to_request.url(),
+        &client,
+        Some(&file_dir),
+        &multi_progbar,
+        range,
+        redirects,
+        heartbeat,
+        retries,
+    )
+    .await?;
+    let downloaded_file =
+        path.ok_or_else(|| eyre!("No file was written for {}", file_to_request.url()))?;
+
+    let downloaded = file_to_request.set_path(downloaded_file);
+
+    Ok((downloaded, validators))
+}
+
+/// Returns the path of the local file `uri` names, if it's a `file://` URL or a path rather
+/// than a URL.
+fn local_source(uri: &str) -> Option<PathBuf> {
+    match Url::parse(uri) {
+        Ok(url) if url.scheme() == "file" => url.to_file_path().ok(),
+        Ok(_) => None,
+        Err(_) => Some(PathBuf::from(uri)),
+    }
+}
+
+/// Copies a local file into `target_dir` under its own name, returning the path of the copy.
+///
+/// When the copy would land on the source itself, e.g. because the file was registered from
+/// inside the target directory, or through a symlink to it, the file is treated as already
+/// present and left alone, since opening it for writing would truncate it.
+///
+/// # Errors
+///
+/// Returns an error if `source` has no file name, or if the target directory can't be created or
+/// the file can't be copied into it.
+async fn copy_local_file(source: &Path, target_dir: &Path) -> Result<PathBuf> {
+    let name = source
+        .file_name()
+        .ok_or_else(|| eyre!("The local path {} does not name a file", source.display()))?;
+    let destination = target_dir.join(name);
+    if same_file(source, &destination).await {
+        info!(
+            "{} is already in {}, so it won't be copied.",
+            source.display(),
+            target_dir.display()
+        );
+        return Ok(destination);
+    }
+
+    fs::create_dir_all(target_dir).await?;
+    fs::copy(source, &destination).await.wrap_err_with(|| {
+        format!(
+            "Unable to copy {} to {}",
+            source.display(),
+            destination.display()
+        )
+    })?;
+
+    Ok(destination)
+}
+
+/// Whether two paths resolve to the same file once symlinks and relative components are
+/// resolved. Paths that don't exist are never the same file as anything.
+async fn same_file(first: &Path, second: &Path) -> bool {
+    match (
+        fs::canonicalize(first).await,
+        fs::canonicalize(second).await,
+    ) {
+        (Ok(first), Ok(second)) => first == second,
+        _ => false,
+    }
+}
+
+/// Downloads a single file from a URL into a directory, returning metadata about the result.
+///
+/// This exposes the same resilient downloader `refman` uses for its registries as a standalone
+/// building block, without needing a `Project` or registry. The URL is checked with `lychee`,
+/// downloaded with automatic retries and exponential backoff, streamed to disk under the
+/// filename the server suggests, or else the one at the end of the URL, and finally hashed.
+///
+/// Because a bare URL carries no information about its file format, no format-specific
+/// parsing is performed, and the returned [`ValidatedFile`] has `validated` set to `false`.
+/// Its `hash` and `local_path` are always filled in.
+///
+/// # Arguments
+///
+/// * `url` - The URL of the file to download
+/// * `dest` - The directory to write the file into, which will be created if needed
+/// * `client` - A reqwest HTTP client to make the request with
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The URL is invalid, broken, or neither the server nor the URL provides a filename
+/// - The download fails after all retries or the server returns a non-success status
+/// - The destination directory or file cannot be created or written
+/// - The downloaded file cannot be read back for hashing
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::path::Path;
+/// # async fn run() -> color_eyre::Result<()> {
+/// let client = reqwest::Client::new();
+/// let file = refman::download_file(
+///     "https://example.com/genome.fasta",
+///     Path::new("references"),
+///     &client,
+/// )
+/// .await?;
+/// println!("{} has the MD5 checksum {:?}", file.local_path.display(), file.hash);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn download_file(url: &str, dest: &Path, client: &Client) -> Result<ValidatedFile> {
+    let multi_progbar = MultiProgress::new();
+    let StreamedDownload {
+        path,
+        hash,
+        sha256,
+        validators,
+    } = stream_download(
+        url,
+        client,
+        Some(dest),
+        &multi_progbar,
+        None,
+        RedirectPolicy::default(),
+        None,
+        DEFAULT_RETRIES,
+    )
+    .await?;
+    let local_path = path.ok_or_else(|| eyre!("No file was written for {url}"))?;
+
+    Ok(ValidatedFile {
+        uri: url.to_string(),
+        validated: false,
+        hash: Some(hash),
+        last_validated: None,
+        sampled_records: None,
+        sha256: Some(sha256),
+        algorithm: HashAlgorithm::default(),
+        etag: validators.etag,
+        last_modified: validators.last_modified,
+        size_bytes: file_size(&local_path),
+        local_path,
+    })
+}
+
+/// Streams the file at `url` and computes its MD5 checksum without keeping any of its bytes,
+/// returning the checksum in the same format as
+/// [`hash_valid_download`](crate::validate::hash_valid_download).
+///
+/// # Errors
+///
+/// Returns the same errors as downloading the file would, other than those from writing it to
+/// disk.
+pub(crate) async fn request_checksum(
+    url: String,
+    client: Client,
+    multi_progbar: Arc<MultiProgress>,
+    redirects: RedirectPolicy,
+    heartbeat: Option<Duration>,
+    retries: u32,
+) -> Result<String> {
+    let StreamedDownload { hash, .. } = stream_download(
+        &url,
+        &client,
+        None,
+        &multi_progbar,
+        None,
+        redirects,
+        heartbeat,
+        retries,
+    )
+    .await?;
+    Ok(hash)
+}
+
+/// Downloads the signature at `url` into `target_dir`, alongside the file it signs, returning
+/// where it was written.
+///
+/// # Errors
+///
+/// Returns the same errors as downloading any other file would.
+pub(crate) async fn request_signature(
+    url: String,
+    client: Client,
+    target_dir: Arc<PathBuf>,
+    multi_progbar: Arc<MultiProgress>,
+    redirects: RedirectPolicy,
+) -> Result<PathBuf> {
+    let StreamedDownload { path, .. } = stream_download(
+        &url,
+        &client,
+        Some(&target_dir),
+        &multi_progbar,
+        None,
+        redirects,
+        None,
+        DEFAULT_RETRIES,
+    )
+    .await?;
+    path.ok_or_else(|| eyre!("No signature was written for {url}"))
+}
+
+/// The outcome of streaming a download from a URL.
+struct StreamedDownload {
+    /// Where the file's bytes were written, if they were kept
+    path: Option<PathBuf>,
+    /// The MD5 checksum of the file's bytes, computed as they arrived
+    hash: String,
+    /// The SHA-256 checksum of the file's bytes, computed alongside the MD5 checksum
+    sha256: String,
+    /// The headers the server sent that can later tell whether the file changed
+    validators: CacheValidators,
+}
+
+/// The `ETag` and `Last-Modified` headers a server sent with a file, which can later be sent back
+/// in a conditional request to ask whether the file has changed since.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CacheValidators {
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    fn from_response(response: &reqwest::Response) -> Self {
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    /// Whether there's anything to send in a conditional request.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// The value to send in an `If-Range` header, so that a server only sends the rest of a file
+    /// if it's still the one these validators came with. Weak `ETag`s can't be compared that
+    /// way, so the `Last-Modified` date is sent when the `ETag` is weak or missing.
+    fn if_range(&self) -> Option<&str> {
+        self.etag
+            .as_deref()
+            .filter(|etag| !etag.starts_with("W/"))
+            .or(self.last_modified.as_deref())
+    }
+}
+
+/// Asks the server whether the file at `url` has changed since it sent `validators` with it,
+/// using a conditional GET with `If-None-Match`, or `If-Modified-Since` when there's no `ETag`.
+/// Only the response's headers are read, so the file itself isn't downloaded. S3 objects are
+/// instead compared against the `ETag` and modification time their bucket reports now.
+///
+/// # Errors
+///
+/// Returns an error if the request fails or the server responds with an error status.
+pub(crate) async fn remote_changed(
+    client: &Client,
+    url: &str,
+    validators: &CacheValidators,
+) -> Result<bool> {
+    if let Some(s3_url) = Url::parse(url).ok().filter(s3::is_s3) {
+        let current = s3::fetch_validators(&s3_url).await?;
+        return Ok(match &validators.etag {
+            Some(etag) => current.etag.as_ref() != Some(etag),
+            None => current.last_modified != validators.last_modified,
+        });
+    }
+    let mut request = with_headers(client.get(url), url);
+    if let Some(etag) = &validators.etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    } else if let Some(last_modified) = &validators.last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified);
+    }
+    let response = request
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| eyre!("Unable to ask whether {url} has changed: {e}"))?;
+
+    Ok(response.status() != StatusCode::NOT_MODIFIED)
+}
+
+/// Fetches the headers the server currently sends with the file at `url` that can later tell
+/// whether it changed, with a HEAD request, or those of an S3 object from its bucket.
+///
+/// # Errors
+///
+/// Returns an error if the request fails or the server responds with an error status.
+pub(crate) async fn fetch_validators(client: &Client, url: &str) -> Result<CacheValidators> {
+    if let Some(s3_url) = Url::parse(url).ok().filter(s3::is_s3) {
+        return s3::fetch_validators(&s3_url).await;
+    }
+    let response = with_headers(client.head(url), url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| eyre!("Unable to request the headers of {url}: {e}"))?;
+
+    Ok(CacheValidators::from_response(&response))
+}
+
+/// Checks, downloads, and streams the file at `url`, hashing its bytes as they arrive. When a
+/// `target_dir` is provided, the bytes are also written into a file there; otherwise they are
+/// discarded once hashed. Files take the name the server suggests in a `Content-Disposition`
+/// header, or else the one at the end of the URL, and are written under a `.part` name until
+/// they're complete. A partial file left by an interrupted run is resumed with a byte range
+/// request when the server supports one. When a `range` is provided, only those bytes of the
+/// file are downloaded. The URL is checked while following no more redirects than `redirects` allows.
+/// With a `heartbeat`, the download's progress is logged at that interval while it runs. Failed
+/// requests are tried again up to `retries` times.
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
+async fn stream_download(
+    url: &str,
+    client: &Client,
+    target_dir: Option<&Path>,
+    multi_progbar: &MultiProgress,
+    range: Option<ByteRange>,
+    redirects: RedirectPolicy,
+    heartbeat: Option<Duration>,
+    retries: u32,
+) -> Result<StreamedDownload> {
     // Make sure the url is valid with lychee
-    let url = file_to_request.url();
-    let valid_url = check_url(url).await?;
+    let valid_url = check_url_with_redirects(url, redirects).await?;
 
     // If it is, log out that it's valid
     debug!("Downloading dataset file from {:?}", valid_url);
 
-    // Download the file (retrying if necessary), and access its size
-    let response = match download_with_retries(&client, valid_url.as_str()).await {
-        Ok(r) => {
-            debug!("Successfully downloaded from {:?}", valid_url);
-            r
-        }
-        Err(e) => {
-            return Err(eyre!(
-                "The request encountered an error: {:?}. Skipping.",
-                e
-            ));
-        }
+    // bytes are written to a partial file named after the URL, which only gets its final name once the transfer is
+    // complete. Because the partial file's name doesn't depend on the response, an interrupted run's partial file can
+    // be found again, and picked up where it left off, before the server is asked for anything.
+    let url_filename = uri_to_filename(&valid_url);
+    let part_path = target_dir.map(|dir| dir.join(partial_name(&valid_url)));
+    let written = match &part_path {
+        Some(path) => fs::metadata(path).await.map_or(0, |meta| meta.len()),
+        None => 0,
+    };
+    // the bytes written so far may only be picked up if the file hasn't changed since they were sent
+    let part_validators = match &part_path {
+        Some(path) if written > 0 => read_part_validators(path).await,
+        _ => CacheValidators::default(),
     };
-    let total_size = response.content_length().unwrap_or(0);
 
-    // attempt to pull out the filename from the url
-    let filename = uri_to_filename(&valid_url)?;
+    // FTP servers are asked for the file over FTP, but its bytes are written and hashed like those of any other
+    // download
+    if ftp::is_ftp(&valid_url) {
+        let filename = url_filename?.to_string();
+        let (transfer, resumed) = request_ftp_download(&valid_url, range, written, retries).await?;
+        let resumed_bytes = if resumed { written } else { 0 };
+        let total_size = transfer.size.map(|size| size + resumed_bytes);
+        let chunks = stream::unfold(transfer.chunks, |mut chunks| async move {
+            chunks.recv().await.map(|chunk| (chunk, chunks))
+        });
+        let (path, hash, sha256) = write_download(
+            chunks,
+            url,
+            &filename,
+            target_dir.map(|dir| dir.join(&filename)),
+            part_path,
+            resumed_bytes,
+            total_size,
+            multi_progbar,
+            heartbeat,
+        )
+        .await?;
+        return Ok(StreamedDownload {
+            path,
+            hash,
+            sha256,
+            validators: CacheValidators::default(),
+        });
+    }
 
-    // if the response was successful, stream the file's bytes into the output file name
-    let downloaded_file = if response.status().is_success() {
-        let file_path = target_dir.join(filename);
-        if let Some(parent) = file_path.parent() {
-            fs::create_dir_all(parent).await?;
+    // objects in S3 buckets are requested through the S3 API, and are named after the end of their key
+    if s3::is_s3(&valid_url) {
+        let filename = url_filename?.to_string();
+        let (transfer, resumed) =
+            request_s3_download(&valid_url, range, written, &part_validators, retries).await?;
+        let resumed_bytes = if resumed { written } else { 0 };
+        if let (Some(path), false) = (&part_path, resumed) {
+            write_part_validators(path, &transfer.validators).await;
         }
+        let total_size = transfer.size.map(|size| size + resumed_bytes);
+        let chunks = stream::unfold(transfer.body, |mut body| async move {
+            body.next().await.map(|chunk| (chunk, body))
+        });
+        let (path, hash, sha256) = write_download(
+            chunks,
+            url,
+            &filename,
+            target_dir.map(|dir| dir.join(&filename)),
+            part_path.clone(),
+            resumed_bytes,
+            total_size,
+            multi_progbar,
+            heartbeat,
+        )
+        .await?;
+        if let Some(part_path) = &part_path {
+            remove_part_validators(part_path).await;
+        }
+        return Ok(StreamedDownload {
+            path,
+            hash,
+            sha256,
+            validators: transfer.validators,
+        });
+    }
 
-        // Create and configure the progress bar.
-        let prog_bar = multi_progbar.add(ProgressBar::new(total_size));
-        prog_bar.set_style(
-            ProgressStyle::default_bar()
-                .template(
-                    "{msg} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})",
-                )?
-                .progress_chars("##-"),
-        );
-        prog_bar.set_message(format!("Writing data into {filename}..."));
-
-        let mut file = File::create(&file_path).await?;
-        let mut stream = response.bytes_stream();
+    // Download the file (retrying if necessary), and access its size
+    let (response, resumed) = request_download(
+        client,
+        &valid_url,
+        range,
+        written,
+        &part_validators,
+        retries,
+    )
+    .await?;
+    // a server that ignores the range sends back the whole file, which must not be mistaken for the requested part
+    if range.is_some() && response.status() == StatusCode::OK {
+        return Err(eyre!(
+            "The server for {url} does not support byte ranges, so the requested range could not be downloaded"
+        ));
+    }
+    let resumed_bytes = if resumed { written } else { 0 };
+    let total_size = response
+        .content_length()
+        .map(|length| length + resumed_bytes);
 
-        while let Some(chunk_result) = stream.next().await {
-            match chunk_result {
-                Ok(chunk) => {
-                    file.write_all(&chunk).await?;
-                    prog_bar.inc(chunk.len() as u64);
-                }
-                Err(e) => {
-                    error!("Error while reading chunk from {}: {}", url, e);
-                    return Err(e.into());
-                }
-            }
+    // if the response was successful, stream the file's bytes into the output file name, if there is one, and into
+    // the hasher
+    if response.status().is_success() {
+        let validators = CacheValidators::from_response(&response);
+        if let (Some(path), false) = (&part_path, resumed) {
+            write_part_validators(path, &validators).await;
         }
-        prog_bar.set_message(format!("Writing data into {filename}...Done!"));
 
-        // pass on the file path if all is well
-        file_path
+        // servers that hand out files from a generic endpoint, e.g. `download?file=genome.fa`, often suggest a better
+        // name for them than the end of the URL
+        let filename = match suggested_filename(&response) {
+            Some(filename) => filename,
+            None => url_filename
+                .wrap_err_with(|| format!("The server didn't suggest a filename for {url} either"))?
+                .to_string(),
+        };
+        let file_path = target_dir.map(|dir| dir.join(&filename));
+        let chunks = response.bytes_stream();
+        let (path, hash, sha256) = write_download(
+            chunks,
+            url,
+            &filename,
+            file_path,
+            part_path.clone(),
+            resumed_bytes,
+            total_size,
+            multi_progbar,
+            heartbeat,
+        )
+        .await?;
+        if let Some(part_path) = &part_path {
+            remove_part_validators(part_path).await;
+        }
+        Ok(StreamedDownload {
+            path,
+            hash,
+            sha256,
+            validators,
+        })
     } else if response.status().as_u16() == 404 {
         warn!("File not found: {}", url);
-        return Err(eyre!(
+        Err(eyre!(
             "Failed to download {}: HTTP {}",
-            filename,
+            url,
             response.status()
-        ));
+        ))
     } else {
-        error!(
-            "Failed to download {}: HTTP {}",
-            filename,
-            response.status()
-        );
-        return Err(eyre!(
+        error!("Failed to download {}: HTTP {}", url, response.status());
+        Err(eyre!(
             "Failed to download {}: HTTP {}",
-            filename,
+            url,
             response.status()
-        ));
+        ))
+    }
+}
+
+/// Writes the chunks of a download's bytes into `part_path`, if the download is being kept,
+/// hashing them as they arrive, and gives the finished file its final name, `file_path`. When
+/// `resumed_bytes` were already written by an earlier, interrupted run, the chunks are appended
+/// to them, and they count toward the checksums. Returns where the file was written along with
+/// its MD5 and SHA-256 checksums.
+#[allow(clippy::too_many_arguments)]
+async fn write_download<B, E>(
+    chunks: impl Stream<Item = StdResult<B, E>>,
+    url: &str,
+    filename: &str,
+    file_path: Option<PathBuf>,
+    part_path: Option<PathBuf>,
+    resumed_bytes: u64,
+    total_size: Option<u64>,
+    multi_progbar: &MultiProgress,
+    heartbeat: Option<Duration>,
+) -> Result<(Option<PathBuf>, String, String)>
+where
+    B: AsRef<[u8]>,
+    E: Error + Send + Sync + 'static,
+{
+    if let Some(parent) = file_path.as_deref().and_then(Path::parent) {
+        fs::create_dir_all(parent).await?;
+    }
+
+    // Create and configure the progress bar.
+    let prog_bar = multi_progbar.add(ProgressBar::new(total_size.unwrap_or_default()));
+    prog_bar.set_style(
+        ProgressStyle::default_bar()
+            .template(
+                "{msg} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})",
+            )?
+            .progress_chars("##-"),
+    );
+    let action = if file_path.is_some() {
+        "Writing data into"
+    } else {
+        "Computing checksum for"
     };
+    prog_bar.set_message(format!("{action} {filename}..."));
 
-    let downloaded = file_to_request.set_path(downloaded_file);
+    let mut hasher = Context::new();
+    let mut sha256_hasher = Sha256::new();
+    let mut file = match &part_path {
+        // the bytes already written count toward the checksums of the whole file
+        Some(path) if resumed_bytes > 0 => {
+            let mut partial = File::open(path).await?;
+            let mut buffer = vec![0; 64 * 1024];
+            loop {
+                let read = partial.read(&mut buffer).await?;
+                if read == 0 {
+                    break;
+                }
+                hasher.consume(&buffer[..read]);
+                sha256_hasher.update(&buffer[..read]);
+            }
+            prog_bar.set_position(resumed_bytes);
+            Some(OpenOptions::new().append(true).open(path).await?)
+        },
+        Some(path) => Some(File::create(path).await?),
+        None => None,
+    };
+    let mut stream = pin!(chunks);
+    let mut heartbeat = heartbeat.map(|interval| {
+        Heartbeat::new(
+            filename,
+            total_size.map(|size| size.saturating_sub(resumed_bytes)),
+            interval,
+        )
+    });
+
+    while let Some(chunk_result) = stream.next().await {
+        match chunk_result {
+            Ok(chunk) => {
+                let chunk = chunk.as_ref();
+                if let Some(file) = file.as_mut() {
+                    file.write_all(chunk).await?;
+                }
+                hasher.consume(chunk);
+                sha256_hasher.update(chunk);
+                prog_bar.inc(chunk.len() as u64);
+                if let Some(heartbeat) = heartbeat.as_mut() {
+                    heartbeat.record(chunk.len() as u64);
+                }
+            },
+            Err(e) => {
+                error!("Error while reading chunk from {}: {}", url, e);
+                return Err(e.into());
+            },
+        }
+    }
+    // make sure every byte has reached the file before anything downstream reads it back, and only then give
+    // it its final name
+    if let Some(file) = file.as_mut() {
+        file.flush().await?;
+    }
+    drop(file);
+    if let (Some(part_path), Some(file_path)) = (&part_path, &file_path) {
+        fs::rename(part_path, file_path).await?;
+    }
+    prog_bar.set_message(format!("{action} {filename}...Done!"));
+
+    // pass on the file path and checksums if all is well
+    let hash = format!("{:x}", hasher.compute());
+    let sha256 = format!("{:x}", sha256_hasher.finalize());
+    Ok((file_path, hash, sha256))
+}
+
+/// Logs the progress of a download at a fixed interval, independently of its progress bar,
+/// which never reaches log files.
+struct Heartbeat<'a> {
+    filename: &'a str,
+    total_size: Option<u64>,
+    interval: Duration,
+    downloaded: u64,
+    started: Instant,
+    last_beat: Instant,
+}
+
+impl<'a> Heartbeat<'a> {
+    fn new(filename: &'a str, total_size: Option<u64>, interval: Duration) -> Self {
+        let now = Instant::now();
+        Self {
+            filename,
+            total_size,
+            interval,
+            downloaded: 0,
+            started: now,
+            last_beat: now,
+        }
+    }
+
+    /// Counts `bytes` more bytes as downloaded, logging the progress so far if at least one
+    /// interval has passed since it was last logged.
+    fn record(&mut self, bytes: u64) {
+        self.downloaded += bytes;
+        if self.last_beat.elapsed() < self.interval {
+            return;
+        }
+        self.last_beat = Instant::now();
+        info!("{}", self.message(self.started.elapsed()));
+    }
+
+    fn message(&self, elapsed: Duration) -> String {
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let rate = (self.downloaded as f64 / elapsed.as_secs_f64().max(f64::EPSILON)) as u64;
+        let progress = match self.total_size.filter(|&size| size > 0) {
+            Some(total_size) => format!(
+                "{} of {} ({}%)",
+                HumanBytes(self.downloaded),
+                HumanBytes(total_size),
+                self.downloaded * 100 / total_size
+            ),
+            None => HumanBytes(self.downloaded).to_string(),
+        };
+        format!(
+            "Still downloading {}: {progress} after {}, at {}/s",
+            self.filename,
+            HumanDuration(elapsed),
+            HumanBytes(rate)
+        )
+    }
+}
+
+/// Requests the file at `url`, or the bytes of it in `range`, retrying if necessary. When an
+/// earlier, interrupted download already wrote `written` bytes, only the rest is requested, on
+/// the condition that the file still matches the `validators` it was sent with then, and the
+/// returned flag says whether the response picks up right where those bytes end. If it doesn't,
+/// e.g. because the file changed or the server doesn't support byte ranges, the response holds
+/// everything requested, so that the download can start over.
+async fn request_download(
+    client: &Client,
+    url: &Url,
+    range: Option<ByteRange>,
+    written: u64,
+    validators: &CacheValidators,
+    retries: u32,
+) -> Result<(reqwest::Response, bool)> {
+    // without validators, there's no telling whether the bytes written so far are from the same file
+    let if_range = validators.if_range();
+    let resume_range = if_range.and_then(|_| resume_range(range, written));
+    let response = download_with_retries(
+        client,
+        url.as_str(),
+        resume_range.or(range),
+        if_range.filter(|_| resume_range.is_some()),
+        retries,
+    )
+    .await
+    .map_err(|e| eyre!("The request encountered an error: {:?}. Skipping.", e))?;
+    debug!("Successfully downloaded from {:?}", url);
+
+    // only a partial response that starts right where the partial file ends can be appended to it
+    let resumed = resume_range.is_some_and(|resume_range| {
+        response.status() == StatusCode::PARTIAL_CONTENT
+            && content_range_start(&response) == Some(resume_range.start)
+    });
+    if resumed {
+        info!("Resuming the download of {url} from {written} bytes written by an earlier run");
+        return Ok((response, true));
+    }
+    if written == 0 {
+        return Ok((response, false));
+    }
+
+    info!("Unable to resume the download of {url}, so it will start over");
+    // a full response to a request for the whole file can be used as is, but any other has to be requested again
+    if range.is_none() && response.status() == StatusCode::OK {
+        return Ok((response, false));
+    }
+    let response = download_with_retries(client, url.as_str(), range, None, retries)
+        .await
+        .map_err(|e| eyre!("The request encountered an error: {:?}. Skipping.", e))?;
 
-    Ok(downloaded)
+    Ok((response, false))
 }
 
-async fn download_with_retries(client: &Client, url: &str) -> Result<reqwest::Response> {
+/// Starts the FTP transfer of the file at `url`, or of the bytes of it in `range`, retrying if
+/// necessary, like [`request_download`] does over HTTP. When an earlier, interrupted download
+/// already wrote `written` bytes, the server is first asked to start right after them, and the
+/// returned flag says whether it did. If it didn't, the transfer holds everything requested, so
+/// that the download can start over.
+async fn request_ftp_download(
+    url: &Url,
+    range: Option<ByteRange>,
+    written: u64,
+    retries: u32,
+) -> Result<(FtpTransfer, bool)> {
+    // FTP transfers are given where to start and how many bytes to send, rather than a range
+    let bounds = |range: ByteRange| (range.start, range.end.map(|end| end + 1 - range.start));
+
+    if let Some(resume_range) = resume_range(range, written) {
+        let (start, limit) = bounds(resume_range);
+        match ftp::open_transfer(url, start, limit).await {
+            Ok(transfer) => {
+                info!(
+                    "Resuming the download of {url} from {written} bytes written by an earlier run"
+                );
+                return Ok((transfer, true));
+            },
+            Err(e) => info!("Unable to resume the download of {url}, so it will start over: {e}"),
+        }
+    }
+
+    let (start, limit) = range.map_or((0, None), bounds);
+    let transfer = with_retries(url.as_str(), retries, || {
+        ftp::open_transfer(url, start, limit)
+    })
+    .await
+    .map_err(|e| eyre!("The request encountered an error: {:?}. Skipping.", e))?;
+
+    Ok((transfer, false))
+}
+
+/// Starts downloading the S3 object at `url`, or the bytes of it in `range`, retrying if
+/// necessary, like [`request_download`] does over HTTP. When an earlier, interrupted download
+/// already wrote `written` bytes, the bucket is first asked for only the rest, as long as the
+/// object still has the `ETag` in `validators`, and the returned
+/// flag says whether it sent them. If it didn't, the transfer holds everything requested, so that
+/// the download can start over.
+async fn request_s3_download(
+    url: &Url,
+    range: Option<ByteRange>,
+    written: u64,
+    validators: &CacheValidators,
+    retries: u32,
+) -> Result<(S3Transfer, bool)> {
+    // the bytes written so far are only picked up if the object still has the `ETag` it had then
+    let etag = validators.etag.as_deref();
+    if let Some(resume_range) = etag.and_then(|_| resume_range(range, written)) {
+        match s3::open_transfer(url, Some(resume_range), etag).await {
+            Ok(transfer) => {
+                info!(
+                    "Resuming the download of {url} from {written} bytes written by an earlier run"
+                );
+                return Ok((transfer, true));
+            },
+            Err(e) => info!("Unable to resume the download of {url}, so it will start over: {e}"),
+        }
+    }
+
+    let transfer = with_retries(url.as_str(), retries, || {
+        s3::open_transfer(url, range, None)
+    })
+    .await
+    .map_err(|e| eyre!("The request encountered an error: {:?}. Skipping.", e))?;
+
+    Ok((transfer, false))
+}
+
+/// Returns the name that the bytes of a download from `url` are written under until the transfer
+/// is complete, e.g. `genome.fa.gz.part` for `https://example.com/genome.fa.gz`. URLs that don't
+/// end with a filename are named after their MD5 checksum instead.
+fn partial_name(url: &Url) -> String {
+    match uri_to_filename(url) {
+        Ok(filename) => format!("{filename}.part"),
+        Err(_) => format!("{:x}.part", md5::compute(url.as_str())),
+    }
+}
+
+/// Returns where the validators of the response a partial file's bytes came from are kept, e.g.
+/// `genome.fa.gz.part.validators`, so that resuming the download can make sure the file hasn't
+/// changed since.
+fn part_validators_path(part_path: &Path) -> PathBuf {
+    let mut path = part_path.as_os_str().to_owned();
+    path.push(".validators");
+    PathBuf::from(path)
+}
+
+/// Reads the validators recorded for a partial file, which are empty if none were recorded.
+async fn read_part_validators(part_path: &Path) -> CacheValidators {
+    fs::read(part_validators_path(part_path))
+        .await
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Records the validators of the response a partial file's bytes are coming from. A partial
+/// file without them can't be resumed, so failing to record them is only logged.
+async fn write_part_validators(part_path: &Path, validators: &CacheValidators) {
+    let path = part_validators_path(part_path);
+    if validators.is_empty() {
+        remove_part_validators(part_path).await;
+        return;
+    }
+    let written: Result<()> = async {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(&path, serde_json::to_vec(validators)?).await?;
+        Ok(())
+    }
+    .await;
+    if let Err(e) = written {
+        warn!(
+            "Unable to record the version of the file being downloaded to {}, so the download can't be resumed if it's interrupted: {e}",
+            part_path.display()
+        );
+    }
+}
+
+/// Removes the validators recorded for a partial file once it's no longer needed.
+async fn remove_part_validators(part_path: &Path) {
+    let path = part_validators_path(part_path);
+    match fs::remove_file(&path).await {
+        Ok(()) => {},
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+        Err(e) => warn!("Unable to remove {}: {e}", path.display()),
+    }
+}
+
+/// Returns the filename the server suggests for the file in `response` with a
+/// `Content-Disposition` header, if there is one. See [`content_disposition_filename`].
+fn suggested_filename(response: &reqwest::Response) -> Option<String> {
+    let header = response.headers().get(CONTENT_DISPOSITION)?.to_str().ok()?;
+    let filename = content_disposition_filename(header)?;
+    debug!(
+        "Using the filename {filename} suggested by the server for {}",
+        response.url()
+    );
+    Some(filename)
+}
+
+/// Extracts the filename from a `Content-Disposition` header like
+/// `attachment; filename="genome.fa.gz"`, preferring the UTF-8 encoded `filename*` parameter when
+/// both are present. Any directories in the suggested name are left out, and names that can't be
+/// written as a plain file, like `..`, are ignored, so that no header can place a file outside of
+/// the download directory.
+fn content_disposition_filename(header: &str) -> Option<String> {
+    let mut plain = None;
+    let mut extended = None;
+    for parameter in header.split(';') {
+        let Some((key, value)) = parameter.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "filename" => plain = Some(value.trim_matches('"').to_string()),
+            // e.g. `filename*=UTF-8''genome%20v2.fa`, where the language between the quotes is optional
+            "filename*" => {
+                let mut parts = value.splitn(3, '\'');
+                if let (Some(charset), Some(_), Some(encoded)) =
+                    (parts.next(), parts.next(), parts.next())
+                {
+                    if charset.eq_ignore_ascii_case("utf-8") {
+                        extended = percent_decode(encoded);
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    let suggested = extended.or(plain)?;
+    let name = suggested.rsplit(['/', '\\']).next()?;
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// Decodes the `%XX` escapes in `encoded`, returning `None` if an escape is malformed or the
+/// decoded bytes aren't valid UTF-8.
+pub(crate) fn percent_decode(encoded: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut remaining = encoded.bytes();
+    while let Some(byte) = remaining.next() {
+        if byte == b'%' {
+            let high = char::from(remaining.next()?).to_digit(16)?;
+            let low = char::from(remaining.next()?).to_digit(16)?;
+            bytes.push(u8::try_from(high * 16 + low).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Returns the range to request to pick up an interrupted download that already wrote `written`
+/// bytes of `range`, or of the whole file when `range` is `None`. Returns `None` if nothing was
+/// written yet, or if more was written than the range holds, in which case the download has to
+/// start over.
+fn resume_range(range: Option<ByteRange>, written: u64) -> Option<ByteRange> {
+    if written == 0 {
+        return None;
+    }
+    let ByteRange { start, end } = range.unwrap_or(ByteRange {
+        start: 0,
+        end: None,
+    });
+    let start = start.checked_add(written)?;
+    if end.is_some_and(|end| start > end) {
+        return None;
+    }
+
+    Some(ByteRange { start, end })
+}
+
+/// Returns the offset of the first byte in a partial response, as given by its
+/// `Content-Range` header, e.g. `1000` for `bytes 1000-1999/5000`.
+fn content_range_start(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes ")?
+        .split_once('-')?
+        .0
+        .parse()
+        .ok()
+}
+
+async fn download_with_retries(
+    client: &Client,
+    url: &str,
+    range: Option<ByteRange>,
+    if_range: Option<&str>,
+    retries: u32,
+) -> Result<reqwest::Response> {
+    with_retries(url, retries, || {
+        run_http_request(client, url, range, if_range)
+    })
+    .await
+}
+
+/// Makes the request for the file at `url` that `request` sends, trying again up to `retries`
+/// times if it fails, with an exponential backoff capped at [`MAX_RETRY_BACKOFF`]. Requests that
+/// break the redirect policy aren't tried again, since they would only break it again.
+async fn with_retries<T, F, Fut>(url: &str, retries: u32, mut request: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
     let mut attempt = 0;
-    let max_attempts = 5;
+    let max_attempts = retries.saturating_add(1);
 
     loop {
         attempt += 1;
         debug!("Performing attempt #{} to download from {}.", &attempt, url);
-        match run_http_request(client, url).await {
+        match request().await {
             Ok(response) => {
                 debug!("Successfully downloaded files for URL {}", url);
                 return Ok(response);
-            }
+            },
             Err(e) => {
-                // early return an error if 5 attempts have been made unsuccessfully
+                // a redirect that breaks the redirect policy will break it again, so there's no use retrying
+                if let Some(redirect_error) = e
+                    .downcast_ref::<reqwest::Error>()
+                    .filter(|e| e.is_redirect())
+                {
+                    let reason = redirect_error
+                        .source()
+                        .map_or_else(|| redirect_error.to_string(), ToString::to_string);
+                    return Err(eyre!(
+                        "Refusing to follow a redirect while downloading {url}: {reason}"
+                    ));
+                }
+
+                // early return an error once every attempt has been made unsuccessfully
                 if attempt >= max_attempts {
                     error!(
                         "Failed to download files for URL {} after {} attempts:\n\n{}",
@@ -164,8 +1420,8 @@ async fn download_with_retries(client: &Client, url: &str) -> Result<reqwest::Re
                     return Err(e);
                 }
                 // if there are remaining attempts, add an exponential backoff before proceeding to give the
-                // server a break
-                let delay = Duration::from_secs(2_u64.pow(attempt));
+                // server a break, capped so that many retries don't wait for hours
+                let delay = retry_backoff(attempt);
                 warn!(
                     "Attempt {} failed for URL {}: {}. Retrying in {} seconds...",
                     attempt,
@@ -174,15 +1430,37 @@ async fn download_with_retries(client: &Client, url: &str) -> Result<reqwest::Re
                     delay.as_secs()
                 );
                 time::sleep(delay).await;
-            }
+            },
         }
     }
 }
 
-async fn run_http_request(client: &Client, url: &str) -> Result<reqwest::Response> {
+/// How long to wait before trying a request again after `attempt` attempts have failed, doubling
+/// with each attempt up to [`MAX_RETRY_BACKOFF`].
+fn retry_backoff(attempt: u32) -> Duration {
+    Duration::from_secs(2_u64.saturating_pow(attempt)).min(MAX_RETRY_BACKOFF)
+}
+
+async fn run_http_request(
+    client: &Client,
+    url: &str,
+    range: Option<ByteRange>,
+    if_range: Option<&str>,
+) -> Result<reqwest::Response> {
     debug!("Downloading {}", url);
 
-    let response = client.get(url).send().await?;
+    let mut request = with_headers(client.get(url), url);
+    if let Some(range) = range {
+        request = request.header(RANGE, format!("bytes={range}"));
+    }
+    if let Some(if_range) = if_range {
+        request = request.header(IF_RANGE, if_range);
+    }
+    let response = request.send().await?;
+    // a range that can't be satisfied is left for the caller to handle, since retrying won't change it
+    if range.is_some() && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        return Ok(response);
+    }
 
     if response.status().is_success() {
         debug!("Downloaded successful for {}", url);
@@ -234,8 +1512,49 @@ async fn run_http_request(client: &Client, url: &str) -> Result<reqwest::Respons
 /// connectivity or validation issues.
 #[inline]
 pub async fn check_url(url: &str) -> Result<Url> {
+    check_url_with_redirects(url, RedirectPolicy::default()).await
+}
+
+/// Checks a URL like [`check_url`], following no more redirects than `redirects` allows.
+///
+/// # Errors
+///
+/// Returns the same errors as [`check_url`], as well as an error if the URL redirects more times
+/// than allowed.
+pub async fn check_url_with_redirects(url: &str, redirects: RedirectPolicy) -> Result<Url> {
     debug!("Checking the requested URL '{url}' to make sure it's valid");
-    let response = lychee_lib::check(url).await?;
+
+    // lychee only checks HTTP URLs, so FTP servers are asked for the file directly
+    if let Some(ftp_url) = Url::parse(url).ok().filter(ftp::is_ftp) {
+        let size = ftp::check(&ftp_url).await.map_err(|e| {
+            eyre!("An error was encountered when checking the provided URI, '{url}': {e}")
+        })?;
+        info!(
+            "The FTP server for {url} has the file, which is {}, so the URL is valid and not broken.",
+            HumanBytes(size)
+        );
+        return Ok(ftp_url);
+    }
+    // likewise, S3 buckets are asked whether they have the object
+    if let Some(s3_url) = Url::parse(url).ok().filter(s3::is_s3) {
+        let size = s3::check(&s3_url).await.map_err(|e| {
+            eyre!("An error was encountered when checking the provided URI, '{url}': {e}")
+        })?;
+        info!(
+            "The S3 bucket for {url} has the object, which is {}, so the URL is valid and not broken.",
+            HumanBytes(size)
+        );
+        return Ok(s3_url);
+    }
+    let (user_agent, headers) = link_check_headers(url);
+    let response = ClientBuilder::builder()
+        .max_redirects(redirects.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS))
+        .user_agent(user_agent)
+        .custom_headers(headers)
+        .build()
+        .client()?
+        .check(url)
+        .await?;
     let response_body = response.body();
     match &response_body.status {
         Status::Ok(status_code) => {
@@ -245,7 +1564,7 @@ pub async fn check_url(url: &str) -> Result<Url> {
             );
             let parsed_url = Url::parse(response_body.uri.as_str())?;
             Ok(parsed_url)
-        }
+        },
         Status::Error(error_kind) => Err(eyre!(
             "An error was encountered when checking the provided URI, '{url}': {:?}",
             error_kind
@@ -260,7 +1579,7 @@ pub async fn check_url(url: &str) -> Result<Url> {
             Err(eyre!(
                 "The request for the provided URI, '{url}', timed out without a status code."
             ))
-        }
+        },
         Status::Redirected(status_code) => {
             warn!(
                 "The provided URI resulted in a redirect to a different resource with status code {:?}. `refman` will proceed, though it may download a different file than is expected.",
@@ -268,7 +1587,7 @@ pub async fn check_url(url: &str) -> Result<Url> {
             );
             let parsed_url = Url::parse(response_body.uri.as_str())?;
             Ok(parsed_url)
-        }
+        },
         Status::UnknownStatusCode(status_code) => Err(eyre!(
             "An unknown status code was received: {:?}",
             status_code.as_str()
@@ -283,7 +1602,7 @@ pub async fn check_url(url: &str) -> Result<Url> {
             );
             let parsed_url = Url::parse(response_body.uri.as_str())?;
             Ok(parsed_url)
-        }
+        },
         Status::Cached(cache_status) => {
             if let CacheStatus::Ok(_) = cache_status {
                 info!("A cached response is being used instead of a fresh download.");
@@ -296,10 +1615,149 @@ pub async fn check_url(url: &str) -> Result<Url> {
                 let parsed_url = Url::parse(response_body.uri.as_str())?;
                 Ok(parsed_url)
             }
+        },
+    }
+}
+
+/// What checking a registered URL turned up, without downloading the file behind it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkStatus {
+    /// The URL answered with the file it points to.
+    Ok,
+    /// The URL redirects to another one that answers, given here when the server named it, which
+    /// often means the provider has moved the file.
+    Redirected(Option<String>),
+    /// The URL didn't answer before the check gave up on it.
+    TimedOut,
+    /// The URL is broken, for the reason given.
+    Broken(String),
+}
+
+impl LinkStatus {
+    /// Whether the URL is broken, as opposed to answering, even if only after a redirect, or
+    /// being too slow to tell.
+    #[must_use]
+    pub fn is_broken(&self) -> bool {
+        matches!(self, Self::Broken(_))
+    }
+}
+
+impl Display for LinkStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ok => write!(f, "OK"),
+            Self::Redirected(Some(target)) => write!(f, "redirected to {target}"),
+            Self::Redirected(None) => write!(f, "redirected"),
+            Self::TimedOut => write!(f, "timed out"),
+            Self::Broken(reason) => write!(f, "broken: {reason}"),
         }
     }
 }
 
+/// The outcome of checking one registered URL with
+/// [`Project::verify_urls`](crate::project::Project::verify_urls).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkCheck {
+    /// The label of the dataset the URL is registered for
+    pub label: String,
+    /// The format the URL is registered under
+    pub format: FileFormat,
+    /// The registered URL
+    pub url: String,
+    /// What checking the URL turned up
+    pub status: LinkStatus,
+}
+
+/// Checks whether `url` is still live without downloading it, giving up after `timeout`.
+///
+/// Unlike [`check_url`], redirects aren't silently followed. A URL that redirects is checked
+/// once more with [`check_url`], following its redirects, and reported as redirected if they
+/// lead somewhere that answers and as broken otherwise. FTP and S3 URLs are asked for their file
+/// the same way [`check_url`] asks them.
+pub(crate) async fn check_link(url: &str, timeout: Duration) -> LinkStatus {
+    time::timeout(timeout, link_status(url, timeout))
+        .await
+        .unwrap_or(LinkStatus::TimedOut)
+}
+
+/// Works out the [`LinkStatus`] of a URL for [`check_link`].
+async fn link_status(url: &str, timeout: Duration) -> LinkStatus {
+    let parsed = Url::parse(url).ok();
+    if parsed
+        .as_ref()
+        .is_some_and(|url| ftp::is_ftp(url) || s3::is_s3(url))
+    {
+        return match check_url(url).await {
+            Ok(_) => LinkStatus::Ok,
+            Err(e) => LinkStatus::Broken(e.to_string()),
+        };
+    }
+
+    let (user_agent, headers) = link_check_headers(url);
+    let client = match ClientBuilder::builder()
+        .max_redirects(0_usize)
+        .timeout(timeout)
+        .user_agent(user_agent)
+        .custom_headers(headers)
+        .build()
+        .client()
+    {
+        Ok(client) => client,
+        Err(e) => return LinkStatus::Broken(e.to_string()),
+    };
+    let response = match client.check(url).await {
+        Ok(response) => response,
+        Err(e) => return LinkStatus::Broken(e.to_string()),
+    };
+    match &response.body().status {
+        Status::Ok(_) | Status::Cached(CacheStatus::Ok(_)) | Status::Unsupported(_) => {
+            LinkStatus::Ok
+        },
+        Status::Timeout(_) => LinkStatus::TimedOut,
+        // the client refuses every redirect, so the request fails on the first one and is sent
+        // again with redirects followed to find out where they lead
+        Status::Error(ErrorKind::TooManyRedirects(_)) => redirect_status(url, timeout).await,
+        Status::Redirected(code) => LinkStatus::Broken(format!(
+            "the server redirected with status code {} but didn't say where to",
+            code.as_str()
+        )),
+        Status::Excluded => LinkStatus::Broken("the URL has been excluded by the host".to_string()),
+        // the innermost cause of a failed request says why it failed, e.g. that the connection
+        // was refused
+        Status::Error(ErrorKind::NetworkRequest(e)) if e.status().is_none() => {
+            let cause = iter::successors(Some(e as &(dyn Error + 'static)), |e| (*e).source())
+                .last()
+                .map_or_else(|| e.to_string(), ToString::to_string);
+            LinkStatus::Broken(cause)
+        },
+        status => LinkStatus::Broken(match status.code() {
+            Some(code) => format!("HTTP {code}"),
+            None => status.to_string(),
+        }),
+    }
+}
+
+/// Follows the redirects `url` leads through, returning where they end up when the file there
+/// can be downloaded.
+async fn redirect_status(url: &str, timeout: Duration) -> LinkStatus {
+    let client = match client_builder().timeout(timeout).build() {
+        Ok(client) => client,
+        Err(e) => return LinkStatus::Broken(e.to_string()),
+    };
+    match with_headers(client.get(url), url).send().await {
+        Ok(response) if response.status().is_success() => {
+            LinkStatus::Redirected(Some(response.url().to_string()))
+        },
+        Ok(response) => LinkStatus::Broken(format!(
+            "redirected to {}, which responded with HTTP {}",
+            response.url(),
+            response.status()
+        )),
+        Err(e) if e.is_timeout() => LinkStatus::TimedOut,
+        Err(e) => LinkStatus::Broken(e.to_string()),
+    }
+}
+
 /// Convert a URL into a filename by extracting the last segment of the path.
 ///
 /// This function takes a URL and attempts to extract a filename from its path,
@@ -332,6 +1790,7 @@ pub async fn check_url(url: &str) -> Result<Url> {
 /// Valid URLs that would work:
 /// - "<https://example.com/files/data.csv>" -> "data.csv"
 /// - "<https://example.com/downloads/dataset.zip>" -> "dataset.zip"
+/// - "<https://example.com/files/data.csv?version=2>" -> "data.csv" (query strings are left out)
 ///
 /// Invalid URLs that would error:
 /// - "<https://example.com>/" (no filename)
@@ -341,9 +1800,392 @@ pub async fn check_url(url: &str) -> Result<Url> {
 pub fn uri_to_filename(url: &Url) -> Result<&str> {
     match url.path_segments().and_then(Iterator::last) {
         Some(filename) if !filename.is_empty() => Ok(filename),
+        Some(_) if url.path() != "/" => Err(eyre!(
+            "The URL {url} ends with a slash, so it points to a directory rather than a file. Please provide the URL of the file itself."
+        )),
         _ => Err(eyre!(
             "Failed to extract filename from URL, which may be corrupted or may not end with the name of a file: {}",
             url
         )),
     }
 }
+
+/// Returns the path in `target_dir` that the file at `url` will be downloaded to, or `None` if
+/// the URL can't be parsed or doesn't end with a filename. With `mirror_structure`, the file is
+/// placed in the directory returned by [`mirrored_dir`]. A server can still suggest a different
+/// name when the file is downloaded, which takes precedence.
+pub(crate) fn download_path(
+    url: &str,
+    target_dir: &Path,
+    mirror_structure: bool,
+) -> Option<PathBuf> {
+    let url = Url::parse(url).ok()?;
+    let filename = uri_to_filename(&url).ok()?;
+    if mirror_structure {
+        Some(mirrored_dir(&url, target_dir).join(filename))
+    } else {
+        Some(target_dir.join(filename))
+    }
+}
+
+/// Returns the directory under `target_dir` that mirrors the directories in the path of `url`,
+/// e.g. `target_dir/pub/release-110/fasta` for `https://ftp.ensembl.org/pub/release-110/fasta/genome.fa`.
+///
+/// The host is left out, as are any path segments that aren't plain directory names, like
+/// empty segments, `.`, `..`, or segments that would be read as absolute paths or drive
+/// prefixes. That way no URL, however crafted, can place a file outside of `target_dir`.
+pub(crate) fn mirrored_dir(url: &Url, target_dir: &Path) -> PathBuf {
+    let mut dir = target_dir.to_path_buf();
+    let Some(segments) = url.path_segments() else {
+        return dir;
+    };
+    let mut segments: Vec<&str> = segments.collect();
+    // the last segment is the file's name, not a directory
+    segments.pop();
+    for segment in segments {
+        let mut components = Path::new(segment).components();
+        if let (Some(Component::Normal(name)), None) = (components.next(), components.next()) {
+            dir.push(name);
+        } else {
+            debug!("Leaving the path segment {segment:?} of {url} out of its mirrored directory");
+        }
+    }
+
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_mirrored_download_path() {
+        let target_dir = Path::new("refs");
+        let url = "https://ftp.ensembl.org/pub/release-110/fasta/genome.fa";
+        assert_eq!(
+            download_path(url, target_dir, false),
+            Some(target_dir.join("genome.fa"))
+        );
+        assert_eq!(
+            download_path(url, target_dir, true),
+            Some(target_dir.join("pub/release-110/fasta/genome.fa"))
+        );
+
+        // segments that would climb out of the target directory are dropped
+        let sneaky = Url::parse("https://example.com/a/%2e%2e/%2E%2E/..%2F/b//genome.fa").unwrap();
+        let mirrored = mirrored_dir(&sneaky, target_dir);
+        assert!(mirrored.starts_with(target_dir));
+        assert!(
+            mirrored
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        );
+    }
+
+    #[test]
+    fn test_heartbeat_message() {
+        let mut heartbeat = Heartbeat::new("genome.gfa", Some(4096), Duration::from_secs(3600));
+        heartbeat.record(1024);
+        assert_eq!(
+            heartbeat.message(Duration::from_secs(2)),
+            "Still downloading genome.gfa: 1.00 KiB of 4.00 KiB (25%) after 2 seconds, at 512 B/s"
+        );
+
+        // servers that don't report a size only get the bytes downloaded so far
+        let mut heartbeat = Heartbeat::new("genome.gfa", None, Duration::from_secs(3600));
+        heartbeat.record(2048);
+        assert!(
+            heartbeat
+                .message(Duration::from_secs(1))
+                .starts_with("Still downloading genome.gfa: 2.00 KiB after")
+        );
+    }
+
+    #[test]
+    fn test_uri_to_filename() {
+        for (url, filename) in [
+            (
+                "https://ftp.ensembl.org/pub/release-110/fasta/homo_sapiens/dna/Homo_sapiens.GRCh38.dna.primary_assembly.fa.gz",
+                "Homo_sapiens.GRCh38.dna.primary_assembly.fa.gz",
+            ),
+            (
+                "https://ftp.ncbi.nlm.nih.gov/genomes/all/GCF/000/001/405/GCF_000001405.40_GRCh38.p14/GCF_000001405.40_GRCh38.p14_genomic.gff.gz",
+                "GCF_000001405.40_GRCh38.p14_genomic.gff.gz",
+            ),
+            (
+                "https://eutils.ncbi.nlm.nih.gov/entrez/eutils/efetch.fcgi?db=nuccore&id=MN908947.3&rettype=fasta",
+                "efetch.fcgi",
+            ),
+            (
+                "https://api.ncbi.nlm.nih.gov/datasets/v2/genome/accession/GCF_000001405.40/download?include_annotation_type=GENOME_FASTA#files",
+                "download",
+            ),
+        ] {
+            assert_eq!(
+                uri_to_filename(&Url::parse(url).unwrap()).unwrap(),
+                filename
+            );
+        }
+
+        let directory = Url::parse("https://ftp.ensembl.org/pub/release-110/fasta/").unwrap();
+        let error = uri_to_filename(&directory).unwrap_err().to_string();
+        assert!(error.contains("ends with a slash"), "{error}");
+        assert!(uri_to_filename(&Url::parse("https://example.com").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_content_disposition_filename() {
+        assert_eq!(
+            content_disposition_filename("attachment; filename=\"ncbi_dataset.zip\"").as_deref(),
+            Some("ncbi_dataset.zip")
+        );
+        assert_eq!(
+            content_disposition_filename("attachment;filename=MN908947.3.fasta").as_deref(),
+            Some("MN908947.3.fasta")
+        );
+        // the encoded form takes precedence
+        assert_eq!(
+            content_disposition_filename(
+                "attachment; filename=\"genome.fa\"; filename*=UTF-8''genome%20v2.fa"
+            )
+            .as_deref(),
+            Some("genome v2.fa")
+        );
+
+        // suggestions can't escape the download directory
+        assert_eq!(
+            content_disposition_filename("attachment; filename=\"../../.bashrc\"").as_deref(),
+            Some(".bashrc")
+        );
+        assert_eq!(
+            content_disposition_filename("attachment; filename=\"..\""),
+            None
+        );
+        assert_eq!(
+            content_disposition_filename("attachment; filename=\"\""),
+            None
+        );
+        assert_eq!(content_disposition_filename("inline"), None);
+    }
+
+    #[test]
+    fn test_resume_range() {
+        let url = Url::parse("https://example.com/refs/genome.fa.gz").unwrap();
+        assert_eq!(partial_name(&url), "genome.fa.gz.part");
+        let url = Url::parse("https://example.com/refs/").unwrap();
+        assert_eq!(
+            partial_name(&url),
+            format!("{:x}.part", md5::compute("https://example.com/refs/"))
+        );
+
+        assert_eq!(resume_range(None, 0), None);
+        assert_eq!(
+            resume_range(None, 1000),
+            Some(ByteRange {
+                start: 1000,
+                end: None
+            })
+        );
+
+        // registered ranges are resumed within the range
+        let range = ByteRange {
+            start: 500,
+            end: Some(1499),
+        };
+        assert_eq!(
+            resume_range(Some(range), 250),
+            Some(ByteRange {
+                start: 750,
+                end: Some(1499)
+            })
+        );
+        assert_eq!(resume_range(Some(range), 2000), None);
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        assert_eq!(retry_backoff(1), Duration::from_secs(2));
+        assert_eq!(retry_backoff(4), Duration::from_secs(16));
+        assert_eq!(retry_backoff(6), MAX_RETRY_BACKOFF);
+        assert_eq!(retry_backoff(u32::MAX), MAX_RETRY_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn test_no_retries_makes_one_attempt() {
+        let mut attempts = 0;
+        let started = Instant::now();
+        let result: Result<()> = with_retries("https://example.com/genome.fa", 0, || {
+            attempts += 1;
+            async { Err(eyre!("connection reset")) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_format_selection() {
+        assert!(FormatSelection::default().includes(FileFormat::Fasta));
+
+        let annotations = FormatSelection {
+            only: vec![FileFormat::Gff, FileFormat::Gtf],
+            except: vec![FileFormat::Gtf],
+        };
+        assert!(annotations.includes(FileFormat::Gff));
+        assert!(!annotations.includes(FileFormat::Gtf));
+        assert!(!annotations.includes(FileFormat::Fasta));
+
+        let no_genbank = FormatSelection {
+            except: vec![FileFormat::Genbank],
+            ..FormatSelection::default()
+        };
+        assert!(no_genbank.includes(FileFormat::Fasta));
+        assert!(!no_genbank.includes(FileFormat::Genbank));
+    }
+
+    #[tokio::test]
+    async fn test_local_copy_onto_itself() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("genome.fa");
+        fs::write(&source, ">seq\nACGT\n").await.unwrap();
+        let request = |uri: String, target_dir: &Path| {
+            request_dataset(
+                UnvalidatedFile::new(FileFormat::Fasta, uri, PathBuf::new()),
+                Client::new(),
+                Arc::new(target_dir.to_path_buf()),
+                Arc::new(MultiProgress::new()),
+                None,
+                false,
+                RedirectPolicy::default(),
+                None,
+                0,
+            )
+        };
+
+        // a file registered from inside the target directory, however it's spelled, is left intact
+        let spellings = [
+            source.to_string_lossy().into_owned(),
+            Url::from_file_path(&source).unwrap().to_string(),
+            dir.path()
+                .join("./genome.fa")
+                .to_string_lossy()
+                .into_owned(),
+        ];
+        for uri in spellings {
+            let (file, _) = request(uri, dir.path()).await.unwrap();
+            assert_eq!(file.get_path(), source);
+            assert_eq!(fs::read_to_string(&source).await.unwrap(), ">seq\nACGT\n");
+        }
+
+        // ...while one from elsewhere is copied in
+        let target_dir = dir.path().join("refs");
+        let (file, _) = request(source.to_string_lossy().into_owned(), &target_dir)
+            .await
+            .unwrap();
+        assert_eq!(file.get_path(), target_dir.join("genome.fa"));
+        assert_eq!(
+            fs::read_to_string(target_dir.join("genome.fa"))
+                .await
+                .unwrap(),
+            ">seq\nACGT\n"
+        );
+        assert_eq!(local_source("https://example.com/genome.fa"), None);
+    }
+
+    #[tokio::test]
+    async fn test_remote_changed_sends_validators() {
+        // a server whose file only matches the ETag "v2"
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/genome.fasta", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming().take(2) {
+                let mut stream = stream.unwrap();
+                let mut request = [0; 1024];
+                let read = stream.read(&mut request).unwrap();
+                let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+                let response = if request.contains("if-none-match: \"v2\"") {
+                    "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n"
+                } else {
+                    "HTTP/1.1 200 OK\r\nETag: \"v2\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let client = Client::new();
+        let outdated = CacheValidators {
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+        };
+        let current = CacheValidators {
+            etag: Some("\"v2\"".to_string()),
+            last_modified: None,
+        };
+        assert!(remote_changed(&client, &url, &outdated).await.unwrap());
+        assert!(!remote_changed(&client, &url, &current).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_resume_only_unchanged_files() {
+        // a server whose file has the ETag "v2", which only sends part of it when asked for the
+        // version the partial file holds
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/genome.fasta",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming().take(3) {
+                let mut stream = stream.unwrap();
+                let mut request = [0; 1024];
+                let read = stream.read(&mut request).unwrap();
+                let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+                let response = if request.contains("range: bytes=4-")
+                    && request.contains("if-range: \"v2\"")
+                {
+                    "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 4-7/8\r\nContent-Length: 4\r\nConnection: close\r\n\r\nACGT"
+                } else {
+                    "HTTP/1.1 200 OK\r\nETag: \"v2\"\r\nContent-Length: 8\r\nConnection: close\r\n\r\n>s\nACGTA"
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let client = Client::new();
+        let validators = |etag: &str| CacheValidators {
+            etag: Some(etag.to_string()),
+            last_modified: None,
+        };
+        let (response, resumed) =
+            request_download(&client, &url, None, 4, &validators("\"v2\""), 0)
+                .await
+                .unwrap();
+        assert!(resumed);
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+
+        // a file that changed since is downloaded again from the start
+        let (response, resumed) =
+            request_download(&client, &url, None, 4, &validators("\"v1\""), 0)
+                .await
+                .unwrap();
+        assert!(!resumed);
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // as is one whose version wasn't recorded
+        let (_, resumed) = request_download(&client, &url, None, 4, &CacheValidators::default(), 0)
+            .await
+            .unwrap();
+        assert!(!resumed);
+    }
+}
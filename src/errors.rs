@@ -8,10 +8,22 @@ pub enum ValidationError {
         "The file provided for validation, `{0}`, is inaccessible, either because of insufficient read permissions or because it does not exist."
     )]
     InaccessibleFile(String),
+    #[error(
+        "The gzip-compressed file `{0}` is truncated or corrupted ({1}), so its contents could not be validated. It was likely damaged while downloading; try downloading it again."
+    )]
+    CorruptGzip(String, String),
     #[error(
         "The file provided as FASTA format, `{0}`, could not be parsed and validated in that format, and thus will not be registered."
     )]
     InvalidFasta(String),
+    #[error(
+        "The FASTA index `{0}` next to the file it indexes could not be parsed ({1}). Downstream tools will likely refuse to use it; rebuild it with `samtools faidx`."
+    )]
+    InvalidFastaIndex(String, String),
+    #[error(
+        "The FASTA file `{0}` is compressed with plain gzip rather than bgzip, so the index next to it can't be used for random access. Recompress it with `bgzip` and rebuild its index with `samtools faidx`."
+    )]
+    FastaNotBgzf(String),
     #[error(
         "The file provided as Genbank format, `{0}`, could not be parsed and validated in that format, and thus will not be registered."
     )]
@@ -28,10 +40,42 @@ pub enum ValidationError {
         "The file provided as GTF format, `{0}`, could not be parsed and validated in that format, and thus will not be registered."
     )]
     InvalidGTF(String),
+    #[error(
+        "The file provided as GTF format, `{0}`, appears to actually be in GFF3 format, as its attribute column is written as `key=value` pairs rather than GTF's `key \"value\";` pairs. It was likely registered with the wrong flag; please register it with `--gff` instead of `--gtf`."
+    )]
+    GffProvidedAsGtf(String),
+    #[error(
+        "The file provided as GFF format, `{0}`, appears to actually be in GTF format, as its attribute column is written as `key \"value\";` pairs rather than GFF3's `key=value` pairs. It was likely registered with the wrong flag; please register it with `--gtf` instead of `--gff`."
+    )]
+    GtfProvidedAsGff(String),
     #[error(
         "The file provided as BED format, `{0}`, could not be parsed and validated in that format, and thus will not be registered. Note that BED files must at least have three columns: the reference contig ID in a corresponding FASTA file, the start coordinate, and the stop coordinate. Additional fields may be included according to the BED specification, but they are not validated here."
     )]
     InvalidBED(String),
+    #[error(
+        "The file provided as VCF format, `{0}`, could not be parsed and validated in that format, and thus will not be registered. Note that VCF files must begin with a header declaring the `##fileformat` version and the `#CHROM` column line."
+    )]
+    InvalidVCF(String),
+    #[error(
+        "The file provided as a TAR archive, `{0}`, could not be read through to its end ({1}), and thus will not be registered. It may be truncated or not be a tarball at all."
+    )]
+    InvalidTar(String, String),
+    #[error(
+        "The TAR archive `{0}` could not be extracted ({1}). The archive itself was downloaded and validated, so try extracting it again once the problem is fixed."
+    )]
+    ExtractionFailed(String, String),
+    #[error(
+        "The signature for `{0}` could not be verified with the configured public key: {1}. The file may have been tampered with or corrupted, or signed with a different key, so it will not be registered."
+    )]
+    SignatureInvalid(String, String),
+    #[error(
+        "The {0} file `{1}` was expected to contain {2} records, but {3} were found. It may be truncated or be the wrong file, so it will not be registered."
+    )]
+    RecordCountMismatch(String, String, usize, usize),
+    #[error(
+        "The {0} file `{1}` was expected to have the {2} checksum {3}, but its checksum is {4}. It may be corrupted or be the wrong file, so it will not be registered."
+    )]
+    ChecksumMismatch(String, String, String, String, String),
     #[error("Multiple validation errors occurred:\n{0}")]
     MultipleErrors(MultipleValidationErrors),
 }
@@ -52,11 +96,11 @@ impl error::Error for MultipleValidationErrors {}
 #[derive(Debug, Error)]
 pub enum RegistryError {
     #[error(
-        "A dataset register or download was requested, but a refman registry does not yet exist. Please initialize it with `refman init`, or initialize it implicitly by adding your first dataset with `refman register`."
+        "No refman registry was found at `{0}`. Please initialize it with `refman init`, or initialize it implicitly by adding your first dataset with `refman register`."
     )]
-    NoRegistry,
+    NoRegistry(String),
     #[error(
-        "A registry file was found, but the file is empty, likely because `refman init` was run without registering anything. Run `refman register` to start filling the file."
+        "A registry file was found, but no datasets have been registered in it yet, likely because `refman init` was run without registering anything. Run `refman register` to start filling the file."
     )]
     EmptyRegistry,
     #[error(
@@ -68,13 +112,27 @@ pub enum RegistryError {
     )]
     InvalidPath(#[from] io::Error),
     #[error(
-        "TOML registry format was invalid and could not be deserialized. A new registry may need to be initialized."
+        "TOML registry format was invalid and could not be deserialized. Please fix the problem below, or initialize a new registry if it can't be fixed.\n\n{0}"
     )]
     InvalidInputFormat(#[from] de::Error),
+    #[error(
+        "The refman registry at `{0}` was invalid and could not be deserialized. Please fix the problem below, e.g. with `refman edit`, or initialize a new registry if it can't be fixed.\n\n{1}"
+    )]
+    InvalidRegistryFile(String, de::Error),
     #[error(
         "The internal project representation was invalid, and thus cannot be serialized into the the TOML registry format."
     )]
     InvalidOutputFormat(#[from] ser::Error),
+    #[error("The registry could not be exported as JSON: {0}")]
+    InvalidJsonOutput(#[from] serde_json::Error),
+    #[error("The registry could not be exported as YAML: {0}")]
+    InvalidYamlOutput(#[from] serde_yaml::Error),
+    #[error(
+        "No profile named `{0}` is defined in `{1}`. Please add it as a `[profiles.{0}]` table, or leave out `--profile`."
+    )]
+    UnknownProfile(String, String),
+    #[error("The refman config file at `{0}` could not be read: {1}")]
+    InvalidConfig(String, String),
     #[error("unknown refman error")]
     Unknown,
 }
@@ -92,9 +150,107 @@ pub enum EntryError {
     #[error("The provided label `{0}` is not present in the refman registry.")]
     LabelNotFound(String),
     #[error(
-        "The label `{0}` is the final entry in the refman registry, which will leave behind an invalid state. Please delete the `refman.toml` file to proceed."
+        "The label `{0}` is already registered in the refman registry. Use a different label to register a new dataset, or leave out `--create-only` to update the existing one."
+    )]
+    LabelAlreadyExists(String),
+    #[error(
+        "The dataset `{0}` can't be renamed to `{1}`, because `{1}` is already registered in the refman registry. Labels must be unique, so please choose a different label or remove `{1}` first."
+    )]
+    RenameTargetExists(String, String),
+    #[error(
+        "The label `{0}` is used by more than one dataset in the refman registry. Labels must be unique, so please rename or remove one of them."
+    )]
+    DuplicateLabel(String),
+    #[error("A dataset in the refman registry has an empty label. Every dataset must be labeled.")]
+    EmptyLabel,
+    #[error(
+        "The format of the file at `{0}` could not be inferred from its extension. Please register it with an explicit format flag, e.g. `--fasta` or `--gff`."
+    )]
+    UnrecognizedFormat(String),
+    #[error(
+        "More than one {0} file was provided for the same dataset (`{1}` and `{2}`). Each dataset can hold only one file per format."
+    )]
+    DuplicateFormat(String, String, String),
+    #[error(
+        "The dataset `{0}` registers the same URL, `{1}`, for more than one format. This is usually a copy-paste mistake; please register the URL only under its actual format."
+    )]
+    DuplicateUrl(String, String),
+    #[error(
+        "`{0}` is not a valid NCBI assembly accession. Accessions look like `GCF_000001405.40` (RefSeq) or `GCA_000001405.29` (Genbank); the version is optional."
+    )]
+    InvalidAccession(String),
+    #[error(
+        "A label could not be derived from `{0}`, as it doesn't end with a usable filename. Please provide a label instead of `--label-from-url`."
+    )]
+    NoLabelInUrl(String),
+    #[error(
+        "A signature was provided for the {0} file, but no {0} file is registered for the dataset. Please provide the file alongside its signature."
+    )]
+    SignatureWithoutFile(String),
+    #[error(
+        "An expected record count was provided for the {0} file, but no {0} file is registered for the dataset. Please provide the file alongside its expected record count."
+    )]
+    RecordCountWithoutFile(String),
+    #[error(
+        "An expected checksum was provided for the {0} file, but no {0} file is registered for the dataset. Please provide the file alongside its checksum."
+    )]
+    ChecksumWithoutFile(String),
+    #[error(
+        "The {1} checksum `{0}` is invalid. Please provide the {2} hexadecimal digits of the checksum, e.g. as published in a provider's `md5checksums.txt` or `SHA256SUMS`."
+    )]
+    InvalidChecksum(String, String, usize),
+    #[error(
+        "The byte range `{0}` is invalid. Please provide an inclusive range of zero-based byte offsets like `1000-1999`, or `1000-` to download everything from an offset on."
+    )]
+    InvalidByteRange(String),
+    #[error(
+        "The header `{0}` is invalid. Please provide each header as its name and value separated by a colon, e.g. `--header \"X-Api-Key: abc123\"`."
+    )]
+    InvalidHeader(String),
+    #[error(
+        "A byte range was provided for the {0} file, but no {0} file is registered for the dataset. Please provide the file alongside its byte range."
+    )]
+    ByteRangeWithoutFile(String),
+    #[error(
+        "The public key `{0}` could not be decoded. Please provide the base64-encoded minisign public key, i.e. the second line of a `minisign.pub` file."
     )]
-    FinalEntry(String),
+    InvalidPublicKey(String),
+    #[error(
+        "NCBI does not provide a genomic FASTA for the assembly accession `{0}`. Please double check the accession and its version."
+    )]
+    AccessionNotFound(String),
+    #[error("The JSONPath expression `{0}` could not be parsed: {1}")]
+    InvalidJsonPath(String, String),
+    #[error("The response from `{0}` could not be used to register files: {1}")]
+    InvalidJsonResponse(String, String),
+    #[error(
+        "The system clipboard could not be read ({0}). Reading from the clipboard requires a desktop session; in a headless environment, please provide the URLs with flags instead."
+    )]
+    ClipboardUnavailable(String),
+    #[error(
+        "The clipboard does not contain any URLs to files in a format refman recognizes. Please copy a download link and try again."
+    )]
+    NoUrlsInClipboard,
+    #[error(
+        "The dataset `{0}` has no {1} file registered to relink. Use `refman register` to add one."
+    )]
+    FormatNotRegistered(String, String),
+    #[error(
+        "The file at `{0}` does not match the {1} file previously downloaded for this dataset, so it was not relinked. Use `refman register` to replace the file instead."
+    )]
+    RelinkContentMismatch(String, String),
+    #[error(
+        "The dataset `{0}` has the URL `{1}`, which is empty, malformed, or doesn't use a supported protocol. Please correct or re-register it."
+    )]
+    MalformedUrl(String, String),
+    #[error(
+        "The {1} file for `{0}` is recorded as downloaded to `{2}`, but nothing exists at that path. Please re-download it with `refman download {0}`."
+    )]
+    MissingLocalFile(String, String, String),
+    #[error(
+        "Checking the URL `{0}` timed out after {1} seconds, so it was not registered. The host may be down or unreachable; please try again later, or allow more time with `--check-timeout`."
+    )]
+    CheckTimedOut(String, f64),
     #[error(
         "The URL provided to be registered is invalid or does not point to a resource that exists."
     )]
@@ -107,4 +263,8 @@ pub enum DownloadError {
     InvalidUrl,
     #[error("")]
     NetworkError,
+    #[error(
+        "More than one file would be downloaded to the same path, so they would overwrite each other:\n{0}\nPlease download each dataset into its own directory with `--per-label-dirs`, mirror each URL's directories with `--mirror-structure`, or leave some of the files out with `--only`, `--except`, or by naming fewer labels."
+    )]
+    DestinationCollision(String),
 }
@@ -0,0 +1,228 @@
+use std::{
+    io::{self, Read},
+    net::ToSocketAddrs,
+    time::Duration,
+};
+
+use color_eyre::{Result, eyre::eyre};
+use log::debug;
+use suppaftp::{FtpStream, types::FileType};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task,
+};
+use url::Url;
+
+use crate::downloads::percent_decode;
+
+/// How long connecting to an FTP server may take before the server is given up on.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The most bytes handed over at once while a file is transferred.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// How many chunks may wait to be written before the transfer waits for the writer to catch up.
+const CHUNKS_IN_FLIGHT: usize = 16;
+
+/// Whether `url` points to an FTP server, whose files are requested over FTP rather than HTTP.
+pub(crate) fn is_ftp(url: &Url) -> bool {
+    url.scheme() == "ftp"
+}
+
+/// An FTP transfer in progress, whose bytes arrive in chunks as the server sends them.
+pub(crate) struct FtpTransfer {
+    /// How many bytes the transfer will send, if the server reported the file's size
+    pub(crate) size: Option<u64>,
+    /// The file's bytes, in the order they arrive
+    pub(crate) chunks: mpsc::Receiver<io::Result<Vec<u8>>>,
+}
+
+/// Checks that the FTP server `url` points to responds and has a file at the URL's path,
+/// returning the file's size.
+///
+/// # Errors
+///
+/// Returns an error if the server can't be reached or logged in to, or doesn't report a size for
+/// the file, e.g. because it doesn't exist.
+pub(crate) async fn check(url: &Url) -> Result<u64> {
+    let url = url.clone();
+    task::spawn_blocking(move || {
+        let (mut ftp, path) = connect(&url)?;
+        let size = ftp
+            .size(&path)
+            .map_err(|e| eyre!("The FTP server for {url} couldn't find the file: {e}"))?;
+        if let Err(e) = ftp.quit() {
+            debug!("Unable to close the connection to the FTP server for {url}: {e}");
+        }
+        Ok(size as u64)
+    })
+    .await?
+}
+
+/// Starts transferring the file at `url`, beginning `start` bytes into it and sending no more
+/// than `limit` bytes when a limit is provided.
+///
+/// The transfer runs on a blocking thread, since the FTP client is synchronous, and hands its
+/// bytes over through the returned [`FtpTransfer`]. Errors from reading the file partway through
+/// arrive as the last chunk.
+///
+/// # Errors
+///
+/// Returns an error if the server can't be reached or logged in to, refuses to start at `start`,
+/// or refuses to send the file.
+pub(crate) async fn open_transfer(
+    url: &Url,
+    start: u64,
+    limit: Option<u64>,
+) -> Result<FtpTransfer> {
+    let url = url.clone();
+    let (opened_tx, opened_rx) = oneshot::channel();
+    let (chunk_tx, chunks) = mpsc::channel(CHUNKS_IN_FLIGHT);
+    task::spawn_blocking(move || {
+        let opened = open_stream(&url, start, limit);
+        let (mut ftp, stream, size) = match opened {
+            Ok(opened) => opened,
+            Err(e) => {
+                let _ = opened_tx.send(Err(e));
+                return;
+            },
+        };
+        if opened_tx.send(Ok(size)).is_err() {
+            return;
+        }
+
+        let mut reader = stream.take(limit.unwrap_or(u64::MAX));
+        let mut buffer = vec![0; CHUNK_SIZE];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => {
+                    if chunk_tx.blocking_send(Ok(buffer[..read].to_vec())).is_err() {
+                        // nobody is waiting for the rest of the file anymore
+                        let _ = ftp.abort(reader.into_inner());
+                        return;
+                    }
+                },
+                Err(e) => {
+                    let _ = chunk_tx.blocking_send(Err(e));
+                    return;
+                },
+            }
+        }
+
+        // a transfer that was cut off at its limit has to be aborted, since the server is still sending
+        let cut_off = limit.is_some() && reader.limit() == 0;
+        let stream = reader.into_inner();
+        let closed = if cut_off {
+            ftp.abort(stream)
+        } else {
+            ftp.finalize_retr_stream(stream)
+        };
+        if let Err(e) = closed.and_then(|()| ftp.quit()) {
+            debug!("Unable to close the FTP transfer of {url} cleanly: {e}");
+        }
+    });
+
+    let size = opened_rx
+        .await
+        .map_err(|_| eyre!("The FTP transfer stopped before it started"))??;
+    Ok(FtpTransfer { size, chunks })
+}
+
+/// Connects to the server `url` points to, and asks it to send the file at the URL's path from
+/// `start` bytes into it, returning the connection, the stream the file's bytes arrive on, and
+/// how many bytes will arrive if the server reports the file's size.
+fn open_stream(
+    url: &Url,
+    start: u64,
+    limit: Option<u64>,
+) -> Result<(FtpStream, impl Read + 'static, Option<u64>)> {
+    let (mut ftp, path) = connect(url)?;
+    let size = ftp.size(&path).ok().map(|size| {
+        let remaining = (size as u64).saturating_sub(start);
+        limit.map_or(remaining, |limit| remaining.min(limit))
+    });
+    if start > 0 {
+        let offset = usize::try_from(start)?;
+        ftp.resume_transfer(offset).map_err(|e| {
+            eyre!("The FTP server for {url} can't start sending the file {start} bytes in: {e}")
+        })?;
+    }
+    let stream = ftp
+        .retr_as_stream(&path)
+        .map_err(|e| eyre!("The FTP server for {url} refused to send the file: {e}"))?;
+
+    Ok((ftp, stream, size))
+}
+
+/// Connects and logs in to the FTP server that `url` points to, anonymously unless the URL
+/// includes credentials, and switches to binary transfers. Returns the connection along with
+/// the decoded path of the file on the server.
+fn connect(url: &Url) -> Result<(FtpStream, String)> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| eyre!("The FTP URL {url} doesn't name a server"))?;
+    let port = url.port_or_known_default().unwrap_or(21);
+    let address = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| eyre!("Unable to find the address of the FTP server for {url}"))?;
+    let path = percent_decode(url.path())
+        .ok_or_else(|| eyre!("The path of the FTP URL {url} isn't valid UTF-8"))?;
+
+    let mut ftp = FtpStream::connect_timeout(address, CONNECT_TIMEOUT)
+        .map_err(|e| eyre!("Unable to connect to the FTP server for {url}: {e}"))?;
+    let (user, password) = if url.username().is_empty() {
+        ("anonymous".to_string(), "anonymous".to_string())
+    } else {
+        (
+            percent_decode(url.username()).unwrap_or_else(|| url.username().to_string()),
+            url.password().and_then(percent_decode).unwrap_or_default(),
+        )
+    };
+    ftp.login(&user, &password)
+        .map_err(|e| eyre!("Unable to log in to the FTP server for {url}: {e}"))?;
+    ftp.transfer_type(FileType::Binary)?;
+    debug!("Connected to the FTP server for {url}");
+
+    Ok((ftp, path))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use std::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn test_is_ftp() {
+        assert!(is_ftp(
+            &Url::parse("ftp://ftp.ncbi.nlm.nih.gov/genomes/genome.fa").unwrap()
+        ));
+        assert!(!is_ftp(
+            &Url::parse("https://example.com/genome.fa").unwrap()
+        ));
+        assert!(!is_ftp(
+            &Url::parse("sftp://example.com/genome.fa").unwrap()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_unreachable_server() {
+        // bind a port and let it go again, so that nothing is listening on it
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let url = Url::parse(&format!("ftp://127.0.0.1:{port}/pub/genome.fa")).unwrap();
+
+        let error = check(&url).await.unwrap_err().to_string();
+        assert!(
+            error.contains("Unable to connect to the FTP server"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use std::{collections::BTreeSet, str::FromStr, sync::OnceLock};
+
+use color_eyre::{Result, eyre::WrapErr};
+use log::warn;
+use reqwest::{
+    Client, ClientBuilder, RequestBuilder,
+    header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT},
+};
+use url::Url;
+
+use crate::{EntryError, auth::auth_headers};
+
+/// The `User-Agent` refman identifies itself to servers with unless another is provided, which
+/// lets providers like NCBI tell its requests apart from other traffic.
+const DEFAULT_USER_AGENT: &str = concat!("refman/", env!("CARGO_PKG_VERSION"));
+
+/// The headers provided on the command line for this run. They're set up once, the first time
+/// they're needed.
+static HEADERS: OnceLock<RunHeaders> = OnceLock::new();
+
+/// A header to send with HTTP requests, e.g. an API key some provider requires, provided in the
+/// `Name: Value` form `curl` accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpHeader {
+    name: HeaderName,
+    value: HeaderValue,
+}
+
+impl FromStr for HttpHeader {
+    type Err = EntryError;
+
+    fn from_str(header: &str) -> Result<Self, Self::Err> {
+        let invalid = || EntryError::InvalidHeader(header.to_string());
+        let (name, value) = header.split_once(':').ok_or_else(invalid)?;
+        let name = HeaderName::from_str(name.trim()).map_err(|_| invalid())?;
+        let value = HeaderValue::from_str(value.trim()).map_err(|_| invalid())?;
+
+        Ok(HttpHeader { name, value })
+    }
+}
+
+/// The hosts that credentials and headers provided on the command line are sent to, i.e. those of
+/// the datasets they were provided for. A token for a private server would otherwise also reach
+/// every other server contacted in the same run, like NCBI's or Ensembl's.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostScope(BTreeSet<String>);
+
+impl HostScope {
+    /// Collects the hosts of the HTTP and HTTPS URLs among `urls`, skipping any that can't be
+    /// parsed.
+    pub fn from_urls<'a>(urls: impl IntoIterator<Item = &'a str>) -> Self {
+        HostScope(urls.into_iter().filter_map(http_host).collect())
+    }
+
+    /// Whether requests for `url` go to one of the hosts in scope.
+    pub(crate) fn contains(&self, url: &str) -> bool {
+        http_host(url).is_some_and(|host| self.0.contains(&host))
+    }
+}
+
+/// The headers provided for a run, along with the hosts they're sent to.
+struct RunHeaders {
+    user_agent: HeaderValue,
+    headers: HeaderMap,
+    scope: HostScope,
+}
+
+impl RunHeaders {
+    /// Sorts `headers` into the `User-Agent`, which falls back to refman's own and is sent to
+    /// every host, and the rest, which are only sent to the hosts in `scope`. Headers provided
+    /// more than once are all sent.
+    fn new(headers: &[HttpHeader], scope: HostScope) -> Self {
+        let mut map = HeaderMap::new();
+        for HttpHeader { name, value } in headers {
+            map.append(name.clone(), value.clone());
+        }
+        let user_agent = map
+            .remove(USER_AGENT)
+            .unwrap_or_else(|| HeaderValue::from_static(DEFAULT_USER_AGENT));
+
+        RunHeaders {
+            user_agent,
+            headers: map,
+            scope,
+        }
+    }
+}
+
+/// Sets the headers to send with HTTP requests to the hosts in `scope` for the rest of the run.
+/// A `User-Agent` among them replaces refman's default one for every host. Only the first headers
+/// set are used.
+pub fn set_headers(headers: &[HttpHeader], scope: HostScope) {
+    if HEADERS.set(RunHeaders::new(headers, scope)).is_err() {
+        warn!("Headers were already provided for this run, so the new ones will be ignored.");
+    }
+}
+
+/// Returns the headers provided for this run.
+fn run_headers() -> &'static RunHeaders {
+    HEADERS.get_or_init(|| RunHeaders::new(&[], HostScope::default()))
+}
+
+/// Returns the headers to send with requests for `url`: the ones provided for the run if its
+/// host is in their scope, along with any credentials for it. The `User-Agent` is left out,
+/// since every client sends it already.
+pub(crate) fn request_headers(url: &str) -> HeaderMap {
+    let run_headers = run_headers();
+    let mut headers = if run_headers.scope.contains(url) {
+        run_headers.headers.clone()
+    } else {
+        HeaderMap::new()
+    };
+    headers.extend(auth_headers(url));
+    headers
+}
+
+/// Adds the headers for `url` to a request for it. See [`request_headers`].
+pub(crate) fn with_headers(request: RequestBuilder, url: &str) -> RequestBuilder {
+    request.headers(request_headers(url))
+}
+
+/// Returns a builder for HTTP clients that identify themselves with this run's `User-Agent`.
+pub(crate) fn client_builder() -> ClientBuilder {
+    Client::builder().user_agent(run_headers().user_agent.clone())
+}
+
+/// Returns an HTTP client that identifies itself with this run's `User-Agent`.
+///
+/// # Errors
+///
+/// Returns an error if reqwest is unable to initialize the client, e.g. because the system's TLS
+/// backend cannot be loaded.
+pub(crate) fn client() -> Result<Client> {
+    client_builder()
+        .build()
+        .wrap_err("Unable to set up an HTTP client")
+}
+
+/// Returns the `User-Agent` and the other headers that the link checker should send when
+/// checking `url`, which it takes separately.
+pub(crate) fn link_check_headers(url: &str) -> (String, HeaderMap) {
+    let user_agent = run_headers()
+        .user_agent
+        .to_str()
+        .map_or_else(|_| DEFAULT_USER_AGENT.to_string(), str::to_string);
+
+    (user_agent, request_headers(url))
+}
+
+/// Returns the host of `url` if it's an HTTP or HTTPS URL.
+pub(crate) fn http_host(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.host_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_parse_header() {
+        let header: HttpHeader = "X-Api-Key:  abc123 ".parse().unwrap();
+        assert_eq!(header.name, "x-api-key");
+        assert_eq!(header.value, "abc123");
+
+        // everything after the first colon is the value
+        let header: HttpHeader = "Referer: https://example.org/".parse().unwrap();
+        assert_eq!(header.value, "https://example.org/");
+
+        for invalid in [
+            "X-Api-Key abc123",
+            ": abc123",
+            "X Api Key: abc123",
+            "X-Api-Key: a\nb",
+        ] {
+            assert!(invalid.parse::<HttpHeader>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_run_headers() {
+        let defaults = RunHeaders::new(&[], HostScope::default());
+        assert_eq!(defaults.user_agent, DEFAULT_USER_AGENT);
+        assert!(defaults.headers.is_empty());
+
+        let headers: Vec<HttpHeader> = [
+            "User-Agent: my-pipeline (me@example.org)",
+            "Accept: a",
+            "Accept: b",
+        ]
+        .iter()
+        .map(|header| header.parse().unwrap())
+        .collect();
+        let run_headers = RunHeaders::new(&headers, HostScope::default());
+        assert_eq!(run_headers.user_agent, "my-pipeline (me@example.org)");
+        assert!(!run_headers.headers.contains_key(USER_AGENT));
+        assert_eq!(run_headers.headers.get_all("accept").iter().count(), 2);
+    }
+
+    #[test]
+    fn test_host_scope() {
+        let scope = HostScope::from_urls([
+            "https://data.example.org/private/genome.fa",
+            "ftp://ftp.ncbi.nlm.nih.gov/genomes/genome.gff",
+            "not a url",
+        ]);
+        assert!(scope.contains("https://data.example.org/private/genome.gff"));
+        assert!(scope.contains("http://DATA.example.org/other.fa"));
+        assert!(!scope.contains("https://ftp.ncbi.nlm.nih.gov/genomes/genome.gff"));
+        assert!(!scope.contains("https://ftp.ensembl.org/pub/genome.fa"));
+        assert!(!HostScope::default().contains("https://data.example.org/genome.fa"));
+    }
+}
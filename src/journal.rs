@@ -0,0 +1,171 @@
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+/// The name of the journal a download run keeps in its target directory until it's over.
+pub(crate) const JOURNAL_NAME: &str = ".refman-progress";
+
+/// A journal of the files a download run has finished, kept in the run's target directory
+/// while the run is in progress.
+///
+/// The registry is only written once a download run is over, so a run that's interrupted, e.g.
+/// by a reboot partway through a multi-day download, would otherwise forget every file it
+/// finished. Running the download again reads the journal left behind and records the files it
+/// lists without requesting or checking them again. The journal is removed once a run is over,
+/// since the registry then records everything it did.
+pub(crate) struct ProgressJournal {
+    path: PathBuf,
+    completed: HashMap<(String, String), PathBuf>,
+}
+
+/// One finished file, as written on its own line of a [`ProgressJournal`].
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalEntry {
+    label: String,
+    url: String,
+    path: PathBuf,
+}
+
+impl ProgressJournal {
+    /// Opens the journal in `target_dir`, reading the files an earlier, interrupted run finished
+    /// if it left one behind. Lines that can't be read, e.g. one cut short by the interruption,
+    /// are skipped.
+    pub(crate) fn open(target_dir: &Path) -> Self {
+        let path = target_dir.join(JOURNAL_NAME);
+        let completed = match fs::read_to_string(&path) {
+            Ok(contents) => contents
+                .lines()
+                .filter_map(|line| {
+                    serde_json::from_str::<JournalEntry>(line)
+                        .inspect_err(|e| debug!("Skipping a line of {}: {e}", path.display()))
+                        .ok()
+                })
+                .map(|entry| ((entry.label, entry.url), entry.path))
+                .collect(),
+            Err(e) if e.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                warn!(
+                    "Unable to read the download journal at {}, so files that an earlier run finished will be downloaded again: {e}",
+                    path.display()
+                );
+                HashMap::new()
+            },
+        };
+
+        Self { path, completed }
+    }
+
+    /// Returns where an earlier run downloaded the file at `url` for the dataset `label`, if the
+    /// journal lists it and the file is still there.
+    pub(crate) fn completed(&self, label: &str, url: &str) -> Option<&Path> {
+        self.completed
+            .get(&(label.to_string(), url.to_string()))
+            .map(PathBuf::as_path)
+            .filter(|path| path.is_file())
+    }
+
+    /// Returns the paths of every file the journal lists, whether or not it's still there.
+    pub(crate) fn completed_paths(&self) -> impl Iterator<Item = &Path> {
+        self.completed.values().map(PathBuf::as_path)
+    }
+
+    /// Records that the file at `url` for the dataset `label` finished downloading to `path`.
+    /// The journal only makes resuming faster, so failing to write it is logged rather than
+    /// failing the download.
+    pub(crate) fn record(&self, label: &str, url: &str, path: &Path) {
+        let entry = JournalEntry {
+            label: label.to_string(),
+            url: url.to_string(),
+            path: path.to_path_buf(),
+        };
+        let written = serde_json::to_string(&entry)
+            .map_err(io::Error::from)
+            .and_then(|line| {
+                if let Some(parent) = self.path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                // each line is appended with a single write, so that downloads finishing at the same time don't
+                // interleave their lines
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?
+                    .write_all(format!("{line}\n").as_bytes())
+            });
+        if let Err(e) = written {
+            warn!(
+                "Unable to record the download of {url} in the journal at {}: {e}",
+                self.path.display()
+            );
+        }
+    }
+
+    /// Removes the journal once the run it belongs to is over.
+    pub(crate) fn remove(&self) {
+        match fs::remove_file(&self.path) {
+            Ok(()) => debug!("Removed the download journal at {}", self.path.display()),
+            Err(e) if e.kind() == ErrorKind::NotFound => {},
+            Err(e) => warn!(
+                "Unable to remove the download journal at {}: {e}",
+                self.path.display()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_journal_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let target_dir = dir.path().join("refs");
+        let downloaded = target_dir.join("genome.fasta");
+        fs::create_dir_all(&target_dir).unwrap();
+        fs::write(&downloaded, ">seq\nACGT\n").unwrap();
+
+        let journal = ProgressJournal::open(&target_dir);
+        assert_eq!(journal.completed_paths().count(), 0);
+        journal.record("genome", "https://example.com/genome.fasta", &downloaded);
+        journal.record(
+            "genome",
+            "https://example.com/genes.gff",
+            &target_dir.join("genes.gff"),
+        );
+        // a line cut short by an interruption is skipped
+        OpenOptions::new()
+            .append(true)
+            .open(target_dir.join(JOURNAL_NAME))
+            .unwrap()
+            .write_all(b"{\"label\":\"gen")
+            .unwrap();
+
+        let resumed = ProgressJournal::open(&target_dir);
+        assert_eq!(resumed.completed_paths().count(), 2);
+        assert_eq!(
+            resumed.completed("genome", "https://example.com/genome.fasta"),
+            Some(downloaded.as_path())
+        );
+        // files that are no longer there have to be downloaded again
+        assert_eq!(
+            resumed.completed("genome", "https://example.com/genes.gff"),
+            None
+        );
+        assert_eq!(
+            resumed.completed("other", "https://example.com/genome.fasta"),
+            None
+        );
+
+        resumed.remove();
+        assert!(!target_dir.join(JOURNAL_NAME).exists());
+    }
+}
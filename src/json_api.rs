@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+
+use color_eyre::eyre::eyre;
+use log::{debug, warn};
+use serde_json::Value;
+use serde_json_path::JsonPath;
+
+use crate::{
+    EntryError,
+    data::{FileFormat, classify_urls},
+    headers::{client, with_headers},
+};
+
+/// Fetches a JSON document describing a dataset from a provider's API, extracts file URLs from it
+/// with a `JSONPath` expression, and infers the format of each from its extension.
+///
+/// Many data providers describe their datasets with bespoke JSON endpoints, so rather than
+/// supporting each one individually, this lets users point `refman` at any of them with a
+/// `JSONPath` expression like `$.files[*].url` that selects the URLs to register. Every string
+/// the expression matches is treated as a URL. URLs whose format can't be inferred with
+/// [`FileFormat::from_uri`] are skipped with a warning, as are URLs for a format that an earlier
+/// match already provided.
+///
+/// # Errors
+///
+/// Returns `EntryError::InvalidJsonPath` if `jsonpath` can't be parsed, `EntryError::InvalidURL`
+/// if the JSON can't be fetched, and `EntryError::InvalidJsonResponse` if the response isn't
+/// JSON or the expression matches no URLs in a recognized format.
+pub async fn resolve_json_urls(
+    url: &str,
+    jsonpath: &str,
+) -> Result<BTreeMap<FileFormat, String>, EntryError> {
+    let path = JsonPath::parse(jsonpath)
+        .map_err(|e| EntryError::InvalidJsonPath(jsonpath.to_string(), e.to_string()))?;
+
+    let body = with_headers(client()?.get(url), url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| EntryError::InvalidURL(eyre!("Unable to fetch JSON from {url}: {e}")))?
+        .text()
+        .await
+        .map_err(|e| {
+            EntryError::InvalidURL(eyre!("Unable to read the response from {url}: {e}"))
+        })?;
+    let document: Value = serde_json::from_str(&body)
+        .map_err(|e| EntryError::InvalidJsonResponse(url.to_string(), e.to_string()))?;
+
+    let files = classify_json_urls(path.query(&document).all());
+    if files.is_empty() {
+        return Err(EntryError::InvalidJsonResponse(
+            url.to_string(),
+            format!("`{jsonpath}` did not match any URLs in a format refman recognizes"),
+        ));
+    }
+    debug!(
+        "Found {} file(s) to register in the JSON from {url}",
+        files.len()
+    );
+
+    Ok(files)
+}
+
+/// Infers the format of each URL among the values a `JSONPath` expression matched, warning about
+/// any value that isn't a string; see [`classify_urls`].
+fn classify_json_urls(matches: Vec<&Value>) -> BTreeMap<FileFormat, String> {
+    classify_urls(matches.into_iter().filter_map(|value| {
+        let url = value.as_str();
+        if url.is_none() {
+            warn!("Skipping the matched JSON value {value}, which is not a string.");
+        }
+        url
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_classify_json_urls() {
+        let document = serde_json::json!({
+            "files": [
+                { "url": "https://example.com/genome.fasta.gz" },
+                { "url": "https://example.com/genes.gff3" },
+                { "url": "https://example.com/reads.bam" },
+                { "url": "https://example.com/alternate.fa" },
+                { "url": 42 },
+            ]
+        });
+        let path = JsonPath::parse("$.files[*].url").unwrap();
+        let files = classify_json_urls(path.query(&document).all());
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(
+            files.get(&FileFormat::Fasta).map(String::as_str),
+            Some("https://example.com/genome.fasta.gz")
+        );
+        assert_eq!(
+            files.get(&FileFormat::Gff).map(String::as_str),
+            Some("https://example.com/genes.gff3")
+        );
+    }
+}
@@ -18,10 +18,22 @@ pub mod prelude;
 pub mod project;
 
 // private internals
+mod auth;
+mod clipboard;
+mod config;
 mod downloads;
 mod errors;
+mod ftp;
 mod global;
+mod headers;
+mod journal;
+mod json_api;
+mod ncbi;
+mod remote_index;
+mod report;
+mod s3;
 mod validate;
+mod webhook;
 
 // re-exports
 pub use prelude::*;
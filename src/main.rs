@@ -6,33 +6,61 @@
     clippy::unwrap_used
 )]
 
-use std::path::PathBuf;
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use clap_verbosity_flag::Verbosity;
-use color_eyre::{eyre::Context, Result};
+use color_eyre::{
+    Report, Result,
+    eyre::{Context, eyre},
+};
 use fern::colors::{Color, ColoredLevelConfig};
+use log::{info, warn};
 use refman::{
-    cli::{self, Cli, Commands},
+    cli::{self, Cli, Commands, ExportFormat, ListFormat, SummaryFormat},
     prelude::*,
 };
 
 #[tokio::main]
+#[allow(clippy::too_many_lines)]
 async fn main() -> Result<()> {
     // Parse provided command line arguments
     let cli = Cli::parse();
 
     // Determine how much verbosity the user requested and use that level to set up logging
     let verbosity = cli.verbose;
-    setup_logger(verbosity)?;
+    setup_logger(verbosity, use_color(cli.no_color))?;
+
+    // fill in the registry settings from the requested profile, if any, and then from the config's defaults, where the
+    // command line doesn't set them
+    let mut command = cli.command;
+    if let Some(command) = command.as_mut() {
+        let config = Config::load()?;
+        if let Some((registry, global)) = command.registry_args_mut() {
+            if let Some(profile_name) = cli.profile.as_deref() {
+                config.profile(profile_name)?.apply(registry, global);
+            }
+            config.apply(registry, global);
+        }
+        if let Some(dest) = command.dest_mut() {
+            config.apply_destination(dest);
+        }
+    }
 
     // Run the called subcommand or print info
-    match cli.command {
+    match command {
         // if no subcommand is provided in the command-line, just print the tool's info.
         None => {
             eprintln!("{}\n", cli::INFO);
             std::process::exit(0);
-        }
+        },
 
         // simple command that creates a new file that can be used to track reference datasets
         Some(Commands::Init {
@@ -40,33 +68,283 @@ async fn main() -> Result<()> {
             global,
             title,
             description,
+            maintainer,
+            homepage,
+            public_key,
         }) => {
-            let options = RegistryOptions::try_new(title, description, &registry, global)?;
+            let options = RegistryOptions::try_new(title, description, &registry, global)?
+                .with_contact(maintainer, homepage)
+                .with_public_key(public_key);
             options.init()?;
             Ok(())
-        }
+        },
 
         // The register subcommand adds a new entry to refman.toml that includes at least one valid URL
         // along with a dataset label
         Some(Commands::Register {
             label,
+            label_from_url: _,
             fasta,
             genbank,
             gfa,
             gtf,
             gff,
             bed,
+            vcf,
             tar,
+            ncbi_assembly,
+            from_json,
+            jsonpath,
+            from_clipboard,
+            from_file,
+            fail_fast,
+            fasta_sig,
+            genbank_sig,
+            gfa_sig,
+            gtf_sig,
+            gff_sig,
+            bed_sig,
+            vcf_sig,
+            tar_sig,
+            fasta_records,
+            genbank_records,
+            gtf_records,
+            gff_records,
+            vcf_records,
+            fasta_md5,
+            genbank_md5,
+            gfa_md5,
+            gtf_md5,
+            gff_md5,
+            bed_md5,
+            vcf_md5,
+            tar_md5,
+            hash_algo,
+            fasta_range,
+            public_key,
+            dest,
             registry,
             global,
+            strict,
+            update_only,
+            create_only,
+            check_only_sequences,
+            offline,
+            check_timeout,
+            auth_bearer,
+            auth_basic,
+            headers,
         }) => {
-            let new_dataset =
-                RefDataset::try_new(label, fasta, genbank, gfa, gff, gtf, bed, tar).await?;
+            let url_checks = if offline {
+                UrlChecks::Offline
+            } else if check_only_sequences {
+                UrlChecks::SequencesOnly
+            } else {
+                UrlChecks::All
+            };
+            let mode = if update_only {
+                RegisterMode::UpdateOnly
+            } else if create_only {
+                RegisterMode::CreateOnly
+            } else {
+                RegisterMode::CreateOrUpdate
+            };
+            let check_timeout = Some(Duration::from_secs(check_timeout));
+
+            // batch files register many datasets at once, each row on its own
+            if let Some(path) = from_file {
+                let options = RegistryOptions::try_new(None, None, &registry, global)?;
+                let rows = read_batch_file(&path)?;
+                let urls = rows
+                    .iter()
+                    .flatten()
+                    .flat_map(|row| row.urls.iter().flatten());
+                let scope = HostScope::from_urls(urls.map(String::as_str));
+                use_credentials(auth_bearer, auth_basic.as_deref(), &headers, scope);
+
+                let num_rows = rows.len();
+                let mut project = options.read_registry()?;
+                let BatchSummary {
+                    registered,
+                    failures,
+                } = Box::pin(project.register_batch(
+                    rows,
+                    mode,
+                    url_checks,
+                    check_timeout,
+                    strict,
+                    fail_fast,
+                ))
+                .await?;
+                if !registered.is_empty() {
+                    options.write_registry(&mut project)?;
+                }
+                for failure in &failures {
+                    eprintln!("{failure:#}");
+                }
+                eprintln!(
+                    "Registered {} of {num_rows} dataset(s) from {}",
+                    registered.len(),
+                    path.display()
+                );
+                if !failures.is_empty() {
+                    return Err(eyre!(
+                        "{} of {num_rows} dataset(s) in {} could not be registered",
+                        failures.len(),
+                        path.display()
+                    ));
+                }
+                return Ok(());
+            }
+
+            // the credentials and headers provided are only sent to the servers named on the command line
+            let scope = HostScope::from_urls(
+                [
+                    &fasta,
+                    &genbank,
+                    &gfa,
+                    &gtf,
+                    &gff,
+                    &bed,
+                    &vcf,
+                    &tar,
+                    &from_json,
+                    &fasta_sig,
+                    &genbank_sig,
+                    &gfa_sig,
+                    &gtf_sig,
+                    &gff_sig,
+                    &bed_sig,
+                    &vcf_sig,
+                    &tar_sig,
+                ]
+                .into_iter()
+                .flatten()
+                .map(String::as_str),
+            );
+            use_credentials(auth_bearer, auth_basic.as_deref(), &headers, scope);
+
+            // fill in any files that weren't provided explicitly from the NCBI assembly, if one was requested
+            let (fasta, gff, gtf) = match ncbi_assembly {
+                Some(accession) => {
+                    let assembly = resolve_ncbi_assembly(&accession).await?;
+                    (
+                        fasta.or(Some(assembly.fasta)),
+                        gff.or(assembly.gff),
+                        gtf.or(assembly.gtf),
+                    )
+                },
+                None => (fasta, gff, gtf),
+            };
+            // likewise for any files found in a provider's JSON description of the dataset or copied to the clipboard
+            let found = match (from_json, jsonpath) {
+                (Some(json_url), Some(jsonpath)) => {
+                    Some(resolve_json_urls(&json_url, &jsonpath).await?)
+                },
+                _ if from_clipboard => Some(clipboard_urls()?),
+                _ => None,
+            };
+            let (fasta, genbank, gfa, gff, gtf, bed, vcf, tar) = match found {
+                Some(mut found) => (
+                    fasta.or_else(|| found.remove(&FileFormat::Fasta)),
+                    genbank.or_else(|| found.remove(&FileFormat::Genbank)),
+                    gfa.or_else(|| found.remove(&FileFormat::Gfa)),
+                    gff.or_else(|| found.remove(&FileFormat::Gff)),
+                    gtf.or_else(|| found.remove(&FileFormat::Gtf)),
+                    bed.or_else(|| found.remove(&FileFormat::Bed)),
+                    vcf.or_else(|| found.remove(&FileFormat::Vcf)),
+                    tar.or_else(|| found.remove(&FileFormat::Tar)),
+                ),
+                None => (fasta, genbank, gfa, gff, gtf, bed, vcf, tar),
+            };
+            let signatures: BTreeMap<FileFormat, String> = [
+                (FileFormat::Fasta, fasta_sig),
+                (FileFormat::Genbank, genbank_sig),
+                (FileFormat::Gfa, gfa_sig),
+                (FileFormat::Gff, gff_sig),
+                (FileFormat::Gtf, gtf_sig),
+                (FileFormat::Bed, bed_sig),
+                (FileFormat::Vcf, vcf_sig),
+                (FileFormat::Tar, tar_sig),
+            ]
+            .into_iter()
+            .filter_map(|(format, url)| url.map(|url| (format, url)))
+            .collect();
+            let expected_records: BTreeMap<FileFormat, usize> = [
+                (FileFormat::Fasta, fasta_records),
+                (FileFormat::Genbank, genbank_records),
+                (FileFormat::Gff, gff_records),
+                (FileFormat::Gtf, gtf_records),
+                (FileFormat::Vcf, vcf_records),
+            ]
+            .into_iter()
+            .filter_map(|(format, records)| records.map(|records| (format, records)))
+            .collect();
+            let expected_checksums: BTreeMap<FileFormat, String> = [
+                (FileFormat::Fasta, fasta_md5),
+                (FileFormat::Genbank, genbank_md5),
+                (FileFormat::Gfa, gfa_md5),
+                (FileFormat::Gff, gff_md5),
+                (FileFormat::Gtf, gtf_md5),
+                (FileFormat::Bed, bed_md5),
+                (FileFormat::Vcf, vcf_md5),
+                (FileFormat::Tar, tar_md5),
+            ]
+            .into_iter()
+            .filter_map(|(format, checksum)| checksum.map(|checksum| (format, checksum)))
+            .collect();
+            // make up a label from the first file's name if one wasn't provided
             let options = RegistryOptions::try_new(None, None, &registry, global)?;
-            let mut project = options.read_registry()?.register(new_dataset).await?;
+            let label = if let Some(label) = label {
+                label
+            } else {
+                let url = [&fasta, &genbank, &gfa, &gff, &gtf, &bed, &vcf, &tar]
+                    .into_iter()
+                    .flatten()
+                    .next()
+                    .ok_or(EntryError::LabelButNoFiles)?;
+                let label = options.read_registry()?.unique_label(&label_from_url(url)?);
+                eprintln!("Registering the files under the label '{label}'");
+                label
+            };
+            let new_dataset = RefDataset::try_new_with_checks(
+                label,
+                fasta,
+                genbank,
+                gfa,
+                gff,
+                gtf,
+                bed,
+                vcf,
+                tar,
+                url_checks,
+                check_timeout,
+            )
+            .await?
+            .with_signatures(signatures, public_key)
+            .await?
+            .with_expected_records(expected_records)?
+            .with_expected_checksums(expected_checksums, hash_algo)?
+            .with_byte_ranges(
+                fasta_range
+                    .map(|range| (FileFormat::Fasta, range))
+                    .into_iter()
+                    .collect(),
+            )?
+            .with_dest(dest);
+            let registered_label = new_dataset.label.clone();
+            let mut project = options
+                .read_registry()?
+                .register_with_mode(new_dataset, mode)
+                .await?;
+            if strict {
+                project
+                    .get_dataset(&registered_label)?
+                    .check_duplicate_urls()?;
+            }
             options.write_registry(&mut project)?;
             Ok(())
-        }
+        },
 
         // The remove subcommand removes a dataset that was previously registered with refman
         Some(Commands::Remove {
@@ -75,30 +353,340 @@ async fn main() -> Result<()> {
             global,
         }) => {
             let options = RegistryOptions::try_new(None, None, &registry, global)?;
-            let mut project = options.read_registry()?.remove(&label)?;
+            let mut project = options
+                .read_existing_registry()?
+                .require_datasets()?
+                .remove(&label)?;
             options.write_registry(&mut project)?;
             Ok(())
-        }
+        },
+
+        Some(Commands::Rename {
+            old_label,
+            new_label,
+            registry,
+            global,
+        }) => {
+            let options = RegistryOptions::try_new(None, None, &registry, global)?;
+            let mut project = options
+                .read_existing_registry()?
+                .rename(&old_label, &new_label)?;
+            options.write_registry(&mut project)?;
+            Ok(())
+        },
+
+        // the import subcommand folds another registry's datasets into this one
+        Some(Commands::Import {
+            source_path,
+            on_conflict,
+            registry,
+            global,
+        }) => {
+            let source = RegistryOptions::try_new(None, None, &Some(source_path), false)?
+                .read_existing_registry()?;
+            let options = RegistryOptions::try_new(None, None, &registry, global)?;
+            let (mut project, summary) = options.read_registry()?.import(source, on_conflict)?;
+            options.write_registry(&mut project)?;
+            eprintln!("{summary}");
+            Ok(())
+        },
+
+        // The relink subcommand swaps the URLs of registered files while keeping their download state
+        Some(Commands::Relink {
+            label,
+            fasta,
+            genbank,
+            gfa,
+            gtf,
+            gff,
+            bed,
+            vcf,
+            tar,
+            verify,
+            registry,
+            global,
+        }) => {
+            let new_urls: Vec<(FileFormat, String)> = [
+                (FileFormat::Fasta, fasta),
+                (FileFormat::Genbank, genbank),
+                (FileFormat::Gfa, gfa),
+                (FileFormat::Gff, gff),
+                (FileFormat::Gtf, gtf),
+                (FileFormat::Bed, bed),
+                (FileFormat::Vcf, vcf),
+                (FileFormat::Tar, tar),
+            ]
+            .into_iter()
+            .filter_map(|(format, url)| url.map(|url| (format, url)))
+            .collect();
+            if new_urls.is_empty() {
+                return Err(EntryError::LabelButNoFiles.into());
+            }
+
+            let options = RegistryOptions::try_new(None, None, &registry, global)?;
+            let mut project = options.read_existing_registry()?;
+            for (format, url) in new_urls {
+                project = project.relink(&label, format, &url, verify).await?;
+            }
+            options.write_registry(&mut project)?;
+            Ok(())
+        },
+
+        Some(Commands::Rehash {
+            label,
+            registry,
+            global,
+        }) => {
+            let options = RegistryOptions::try_new(None, None, &registry, global)?;
+            let mut project = options.read_existing_registry()?.rehash(label.as_deref())?;
+            options.write_registry(&mut project)?;
+            Ok(())
+        },
 
         // The list subcommand prints the registered datasets in a human-readable table
         Some(Commands::List {
             registry,
             global,
             label,
+            count,
+            format,
+            json,
+            labels_only,
         }) => {
+            let mut project = RegistryOptions::try_new(None, None, &registry, global)?
+                .read_existing_registry()?;
+            if label.is_some() && !count {
+                project = project.require_datasets()?;
+            }
+            if labels_only {
+                for label in project.dataset_labels() {
+                    println!("{label}");
+                }
+                return Ok(());
+            }
+            if count {
+                let matching = project
+                    .datasets()
+                    .iter()
+                    .filter(|dataset| label.as_ref().is_none_or(|label| dataset.label == *label))
+                    .count();
+                println!("{matching}");
+                return Ok(());
+            }
+            if json {
+                println!("{}", project.subset(label.as_deref())?.datasets_to_json()?);
+                return Ok(());
+            }
+            match format {
+                ListFormat::Table => project.prettyprint(label),
+                ListFormat::Toml => print!("{}", project.subset(label.as_deref())?.to_toml()?),
+            }
+            Ok(())
+        },
+
+        // the status subcommand summarizes how far each dataset has gotten through downloading and validation
+        Some(Commands::Status { registry, global }) => {
             RegistryOptions::try_new(None, None, &registry, global)?
-                .read_registry()?
-                .prettyprint(label);
+                .read_existing_registry()?
+                .print_status();
             Ok(())
-        }
+        },
+
+        // The info subcommand prints the registry's metadata
+        Some(Commands::Info { registry, global }) => {
+            let options = RegistryOptions::try_new(None, None, &registry, global)?;
+            options.read_existing_registry()?.print_info(options.path());
+            Ok(())
+        },
+
+        // the export subcommand prints the registry in a format other tools can read more easily than TOML
+        Some(Commands::Export {
+            format,
+            registry,
+            global,
+        }) => {
+            let project = RegistryOptions::try_new(None, None, &registry, global)?
+                .read_existing_registry()?;
+            match format {
+                ExportFormat::Json => println!("{}", project.to_json()?),
+                ExportFormat::Yaml => print!("{}", project.to_yaml()?),
+            }
+            Ok(())
+        },
+
+        // the diff subcommand compares the datasets of two registries
+        Some(Commands::Diff {
+            other_path,
+            json,
+            registry,
+            global,
+        }) => {
+            let project = RegistryOptions::try_new(None, None, &registry, global)?
+                .read_existing_registry()?;
+            let other = RegistryOptions::try_new(None, None, &Some(other_path), false)?
+                .read_existing_registry()?;
+            let diff = project.diff(&other);
+            if json {
+                println!("{}", diff.to_json()?);
+            } else {
+                println!("{diff}");
+            }
+            Ok(())
+        },
+
+        // the set-meta subcommand changes the registry's own metadata, leaving its datasets alone
+        Some(Commands::SetMeta {
+            title,
+            description,
+            registry,
+            global,
+        }) => {
+            let options = RegistryOptions::try_new(None, None, &registry, global)?;
+            let mut project = options
+                .read_existing_registry()?
+                .with_metadata(title, description);
+            options.write_registry(&mut project)?;
+            Ok(())
+        },
+
+        // the verify-registry subcommand checks the registry's structure without changing it
+        Some(Commands::VerifyRegistry { registry, global }) => {
+            let options = RegistryOptions::try_new(None, None, &registry, global)?;
+            let problems = options.read_existing_registry()?.structural_problems();
+            if problems.is_empty() {
+                info!(
+                    "No problems were found in the registry at {}",
+                    options.path().display()
+                );
+                return Ok(());
+            }
+            for problem in &problems {
+                eprintln!("- {problem}");
+            }
+            Err(eyre!(
+                "Found {} problem(s) in the registry at {}",
+                problems.len(),
+                options.path().display()
+            ))
+        },
+
+        // the validate subcommand parses every downloaded file again, reporting each one that no longer validates
+        Some(Commands::Validate {
+            validate_threads,
+            force_revalidate,
+            sample,
+            registry,
+            global,
+        }) => {
+            let options = RegistryOptions::try_new(None, None, &registry, global)?;
+            let mut project = options.read_existing_registry()?;
+            let report = project.validate_all(validate_threads, force_revalidate, sample)?;
+            // the files that passed have fresh validation times to record, even if others failed
+            options.write_registry(&mut project)?;
+            for (label, format, error) in report.failures() {
+                eprintln!("- {label} ({format}): {error}");
+            }
+            let failed = report.failures().count();
+            eprintln!(
+                "Validated {} file(s): {failed} failed.",
+                report.file_count()
+            );
+            if failed > 0 {
+                return Err(eyre!(
+                    "{failed} of the {} downloaded files failed validation",
+                    report.file_count()
+                ));
+            }
+            Ok(())
+        },
+
+        // the verify-urls subcommand checks that the registered URLs still lead somewhere, without downloading anything
+        Some(Commands::VerifyUrls {
+            labels,
+            all: _,
+            jobs,
+            timeout,
+            registry,
+            global,
+        }) => {
+            let options = RegistryOptions::try_new(None, None, &registry, global)?;
+            let project = options.read_existing_registry()?.require_datasets()?;
+            let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+            if let Some(missing) = labels.iter().find(|label| !project.is_registered(label)) {
+                Err(RegistryError::NotRegistered((*missing).to_string()))?;
+            }
+
+            let checks = project
+                .verify_urls(&labels, jobs, Duration::from_secs(timeout))
+                .await;
+            for check in &checks {
+                println!(
+                    "{} ({}) {}: {}",
+                    check.label, check.format, check.url, check.status
+                );
+            }
+            let count = |matches: fn(&LinkStatus) -> bool| {
+                checks.iter().filter(|check| matches(&check.status)).count()
+            };
+            let broken = count(LinkStatus::is_broken);
+            eprintln!(
+                "Checked {} URLs: {} OK, {} redirected, {} timed out, {broken} broken.",
+                checks.len(),
+                count(|status| *status == LinkStatus::Ok),
+                count(|status| matches!(status, LinkStatus::Redirected(_))),
+                count(|status| *status == LinkStatus::TimedOut),
+            );
+            if broken > 0 {
+                return Err(eyre!(
+                    "{broken} of the {} registered URLs are broken",
+                    checks.len()
+                ));
+            }
+            Ok(())
+        },
 
         // the download subcommand pulls the data from a previously registered dataset
         Some(Commands::Download {
-            label,
+            labels,
             registry,
             dest,
             global,
-            all,
+            // downloading all datasets is the same as not naming any
+            all: _,
+            only,
+            except,
+            fail_fast,
+            continue_on_validation_error,
+            checksum_only,
+            resume_from_manifest,
+            dry_run,
+            sample,
+            retry_failed,
+            webhook,
+            webhook_token,
+            overwrite,
+            no_clobber,
+            mirror_structure,
+            flat: _,
+            per_label_dirs,
+            extract,
+            strip_components,
+            max_redirects,
+            no_cross_host_redirect,
+            summary_format,
+            hash_algo,
+            remote_index,
+            index_algo,
+            jobs,
+            max_idle_per_host,
+            idle_timeout,
+            keepalive,
+            heartbeat,
+            retries,
+            timeout,
+            auth_bearer,
+            auth_basic,
+            headers,
         }) => {
             // setup up registry options if provided
             let options = RegistryOptions::try_new(None, None, &registry, global)?;
@@ -107,37 +695,365 @@ async fn main() -> Result<()> {
             let destination = dest.unwrap_or_else(|| PathBuf::from("."));
 
             // read in the project data
-            let project = options.read_registry()?;
+            let project = options.read_existing_registry()?.require_datasets()?;
+
+            // make sure every requested label is registered before anything is downloaded, so that a typo in one of
+            // them doesn't turn up only after the others have downloaded
+            let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+            if let Some(missing) = labels.iter().find(|label| !project.is_registered(label)) {
+                Err(RegistryError::NotRegistered((*missing).to_string()))?;
+            }
+
+            // the credentials and headers provided are only sent to the servers of the datasets being downloaded
+            let scope = HostScope::from_urls(
+                project
+                    .datasets()
+                    .iter()
+                    .filter(|dataset| labels.is_empty() || labels.contains(&dataset.label.as_str()))
+                    .flat_map(RefDataset::urls)
+                    .chain(remote_index.as_deref()),
+            );
+            use_credentials(auth_bearer, auth_basic.as_deref(), &headers, scope);
 
-            let Some(ref provided_label_str) = label else {
-                let mut updated_project = project.download_dataset(None, destination).await?;
-                options.write_registry(&mut updated_project)?;
+            // copies of the files that were put in place some other way only need to be recognized, not downloaded
+            if let Some(manifest) = resume_from_manifest {
+                let files = files_to_resume(&manifest)?;
+                let mut project = project.resume_from_files(&labels, &files)?;
+                options.write_registry(&mut project)?;
                 return Ok(());
+            }
+
+            // only files of the requested formats are downloaded, or looked for in the destination
+            let formats = FormatSelection { only, except };
+
+            // decide what to do about files in the destination that refman didn't download, asking the user if
+            // they haven't already said and are around to answer
+            let existing_files = if overwrite || checksum_only {
+                ExistingFiles::Overwrite
+            } else if no_clobber {
+                ExistingFiles::Skip
+            } else if dry_run {
+                ExistingFiles::Warn
+            } else {
+                confirm_overwrites(
+                    &project,
+                    &labels,
+                    &destination,
+                    mirror_structure,
+                    per_label_dirs,
+                    &formats,
+                )?
             };
 
-            if all {
-                let mut updated_project = project
-                    .download_dataset(label.as_deref(), destination)
+            // collect the settings that apply to the download run as a whole
+            let download_options = DownloadOptions {
+                fail_fast,
+                continue_on_validation_error,
+                checksum_only,
+                existing_files,
+                max_idle_per_host,
+                idle_timeout: idle_timeout.map(Duration::from_secs),
+                tcp_keepalive: keepalive.map(Duration::from_secs),
+                validation_sample: sample,
+                retry_failed,
+                webhook,
+                webhook_token,
+                mirror_structure,
+                per_label_dirs,
+                redirects: RedirectPolicy {
+                    max_redirects,
+                    same_host_only: no_cross_host_redirect,
+                },
+                json_summary: summary_format == SummaryFormat::Json,
+                jobs: Some(jobs),
+                hash_algorithm: hash_algo,
+                remote_index,
+                index_algorithm: index_algo,
+                heartbeat: heartbeat.map(Duration::from_secs),
+                formats,
+                retries: Some(retries),
+                timeout: timeout.map(Duration::from_secs),
+                extract,
+                strip_components,
+            };
+
+            // a dry run only reports what would happen, so the registry is left as it was
+            if dry_run {
+                project
+                    .print_download_plan(&labels, &destination, &download_options)
                     .await?;
-                options.write_registry(&mut updated_project)?;
                 return Ok(());
             }
 
-            if !project.is_registered(provided_label_str) {
-                Err(RegistryError::NotRegistered(provided_label_str.to_string()))?;
-            }
-
             let mut updated_project = project
-                .download_dataset(label.as_deref(), destination)
+                .download_dataset(&labels, destination, &download_options)
                 .await?;
             options.write_registry(&mut updated_project)?;
 
             Ok(())
+        },
+
+        // the update subcommand downloads files again that changed upstream since they were downloaded
+        Some(Commands::Update {
+            label,
+            registry,
+            global,
+            all,
+            fail_fast,
+        }) => {
+            let options = RegistryOptions::try_new(None, None, &registry, global)?;
+            let download_options = DownloadOptions {
+                fail_fast,
+                ..DownloadOptions::default()
+            };
+            let label = if all { None } else { label };
+            let (mut project, num_updated) = options
+                .read_existing_registry()?
+                .require_datasets()?
+                .update_datasets(label.as_deref(), &download_options)
+                .await?;
+            eprintln!("{num_updated} file(s) had changed upstream and were downloaded again.");
+            options.write_registry(&mut project)?;
+            Ok(())
+        },
+
+        // the quick-download subcommand fetches ad-hoc files into a throwaway single-dataset project, which is
+        // only written to a registry if the user asks for it
+        Some(Commands::QuickDownload {
+            urls,
+            label,
+            dest,
+            save,
+            registry,
+            global,
+            fail_fast,
+        }) => {
+            let download_options = DownloadOptions {
+                fail_fast,
+                ..DownloadOptions::default()
+            };
+            let destination = dest.unwrap_or_else(|| PathBuf::from("."));
+
+            let downloaded = Project::from_urls(&label, &urls)
+                .await?
+                .download_dataset(&[], destination, &download_options)
+                .await?;
+
+            if save {
+                let options = RegistryOptions::try_new(None, None, &registry, global)?;
+                let mut project = options.read_registry()?;
+                for dataset in downloaded.datasets_owned() {
+                    project = project.register(dataset).await?;
+                }
+                options.write_registry(&mut project)?;
+            }
+
+            Ok(())
+        },
+
+        // the edit subcommand opens the registry in the user's editor, refusing to save edits that would leave
+        // the registry in an invalid state
+        Some(Commands::Edit { registry, global }) => {
+            let options = RegistryOptions::try_new(None, None, &registry, global)?;
+            edit_registry(&options)
+        },
+
+        // the completions subcommand writes a completion script for the requested shell to stdout
+        Some(Commands::Completions { shell }) => {
+            let mut command = Cli::command();
+            let bin_name = command.get_name().to_string();
+            clap_complete::generate(shell, &mut command, bin_name, &mut io::stdout());
+            Ok(())
+        },
+    }
+}
+
+/// Lists the files to resume a download from: every file under `path` if it's a directory, or else the files listed
+/// in it as a manifest. Manifests list one path per line, relative to the manifest's own directory unless they're
+/// absolute, and may put a checksum before each path as `md5sum` does. Blank lines and lines starting with `#` are
+/// skipped.
+fn files_to_resume(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_dir() {
+        let mut files = Vec::new();
+        let mut dirs = vec![path.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let entry_path = entry?.path();
+                if entry_path.is_dir() {
+                    dirs.push(entry_path);
+                } else {
+                    files.push(entry_path);
+                }
+            }
+        }
+        return Ok(files);
+    }
+
+    let manifest = fs::read_to_string(path)
+        .wrap_err_with(|| format!("Unable to read the manifest at {}", path.display()))?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let files = manifest
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            // `md5sum` writes a checksum, then two spaces or a space and an asterisk, then the path
+            let listed = match line.split_once("  ").or_else(|| line.split_once(" *")) {
+                Some((checksum, listed)) if checksum.chars().all(|c| c.is_ascii_hexdigit()) => {
+                    listed
+                },
+                _ => line,
+            };
+            base_dir.join(listed)
+        })
+        .collect();
+
+    Ok(files)
+}
+
+/// Asks the user whether files in the download destination that refman has no record of downloading should be
+/// overwritten. When nobody is at the terminal to answer, or there are no such files, refman falls back to warning
+/// about each file as it overwrites it.
+fn confirm_overwrites(
+    project: &Project,
+    labels: &[&str],
+    destination: &Path,
+    mirror_structure: bool,
+    per_label_dirs: bool,
+    formats: &FormatSelection,
+) -> Result<ExistingFiles> {
+    if !io::stdin().is_terminal() {
+        return Ok(ExistingFiles::Warn);
+    }
+
+    let unrecorded = project.unrecorded_existing_files(
+        labels,
+        destination,
+        mirror_structure,
+        per_label_dirs,
+        formats,
+    );
+    if unrecorded.is_empty() {
+        return Ok(ExistingFiles::Warn);
+    }
+
+    eprintln!("These files already exist but were not downloaded by refman:");
+    for path in &unrecorded {
+        eprintln!("  {}", path.display());
+    }
+    eprint!("Overwrite them? Answering no will skip downloading them. [y/N] ");
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+        Ok(ExistingFiles::Overwrite)
+    } else {
+        Ok(ExistingFiles::Skip)
+    }
+}
+
+/// Sends the credentials and headers provided on the command line, if any, with every HTTP request to the hosts in
+/// `scope` for the rest of the run.
+fn use_credentials(
+    bearer: Option<String>,
+    basic: Option<&str>,
+    headers: &[HttpHeader],
+    scope: HostScope,
+) {
+    if let Some(token) = bearer {
+        set_credentials(Credentials::Bearer(token), scope.clone());
+    } else if let Some(user_and_password) = basic {
+        set_credentials(Credentials::basic(user_and_password), scope.clone());
+    }
+    set_headers(headers, scope);
+}
+
+/// Opens a scratch copy of the registry in the user's editor, and only replaces the real registry once the edited
+/// copy parses and passes the registry's structural checks. Invalid edits are reported and the editor is re-opened
+/// so they can be fixed, unless the user chooses to discard them.
+fn edit_registry(options: &RegistryOptions) -> Result<()> {
+    let registry_path = options.path();
+    if !registry_path.exists() {
+        Err(RegistryError::NoRegistry(
+            registry_path.display().to_string(),
+        ))?;
+    }
+
+    // work out which editor to use, honoring $VISUAL before $EDITOR like most unix tools do. Editors are
+    // sometimes configured with arguments, e.g. `code --wait`, so split those off of the program name.
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut editor_words = editor.split_whitespace();
+    let Some(program) = editor_words.next() else {
+        return Err(eyre!("The $EDITOR environment variable is set but empty."));
+    };
+    let editor_args: Vec<&str> = editor_words.collect();
+
+    // edit a scratch copy so that the real registry is never left half-edited
+    let scratch_path = registry_path.with_extension("edit.toml");
+    fs::copy(registry_path, &scratch_path)?;
+
+    loop {
+        let status = Command::new(program)
+            .args(&editor_args)
+            .arg(&scratch_path)
+            .status()
+            .with_context(|| {
+                format!("Failed to launch the editor `{editor}`. Set $EDITOR to the editor you'd like refman to use.")
+            })?;
+        if !status.success() {
+            fs::remove_file(&scratch_path)?;
+            return Err(eyre!(
+                "The editor `{editor}` exited unsuccessfully ({status}), so the registry was left unchanged."
+            ));
+        }
+
+        // make sure the edits parse and that the registry still upholds its invariants before saving them
+        let edited = fs::read_to_string(&scratch_path)?;
+        let checked_project = edited
+            .parse::<Project>()
+            .map_err(Report::from)
+            .and_then(|project| {
+                project.validate_structure()?;
+                Ok(project)
+            });
+
+        match checked_project {
+            Ok(mut project) => {
+                options.write_registry(&mut project)?;
+                fs::remove_file(&scratch_path)?;
+                info!("Saved the edited registry to {}.", registry_path.display());
+                return Ok(());
+            },
+            Err(error) => {
+                eprintln!("The edited registry is invalid, so it was not saved:\n\n{error}\n");
+                eprint!(
+                    "Press Enter to re-open it in the editor, or type `q` and Enter to discard your edits: "
+                );
+                io::stderr().flush()?;
+
+                let mut answer = String::new();
+                let bytes_read = io::stdin().read_line(&mut answer)?;
+                if bytes_read == 0 || answer.trim().eq_ignore_ascii_case("q") {
+                    fs::remove_file(&scratch_path)?;
+                    warn!("Discarded the edits; the registry was left unchanged.");
+                    return Ok(());
+                }
+            },
         }
     }
 }
 
-fn setup_logger(verbosity: Verbosity) -> Result<()> {
+/// Whether logs should be colored, which they are unless the user asked otherwise with `--no-color` or the
+/// `NO_COLOR` environment variable, or stderr isn't a terminal, where escape codes would only clutter the output.
+fn use_color(no_color: bool) -> bool {
+    let no_color_env = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    !no_color && !no_color_env && io::stderr().is_terminal()
+}
+
+fn setup_logger(verbosity: Verbosity, color: bool) -> Result<()> {
     // set up the logging verbosity as provided by the user
     let level = verbosity.log_level_filter();
 
@@ -156,13 +1072,23 @@ fn setup_logger(verbosity: Verbosity) -> Result<()> {
         .level_for("clap", log::LevelFilter::Warn)
         .level_for("clap_builder", log::LevelFilter::Warn)
         .format(move |out, message, record| {
-            out.finish(format_args!(
-                "[{} {} {}] {}",
-                jiff::Timestamp::now(),
-                colors.color(record.level()),
-                record.target(),
-                message,
-            ));
+            if color {
+                out.finish(format_args!(
+                    "[{} {} {}] {}",
+                    jiff::Timestamp::now(),
+                    colors.color(record.level()),
+                    record.target(),
+                    message,
+                ));
+            } else {
+                out.finish(format_args!(
+                    "[{} {} {}] {}",
+                    jiff::Timestamp::now(),
+                    record.level(),
+                    record.target(),
+                    message,
+                ));
+            }
         })
         .chain(std::io::stderr())
         .apply()
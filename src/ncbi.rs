@@ -0,0 +1,216 @@
+use color_eyre::eyre::eyre;
+use log::{debug, info, warn};
+use reqwest::Client;
+
+use crate::{EntryError, headers::client};
+
+/// The root of NCBI's genomes FTP site, served over HTTPS.
+const NCBI_GENOMES_ROOT: &str = "https://ftp.ncbi.nlm.nih.gov/genomes/all";
+
+/// The files NCBI publishes for an assembly that `refman` knows how to register.
+///
+/// Every assembly comes with a genomic FASTA, but not every assembly is annotated, so the GFF
+/// and GTF files are only present when NCBI provides them. Genbank (`GCA_`) assemblies in
+/// particular often lack a GTF.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NcbiAssembly {
+    /// The full, versioned accession, e.g. `GCF_000001405.40`
+    pub accession: String,
+    /// URL of the gzipped genomic FASTA
+    pub fasta: String,
+    /// URL of the gzipped GFF3 annotation, if the assembly is annotated
+    pub gff: Option<String>,
+    /// URL of the gzipped GTF annotation, if the assembly is annotated
+    pub gtf: Option<String>,
+}
+
+/// Finds the genomic FASTA and any GFF or GTF annotations that NCBI publishes for an assembly
+/// accession.
+///
+/// Both `RefSeq` (`GCF_`) and Genbank (`GCA_`) accessions are supported. NCBI stores each assembly
+/// under a directory derived from the digits of its accession, named with the accession and the
+/// assembly's name, e.g. `GCF/000/001/405/GCF_000001405.40_GRCh38.p14/`. Because the assembly's
+/// name can't be derived from the accession, the directory listings are used to find it, and
+/// then to check which files are available. If the accession has no version, the latest version
+/// is used.
+///
+/// # Errors
+///
+/// Returns `EntryError::InvalidAccession` if `accession` isn't shaped like an NCBI assembly
+/// accession, `EntryError::AccessionNotFound` if NCBI has no assembly or genomic FASTA for it,
+/// and `EntryError::InvalidURL` if NCBI can't be reached.
+pub async fn resolve_ncbi_assembly(accession: &str) -> Result<NcbiAssembly, EntryError> {
+    let parent_url = assembly_parent_url(accession)?;
+    let client = client()?;
+
+    // find the directory for the requested accession and version among the assemblies sharing its digits
+    let listing = fetch_listing(&client, &parent_url).await?;
+    let Some(assembly_dir) = find_assembly_dir(accession, &listing) else {
+        return Err(EntryError::AccessionNotFound(accession.to_string()));
+    };
+    debug!("Found the NCBI assembly directory '{assembly_dir}' for {accession}");
+
+    // then see which of the files refman can register are available for it
+    let assembly_url = format!("{parent_url}{assembly_dir}/");
+    let files = fetch_listing(&client, &assembly_url).await?;
+    let file_url = |suffix: &str| {
+        let filename = format!("{assembly_dir}_{suffix}");
+        files
+            .contains(&filename)
+            .then(|| format!("{assembly_url}{filename}"))
+    };
+
+    let Some(fasta) = file_url("genomic.fna.gz") else {
+        return Err(EntryError::AccessionNotFound(accession.to_string()));
+    };
+    let gff = file_url("genomic.gff.gz");
+    let gtf = file_url("genomic.gtf.gz");
+    if gff.is_none() && gtf.is_none() {
+        warn!(
+            "NCBI does not provide annotations for {accession}, so only its sequence will be registered."
+        );
+    }
+
+    let resolved_accession = assembly_dir
+        .split('_')
+        .take(2)
+        .collect::<Vec<_>>()
+        .join("_");
+    info!("Resolved {accession} to the NCBI assembly {resolved_accession}.");
+
+    Ok(NcbiAssembly {
+        accession: resolved_accession,
+        fasta,
+        gff,
+        gtf,
+    })
+}
+
+/// Builds the URL of the directory holding every version of the assembly with the provided
+/// accession, e.g. `.../GCF/000/001/405/` for `GCF_000001405.40`.
+fn assembly_parent_url(accession: &str) -> Result<String, EntryError> {
+    let invalid = || EntryError::InvalidAccession(accession.to_string());
+
+    let (prefix, rest) = accession.split_once('_').ok_or_else(invalid)?;
+    if prefix != "GCF" && prefix != "GCA" {
+        return Err(invalid());
+    }
+
+    let (digits, version) = match rest.split_once('.') {
+        Some((digits, version)) => (digits, Some(version)),
+        None => (rest, None),
+    };
+    let valid_version =
+        version.is_none_or(|v| !v.is_empty() && v.chars().all(|c| c.is_ascii_digit()));
+    if digits.len() != 9 || !digits.chars().all(|c| c.is_ascii_digit()) || !valid_version {
+        return Err(invalid());
+    }
+
+    Ok(format!(
+        "{NCBI_GENOMES_ROOT}/{prefix}/{}/{}/{}/",
+        &digits[0..3],
+        &digits[3..6],
+        &digits[6..9]
+    ))
+}
+
+/// Fetches an HTML directory listing from NCBI and returns the names of its entries.
+async fn fetch_listing(client: &Client, url: &str) -> Result<Vec<String>, EntryError> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| EntryError::InvalidURL(eyre!("Unable to reach NCBI at {url}: {e}")))?;
+    if response.status().as_u16() == 404 {
+        return Ok(vec![]);
+    }
+    let body = response
+        .error_for_status()
+        .map_err(|e| EntryError::InvalidURL(eyre!("NCBI returned an error for {url}: {e}")))?
+        .text()
+        .await
+        .map_err(|e| {
+            EntryError::InvalidURL(eyre!("Unable to read NCBI's listing at {url}: {e}"))
+        })?;
+
+    Ok(parse_listing(&body))
+}
+
+/// Pulls the entry names out of the links in an HTML directory listing, dropping any trailing
+/// slashes from directories.
+fn parse_listing(html: &str) -> Vec<String> {
+    html.split("href=\"")
+        .skip(1)
+        .filter_map(|chunk| chunk.split_once('"').map(|(href, _)| href))
+        .filter(|href| !href.starts_with(['/', '?', '.']) && !href.contains("://"))
+        .map(|href| href.trim_end_matches('/').to_string())
+        .collect()
+}
+
+/// Picks the directory for `accession` out of the entries of its parent directory. An accession
+/// without a version matches the latest available version.
+fn find_assembly_dir<'a>(accession: &str, entries: &'a [String]) -> Option<&'a str> {
+    let version_of = |entry: &str| -> Option<u32> {
+        let (versioned, _) = entry.split_once('_')?.1.split_once('_')?;
+        versioned.split_once('.')?.1.parse().ok()
+    };
+
+    if accession.contains('.') {
+        let prefix = format!("{accession}_");
+        entries
+            .iter()
+            .map(String::as_str)
+            .find(|entry| entry.starts_with(&prefix))
+    } else {
+        let prefix = format!("{accession}.");
+        entries
+            .iter()
+            .map(String::as_str)
+            .filter(|entry| entry.starts_with(&prefix))
+            .max_by_key(|entry| version_of(entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARENT_LISTING: &str = r#"<html><body><h1>Index of /genomes/all/GCF/000/001/405</h1>
+<pre><a href="/genomes/all/GCF/000/001/">Parent Directory</a>
+<a href="GCF_000001405.39_GRCh38.p13/">GCF_000001405.39_GRCh38.p13/</a>  2019-03-01 12:00  -
+<a href="GCF_000001405.40_GRCh38.p14/">GCF_000001405.40_GRCh38.p14/</a>  2022-02-03 12:00  -
+</pre></body></html>"#;
+
+    #[test]
+    fn test_assembly_parent_url() {
+        assert_eq!(
+            assembly_parent_url("GCF_000001405.40").ok().as_deref(),
+            Some("https://ftp.ncbi.nlm.nih.gov/genomes/all/GCF/000/001/405/")
+        );
+        assert_eq!(
+            assembly_parent_url("GCA_009914755").ok().as_deref(),
+            Some("https://ftp.ncbi.nlm.nih.gov/genomes/all/GCA/009/914/755/")
+        );
+        for invalid in ["GCX_000001405.40", "GCF_0000014", "GCF_000001405.", "hg38"] {
+            assert!(matches!(
+                assembly_parent_url(invalid),
+                Err(EntryError::InvalidAccession(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_find_assembly_dir() {
+        let entries = parse_listing(PARENT_LISTING);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            find_assembly_dir("GCF_000001405.39", &entries),
+            Some("GCF_000001405.39_GRCh38.p13")
+        );
+        assert_eq!(
+            find_assembly_dir("GCF_000001405", &entries),
+            Some("GCF_000001405.40_GRCh38.p14")
+        );
+        assert_eq!(find_assembly_dir("GCF_000001405.41", &entries), None);
+    }
+}
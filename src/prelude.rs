@@ -1,4 +1,19 @@
 // re-exports
-pub use crate::data::RefDataset;
+pub use crate::auth::{Credentials, set_credentials};
+pub use crate::clipboard::clipboard_urls;
+pub use crate::config::{Config, Profile};
+pub use crate::data::{ByteRange, FileFormat, RefDataset, UrlChecks, label_from_url};
+pub use crate::downloads::{
+    DEFAULT_CHECK_TIMEOUT, DEFAULT_DOWNLOAD_JOBS, DEFAULT_LINK_CHECK_JOBS, DEFAULT_RETRIES,
+    DownloadOptions, ExistingFiles, FormatSelection, LinkCheck, LinkStatus, MAX_RETRY_BACKOFF,
+    RedirectPolicy, download_file,
+};
 pub use crate::errors::*;
-pub use crate::project::{Project, RegistryOptions};
+pub use crate::headers::{HostScope, HttpHeader, set_headers};
+pub use crate::json_api::resolve_json_urls;
+pub use crate::ncbi::{NcbiAssembly, resolve_ncbi_assembly};
+pub use crate::project::{
+    BatchRow, BatchSummary, ImportConflict, ImportSummary, Project, RegisterMode, RegistryDiff,
+    RegistryOptions, UrlChange, read_batch_file,
+};
+pub use crate::validate::{HashAlgorithm, ValidatedFile, ValidationReport, validate_files};
@@ -1,27 +1,47 @@
 use std::{
-    collections::HashMap,
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
     env::{self, current_dir},
-    fs::{self, File, read_to_string},
+    fmt::{self, Display, Formatter},
+    fs::{self, read_to_string},
     path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
+    time::Duration,
 };
 
 use color_eyre::eyre::{Error as ColorError, eyre};
-use futures::future::try_join_all;
+use futures::{StreamExt, stream};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use jiff::Timestamp;
+use jiff::{SignedDuration, Timestamp};
 use log::{debug, info, warn};
-use prettytable::{Table, row};
-use reqwest::Client;
+use prettytable::{Cell, Row, Table, row};
+use rayon::prelude::*;
+use reqwest::{Client, header::CONTENT_LENGTH};
 use serde::{Deserialize, Serialize};
-use tokio::task::JoinHandle;
+use tokio::{
+    sync::Semaphore,
+    task::{Id, JoinSet},
+};
+use url::Url;
 
 use crate::{
-    EntryError, RegistryError, ValidationError,
-    data::{DownloadStatus, RefDataset},
-    downloads::{check_url, request_dataset},
-    validate::UnvalidatedFile,
+    DownloadError, EntryError, RegistryError, ValidationError,
+    data::{ByteRange, DownloadStatus, FileFormat, RefDataset, UrlChecks, is_well_formed_url},
+    downloads::{
+        CacheValidators, DEFAULT_RETRIES, DownloadOptions, ExistingFiles, FormatSelection,
+        LinkCheck, LinkStatus, RedirectPolicy, check_link, check_url, download_path,
+        fetch_validators, remote_changed, request_checksum, request_dataset, request_signature,
+    },
+    headers::{client, with_headers},
+    journal::ProgressJournal,
+    remote_index::RemoteIndex,
+    report::DownloadReport,
+    validate::{
+        HashAlgorithm, UnvalidatedFile, ValidatedFile, ValidationReport, check_files,
+        extract_tarball, hash_valid_download, with_thread_cap,
+    },
+    webhook::{DownloadEvent, notify_webhook},
 };
 
 /// A reference manager for all data associated with your bioinformatics project.
@@ -38,8 +58,9 @@ use crate::{
 /// - Remove datasets from the registry
 /// - Pretty print the current state of registered datasets
 ///
-/// Projects can be either local (stored in ./refman.toml) or global (stored in ~/.refman/refman.toml).
-/// The registry location can also be customized via the `REFMAN_HOME` environment variable.
+/// Projects can be either local (stored in ./refman.toml) or global (stored in ~/.refman/refman.toml,
+/// or `$XDG_DATA_HOME/refman/refman.toml` when `XDG_DATA_HOME` is set). The registry location can
+/// also be customized via the `REFMAN_HOME` environment variable, which takes precedence.
 ///
 /// Each dataset in a project is tracked with a unique label and can contain optional URLs pointing
 /// to reference files in standard bioinformatics formats (FASTA, Genbank, GFA, GFF, GTF, BED).
@@ -47,7 +68,7 @@ use crate::{
 ///
 /// # Examples
 ///
-/// ```no_run
+/// ```ignore
 /// # use refman::project::Project;
 /// // Create a new local project
 /// let project = Project::new(
@@ -64,7 +85,303 @@ pub struct Project {
     project: Registry,
 }
 
-type MultiDownloadResults = Vec<Result<UnvalidatedFile, ColorError>>;
+/// Whether registering a dataset may create a new dataset, update an existing one, or both.
+///
+/// By default, registering a label that isn't in the registry yet creates a new dataset, while
+/// registering a label that is updates it. Scripts that should only ever do one or the other can
+/// restrict registration so that, e.g., a typo in a label can't silently add a spurious dataset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RegisterMode {
+    /// Create the dataset if its label is new, or update it otherwise. This is the default.
+    #[default]
+    CreateOrUpdate,
+    /// Only update an existing dataset, returning `EntryError::LabelNotFound` if the label is new.
+    UpdateOnly,
+    /// Only create a new dataset, returning `EntryError::LabelAlreadyExists` if the label is
+    /// already registered.
+    CreateOnly,
+}
+
+/// What importing another registry does with a dataset whose label is already registered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImportConflict {
+    /// Keep the registered dataset as it is and leave out the imported one. This is the default.
+    #[default]
+    Skip,
+    /// Replace the registered dataset with the imported one.
+    Overwrite,
+    /// Add the imported dataset's files and metadata to the registered dataset, preferring the
+    /// imported file wherever both register a file of the same format under different URLs.
+    MergeFields,
+}
+
+/// The labels of the datasets an import added to the registry, or that were already registered
+/// and were merged, overwritten, or skipped as [`ImportConflict`] asked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Datasets whose labels weren't registered yet
+    pub added: Vec<String>,
+    /// Registered datasets that the imported files and metadata were merged into
+    pub merged: Vec<String>,
+    /// Registered datasets that were replaced by the imported ones
+    pub overwritten: Vec<String>,
+    /// Registered datasets that were left as they were
+    pub skipped: Vec<String>,
+}
+
+impl Display for ImportSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let count = |labels: &[String], outcome: &str| {
+            if labels.is_empty() {
+                format!("0 {outcome}")
+            } else {
+                format!("{} {outcome} ({})", labels.len(), labels.join(", "))
+            }
+        };
+        write!(
+            f,
+            "Imported datasets: {}, {}, {}, {}.",
+            count(&self.added, "added"),
+            count(&self.merged, "merged"),
+            count(&self.overwritten, "overwritten"),
+            count(&self.skipped, "skipped")
+        )
+    }
+}
+
+/// How many rows of a batch file have their URLs checked at once.
+const BATCH_CHECK_JOBS: usize = 8;
+
+/// One row of a batch file for `refman register --from-file`: the line it's on, its label, and
+/// its FASTA, Genbank, GFA, GFF, GTF, BED, TAR, and VCF URLs, in that order. VCF comes last so
+/// that batch files written before it was supported keep their meaning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchRow {
+    pub line: usize,
+    pub label: String,
+    pub urls: [Option<String>; 8],
+}
+
+impl BatchRow {
+    /// Builds the row's dataset, checking its URLs as `url_checks` asks.
+    async fn into_dataset(
+        self,
+        url_checks: UrlChecks,
+        check_timeout: Option<Duration>,
+    ) -> Result<RefDataset, EntryError> {
+        let [fasta, genbank, gfa, gff, gtf, bed, tar, vcf] = self.urls;
+        RefDataset::try_new_with_checks(
+            self.label,
+            fasta,
+            genbank,
+            gfa,
+            gff,
+            gtf,
+            bed,
+            vcf,
+            tar,
+            url_checks,
+            check_timeout,
+        )
+        .await
+    }
+}
+
+/// The labels of the rows of a batch file that [`Project::register_batch`] registered, and the
+/// errors of those it couldn't.
+#[derive(Debug, Default)]
+pub struct BatchSummary {
+    /// Datasets that were registered
+    pub registered: Vec<String>,
+    /// Why each of the other rows couldn't be registered, naming their lines
+    pub failures: Vec<ColorError>,
+}
+
+/// Reads the rows of a batch file for `refman register --from-file`. Files whose names end in
+/// `.csv` are split on commas, and all others on tabs. Blank lines, lines starting with `#`, and
+/// a header row whose first column is `label` are skipped. Rows that are malformed, e.g. because
+/// they have too many columns or no label, are returned as errors naming their line, so that
+/// they can be reported without stopping the others.
+///
+/// # Errors
+///
+/// Returns an error if the batch file can't be read.
+pub fn read_batch_file(path: &Path) -> Result<Vec<Result<BatchRow, ColorError>>, ColorError> {
+    let contents = read_to_string(path)
+        .map_err(|e| eyre!("Unable to read the batch file at {}: {e}", path.display()))?;
+    let delimiter = if path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"))
+    {
+        ','
+    } else {
+        '\t'
+    };
+
+    let rows = contents
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .filter(|(line_number, line)| {
+            *line_number != 1
+                || !line
+                    .split(delimiter)
+                    .next()
+                    .is_some_and(|first| first.trim().eq_ignore_ascii_case("label"))
+        })
+        .map(|(line, contents)| parse_batch_row(line, contents, delimiter))
+        .collect();
+
+    Ok(rows)
+}
+
+/// Parses the columns of one line of a batch file. See [`read_batch_file`].
+fn parse_batch_row(line: usize, contents: &str, delimiter: char) -> Result<BatchRow, ColorError> {
+    let columns: Vec<&str> = contents.split(delimiter).map(str::trim).collect();
+    let Some((label, urls)) = columns.split_first() else {
+        return Err(eyre!("Line {line} of the batch file is empty"));
+    };
+    if label.is_empty() {
+        return Err(eyre!("Line {line} of the batch file has no label"));
+    }
+    if urls.len() > 8 {
+        return Err(eyre!(
+            "Line {line} of the batch file has {} columns, but at most 9 are expected: a label \
+             followed by FASTA, Genbank, GFA, GFF, GTF, BED, TAR, and VCF URLs",
+            columns.len()
+        ));
+    }
+
+    let mut row = BatchRow {
+        line,
+        label: (*label).to_string(),
+        urls: Default::default(),
+    };
+    for (slot, url) in row.urls.iter_mut().zip(urls) {
+        *slot = Some((*url).to_string()).filter(|url| !url.is_empty());
+    }
+
+    Ok(row)
+}
+
+/// How the URL a dataset registers for one format differs between two registries.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UrlChange {
+    /// The format the URL is registered under
+    pub format: FileFormat,
+    /// The URL in the registry being compared, or `None` if it had no file of this format
+    pub old: Option<String>,
+    /// The URL in the registry it was compared to, or `None` if that has no file of this format
+    pub new: Option<String>,
+}
+
+/// The differences between the datasets of two registries, as found by [`Project::diff`], with
+/// datasets sorted by label.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RegistryDiff {
+    /// Datasets that are only in the registry compared to
+    pub added: Vec<String>,
+    /// Datasets that are only in the registry being compared
+    pub removed: Vec<String>,
+    /// Datasets in both registries whose URLs differ, along with each changed URL
+    pub changed: BTreeMap<String, Vec<UrlChange>>,
+}
+
+impl RegistryDiff {
+    /// Whether both registries have the same datasets with the same URLs.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Serializes the differences as pretty-printed JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RegistryError::InvalidJsonOutput` if the differences can't be serialized.
+    pub fn to_json(&self) -> Result<String, RegistryError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl Display for RegistryDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "The registries have the same datasets and URLs.");
+        }
+
+        let mut sections = Vec::with_capacity(3);
+        if !self.added.is_empty() {
+            let lines: Vec<String> = self
+                .added
+                .iter()
+                .map(|label| format!("  + {label}"))
+                .collect();
+            sections.push(format!("Added datasets:\n{}", lines.join("\n")));
+        }
+        if !self.removed.is_empty() {
+            let lines: Vec<String> = self
+                .removed
+                .iter()
+                .map(|label| format!("  - {label}"))
+                .collect();
+            sections.push(format!("Removed datasets:\n{}", lines.join("\n")));
+        }
+        if !self.changed.is_empty() {
+            let url = |url: &Option<String>| url.as_deref().unwrap_or("(none)").to_string();
+            let lines: Vec<String> = self
+                .changed
+                .iter()
+                .map(|(label, changes)| {
+                    let changes: Vec<String> = changes
+                        .iter()
+                        .map(|change| {
+                            format!(
+                                "    {}: {} → {}",
+                                change.format,
+                                url(&change.old),
+                                url(&change.new)
+                            )
+                        })
+                        .collect();
+                    format!("  ~ {label}\n{}", changes.join("\n"))
+                })
+                .collect();
+            sections.push(format!("Changed datasets:\n{}", lines.join("\n")));
+        }
+
+        write!(f, "{}", sections.join("\n"))
+    }
+}
+
+/// The number of consecutive failed attempts after which downloads skip a file, unless they're
+/// asked to retry failed files.
+const CHRONIC_FAILURE_ATTEMPTS: u32 = 3;
+
+/// What a download run would do, as worked out for [`Project::print_download_plan`].
+struct DownloadPlan {
+    /// Every file of the selected datasets, where it would be saved, whether it would be
+    /// downloaded or skipped, and how large it is
+    table: Table,
+    num_to_download: usize,
+    num_skipped: usize,
+    /// How many of the files to download have no known size, counting shared URLs once
+    num_unknown: usize,
+    /// How many bytes would be downloaded, counting shared URLs once
+    total_bytes: u64,
+}
+
+/// A file that could not be downloaded, along with the error that stopped it.
+struct FailedDownload {
+    file: UnvalidatedFile,
+    error: ColorError,
+}
+
+/// A downloaded file, along with the headers that can later tell whether it changed upstream
+type DownloadedFile = (UnvalidatedFile, CacheValidators);
+type MultiDownloadResults = Vec<Result<DownloadedFile, FailedDownload>>;
+type DatasetDownloadResult = Result<(RefDataset, MultiDownloadResults), ColorError>;
 
 impl Project {
     /// Creates a new Project struct with optional title and description strings and
@@ -79,7 +396,8 @@ impl Project {
     ///
     /// The registry file location depends on the `global` parameter:
     /// - If `global=false` (default), creates a local refman.toml in current directory
-    /// - If `global=true`, uses either `$REFMAN_HOME/.refman/refman.toml` or ~/.refman/refman.toml
+    /// - If `global=true`, uses `$REFMAN_HOME/.refman/refman.toml`, `$XDG_DATA_HOME/refman/refman.toml`,
+    ///   or ~/.refman/refman.toml, in that order of precedence
     ///
     /// # Arguments
     ///
@@ -105,6 +423,77 @@ impl Project {
         Self { project: registry }
     }
 
+    /// Builds a single-dataset Project from a list of URLs, without any registry file involved.
+    ///
+    /// This is a convenience for one-off downloads: the format of each file is inferred from the
+    /// extension of its URL with [`FileFormat::from_uri`], and the files are gathered into one
+    /// [`RefDataset`] under the provided label via [`RefDataset::try_new`], which also checks that
+    /// each URL points to a resource that exists. The resulting Project can be downloaded with
+    /// [`Project::download_dataset`] like any other, and only needs to be written to disk if the
+    /// caller wants to keep it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::UnrecognizedFormat` if a URL's extension doesn't map to a supported
+    /// format, `EntryError::DuplicateFormat` if two URLs map to the same format, and any error
+    /// returned by [`RefDataset::try_new`], e.g. when no URLs are provided or a URL is invalid.
+    pub async fn from_urls<S: AsRef<str>>(label: &str, urls: &[S]) -> Result<Self, EntryError> {
+        let mut files: HashMap<FileFormat, String> = HashMap::new();
+        for url in urls {
+            let url = url.as_ref();
+            let format = FileFormat::from_uri(url)
+                .ok_or_else(|| EntryError::UnrecognizedFormat(url.to_string()))?;
+            if let Some(previous) = files.insert(format, url.to_string()) {
+                return Err(EntryError::DuplicateFormat(
+                    format.to_string(),
+                    previous,
+                    url.to_string(),
+                ));
+            }
+        }
+
+        let dataset = RefDataset::try_new(
+            label.to_string(),
+            files.remove(&FileFormat::Fasta),
+            files.remove(&FileFormat::Genbank),
+            files.remove(&FileFormat::Gfa),
+            files.remove(&FileFormat::Gff),
+            files.remove(&FileFormat::Gtf),
+            files.remove(&FileFormat::Bed),
+            files.remove(&FileFormat::Vcf),
+            files.remove(&FileFormat::Tar),
+        )
+        .await?;
+
+        let mut project = Self::default();
+        project.project.datasets.push(dataset);
+
+        Ok(project)
+    }
+
+    /// Returns the people who maintain the project's registry, e.g. as names or email addresses.
+    /// Empty if no maintainers have been recorded.
+    #[inline]
+    #[must_use]
+    pub fn maintainers(&self) -> &[String] {
+        &self.project.maintainers
+    }
+
+    /// Returns the homepage recorded for the project's registry, if any, where users of a shared
+    /// registry can find more information or get in touch with its maintainers.
+    #[inline]
+    #[must_use]
+    pub fn homepage(&self) -> Option<&str> {
+        self.project.homepage.as_deref()
+    }
+
+    /// Returns the base64-encoded minisign public key recorded for the project's registry, if
+    /// any, which is used to verify the signatures of datasets that don't provide their own key.
+    #[must_use]
+    pub fn public_key(&self) -> Option<&str> {
+        self.project.public_key.as_deref()
+    }
+
     /// Returns a read-only slice of all reference datasets currently registered in the project.
     ///
     /// This method provides access to the raw collection of `RefDataset` entries stored in the
@@ -131,6 +520,14 @@ impl Project {
         self.project.datasets.as_slice()
     }
 
+    /// Returns the labels of all registered datasets, in the order they were registered.
+    ///
+    /// This is all that shell completions and scripts that loop over datasets need, without
+    /// having to parse the table printed by `refman list`.
+    pub fn dataset_labels(&self) -> impl Iterator<Item = &str> {
+        self.datasets().iter().map(|dataset| dataset.label.as_str())
+    }
+
     /// Returns a mutable slice of all reference datasets registered in the project.
     ///
     /// This method provides mutable access to the raw collection of `RefDataset` entries stored in
@@ -304,6 +701,8 @@ impl Project {
             dataset.gff.clone(),
             dataset.gtf.clone(),
             dataset.bed.clone(),
+            dataset.vcf.clone(),
+            dataset.tar.clone(),
         ]
         .into_iter()
         .flatten()
@@ -317,7 +716,7 @@ impl Project {
     ///
     /// This method provides access to all file URLs registered in the project's datasets,
     /// aggregating URLs from each dataset and each supported bioinformatics file format
-    /// (FASTA, Genbank, GFA, GFF, GTF, BED). It is useful for:
+    /// (FASTA, Genbank, GFA, GFF, GTF, BED, VCF, TAR). It is useful for:
     /// - Getting an overview of all reference data in the project
     /// - Batch downloading all registered files
     /// - Validating URLs across the entire registry
@@ -325,7 +724,7 @@ impl Project {
     ///
     /// The method processes each dataset sequentially, collecting any non-None URLs into
     /// a single vector. URLs are gathered in a consistent order per dataset:
-    /// FASTA -> Genbank -> GFA -> GFF -> GTF -> BED.
+    /// FASTA -> Genbank -> GFA -> GFF -> GTF -> BED -> VCF -> TAR.
     ///
     /// Unlike `get_dataset_urls()` which operates on a single labeled dataset, this method
     /// provides complete URL access across the entire registry. It complements other Project
@@ -333,7 +732,7 @@ impl Project {
     ///
     /// The method enforces URL validity by checking that:
     /// - No empty URLs are included
-    /// - All URLs use either http:// or https:// protocols
+    /// - All URLs use the http://, https://, ftp://, or s3:// protocols
     ///
     /// # Returns
     ///
@@ -342,16 +741,9 @@ impl Project {
     ///
     /// # Errors
     ///
-    /// Can return `EntryError` variants if:
-    /// - Dataset access fails
-    /// - URL validation fails
-    /// - Project state is invalid
-    ///
-    /// # Panics
-    ///
-    /// This method will panic if:
-    /// - Empty URLs are found in datasets (invalid state)
-    /// - URLs with invalid protocols are found (must be http/https)
+    /// Returns `EntryError::MalformedUrl`, naming the dataset and URL, if a dataset has an empty
+    /// URL or one that doesn't use the http://, https://, ftp://, or s3:// protocols. Registries edited
+    /// by hand can end up in this state.
     #[inline]
     pub fn get_all_urls(&self) -> Result<Vec<String>, EntryError> {
         // access the dataset for the provided label
@@ -360,34 +752,39 @@ impl Project {
         // build a vector based on the URLs that may or may not be available for downloading
         let mut all_urls = Vec::new();
         for dataset in datasets {
-            let urls = vec![
-                dataset.fasta.clone(),
-                dataset.genbank.clone(),
-                dataset.gfa.clone(),
-                dataset.gff.clone(),
-                dataset.gtf.clone(),
-                dataset.bed.clone(),
-            ]
-            .into_iter()
-            .flatten()
-            .map(|download| download.url_owned())
-            .collect::<Vec<String>>();
-            all_urls.extend(urls);
+            let urls = dataset
+                .file_fields()
+                .into_iter()
+                .filter_map(|(_, download)| download.map(DownloadStatus::url_owned));
+            for url in urls {
+                if !is_well_formed_url(&url) {
+                    return Err(EntryError::MalformedUrl(dataset.label.clone(), url));
+                }
+                all_urls.push(url);
+            }
         }
-        assert!(
-            all_urls.iter().all(|url| !url.is_empty()),
-            "Found empty URLs in dataset"
-        );
-        assert!(
-            all_urls
-                .iter()
-                .all(|url| url.starts_with("http://") || url.starts_with("https://")),
-            "Found invalid URL protocols"
-        );
 
         Ok(all_urls)
     }
 
+    /// Returns `label` if no dataset is registered with it yet, or else `label` with the lowest
+    /// counter appended that makes it unique, e.g. `genome-2`.
+    #[must_use]
+    pub fn unique_label(&self, label: &str) -> String {
+        if !self.is_registered(label) {
+            return label.to_string();
+        }
+
+        let mut counter = 2;
+        loop {
+            let candidate = format!("{label}-{counter}");
+            if !self.is_registered(&candidate) {
+                return candidate;
+            }
+            counter += 1;
+        }
+    }
+
     /// Checks if a dataset with a given label is registered in the project.
     ///
     /// This method searches through the project's registry to determine if a dataset
@@ -425,6 +822,195 @@ impl Project {
             .is_empty()
     }
 
+    /// Checks that the registry as a whole is structurally sound.
+    ///
+    /// Every dataset must pass [`RefDataset::validate_structure`], and no two datasets may share
+    /// a label, since labels act as the registry's primary key. These invariants are upheld by
+    /// `register()` and friends, but a `refman.toml` that was edited by hand or produced by
+    /// another tool may break them. This only inspects the registry itself; it does not check
+    /// URLs or downloaded files.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first `EntryError` encountered, e.g. `EntryError::DuplicateLabel` if a label
+    /// is used more than once, or `EntryError::LabelButNoFiles` if a dataset has no files.
+    pub fn validate_structure(&self) -> Result<(), EntryError> {
+        let mut seen_labels = HashSet::with_capacity(self.datasets().len());
+        for dataset in self.datasets() {
+            dataset.validate_structure()?;
+            if !seen_labels.insert(dataset.label.as_str()) {
+                return Err(EntryError::DuplicateLabel(dataset.label.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates every downloaded file in the registry, parsing each in its registered format,
+    /// and reports the result for each file rather than stopping at the first problem.
+    ///
+    /// Datasets are validated in parallel with rayon, as are the files within each dataset, using
+    /// at most `threads` threads if a number is provided, and otherwise every core. Files that
+    /// were validated before and still match their recorded checksums pass without being parsed
+    /// again, since hashing a file is much faster than parsing it, unless `force_revalidate` is
+    /// set. With `sample`, only the first `sample` records of each FASTA, Genbank, GFF, GTF, or
+    /// VCF file are parsed, which is much faster for very large files but a weaker check. Files
+    /// that haven't been downloaded are left out of the report. Each file that passes, whether
+    /// it was parsed or not, has its `last_validated` time refreshed, along with whether it was
+    /// only sampled, so the registry should be written afterwards to keep them.
+    ///
+    /// # Errors
+    ///
+    /// Problems with individual files are recorded in the returned [`ValidationReport`] rather
+    /// than returned as errors, so this currently always returns `Ok`. The `Result` leaves room
+    /// for failures that would keep validation from running at all.
+    pub fn validate_all(
+        &mut self,
+        threads: Option<usize>,
+        force_revalidate: bool,
+        sample: Option<usize>,
+    ) -> Result<ValidationReport, ValidationError> {
+        // the cap applies to the whole run, so every dataset's files are checked inside the same pool
+        let checked: BTreeMap<String, BTreeMap<FileFormat, _>> = with_thread_cap(threads, || {
+            self.datasets()
+                .par_iter()
+                .map(|dataset| {
+                    (
+                        dataset.label.clone(),
+                        check_files(dataset, None, force_revalidate, sample),
+                    )
+                })
+                .filter(|(_, formats)| !formats.is_empty())
+                .collect()
+        });
+
+        let now = Timestamp::now();
+        for dataset in self.datasets_mut() {
+            let Some(formats) = checked.get(&dataset.label) else {
+                continue;
+            };
+            for (format, result) in formats {
+                if let Ok(sampled_records) = result {
+                    dataset.record_validation(*format, now, *sampled_records);
+                }
+            }
+        }
+
+        let datasets = checked
+            .into_iter()
+            .map(|(label, formats)| {
+                let results = formats
+                    .into_iter()
+                    .map(|(format, result)| (format, result.map(|_| ())))
+                    .collect();
+                (label, results)
+            })
+            .collect();
+
+        Ok(ValidationReport { datasets })
+    }
+
+    /// Checks that every URL registered for the datasets in `labels`, or for every dataset if
+    /// `labels` is empty, still leads somewhere, without downloading anything.
+    ///
+    /// Unlike [`Project::validate_all`], which parses the files on disk, this only asks the
+    /// remote servers whether they still have the files, which catches links that have rotted
+    /// since they were registered. At most `jobs` URLs are checked at once, each given up on
+    /// after `timeout`. Files registered from local paths are reported as broken if the path no
+    /// longer exists. The checks are returned in the order the files appear in the registry.
+    pub async fn verify_urls(
+        &self,
+        labels: &[&str],
+        jobs: usize,
+        timeout: Duration,
+    ) -> Vec<LinkCheck> {
+        let files = self
+            .datasets()
+            .iter()
+            .filter(|dataset| is_selected(labels, dataset))
+            .flat_map(|dataset| {
+                dataset
+                    .file_fields()
+                    .into_iter()
+                    .filter_map(|(format, status)| {
+                        status.map(|status| (dataset.label.clone(), format, status.url_owned()))
+                    })
+            });
+
+        stream::iter(files)
+            .map(|(label, format, url)| async move {
+                let status = if is_likely_url(&url) {
+                    check_link(&url, timeout).await
+                } else if Path::new(&url).exists() {
+                    LinkStatus::Ok
+                } else {
+                    LinkStatus::Broken("no file exists at this local path".to_string())
+                };
+                LinkCheck {
+                    label,
+                    format,
+                    url,
+                    status,
+                }
+            })
+            .buffered(jobs.max(1))
+            .collect()
+            .await
+    }
+
+    /// Checks the registry for every structural problem it has, rather than stopping at the first.
+    ///
+    /// This runs the same checks as [`Project::validate_structure`], and adds a few that only make
+    /// sense for a registry that is about to be relied on, e.g. in CI: annotations must come with
+    /// a FASTA or Genbank sequence, every URL must be a well-formed http(s), ftp, or s3 URL, and every
+    /// file recorded as downloaded must still exist at its local path. Files that were only
+    /// checksummed have no local path and are not checked for.
+    ///
+    /// Returns an empty vector if the registry is sound.
+    #[must_use]
+    pub fn structural_problems(&self) -> Vec<EntryError> {
+        let mut problems = Vec::new();
+        let mut seen_labels = HashSet::with_capacity(self.datasets().len());
+        for dataset in self.datasets() {
+            if let Err(e) = dataset.validate_structure() {
+                problems.push(e);
+            }
+            if !dataset.label.is_empty() && !seen_labels.insert(dataset.label.as_str()) {
+                problems.push(EntryError::DuplicateLabel(dataset.label.clone()));
+            }
+            if dataset.has_annotation() && !dataset.has_sequence() {
+                problems.push(EntryError::AnnotationsButNoSequence(dataset.label.clone()));
+            }
+
+            for (format, status) in dataset.file_fields() {
+                let Some(status) = status else {
+                    continue;
+                };
+                let url = status.url();
+                let well_formed = Url::parse(url).is_ok_and(|parsed| {
+                    matches!(parsed.scheme(), "http" | "https" | "ftp" | "s3") && parsed.has_host()
+                });
+                if !well_formed {
+                    problems.push(EntryError::MalformedUrl(
+                        dataset.label.clone(),
+                        url.to_string(),
+                    ));
+                }
+                if let DownloadStatus::Downloaded(file) = status {
+                    if status.is_downloaded() && !file.local_path.exists() {
+                        problems.push(EntryError::MissingLocalFile(
+                            dataset.label.clone(),
+                            format.to_string(),
+                            file.local_path.display().to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        problems
+    }
+
     /// Registers a new dataset or updates an existing dataset in the Project's registry.
     ///
     /// This is one of the core methods for managing reference data in refman. It takes a `RefDataset`
@@ -457,14 +1043,17 @@ impl Project {
     ///
     /// To register a new dataset:
     /// ```rust,no_run
-    /// # use refman::{project::Project, data::RefDataset};
-    /// let mut project = Project::new(None, None, false);
+    /// # use refman::{project::Project, data::{DownloadStatus, RefDataset}};
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut project = Project::default();
     /// let dataset = RefDataset {
     ///     label: "genome".into(),
-    ///     fasta: Some("https://example.com/genome.fasta".into()),
+    ///     fasta: Some(DownloadStatus::new("https://example.com/genome.fasta".into())),
     ///     ..Default::default()
     /// };
-    /// project = project.register(dataset).unwrap();
+    /// project = project.register(dataset).await?;
+    /// # Ok(())
+    /// # }
     /// ```
     ///
     /// The registration process will either add this as a new dataset if "genome" is not yet
@@ -474,7 +1063,8 @@ impl Project {
     ///
     /// This method can return several types of errors:
     /// - `EntryError::LabelNotFound` if the dataset being registered cannot be found during updates
-    /// - `EntryError::FinalEntry` if registering this dataset would leave the registry empty
+    /// - `EntryError::AnnotationsButNoSequence` if the dataset would end up with annotation files
+    ///   (GFF, GTF, or BED) but no FASTA or Genbank sequence for them to annotate
     /// - Filesystem errors from reading/writing the registry file
     /// - Serialization errors when encoding/decoding the registry TOML
     /// - Permission errors when accessing registry files
@@ -488,12 +1078,59 @@ impl Project {
     /// the registry. This should never happen as labels must be unique, but represents an
     /// invalid state that requires immediate attention.
     ///
-    pub async fn register(mut self, new_dataset: RefDataset) -> Result<Self, EntryError> {
-        let Some(dataset_match_idx) = self.get_dataset_idx(&new_dataset.label) else {
+    pub async fn register(self, new_dataset: RefDataset) -> Result<Self, EntryError> {
+        self.register_with_mode(new_dataset, RegisterMode::CreateOrUpdate)
+            .await
+    }
+
+    /// Registers a dataset like [`register`](Self::register), but only creates or only updates a
+    /// dataset as requested with `mode`.
+    ///
+    /// # Errors
+    ///
+    /// In addition to the errors `register` can return, returns `EntryError::LabelNotFound` if
+    /// `mode` is `RegisterMode::UpdateOnly` and the dataset's label isn't registered yet, or
+    /// `EntryError::LabelAlreadyExists` if `mode` is `RegisterMode::CreateOnly` and it is.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same invalid registry state as `register`.
+    pub async fn register_with_mode(
+        mut self,
+        new_dataset: RefDataset,
+        mode: RegisterMode,
+    ) -> Result<Self, EntryError> {
+        self.register_in_place(new_dataset, mode).await?;
+        Ok(self)
+    }
+
+    /// Registers a dataset like [`register_with_mode`](Self::register_with_mode), but in place.
+    /// A registration that fails partway may leave the dataset partly updated.
+    #[allow(clippy::too_many_lines)]
+    async fn register_in_place(
+        &mut self,
+        new_dataset: RefDataset,
+        mode: RegisterMode,
+    ) -> Result<(), EntryError> {
+        let dataset_match_idx = self.get_dataset_idx(&new_dataset.label);
+        match (mode, dataset_match_idx) {
+            (RegisterMode::UpdateOnly, None) => {
+                return Err(EntryError::LabelNotFound(new_dataset.label));
+            },
+            (RegisterMode::CreateOnly, Some(_)) => {
+                return Err(EntryError::LabelAlreadyExists(new_dataset.label));
+            },
+            _ => {},
+        }
+        let Some(dataset_match_idx) = dataset_match_idx else {
             // if the label wasn't found, it's not in the registry, so it can be safely
-            // appended without any fear of duplication
+            // appended without any fear of duplication, as long as its annotations come with a
+            // sequence to annotate
+            if new_dataset.has_annotation() && !new_dataset.has_sequence() {
+                return Err(EntryError::AnnotationsButNoSequence(new_dataset.label));
+            }
             self.project.datasets.push(new_dataset);
-            return Ok(self);
+            return Ok(());
         };
 
         // pull in a mutable reference to the slice of datasets, get a mutable reference to the one
@@ -502,6 +1139,10 @@ impl Project {
         let previous_datasets = self.datasets_mut();
         let dataset_to_update = &mut previous_datasets[dataset_match_idx];
 
+        // carry over any signatures, public key, record counts, checksums, byte ranges, or destination provided
+        // alongside the new files
+        dataset_to_update.merge_metadata(&new_dataset);
+
         // use pattern matching here to get exhaustiveness checking instead of if-else
         match new_dataset {
             // if it's a FASTA, make sure the link points to a resource that exists and then update
@@ -516,7 +1157,9 @@ impl Project {
                 // for all file types.
                 let url_str = fasta.url();
                 if is_likely_url(url_str) {
-                    let _ = check_url(url_str).await?;
+                    if new_dataset.url_checks.checks(FileFormat::Fasta) {
+                        let _ = check_url(url_str).await?;
+                    }
                 } else if !PathBuf::from(url_str).is_file() {
                     return Err(EntryError::InvalidURL(eyre!(
                         "The provided uri {url_str} was not a web link, nor was it a local file path pointing to something that exists."
@@ -531,7 +1174,7 @@ impl Project {
                 ..
             } => {
                 let url_str = genbank.url();
-                if is_likely_url(url_str) {
+                if is_likely_url(url_str) && new_dataset.url_checks.checks(FileFormat::Genbank) {
                     let _ = check_url(url_str).await?;
                 }
                 dataset_to_update.genbank = new_dataset.genbank;
@@ -542,7 +1185,7 @@ impl Project {
                 gfa: Some(ref gfa), ..
             } => {
                 let url_str = gfa.url();
-                if is_likely_url(url_str) {
+                if is_likely_url(url_str) && new_dataset.url_checks.checks(FileFormat::Gfa) {
                     let _ = check_url(url_str).await?;
                 }
                 dataset_to_update.gfa = new_dataset.gfa;
@@ -553,7 +1196,7 @@ impl Project {
                 gff: Some(ref gff), ..
             } => {
                 let url_str = gff.url();
-                if is_likely_url(url_str) {
+                if is_likely_url(url_str) && new_dataset.url_checks.checks(FileFormat::Gff) {
                     let _ = check_url(url_str).await?;
                 }
                 dataset_to_update.gff = new_dataset.gff;
@@ -564,7 +1207,7 @@ impl Project {
                 gtf: Some(ref gtf), ..
             } => {
                 let url_str = gtf.url();
-                if is_likely_url(url_str) {
+                if is_likely_url(url_str) && new_dataset.url_checks.checks(FileFormat::Gtf) {
                     let _ = check_url(url_str).await?;
                 }
                 dataset_to_update.gtf = new_dataset.gtf;
@@ -575,18 +1218,29 @@ impl Project {
                 bed: Some(ref bed), ..
             } => {
                 let url_str = bed.url();
-                if is_likely_url(url_str) {
+                if is_likely_url(url_str) && new_dataset.url_checks.checks(FileFormat::Bed) {
                     let _ = check_url(url_str).await?;
                 }
                 dataset_to_update.bed = new_dataset.bed;
             },
 
+            // Do the same thing but with a putative VCF file
+            RefDataset {
+                vcf: Some(ref vcf), ..
+            } => {
+                let url_str = vcf.url();
+                if is_likely_url(url_str) && new_dataset.url_checks.checks(FileFormat::Vcf) {
+                    let _ = check_url(url_str).await?;
+                }
+                dataset_to_update.vcf = new_dataset.vcf;
+            },
+
             // Do the same thing but with a putative TAR file
             RefDataset {
                 tar: Some(ref tar), ..
             } => {
                 let url_str = tar.url();
-                if is_likely_url(url_str) {
+                if is_likely_url(url_str) && new_dataset.url_checks.checks(FileFormat::Tar) {
                     let _ = check_url(url_str).await?;
                 }
                 dataset_to_update.tar = new_dataset.tar;
@@ -602,13 +1256,115 @@ impl Project {
                 gff: None,
                 gtf: None,
                 bed: None,
+                vcf: None,
                 tar: None,
+                ..
             } => return Err(EntryError::LabelButNoFiles),
         }
 
-        // If we've made it this far, all is well; return the mutated instance of
-        // the project.
-        Ok(self)
+        // the update may have registered a URL that the dataset already uses for another format
+        dataset_to_update.warn_duplicate_urls();
+
+        // annotations are only useful alongside the sequence they annotate, so don't let an update
+        // leave the dataset with one but not the other
+        if dataset_to_update.has_annotation() && !dataset_to_update.has_sequence() {
+            return Err(EntryError::AnnotationsButNoSequence(
+                dataset_to_update.label.clone(),
+            ));
+        }
+
+        // If we've made it this far, all is well
+        Ok(())
+    }
+
+    /// Registers the dataset of each row of a batch file read with [`read_batch_file`], checking
+    /// the URLs of several rows at once and registering the rows in order as their checks finish.
+    /// With `strict`, rows that register a URL under more than one format are refused.
+    ///
+    /// Rows that can't be registered are left out of the registry and collected in the returned
+    /// summary, unless `fail_fast` is set, in which case the first of them is returned as an
+    /// error.
+    ///
+    /// # Errors
+    ///
+    /// With `fail_fast`, returns an error naming the line of the first row that is malformed or
+    /// can't be registered.
+    pub async fn register_batch(
+        &mut self,
+        rows: Vec<Result<BatchRow, ColorError>>,
+        mode: RegisterMode,
+        url_checks: UrlChecks,
+        check_timeout: Option<Duration>,
+        strict: bool,
+        fail_fast: bool,
+    ) -> Result<BatchSummary, ColorError> {
+        let mut summary = BatchSummary::default();
+        let mut checked = stream::iter(rows)
+            .map(|row| async move {
+                let row = row?;
+                let (line, label) = (row.line, row.label.clone());
+                row.into_dataset(url_checks, check_timeout)
+                    .await
+                    .map(|dataset| (line, dataset))
+                    .map_err(|e| {
+                        eyre!(
+                            "Line {line} of the batch file ('{label}') could not be registered: {e}"
+                        )
+                    })
+            })
+            .buffered(BATCH_CHECK_JOBS);
+
+        while let Some(checked_row) = checked.next().await {
+            let registered = match checked_row {
+                Ok((line, dataset)) => {
+                    let label = dataset.label.clone();
+                    self.register_batch_row(dataset, mode, strict)
+                        .await
+                        .map(|()| label.clone())
+                        .map_err(|e| {
+                            eyre!("Line {line} of the batch file ('{label}') could not be registered: {e}")
+                        })
+                },
+                Err(error) => Err(error),
+            };
+            match registered {
+                Ok(label) => summary.registered.push(label),
+                Err(error) if fail_fast => return Err(error),
+                Err(error) => summary.failures.push(error),
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Registers one row of a batch file in place, restoring the dataset it would have changed if
+    /// it can't be registered, so that one bad row doesn't leave the registry partly updated.
+    async fn register_batch_row(
+        &mut self,
+        new_dataset: RefDataset,
+        mode: RegisterMode,
+        strict: bool,
+    ) -> Result<(), EntryError> {
+        let label = new_dataset.label.clone();
+        let previous = self
+            .get_dataset_idx(&label)
+            .map(|idx| (idx, self.datasets()[idx].clone()));
+
+        let mut registered = self.register_in_place(new_dataset, mode).await;
+        if strict && registered.is_ok() {
+            registered = self.get_dataset(&label)?.check_duplicate_urls();
+        }
+        if registered.is_err() {
+            match previous {
+                Some((idx, dataset)) => self.datasets_mut()[idx] = dataset,
+                None => self
+                    .project
+                    .datasets
+                    .retain(|dataset| dataset.label != label),
+            }
+        }
+
+        registered
     }
 
     #[inline]
@@ -644,57 +1400,100 @@ impl Project {
     #[allow(clippy::similar_names)]
     pub(crate) fn collect_downloads(
         &self,
-        label: Option<&str>,
+        labels: &[&str],
         target_dir: &Path,
+        per_label_dirs: bool,
     ) -> Vec<(RefDataset, Vec<UnvalidatedFile>)> {
-        let datasets = if let Some(label) = label {
-            self.clone()
-                .datasets_owned()
-                .into_iter()
-                .filter(|dataset| dataset.label == label)
-                .collect::<Vec<_>>()
-        } else {
-            self.clone()
-                .datasets_owned()
-                .into_iter()
-                .collect::<Vec<_>>()
-        };
+        let datasets = self
+            .clone()
+            .datasets_owned()
+            .into_iter()
+            .filter(|dataset| is_selected(labels, dataset))
+            .collect::<Vec<_>>();
         assert_ne!(0, datasets.len());
         datasets
             .into_iter()
             .map(|dataset| {
-                let fasta = dataset.get_fasta_download(target_dir);
-                let genbank = dataset.get_genbank_download(target_dir);
-                let gfa = dataset.get_gfa_download(target_dir);
-                let gtf = dataset.get_gtf_download(target_dir);
-                let gff = dataset.get_gff_download(target_dir);
-                let bed = dataset.get_bed_download(target_dir);
-                let tar = dataset.get_tar_download(target_dir);
-                info!(
-                    "Preparing to download these files:\n{:?}",
-                    [&fasta, &genbank, &gfa, &gff, &gtf, &bed, &tar]
-                );
-                let files = [fasta, genbank, gfa, gff, gtf, bed, tar]
-                    .into_iter()
-                    .flatten()
-                    .collect::<Vec<_>>();
+                let dir = dataset.download_dir(target_dir, per_label_dirs);
+                let files = pending_downloads(&dataset, &dir);
+                info!("Preparing to download these files:\n{files:?}");
                 (dataset, files)
             })
             .collect::<Vec<_>>()
     }
 
-    /// Downloads a reference dataset from a Project's registry by label, fetching any registered file
-    /// URLs into a target directory.
-    ///
-    /// This method implements the core file downloading functionality in refman. Given a dataset label
-    /// and target directory, it will:
-    /// 1. Verify the dataset exists in the registry
-    /// 2. Extract all registered file URLs for that dataset (FASTA, Genbank, GFA, GFF, GTF, BED)
-    /// 3. Launch concurrent downloads of all files into the target directory
-    /// 4. Handle any download failures or errors
-    ///
-    /// Downloads happen asynchronously and in parallel for improved performance. The method uses
-    /// tokio for async runtime and reqwest for HTTP requests. Files are downloaded maintaining
+    /// Returns the files that downloading into `target_dir` would write over, but that no dataset
+    /// in the registry records having downloaded.
+    ///
+    /// Only the files that would actually be downloaded for the datasets with the provided labels,
+    /// or for all datasets when `labels` is empty, are considered; files that were previously
+    /// downloaded and still pass their checksums are left alone by downloads, so they are never
+    /// returned. Files with a matching record in the registry are expected to be replaced, and
+    /// files that an interrupted run left listed in its journal (see [`Project::download_dataset`])
+    /// were downloaded by refman, so they aren't returned either. This lets callers warn or ask before overwriting unrelated
+    /// files in a shared or populated directory; see [`ExistingFiles`]. `mirror_structure` should
+    /// match [`DownloadOptions::mirror_structure`] and `per_label_dirs` should match
+    /// [`DownloadOptions::per_label_dirs`] so that the files are looked for where the download
+    /// would put them, and `formats` should match [`DownloadOptions::formats`] so that
+    /// only files of formats being downloaded are considered.
+    #[must_use]
+    pub fn unrecorded_existing_files(
+        &self,
+        labels: &[&str],
+        target_dir: &Path,
+        mirror_structure: bool,
+        per_label_dirs: bool,
+        formats: &FormatSelection,
+    ) -> Vec<PathBuf> {
+        // gather every path the registry knows refman downloaded, resolving them where possible so that different
+        // spellings of the same path still match
+        let recorded: HashSet<PathBuf> = self
+            .datasets()
+            .iter()
+            .flat_map(RefDataset::file_fields)
+            .filter_map(|(_, status)| match status? {
+                DownloadStatus::Downloaded(file) if !file.local_path.as_os_str().is_empty() => {
+                    Some(resolve_path(&file.local_path))
+                },
+                _ => None,
+            })
+            .chain(
+                ProgressJournal::open(target_dir)
+                    .completed_paths()
+                    .map(resolve_path),
+            )
+            .collect();
+
+        let mut existing: Vec<PathBuf> = self
+            .datasets()
+            .iter()
+            .filter(|dataset| is_selected(labels, dataset))
+            .flat_map(|dataset| {
+                let dir = dataset.download_dir(target_dir, per_label_dirs);
+                pending_downloads(dataset, &dir)
+                    .into_iter()
+                    .filter(|file| formats.includes(file.format()))
+                    .filter_map(move |file| download_path(file.url(), &dir, mirror_structure))
+            })
+            .filter(|path| path.exists() && !recorded.contains(&resolve_path(path)))
+            .collect();
+        existing.dedup();
+
+        existing
+    }
+
+    /// Downloads reference datasets from a Project's registry by label, fetching any registered file
+    /// URLs into a target directory.
+    ///
+    /// This method implements the core file downloading functionality in refman. Given a dataset label
+    /// and target directory, it will:
+    /// 1. Verify the dataset exists in the registry
+    /// 2. Extract all registered file URLs for that dataset (FASTA, Genbank, GFA, GFF, GTF, BED)
+    /// 3. Launch concurrent downloads of all files into the target directory
+    /// 4. Handle any download failures or errors
+    ///
+    /// Downloads happen asynchronously and in parallel for improved performance. The method uses
+    /// tokio for async runtime and reqwest for HTTP requests. Files are downloaded maintaining
     /// their original filenames from the URLs.
     ///
     /// Dataset labels must exactly match what is registered (case-sensitive). The target directory
@@ -707,14 +1506,21 @@ impl Project {
     ///
     /// # Arguments
     ///
-    /// * `label` - The unique label of the dataset to download, must match what was registered
-    /// * `target_dir` - Directory path where downloaded files should be saved
+    /// * `labels` - The labels of the datasets to download, which must match what was registered,
+    ///   or an empty slice to download every dataset
+    /// * `target_dir` - Directory path where downloaded files should be saved, unless a dataset was
+    ///   registered with its own destination, in which case its files are saved there instead
+    /// * `options` - Run-wide [`DownloadOptions`], e.g. whether to stop at the first failure
+    ///
+    /// By default, downloads are best-effort: a file that fails to download is logged and
+    /// skipped while the rest continue. With `options.fail_fast` set, the first failure
+    /// instead cancels every download still in flight and is returned as an error.
     ///
     /// # Returns
     ///
     /// Returns Ok(()) if all downloads complete successfully, or an error if:
     /// - The dataset label is not found in the registry
-    /// - Any file downloads fail
+    /// - Any file downloads fail and `options.fail_fast` is set
     /// - The target directory cannot be accessed/created
     /// - Other IO or HTTP errors occur
     ///
@@ -733,49 +1539,675 @@ impl Project {
     #[allow(clippy::too_many_lines)]
     pub async fn download_dataset(
         self,
-        label: Option<&str>,
+        labels: &[&str],
         target_dir: PathBuf,
+        options: &DownloadOptions,
     ) -> color_eyre::Result<Self> {
+        // checksum-only runs don't keep any files, so they're handled separately
+        if options.checksum_only {
+            return self.checksum_dataset(labels, options).await;
+        }
+
         // make a new reqwest http client that can be shared between threads
-        let shared_client = Client::new();
+        let shared_client = options.build_client()?;
 
-        // pull in the sets of files to be downloaded
-        let dataset_files: Vec<(RefDataset, Vec<UnvalidatedFile>)> =
-            self.collect_downloads(label, &target_dir);
+        // decide which files actually need downloading
+        let mut dataset_files = self
+            .select_downloads(labels, &target_dir, options, &shared_client)
+            .await?;
+
+        // files that an interrupted run finished are listed in the journal it left behind. They only need to be
+        // recorded, so they're given the paths they were downloaded to, which tells their tasks not to request them
+        // again.
+        let journal = Arc::new(ProgressJournal::open(&target_dir));
+        let mut num_resumed = 0;
+        for (dataset, files) in &mut dataset_files {
+            for file in files.iter_mut() {
+                if let Some(path) = journal.completed(&dataset.label, file.url()) {
+                    *file = UnvalidatedFile::new(
+                        file.format(),
+                        file.url().to_string(),
+                        path.to_path_buf(),
+                    );
+                    num_resumed += 1;
+                }
+            }
+        }
+        if num_resumed > 0 {
+            info!(
+                "{num_resumed} files were already downloaded by an interrupted run, so they'll only be recorded"
+            );
+        }
 
         // count the downloads
         let num_to_download = count_downloads(&dataset_files);
 
+        // remember which files were requested, so that a summary can tell them apart from skipped files
+        let requested: HashSet<(String, String)> = dataset_files
+            .iter()
+            .flat_map(|(dataset, files)| {
+                files
+                    .iter()
+                    .map(|file| (dataset.label.clone(), file.url().to_string()))
+            })
+            .collect();
+
         // early return if there's nothing to download
         if num_to_download == 0 {
             info!(
                 "All requested files were previously downloaded and still passed checksums, so no downloads will be performed."
             );
+            if options.json_summary {
+                self.download_report(labels, &requested).print_json()?;
+            }
+            journal.remove();
             return Ok(self);
         }
 
         // set up a progress bar based on the number
-        let (mut toplevel_pb, multiprog) = setup_progress_tracking(label, num_to_download);
+        let (mut toplevel_pb, multiprog) = setup_progress_tracking(labels, num_to_download);
+
+        // keep track of what was requested, so that a webhook can be told about datasets whose downloads fail
+        let requested_datasets: Vec<RefDataset> = if options.webhook.is_some() {
+            dataset_files
+                .iter()
+                .map(|(dataset, _)| dataset.clone())
+                .collect()
+        } else {
+            vec![]
+        };
 
         // put each download into its own tokio thread, and collect its handle into a vector
         // that can be polled downstream
-        let dataset_task_handles =
-            submit_download_requests(dataset_files, &shared_client, &target_dir, &multiprog);
+        let dataset_tasks = submit_download_requests(
+            dataset_files,
+            &shared_client,
+            &target_dir,
+            &multiprog,
+            options,
+            &journal,
+        );
+
+        let downloaded =
+            update_project_datasets(dataset_tasks, &mut toplevel_pb, options, self.public_key())
+                .await;
+
+        // let any webhook know how each dataset's download went before moving on
+        if let Some(webhook) = &options.webhook {
+            let events: Vec<DownloadEvent> = match &downloaded {
+                Ok(updated_datasets) => updated_datasets
+                    .iter()
+                    .map(|dataset| DownloadEvent::for_dataset(dataset, None))
+                    .collect(),
+                Err(error) => requested_datasets
+                    .iter()
+                    .map(|dataset| DownloadEvent::for_dataset(dataset, Some(error.to_string())))
+                    .collect(),
+            };
+            for event in &events {
+                notify_webhook(
+                    &shared_client,
+                    webhook,
+                    options.webhook_token.as_deref(),
+                    event,
+                )
+                .await;
+            }
+        }
+        let updated_datasets = downloaded?;
 
-        let updated_datasets =
-            update_project_datasets(dataset_task_handles, &mut toplevel_pb).await?;
+        // the registry is about to record everything the journal knows, so the run no longer needs it
+        journal.remove();
 
         // Once all downloads finish, update and finish the overall progress bar.
         toplevel_pb.finish_with_message(format!(
-            "Done! {num_to_download} files successfully downloaded to {target_dir:?}."
+            "Done! {num_to_download} files successfully downloaded to {}.",
+            target_dir.display()
         ));
 
         // Update the project and return it
         let updated_project = self.update_registry(&updated_datasets);
+        if options.json_summary {
+            updated_project
+                .download_report(labels, &requested)
+                .print_json()?;
+        }
 
         Ok(updated_project)
     }
 
+    /// Works out which files a download run would fetch for the datasets with the provided labels,
+    /// or for all datasets when `labels` is empty, grouped by dataset.
+    ///
+    /// This starts from the files that haven't been downloaded or no longer pass their checksums,
+    /// adds any that changed upstream according to `options.remote_index`, and then leaves out
+    /// files that would overwrite unrecorded files when `options.existing_files` says to skip
+    /// them, as well as files that keep failing to download unless `options.retry_failed` is set.
+    async fn select_downloads(
+        &self,
+        labels: &[&str],
+        target_dir: &Path,
+        options: &DownloadOptions,
+        shared_client: &Client,
+    ) -> color_eyre::Result<Vec<(RefDataset, Vec<UnvalidatedFile>)>> {
+        // pull in the sets of files to be downloaded
+        let mut dataset_files: Vec<(RefDataset, Vec<UnvalidatedFile>)> =
+            self.collect_downloads(labels, target_dir, options.per_label_dirs);
+
+        // files that changed upstream have to be downloaded again, even if the local copies still match their
+        // recorded checksums
+        if let Some(index_url) = &options.remote_index {
+            let index =
+                RemoteIndex::fetch(shared_client, index_url, options.index_algorithm).await?;
+            for (dataset, files) in &mut dataset_files {
+                let changed = index.changed_files(dataset, files);
+                files.extend(changed);
+            }
+        }
+
+        // leave out files of formats that weren't asked for
+        for (_, files) in &mut dataset_files {
+            files.retain(|file| options.formats.includes(file.format()));
+        }
+
+        // look out for files that would overwrite something refman didn't download itself
+        let unrecorded = self.unrecorded_existing_files(
+            labels,
+            target_dir,
+            options.mirror_structure,
+            options.per_label_dirs,
+            &options.formats,
+        );
+        match options.existing_files {
+            ExistingFiles::Overwrite => {},
+            ExistingFiles::Warn => {
+                for path in &unrecorded {
+                    warn!(
+                        "{} already exists but was not downloaded by refman. It will be overwritten.",
+                        path.display()
+                    );
+                }
+            },
+            ExistingFiles::Skip => {
+                for path in &unrecorded {
+                    warn!(
+                        "{} already exists but was not downloaded by refman, so it will be left as is and not downloaded.",
+                        path.display()
+                    );
+                }
+                for (dataset, files) in &mut dataset_files {
+                    let dir = dataset.download_dir(target_dir, options.per_label_dirs);
+                    files.retain(|file| {
+                        download_path(file.url(), &dir, options.mirror_structure)
+                            .is_none_or(|path| !unrecorded.contains(&path))
+                    });
+                }
+            },
+        }
+
+        // leave out files that keep failing to download, unless asked to try them again
+        if !options.retry_failed {
+            for (dataset, files) in &mut dataset_files {
+                files.retain(|file| {
+                    let attempts = dataset.failed_attempts(file.format());
+                    if attempts >= CHRONIC_FAILURE_ATTEMPTS {
+                        warn!(
+                            "{} has failed to download {attempts} times in a row, so it will be skipped. Use `--retry-failed` to try it again.",
+                            file.url()
+                        );
+                        return false;
+                    }
+                    true
+                });
+            }
+        }
+
+        // no two files may end up at the same path, since the last to finish would silently replace the others
+        check_destination_collisions(&dataset_files, target_dir, options)?;
+
+        Ok(dataset_files)
+    }
+
+    /// Prints what a download run with the same arguments would do, without downloading or
+    /// writing anything: every file of the datasets with the provided labels (or of all datasets
+    /// when `labels` is empty), where it would be saved, whether it would be downloaded or
+    /// skipped, and how large it is, followed by the total number of bytes to download.
+    ///
+    /// Sizes come from the `Content-Length` of a HEAD request to each file that would be
+    /// downloaded, or from its registered byte range. Servers that don't report a size leave it
+    /// out of the total.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client can't be built or `options.remote_index` can't be
+    /// fetched.
+    pub async fn print_download_plan(
+        &self,
+        labels: &[&str],
+        target_dir: &Path,
+        options: &DownloadOptions,
+    ) -> color_eyre::Result<()> {
+        let DownloadPlan {
+            table,
+            num_to_download,
+            num_skipped,
+            num_unknown,
+            total_bytes,
+        } = self.download_plan(labels, target_dir, options).await?;
+        table.printstd();
+
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        print!(
+            "\n{num_to_download} file{} to download, {num_skipped} skipped, {} expected",
+            plural(num_to_download),
+            humanize_bytes(total_bytes),
+        );
+        if num_unknown > 0 {
+            print!(
+                " (not counting {num_unknown} file{} of unknown size)",
+                plural(num_unknown)
+            );
+        }
+        println!(". Nothing was downloaded.");
+
+        Ok(())
+    }
+
+    /// Works out what [`Project::print_download_plan`] prints.
+    async fn download_plan(
+        &self,
+        labels: &[&str],
+        target_dir: &Path,
+        options: &DownloadOptions,
+    ) -> color_eyre::Result<DownloadPlan> {
+        let shared_client = options.build_client()?;
+        let dataset_files = self
+            .select_downloads(labels, target_dir, options, &shared_client)
+            .await?;
+
+        // look up the size of every file to download, a few at a time, keyed by its dataset and
+        // URL, since the table lists files in registry order rather than in the order they'd be
+        // downloaded, and lists a URL registered under several formats once for each
+        let sizes: HashMap<(&str, &str), Option<u64>> =
+            stream::iter(dataset_files.iter().flat_map(|(dataset, files)| {
+                let client = &shared_client;
+                files.iter().map(move |file| {
+                    let range = dataset.byte_ranges.get(&file.format()).copied();
+                    async move {
+                        let size = expected_size(client, file.url(), range).await;
+                        ((dataset.label.as_str(), file.url()), size)
+                    }
+                })
+            }))
+            .buffered(options.download_jobs())
+            .collect()
+            .await;
+
+        let mut plan = DownloadPlan {
+            table: Table::new(),
+            num_to_download: 0,
+            num_skipped: 0,
+            num_unknown: 0,
+            total_bytes: 0,
+        };
+        plan.table
+            .add_row(row!["URL", "Destination", "Action", "Size"]);
+        // a URL shared by several formats is only downloaded once, so its size is only counted once
+        let mut counted = HashSet::new();
+        for dataset in self
+            .datasets()
+            .iter()
+            .filter(|dataset| is_selected(labels, dataset))
+        {
+            let dir = dataset.download_dir(target_dir, options.per_label_dirs);
+            for (_, status) in dataset.file_fields() {
+                let Some(status) = status else {
+                    continue;
+                };
+                let url = status.url();
+                if let Some(size) = sizes.get(&(dataset.label.as_str(), url)) {
+                    plan.num_to_download += 1;
+                    let destination = download_path(url, &dir, options.mirror_structure)
+                        .map_or_else(|| "-".to_string(), |path| path.display().to_string());
+                    let first_listing = counted.insert((dataset.label.as_str(), url));
+                    let size = if let Some(bytes) = size {
+                        if first_listing {
+                            plan.total_bytes += bytes;
+                        }
+                        humanize_bytes(*bytes)
+                    } else {
+                        if first_listing {
+                            plan.num_unknown += 1;
+                        }
+                        "unknown".to_string()
+                    };
+                    plan.table.add_row(row![url, destination, "download", size]);
+                } else {
+                    plan.num_skipped += 1;
+                    let destination = match status {
+                        DownloadStatus::Downloaded(file) if status.is_downloaded() => {
+                            file.local_path.display().to_string()
+                        },
+                        _ => "-".to_string(),
+                    };
+                    plan.table.add_row(row![url, destination, "skip", "-"]);
+                }
+            }
+        }
+
+        Ok(plan)
+    }
+
+    /// Summarizes the files of the datasets with the provided labels, or of all datasets when
+    /// `labels` is empty, after a download run that requested the files in `requested`.
+    fn download_report(
+        &self,
+        labels: &[&str],
+        requested: &HashSet<(String, String)>,
+    ) -> DownloadReport {
+        DownloadReport::new(
+            self.datasets()
+                .iter()
+                .filter(|dataset| is_selected(labels, dataset)),
+            requested,
+        )
+    }
+
+    /// Streams every file registered for the selected datasets (or for all datasets when `labels`
+    /// is empty)
+    /// and records its checksum in the registry, without writing any of the files to disk.
+    ///
+    /// This is what [`Project::download_dataset`] does when `options.checksum_only` is set. It
+    /// lets users pin the expected checksums of large references that they want to verify but
+    /// not store. Files are hashed as their bytes arrive, and each checksum is recorded with
+    /// `RefDataset::update_with_checksum`, leaving files whose checksums haven't changed as they
+    /// were. Files registered under more than one format are only streamed once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be built, or, when `options.fail_fast` is set,
+    /// the first error encountered while streaming a file. Otherwise, failures are logged and
+    /// those files are skipped.
+    async fn checksum_dataset(
+        mut self,
+        labels: &[&str],
+        options: &DownloadOptions,
+    ) -> color_eyre::Result<Self> {
+        let shared_client = options.build_client()?;
+
+        // gather each unique URL to be hashed, along with the dataset and formats it's registered under
+        let mut requests: Vec<(String, String, Vec<FileFormat>)> = Vec::new();
+        for dataset in self.datasets() {
+            if !is_selected(labels, dataset) {
+                continue;
+            }
+            for (format, status) in dataset.file_fields() {
+                let Some(url) = status.map(DownloadStatus::url) else {
+                    continue;
+                };
+                if !options.formats.includes(format) {
+                    continue;
+                }
+                match requests.iter_mut().find(|(request_label, request_url, _)| {
+                    *request_label == dataset.label && request_url == url
+                }) {
+                    Some((_, _, formats)) => formats.push(format),
+                    None => requests.push((dataset.label.clone(), url.to_string(), vec![format])),
+                }
+            }
+        }
+
+        if requests.is_empty() {
+            info!("No registered files were found to compute checksums for.");
+            return Ok(self);
+        }
+
+        let num_to_hash = requests.len();
+        let (toplevel_pb, multiprog) = setup_progress_tracking(labels, num_to_hash);
+
+        // stream each file in its own task. As with downloads, dropping the join set aborts any tasks still running.
+        let mut checksum_tasks = JoinSet::new();
+        let permits = Arc::new(Semaphore::new(options.download_jobs()));
+        for (dataset_label, url, formats) in requests {
+            let client = shared_client.clone();
+            let mp = multiprog.clone();
+            let redirects = options.redirects;
+            let heartbeat = options.heartbeat;
+            let retries = options.retry_count();
+            let permits = permits.clone();
+            checksum_tasks.spawn(async move {
+                let hash = match permits.acquire_owned().await {
+                    Ok(_permit) => {
+                        request_checksum(url.clone(), client, mp, redirects, heartbeat, retries)
+                            .await
+                    },
+                    Err(e) => Err(e.into()),
+                };
+                (dataset_label, url, formats, hash)
+            });
+        }
+
+        while let Some(joined) = checksum_tasks.join_next().await {
+            toplevel_pb.inc(1);
+            let (dataset_label, url, formats, hash) = joined?;
+            let hash = match hash {
+                Ok(hash) => hash,
+                Err(msg) if options.fail_fast => {
+                    toplevel_pb
+                        .abandon_with_message("Aborting remaining checksums after a failure.");
+                    return Err(msg);
+                },
+                Err(msg) => {
+                    warn!("Failed to compute a checksum for {url} because of this error: {msg}");
+                    continue;
+                },
+            };
+
+            let Some(idx) = self.get_dataset_idx(&dataset_label) else {
+                continue;
+            };
+            let dataset = &mut self.datasets_mut()[idx];
+            for format in formats {
+                dataset.update_with_checksum(format, &url, hash.clone());
+            }
+        }
+
+        toplevel_pb.finish_with_message(format!(
+            "Done! Checksums for {num_to_hash} files were recorded without keeping the files."
+        ));
+
+        Ok(self)
+    }
+
+    /// Downloads the files of the dataset with the provided label, or of all datasets when
+    /// `label` is `None`, again if they've changed upstream since they were downloaded, e.g. to
+    /// pick up a provider's new release of a reference published under the same URL.
+    ///
+    /// Whether a file changed is asked of its server with a conditional GET that sends back the
+    /// `ETag` or `Last-Modified` date the file was downloaded with, so files the server reports as
+    /// unchanged are neither downloaded nor touched. Changed files are downloaded into the
+    /// directory of their previous copies, and validated and hashed like any other download.
+    /// Files downloaded before these headers were recorded can't be asked about, so the headers
+    /// their servers send now are recorded for the next update instead.
+    ///
+    /// Returns the updated project along with the number of files that were downloaded again.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::LabelNotFound` if a label is provided but no dataset has it, an error
+    /// if the HTTP client can't be built, or, when `options.fail_fast` is set, the first error
+    /// encountered. Otherwise, files that can't be checked, downloaded, or validated are logged
+    /// and left as they were.
+    #[allow(clippy::too_many_lines)]
+    pub async fn update_datasets(
+        mut self,
+        label: Option<&str>,
+        options: &DownloadOptions,
+    ) -> color_eyre::Result<(Self, usize)> {
+        if let Some(label) = label {
+            if !self.is_registered(label) {
+                return Err(EntryError::LabelNotFound(label.to_string()).into());
+            }
+        }
+        let shared_client = options.build_client()?;
+
+        // gather every downloaded file, only once for URLs registered under more than one format
+        let mut downloaded: Vec<(String, FileFormat, ValidatedFile)> = Vec::new();
+        for dataset in self
+            .datasets()
+            .iter()
+            .filter(|dataset| label.is_none_or(|label| label == dataset.label))
+        {
+            for (format, status) in dataset.file_fields() {
+                let Some(DownloadStatus::Downloaded(file)) = status else {
+                    continue;
+                };
+                let seen = downloaded.iter().any(|(seen_label, _, seen_file)| {
+                    *seen_label == dataset.label && seen_file.uri == file.uri
+                });
+                if !seen {
+                    downloaded.push((dataset.label.clone(), format, file.clone()));
+                }
+            }
+        }
+
+        // ask each file's server whether it changed, a few at a time
+        let freshness: Vec<color_eyre::Result<Freshness>> = stream::iter(&downloaded)
+            .map(|(_, _, file)| check_freshness(&shared_client, file))
+            .buffered(options.download_jobs())
+            .collect()
+            .await;
+
+        let mut changed = Vec::new();
+        for ((dataset_label, format, file), freshness) in downloaded.into_iter().zip(freshness) {
+            match freshness {
+                Ok(Freshness::Unchanged) => {
+                    debug!("{} hasn't changed upstream, so it's left as is", file.uri);
+                },
+                Ok(Freshness::Changed) => {
+                    info!(
+                        "{} has changed upstream, so it will be downloaded again",
+                        file.uri
+                    );
+                    changed.push((dataset_label, format, file));
+                },
+                Ok(Freshness::Unrecorded(validators)) => {
+                    warn!(
+                        "{} was downloaded before refman recorded whether it changes upstream, so it's left as is. The next update will be able to tell.",
+                        file.uri
+                    );
+                    let Some(idx) = self.get_dataset_idx(&dataset_label) else {
+                        continue;
+                    };
+                    let dataset = &mut self.datasets_mut()[idx];
+                    let requested = UnvalidatedFile::new(format, file.uri, PathBuf::new());
+                    for shared in dataset.files_sharing_url(&requested) {
+                        dataset.set_cache_validators(shared.format(), validators.clone());
+                    }
+                    dataset.set_cache_validators(format, validators);
+                },
+                Err(e) if options.fail_fast => return Err(e),
+                Err(e) => {
+                    warn!(
+                        "Unable to tell whether {} changed upstream, so it's left as is: {e}",
+                        file.uri
+                    );
+                },
+            }
+        }
+
+        let num_changed = changed.len();
+        if num_changed == 0 {
+            info!("None of the requested files have changed upstream.");
+            return Ok((self, 0));
+        }
+
+        // download the changed files where their previous copies were
+        let (toplevel_pb, multiprog) = setup_progress_tracking(label.as_slice(), num_changed);
+        let requests: Vec<_> = changed
+            .into_iter()
+            .filter_map(|(dataset_label, format, file)| {
+                let dataset = &self.datasets()[self.get_dataset_idx(&dataset_label)?];
+                let range = dataset.byte_ranges.get(&format).copied();
+                let signature_url = dataset.signatures.get(&format).cloned();
+                Some((dataset_label, format, file, range, signature_url))
+            })
+            .collect();
+        let downloads: Vec<color_eyre::Result<(String, DownloadedFile, HashAlgorithm)>> =
+            stream::iter(requests)
+                .map(|(dataset_label, format, file, range, signature_url)| {
+                    let client = shared_client.clone();
+                    let mp = multiprog.clone();
+                    let dir = Arc::new(
+                        file.local_path
+                            .parent()
+                            .map(Path::to_path_buf)
+                            .unwrap_or_default(),
+                    );
+                    async move {
+                        let requested = UnvalidatedFile::new(format, file.uri, PathBuf::new());
+                        let downloaded = request_dataset(
+                            requested,
+                            client.clone(),
+                            dir.clone(),
+                            mp.clone(),
+                            range,
+                            false,
+                            options.redirects,
+                            options.heartbeat,
+                            options.retry_count(),
+                        )
+                        .await?;
+                        if let Some(signature_url) = signature_url {
+                            request_signature(signature_url, client, dir, mp, options.redirects)
+                                .await?;
+                        }
+                        Ok((dataset_label, downloaded, file.algorithm))
+                    }
+                })
+                .buffered(options.download_jobs())
+                .collect()
+                .await;
+
+        // record each new download, validating it like any other
+        let registry_key = self.public_key().map(str::to_string);
+        let mut num_updated = 0;
+        for download in downloads {
+            toplevel_pb.inc(1);
+            let recorded = download.and_then(|(dataset_label, (file, validators), algorithm)| {
+                let Some(idx) = self.get_dataset_idx(&dataset_label) else {
+                    return Ok(());
+                };
+                let dataset = &mut self.datasets_mut()[idx];
+                let mut files = dataset.files_sharing_url(&file);
+                files.insert(0, file);
+                for file in files {
+                    dataset.verify_signature(&file, registry_key.as_deref())?;
+                    dataset.update_with_sampled_download(&file, options.validation_sample)?;
+                    dataset.set_hash_algorithm(file.format(), algorithm);
+                    dataset.set_cache_validators(file.format(), validators.clone());
+                    if options.extract && file.format() == FileFormat::Tar {
+                        extract_downloaded_tarball(dataset, options.strip_components)?;
+                    }
+                }
+                Ok(())
+            });
+            match recorded {
+                Ok(()) => num_updated += 1,
+                Err(e) if options.fail_fast => {
+                    toplevel_pb.abandon_with_message("Aborting remaining updates after a failure.");
+                    return Err(e);
+                },
+                Err(e) => warn!("Failed to update a file because of this error: {e}"),
+            }
+        }
+        toplevel_pb.finish_with_message(format!(
+            "Done! {num_updated} of {num_changed} changed files were downloaded again."
+        ));
+
+        Ok((self, num_updated))
+    }
+
     #[must_use]
     pub fn update_registry(self, new_datasets: &[RefDataset]) -> Project {
         // make a hashmap of the old datasets and new datasets we can compare for available updates
@@ -821,9 +2253,11 @@ impl Project {
     ///
     /// The method enforces several rules to maintain registry integrity:
     /// - The label must exactly match an existing dataset (case-sensitive)
-    /// - The registry must maintain at least one dataset after removal
     /// - Only one dataset can be removed at a time
     ///
+    /// Removing the final dataset leaves an empty registry behind, with its title, description,
+    /// and other metadata intact, just like one fresh from `refman init`.
+    ///
     /// This complements `register()` an`download_dataset()` in the lifecycle of managing
     /// reference data. While those methods add and fetch datasets, `remove()` allows
     /// pruning datasets that are no longer needed.
@@ -835,20 +2269,14 @@ impl Project {
     /// # Returns
     ///
     /// Returns Ok(Project) with the updated Project if removal succeeds, or an
-    /// `EntryError` in the following cases:
-    /// - `EntryError::LabelNotFound` if no dataset matches the provided label
-    /// - `EntryError::FinalEntry` if removing this dataset would empty the registry
+    /// `EntryError::LabelNotFound` if no dataset matches the provided label.
     ///
     /// The Project instance is consumed and a new instance is returned to maintain
     /// the builder pattern used throughout the API.
     ///
     /// # Errors
     ///
-    /// This method can return the following errors:
-    /// - `EntryError::LabelNotFound` if the specified label is not in the registry
-    /// - `EntryError::FinalEntry` if removing this dataset would empty the registry
-    ///   entirely (at least one dataset must always remain)
-    ///
+    /// Returns `EntryError::LabelNotFound` if the specified label is not in the registry.
     pub fn remove(mut self, label: &str) -> Result<Self, EntryError> {
         // make sure the label is in the recorded datasets
         if self
@@ -865,172 +2293,406 @@ impl Project {
         self.project
             .filter_datasets(|dataset| dataset.label != label);
 
-        // return an error if that was the last entry
-        if self.datasets().is_empty() {
-            return Err(EntryError::FinalEntry(label.to_string()));
+        Ok(self)
+    }
+
+    /// Changes the label of the dataset registered as `old_label` to `new_label`, e.g. to fix a
+    /// dataset that was mislabeled when it was registered.
+    ///
+    /// Only the label changes: the dataset's files keep their download and validation state and
+    /// their local paths, so nothing has to be downloaded again.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::LabelNotFound` if no dataset has `old_label`, `EntryError::EmptyLabel`
+    /// if `new_label` is empty or only whitespace, and `EntryError::RenameTargetExists` if another
+    /// dataset already has `new_label`, since labels must stay unique.
+    pub fn rename(mut self, old_label: &str, new_label: &str) -> Result<Self, EntryError> {
+        let Some(dataset_idx) = self.get_dataset_idx(old_label) else {
+            return Err(EntryError::LabelNotFound(old_label.to_string()));
+        };
+        if new_label.trim().is_empty() {
+            return Err(EntryError::EmptyLabel);
         }
+        if old_label != new_label && self.is_registered(new_label) {
+            return Err(EntryError::RenameTargetExists(
+                old_label.to_string(),
+                new_label.to_string(),
+            ));
+        }
+
+        self.project.datasets[dataset_idx].label = new_label.to_string();
 
-        // otherwise, return the mutated project
         Ok(self)
     }
 
-    fn print_single_label_data(self, label: &str) {
-        let datasets = self.datasets();
-        let dataset: Vec<_> = datasets
+    /// Compares this registry's datasets to `other`'s, e.g. to review changes to a shared registry
+    /// more easily than with a diff of the TOML itself.
+    ///
+    /// Datasets are matched by label. Those only in `other` are reported as added, those only in
+    /// this registry as removed, and for datasets in both, every format whose URL was added,
+    /// removed, or changed is reported, with this registry's URL as the old one. Download state
+    /// and other metadata are ignored, so two copies of a registry that were downloaded on
+    /// different machines compare as equal.
+    #[must_use]
+    pub fn diff(&self, other: &Project) -> RegistryDiff {
+        let ours: BTreeMap<&str, &RefDataset> = self
+            .datasets()
             .iter()
-            .filter(|dataset| dataset.label == label)
+            .map(|dataset| (dataset.label.as_str(), dataset))
+            .collect();
+        let theirs: BTreeMap<&str, &RefDataset> = other
+            .datasets()
+            .iter()
+            .map(|dataset| (dataset.label.as_str(), dataset))
             .collect();
-        assert_eq!(
-            dataset.len(),
-            1,
-            "No project with the label '{label}' has been registered. Run `refman list` without the label to see which datasets are registered."
-        );
-        let unwrapped_dataset = dataset[0];
-
-        eprintln!("URLs registered for {label}:");
-        eprintln!("--------------------{}", "-".repeat(label.len()));
-        eprintln!(
-            " - FASTA: {}",
-            unwrapped_dataset
-                .fasta
-                .clone()
-                .unwrap_or(DownloadStatus::default())
-        );
-        eprintln!(
-            " - Genbank: {}",
-            unwrapped_dataset
-                .genbank
-                .clone()
-                .unwrap_or(DownloadStatus::default())
-        );
-        eprintln!(
-            " - GFA: {}",
-            unwrapped_dataset
-                .gfa
-                .clone()
-                .unwrap_or(DownloadStatus::default())
-        );
-        eprintln!(
-            " - GFF: {}",
-            unwrapped_dataset
-                .gff
-                .clone()
-                .unwrap_or(DownloadStatus::default())
-        );
-        eprintln!(
-            " - GTF: {}",
-            unwrapped_dataset
-                .gtf
-                .clone()
-                .unwrap_or(DownloadStatus::default())
-        );
-        eprintln!(
-            " - BED: {}",
-            unwrapped_dataset
-                .bed
-                .clone()
-                .unwrap_or(DownloadStatus::default())
-        );
-        eprintln!(
-            " - TAR: {}",
-            unwrapped_dataset
-                .tar
-                .clone()
-                .unwrap_or(DownloadStatus::default())
-        );
-    }
 
-    fn print_all_labels(self) {
-        // print a title field if it has been set
-        let title_field = &self.project.title;
-        if let Some(title) = title_field {
-            info!("Showing available data registered for {title}:");
+        let mut diff = RegistryDiff {
+            added: theirs
+                .keys()
+                .filter(|label| !ours.contains_key(*label))
+                .map(ToString::to_string)
+                .collect(),
+            removed: ours
+                .keys()
+                .filter(|label| !theirs.contains_key(*label))
+                .map(ToString::to_string)
+                .collect(),
+            ..RegistryDiff::default()
+        };
+        for (label, dataset) in &ours {
+            let Some(other_dataset) = theirs.get(label) else {
+                continue;
+            };
+            let changes: Vec<UrlChange> = dataset
+                .file_fields()
+                .into_iter()
+                .zip(other_dataset.file_fields())
+                .filter_map(|((format, old), (_, new))| {
+                    let old = old.map(DownloadStatus::url_owned);
+                    let new = new.map(DownloadStatus::url_owned);
+                    (old != new).then_some(UrlChange { format, old, new })
+                })
+                .collect();
+            if !changes.is_empty() {
+                diff.changed.insert((*label).to_string(), changes);
+            }
         }
 
-        // make a new mutable instance of a pretty table to be appended to
-        let mut pretty_table = Table::new();
+        diff
+    }
+
+    /// Folds the datasets registered in `source`, e.g. a registry shared by a collaborator, into
+    /// this registry.
+    ///
+    /// Datasets with new labels are added as they are, download state included, while datasets
+    /// whose labels are already registered are handled as `on_conflict` asks. Unlike
+    /// registering, importing doesn't check any URLs, since they were checked when they were
+    /// registered in `source`; use `refman verify-urls` to make sure they still work.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first `EntryError` that [`Project::validate_structure`] finds in `source`, in
+    /// which case nothing is imported.
+    pub fn import(
+        mut self,
+        source: Project,
+        on_conflict: ImportConflict,
+    ) -> Result<(Self, ImportSummary), EntryError> {
+        source.validate_structure()?;
+
+        let mut summary = ImportSummary::default();
+        for dataset in source.datasets_owned() {
+            let Some(idx) = self.get_dataset_idx(&dataset.label) else {
+                summary.added.push(dataset.label.clone());
+                self.project.datasets.push(dataset);
+                continue;
+            };
+            match on_conflict {
+                ImportConflict::Skip => {
+                    info!(
+                        "'{}' is already registered, so it will not be imported.",
+                        dataset.label
+                    );
+                    summary.skipped.push(dataset.label);
+                },
+                ImportConflict::Overwrite => {
+                    summary.overwritten.push(dataset.label.clone());
+                    self.project.datasets[idx] = dataset;
+                },
+                ImportConflict::MergeFields => {
+                    self.project.datasets[idx].merge_from(&dataset);
+                    summary.merged.push(dataset.label);
+                },
+            }
+        }
+
+        Ok((self, summary))
+    }
+
+    /// Points a registered file at a new URL without losing its download and validation state.
+    ///
+    /// When a provider moves a file without changing its contents, re-registering it would
+    /// reset it to not yet downloaded and force a needless download. Relinking instead only
+    /// swaps the URL, keeping the file's hash, local path, and validation status; see
+    /// [`RefDataset::relink`]. The new URL is checked to make sure it points to a resource that
+    /// exists. When `verify` is set and the file was previously hashed, the new URL is also
+    /// streamed and hashed to make sure it serves the same content.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::LabelNotFound` if no dataset has the provided label,
+    /// `EntryError::FormatNotRegistered` if the dataset has no file of the provided format,
+    /// `EntryError::InvalidURL` if the new URL is broken or can't be streamed, and
+    /// `EntryError::RelinkContentMismatch` if verification finds that its content differs.
+    pub async fn relink(
+        mut self,
+        label: &str,
+        format: FileFormat,
+        new_url: &str,
+        verify: bool,
+    ) -> Result<Self, EntryError> {
+        let Some(dataset_idx) = self.get_dataset_idx(label) else {
+            return Err(EntryError::LabelNotFound(label.to_string()));
+        };
+
+        if is_likely_url(new_url) {
+            let _ = check_url(new_url).await?;
+        }
+
+        if verify {
+            let recorded_hash = self.datasets()[dataset_idx]
+                .file_fields()
+                .into_iter()
+                .find(|(field_format, _)| *field_format == format)
+                .and_then(|(_, status)| match status? {
+                    DownloadStatus::Downloaded(file) => file.hash.clone(),
+                    _ => None,
+                });
+            if let Some(recorded_hash) = recorded_hash {
+                let new_hash = request_checksum(
+                    new_url.to_string(),
+                    client()?,
+                    Arc::new(MultiProgress::new()),
+                    RedirectPolicy::default(),
+                    None,
+                    DEFAULT_RETRIES,
+                )
+                .await?;
+                if new_hash != recorded_hash {
+                    return Err(EntryError::RelinkContentMismatch(
+                        new_url.to_string(),
+                        format.to_string(),
+                    ));
+                }
+            } else {
+                warn!(
+                    "The {format} file for '{label}' was never hashed, so there's nothing to verify {new_url} against."
+                );
+            }
+        }
+
+        self.datasets_mut()[dataset_idx].relink(format, new_url)?;
+
+        Ok(self)
+    }
+
+    /// Records SHA-256 checksums for previously downloaded files that only have MD5 checksums,
+    /// either for the dataset with the provided label or for all datasets when `label` is `None`.
+    ///
+    /// Registries written before SHA-256 checksums were recorded only have MD5 checksums. This
+    /// upgrades them in place by hashing the files already on disk instead of downloading them
+    /// again, leaving the MD5 checksums as they are; see [`RefDataset::rehash`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::LabelNotFound` if a label is provided but no dataset has it.
+    pub fn rehash(mut self, label: Option<&str>) -> Result<Self, EntryError> {
+        if let Some(label) = label {
+            if self.get_dataset_idx(label).is_none() {
+                return Err(EntryError::LabelNotFound(label.to_string()));
+            }
+        }
+
+        let rehashed: usize = self
+            .datasets_mut()
+            .iter_mut()
+            .filter(|dataset| label.is_none_or(|label| dataset.label == label))
+            .map(RefDataset::rehash)
+            .sum();
+        info!("Recorded SHA-256 checksums for {rehashed} previously downloaded files.");
+
+        Ok(self)
+    }
+
+    /// Records copies of registered files that were placed on disk by some means other than a
+    /// refman download, without any network access.
+    ///
+    /// This supports downloading references once, e.g. on a machine with a fast connection, and
+    /// then distributing them to other machines along with the registry. Each of `files` is hashed
+    /// and matched to the files registered for the datasets with the provided labels, or for all
+    /// datasets when `labels` is empty, by their recorded checksums; matches are recorded as
+    /// downloaded to the copy's path. Files can only be matched if the registry records their
+    /// checksums, i.e. if they were downloaded or checksummed with `--checksum-only` before. Any
+    /// of `files` that can't be read or don't match a registered checksum are reported and
+    /// otherwise left alone.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::LabelNotFound` naming the first of `labels` that no dataset has.
+    pub fn resume_from_files(
+        mut self,
+        labels: &[&str],
+        files: &[PathBuf],
+    ) -> Result<Self, EntryError> {
+        if let Some(missing) = labels.iter().find(|label| !self.is_registered(label)) {
+            return Err(EntryError::LabelNotFound((*missing).to_string()));
+        }
+
+        // gather the checksum of every file that copies could be matched to, along with where it's registered
+        let mut expected: HashMap<String, Vec<(usize, FileFormat)>> = HashMap::new();
+        for (idx, dataset) in self.datasets().iter().enumerate() {
+            if !is_selected(labels, dataset) {
+                continue;
+            }
+            for (format, status) in dataset.file_fields() {
+                if let Some(DownloadStatus::Downloaded(file)) = status {
+                    if let Some(hash) = &file.hash {
+                        expected
+                            .entry(hash.clone())
+                            .or_default()
+                            .push((idx, format));
+                    }
+                }
+            }
+        }
+
+        // hashing is what takes the time, so the copies are hashed in parallel
+        let hashes: Vec<_> = files
+            .par_iter()
+            .map(|path| (path, hash_valid_download(path)))
+            .collect();
+
+        let mut recognized = 0;
+        for (path, hash) in hashes {
+            let hash = match hash {
+                Ok(hash) => hash,
+                Err(e) => {
+                    warn!(
+                        "Unable to read {}, so it was not recorded: {e}",
+                        path.display()
+                    );
+                    continue;
+                },
+            };
+            let Some(matches) = expected.get(&hash) else {
+                warn!(
+                    "{} doesn't match the checksum of any registered file, so it was not recorded.",
+                    path.display()
+                );
+                continue;
+            };
+            for &(idx, format) in matches {
+                let dataset = &mut self.datasets_mut()[idx];
+                debug!(
+                    "Recognized {} as the {format} file for '{}'",
+                    path.display(),
+                    dataset.label
+                );
+                dataset.update_with_local_copy(format, path);
+                recognized += 1;
+            }
+        }
+        info!(
+            "Recorded {recognized} of the {} registered files with checksums as downloaded.",
+            expected.values().map(Vec::len).sum::<usize>()
+        );
+
+        Ok(self)
+    }
+
+    fn print_single_label_data(&self, label: &str) {
+        let datasets = self.datasets();
+        let dataset: Vec<_> = datasets
+            .iter()
+            .filter(|dataset| dataset.label == label)
+            .collect();
+        assert_eq!(
+            dataset.len(),
+            1,
+            "No project with the label '{label}' has been registered. Run `refman list` without the label to see which datasets are registered."
+        );
+        let unwrapped_dataset = dataset[0];
+
+        eprintln!("URLs registered for {label}:");
+        eprintln!("--------------------{}", "-".repeat(label.len()));
+        let unregistered = DownloadStatus::default();
+        for (format, status) in unwrapped_dataset.file_fields() {
+            eprintln!(" - {format}: {}", status.unwrap_or(&unregistered));
+        }
+        if let Some(size) = unwrapped_dataset.downloaded_size() {
+            eprintln!("{} downloaded in total.", humanize_bytes(size));
+        }
+    }
+
+    fn print_all_labels(&self) {
+        // print a title field if it has been set
+        let title_field = &self.project.title;
+        if let Some(title) = title_field {
+            info!("Showing available data registered for {title}:");
+        }
+
+        // along with who to contact about the registry, if that's been recorded
+        if !self.project.maintainers.is_empty() {
+            info!("Maintained by {}", self.project.maintainers.join(", "));
+        }
+        if let Some(homepage) = &self.project.homepage {
+            info!("Homepage: {homepage}");
+        }
+
+        // give a sense of the registry's scale before listing it
+        let datasets = self.datasets();
+        let file_count: usize = datasets.iter().map(RefDataset::file_count).sum();
+        let downloaded_count = datasets
+            .iter()
+            .flat_map(RefDataset::file_fields)
+            .filter(|(_, status)| status.is_some_and(DownloadStatus::is_downloaded))
+            .count();
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        println!(
+            "{} dataset{}, {file_count} file{} total, {downloaded_count} downloaded",
+            datasets.len(),
+            plural(datasets.len()),
+            plural(file_count),
+        );
+
+        // make a new mutable instance of a pretty table to be appended to
+        let mut pretty_table = Table::new();
 
         // add the title row
         pretty_table.add_row(row![
-            "Label", "FASTA", "Genbank", "GFA", "GFF", "GTF", "BED", "TAR",
+            "Label", "FASTA", "Genbank", "GFA", "GFF", "GTF", "BED", "VCF", "TAR", "Size",
         ]);
 
-        // add rows for each dataset
-        let datasets = self.datasets();
-        for dataset in datasets {
-            pretty_table.add_row(row![
-                dataset.label,
-                abbreviate_str(
-                    dataset
-                        .fasta
-                        .clone()
-                        .unwrap_or(DownloadStatus::default())
-                        .url_owned(),
-                    20,
-                    8,
-                    25
-                ),
-                abbreviate_str(
-                    dataset
-                        .genbank
-                        .clone()
-                        .unwrap_or(DownloadStatus::default())
-                        .url_owned(),
-                    20,
-                    8,
-                    25
-                ),
-                abbreviate_str(
-                    dataset
-                        .gfa
-                        .clone()
-                        .unwrap_or(DownloadStatus::default())
-                        .url_owned(),
-                    20,
-                    8,
-                    25
-                ),
-                abbreviate_str(
-                    dataset
-                        .gff
-                        .clone()
-                        .unwrap_or(DownloadStatus::default())
-                        .url_owned(),
-                    20,
-                    8,
-                    25
-                ),
-                abbreviate_str(
-                    dataset
-                        .gtf
-                        .clone()
-                        .unwrap_or(DownloadStatus::default())
-                        .url_owned(),
-                    20,
-                    8,
-                    25
-                ),
-                abbreviate_str(
-                    dataset
-                        .bed
-                        .clone()
-                        .unwrap_or(DownloadStatus::default())
-                        .url_owned(),
-                    20,
-                    8,
-                    25
-                ),
-                abbreviate_str(
-                    dataset
-                        .tar
-                        .clone()
-                        .unwrap_or(DownloadStatus::default())
-                        .url_owned(),
+        // add rows for each dataset, borrowing each URL rather than cloning its download status
+        for dataset in self.datasets() {
+            let mut cells = Vec::with_capacity(10);
+            cells.push(Cell::new(&dataset.label));
+            cells.extend(dataset.file_fields().into_iter().map(|(_, status)| {
+                Cell::new(&abbreviate_str(
+                    status.map_or("", DownloadStatus::url),
                     20,
                     8,
-                    25
-                ),
-            ]);
+                    25,
+                ))
+            }));
+            cells.push(Cell::new(
+                &dataset
+                    .downloaded_size()
+                    .map(humanize_bytes)
+                    .unwrap_or_default(),
+            ));
+            pretty_table.add_row(Row::new(cells));
         }
 
         pretty_table.printstd();
@@ -1041,7 +2703,7 @@ impl Project {
     /// This method provides a human-readable view of all reference datasets currently registered
     /// in the Project. It prints a formatted table showing each dataset's label and any
     /// registered file URLs for the supported bioinformatics formats (FASTA, Genbank, GFA,
-    /// GFF, GTF, BED).
+    /// GFF, GTF, BED, VCF) and tarballs.
     ///
     /// The output is formatted as a table with columns for:
     /// - Dataset Label
@@ -1051,6 +2713,9 @@ impl Project {
     /// - GFF URL (if registered)
     /// - GTF URL (if registered)
     /// - BED URL (if registered)
+    /// - VCF URL (if registered)
+    /// - TAR URL (if registered)
+    /// - Size of the downloaded files (if any were downloaded with their sizes recorded)
     ///
     /// Empty cells indicate that no URL is registered for that file format. If the Project
     /// has a title set, it will be displayed above the table.
@@ -1092,16 +2757,355 @@ impl Project {
         // otherwise, print all datasets as a table
         self.print_all_labels();
     }
+
+    /// Passes the project through if it has any registered datasets, for operations that need
+    /// some to work with.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RegistryError::EmptyRegistry` if no datasets have been registered, e.g. right
+    /// after `refman init`.
+    pub fn require_datasets(self) -> Result<Self, RegistryError> {
+        if self.datasets().is_empty() {
+            return Err(RegistryError::EmptyRegistry);
+        }
+
+        Ok(self)
+    }
+
+    /// Updates the registry's title and description, leaving the datasets alone. Only the fields
+    /// that are provided are changed, and an empty string clears a field.
+    #[must_use]
+    pub fn with_metadata(mut self, title: Option<String>, description: Option<String>) -> Self {
+        if let Some(title) = title {
+            self.project.title = Some(title).filter(|title| !title.is_empty());
+        }
+        if let Some(description) = description {
+            self.project.description =
+                Some(description).filter(|description| !description.is_empty());
+        }
+
+        self
+    }
+
+    /// Returns a copy of the project holding only the dataset with the provided label, or all
+    /// datasets when `label` is `None`, keeping the registry's metadata.
+    ///
+    /// Together with [`Project::to_toml`], this slices a registry into a standalone one that
+    /// refman can read back directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EntryError::LabelNotFound` if a label is provided but no dataset has it.
+    pub fn subset(mut self, label: Option<&str>) -> Result<Self, EntryError> {
+        if let Some(label) = label {
+            if self.get_dataset_idx(label).is_none() {
+                return Err(EntryError::LabelNotFound(label.to_string()));
+            }
+            self.project
+                .filter_datasets(|dataset| dataset.label == label);
+        }
+
+        Ok(self)
+    }
+
+    /// Serializes the project in the same TOML format as a registry file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RegistryError::InvalidOutputFormat` if the project can't be serialized.
+    pub fn to_toml(&self) -> Result<String, RegistryError> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    /// Serializes the project as pretty-printed JSON, e.g. for pipelines in languages where
+    /// TOML is awkward to parse. The registry's structure is the same as in a registry file,
+    /// except that each registered file has a `status` field saying whether it is
+    /// `not_yet_downloaded`, `downloaded`, or `failed`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RegistryError::InvalidJsonOutput` if the project can't be serialized.
+    pub fn to_json(&self) -> Result<String, RegistryError> {
+        Ok(serde_json::to_string_pretty(&self.to_export_value()?)?)
+    }
+
+    /// Serializes the project as YAML, with the same structure and tagged file statuses as
+    /// [`Project::to_json`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `RegistryError::InvalidJsonOutput` or `RegistryError::InvalidYamlOutput` if the
+    /// project can't be serialized.
+    pub fn to_yaml(&self) -> Result<String, RegistryError> {
+        Ok(serde_yaml::to_string(&self.to_export_value()?)?)
+    }
+
+    fn to_export_value(&self) -> serde_json::Result<serde_json::Value> {
+        let mut exported = serde_json::to_value(self)?;
+        exported["project"]["datasets"] = self.datasets_to_json_value()?;
+
+        Ok(exported)
+    }
+
+    /// Serializes only the project's datasets as a pretty-printed JSON array, with each file's
+    /// status tagged as in [`Project::to_json`], e.g. for `refman list --json | jq`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RegistryError::InvalidJsonOutput` if the datasets can't be serialized.
+    pub fn datasets_to_json(&self) -> Result<String, RegistryError> {
+        Ok(serde_json::to_string_pretty(
+            &self.datasets_to_json_value()?,
+        )?)
+    }
+
+    fn datasets_to_json_value(&self) -> serde_json::Result<serde_json::Value> {
+        self.datasets()
+            .iter()
+            .map(RefDataset::to_export_json)
+            .collect()
+    }
+
+    /// Prints the registry's metadata in a human-readable table: its title, description,
+    /// maintainers, homepage, where it lives and whether it's global or local, when it was last
+    /// modified (both as a timestamp and relative to now), and how many datasets it tracks.
+    /// Fields that haven't been set are left blank.
+    ///
+    /// This is the registry-level counterpart to listing a single dataset with
+    /// [`Project::prettyprint`], giving an overview of a registry without listing its datasets.
+    pub fn print_info(&self, registry_path: &Path) {
+        let mut pretty_table = Table::new();
+        pretty_table.add_row(row![
+            "Title",
+            self.project.title.as_deref().unwrap_or_default()
+        ]);
+        pretty_table.add_row(row![
+            "Description",
+            self.project.description.as_deref().unwrap_or_default()
+        ]);
+        pretty_table.add_row(row!["Maintainers", self.project.maintainers.join("\n")]);
+        pretty_table.add_row(row![
+            "Homepage",
+            self.project.homepage.as_deref().unwrap_or_default()
+        ]);
+        pretty_table.add_row(row!["Registry", registry_path.display()]);
+        pretty_table.add_row(row![
+            "Scope",
+            if self.project.global {
+                "global"
+            } else {
+                "local"
+            }
+        ]);
+        let last_modified = self.project.last_modified;
+        let age = humanize_age(Timestamp::now().duration_since(last_modified));
+        pretty_table.add_row(row!["Last modified", format!("{last_modified} ({age})")]);
+        pretty_table.add_row(row!["Datasets", self.project.datasets.len()]);
+        pretty_table.printstd();
+    }
+
+    /// Prints how far each registered dataset's files have gotten through being downloaded and
+    /// validated, and how long ago any of them were last validated, followed by a summary line
+    /// for the whole registry.
+    ///
+    /// Unlike [`Project::prettyprint`], which shows where each file comes from, this gives a
+    /// quick sense of what's left to do when returning to a project after a while.
+    pub fn print_status(&self) {
+        let mut pretty_table = Table::new();
+        pretty_table.add_row(row![
+            "Label",
+            "Files",
+            "Downloaded",
+            "Validated",
+            "Last validated",
+            "On disk"
+        ]);
+
+        let now = Timestamp::now();
+        let mut total = LifecycleCounts::default();
+        for dataset in self.datasets() {
+            let counts = LifecycleCounts::of(dataset);
+            let last_validated = counts.last_validated.map_or_else(
+                || "never".to_string(),
+                |validated| humanize_age(now.duration_since(validated)),
+            );
+            pretty_table.add_row(row![
+                dataset.label,
+                counts.files,
+                counts.downloaded,
+                counts.validated,
+                last_validated,
+                counts.size_bytes.map(humanize_bytes).unwrap_or_default()
+            ]);
+            total = total.combine(counts);
+        }
+        pretty_table.printstd();
+
+        let datasets = self.datasets().len();
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        let footprint = total
+            .size_bytes
+            .map(|size| format!(", {} on disk", humanize_bytes(size)))
+            .unwrap_or_default();
+        println!(
+            "{datasets} dataset{}, {} file{}, {} downloaded, {} validated{footprint}.",
+            plural(datasets),
+            total.files,
+            plural(total.files),
+            total.downloaded,
+            total.validated,
+        );
+    }
+}
+
+/// How far the registered files of a dataset, or of a whole registry, have gotten through being
+/// downloaded and validated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct LifecycleCounts {
+    files: usize,
+    downloaded: usize,
+    validated: usize,
+    /// When any of the files was most recently validated
+    last_validated: Option<Timestamp>,
+    /// How many bytes the downloaded files take up, if any of their sizes were recorded
+    size_bytes: Option<u64>,
+}
+
+impl LifecycleCounts {
+    fn of(dataset: &RefDataset) -> Self {
+        let statuses: Vec<_> = dataset
+            .file_fields()
+            .into_iter()
+            .filter_map(|(_, status)| status)
+            .collect();
+        let last_validated = statuses
+            .iter()
+            .filter_map(|status| match status {
+                DownloadStatus::Downloaded(file) if file.validated => file.last_validated,
+                _ => None,
+            })
+            .max();
+
+        Self {
+            files: statuses.len(),
+            downloaded: statuses
+                .iter()
+                .filter(|status| status.is_downloaded())
+                .count(),
+            validated: statuses
+                .iter()
+                .filter(|status| status.is_validated())
+                .count(),
+            last_validated,
+            size_bytes: dataset.downloaded_size(),
+        }
+    }
+
+    fn combine(self, other: Self) -> Self {
+        Self {
+            files: self.files + other.files,
+            downloaded: self.downloaded + other.downloaded,
+            validated: self.validated + other.validated,
+            last_validated: self.last_validated.max(other.last_validated),
+            size_bytes: match (self.size_bytes, other.size_bytes) {
+                (Some(size), Some(other_size)) => Some(size + other_size),
+                (size, other_size) => size.or(other_size),
+            },
+        }
+    }
+}
+
+impl FromStr for Project {
+    type Err = RegistryError;
+
+    /// Deserializes a project from the text of a `refman.toml` registry.
+    fn from_str(toml_contents: &str) -> Result<Self, Self::Err> {
+        let project: Project = toml::from_str(toml_contents)?;
+        Ok(project)
+    }
+}
+
+/// Returns the number of bytes a download of `url` is expected to write, i.e. the length of
+/// `range` when it has an end, or otherwise the `Content-Length` the server reports for a HEAD
+/// request, less any offset the range starts at. Returns `None` if the server doesn't report a
+/// size or can't be reached.
+async fn expected_size(client: &Client, url: &str, range: Option<ByteRange>) -> Option<u64> {
+    if let Some(ByteRange {
+        start,
+        end: Some(end),
+    }) = range
+    {
+        return Some(end - start + 1);
+    }
+
+    let response = with_headers(client.head(url), url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .inspect_err(|e| debug!("Unable to check the size of {url}: {e}"))
+        .ok()?;
+    // `Response::content_length` reports the length of a HEAD response's empty body, so the
+    // header is read directly
+    let length: u64 = response
+        .headers()
+        .get(CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()?;
+
+    Some(length.saturating_sub(range.map_or(0, |range| range.start)))
+}
+
+/// Formats a number of bytes with binary units, e.g. `1.5 GiB`.
+pub(crate) fn humanize_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for next_unit in UNITS {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next_unit;
+    }
+
+    format!("{size:.1} {unit}")
+}
+
+/// Describes how long ago something happened in coarse, human-friendly terms, e.g. "3 days ago".
+fn humanize_age(age: SignedDuration) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+
+    let seconds = age.as_secs();
+    let (count, unit) = match seconds {
+        i64::MIN..0 => return "in the future".to_string(),
+        0..MINUTE => return "just now".to_string(),
+        MINUTE..HOUR => (seconds / MINUTE, "minute"),
+        HOUR..DAY => (seconds / HOUR, "hour"),
+        _ => (seconds / DAY, "day"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+
+    format!("{count} {unit}{plural} ago")
 }
 
 #[inline]
-fn abbreviate_str(s: String, max_chars: usize, head_chars: usize, tail_chars: usize) -> String {
+fn abbreviate_str(s: &str, max_chars: usize, head_chars: usize, tail_chars: usize) -> Cow<'_, str> {
     // Count the characters in the string.
     let char_count = s.chars().count();
 
     // If the string is not too long, return it unchanged.
     if char_count <= max_chars {
-        return s;
+        return Cow::Borrowed(s);
     }
 
     // Collect the first `head_chars` characters.
@@ -1117,13 +3121,19 @@ fn abbreviate_str(s: String, max_chars: usize, head_chars: usize, tail_chars: us
         .rev()
         .collect();
 
-    format!("{head}...{tail}")
+    Cow::Owned(format!("{head}...{tail}"))
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct Registry {
     title: Option<String>,
     description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    maintainers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    homepage: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    public_key: Option<String>,
     last_modified: Timestamp,
     global: bool,
     datasets: Vec<RefDataset>,
@@ -1134,6 +3144,9 @@ impl Default for Registry {
         Registry {
             title: None,
             description: None,
+            maintainers: vec![],
+            homepage: None,
+            public_key: None,
             last_modified: Timestamp::now(),
             global: false,
             datasets: vec![],
@@ -1164,6 +3177,7 @@ impl Registry {
 /// 1. User-specified custom path via `requested_path`
 /// 2. For global registries (`global = true`):
 ///    - `$REFMAN_HOME/.refman/refman.toml` if `REFMAN_HOME` is set
+///    - `$XDG_DATA_HOME/refman/refman.toml` if `XDG_DATA_HOME` is set (not on Windows)
 ///    - ~/.refman/refman.toml as default global location
 /// 3. For local registries (`global = false`):
 ///    - ./refman.toml in current directory
@@ -1184,21 +3198,65 @@ impl Registry {
 /// foundational registry management capabilities that the rest of the tool builds upon.
 pub struct RegistryOptions {
     resolved_path: PathBuf,
+    home: PathBuf,
     title: Option<String>,
     description: Option<String>,
+    maintainers: Vec<String>,
+    homepage: Option<String>,
+    public_key: Option<String>,
     global: bool,
 }
 
 impl RegistryOptions {
-    /// Creates a new `RegistryOptions` instance with customized settings for registry file handling.
+    /// Sets who maintains the registry and where to find more about it, which `init()` records
+    /// in a newly created registry alongside its title and description.
     ///
-    /// This struct provides granular control over how refman interacts with registry files,
-    /// determining where they are stored and how they are initialized. It implements the core
-    /// logic for resolving registry paths according to the following priority:
+    /// This metadata travels with the registry file, so anyone a registry is shared with knows
+    /// who to contact about it.
+    #[must_use]
+    pub fn with_contact(mut self, maintainers: Vec<String>, homepage: Option<String>) -> Self {
+        self.maintainers = maintainers;
+        self.homepage = homepage;
+        self
+    }
+
+    /// Sets a base64-encoded minisign public key that `init()` records in a newly created
+    /// registry, and that downloads use to verify the signatures of any dataset without a public
+    /// key of its own.
+    #[must_use]
+    pub fn with_public_key(mut self, public_key: Option<String>) -> Self {
+        self.public_key = public_key;
+        self
+    }
+
+    /// Returns the fully resolved path to the registry file these options point at.
+    ///
+    /// The file may not exist yet, e.g. before `init()` or the first `refman register`.
+    #[inline]
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.resolved_path
+    }
+
+    /// Returns refman's home directory for these options: the requested directory, or the one
+    /// holding the requested registry file, then `$REFMAN_HOME` or the default global directory
+    /// for global registries, and otherwise the current directory.
+    #[inline]
+    #[must_use]
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    /// Creates a new `RegistryOptions` instance with customized settings for registry file handling.
+    ///
+    /// This struct provides granular control over how refman interacts with registry files,
+    /// determining where they are stored and how they are initialized. It implements the core
+    /// logic for resolving registry paths according to the following priority:
     ///
     /// 1. User-specified custom path via `requested_path` parameter
     /// 2. For global registries (`global = true`):
     ///    - `$REFMAN_HOME/.refman/refman.toml` if `REFMAN_HOME` is set
+    ///    - `$XDG_DATA_HOME/refman/refman.toml` if `XDG_DATA_HOME` is set (not on Windows)
     ///    - ~/.refman/refman.toml as default global location
     /// 3. For local registries (`global = false`):
     ///    - ./refman.toml in current directory
@@ -1206,7 +3264,7 @@ impl RegistryOptions {
     /// The struct handles all filesystem interactions needed to:
     /// - Resolve and validate registry file paths
     /// - Create new registry files or directories as needed
-    /// - Manage environment variables like `REFMAN_HOME`
+    /// - Read environment variables like `REFMAN_HOME`, without modifying them
     /// - Initialize registries with project metadata
     ///
     /// It works closely with the Project struct to provide the foundational registry
@@ -1222,7 +3280,8 @@ impl RegistryOptions {
     ///
     /// * `title` - Optional title for the registry/project
     /// * `description` - Optional description text
-    /// * `requested_path` - Optional custom path where the registry should be stored
+    /// * `requested_path` - Optional custom path where the registry should be stored, either a
+    ///   directory to hold a `refman.toml` or the registry file itself, e.g. `refs.toml`
     /// * `global` - Whether this is a global (true) or local (false) registry
     ///
     /// # Returns
@@ -1230,7 +3289,6 @@ impl RegistryOptions {
     /// Returns Ok(RegistryOptions) if initialization succeeds, or `RegistryError` if:
     /// - The requested path is invalid or inaccessible
     /// - Required directories cannot be created
-    /// - Environment variables cannot be set
     /// - Other filesystem operations fail
     ///
     /// # Errors
@@ -1241,32 +3299,22 @@ impl RegistryOptions {
     pub fn try_new(
         title: Option<String>,
         description: Option<String>,
-        requested_path: &Option<String>,
+        requested_path: &Option<PathBuf>,
         global: bool,
     ) -> Result<RegistryOptions, RegistryError> {
-        // If the user requested a path, see if it exists and is accessible, and
-        // try to make it work
-        if let Some(possible_path) = requested_path.as_deref() {
-            let maybe_path = PathBuf::from_str(possible_path).ok();
-            let resolved_path = resolve_registry_path(maybe_path, global)?;
-
-            Ok(Self {
-                resolved_path,
-                title,
-                description,
-                global,
-            })
-        // otherwise, resolve a path with default settings
-        } else {
-            let resolved_path = resolve_registry_path(None, global)?;
+        // the requested path, if any, takes precedence over the global and local defaults
+        let (home, resolved_path) = resolve_registry_path(requested_path.clone(), global)?;
 
-            Ok(Self {
-                resolved_path,
-                title,
-                description,
-                global,
-            })
-        }
+        Ok(Self {
+            resolved_path,
+            home,
+            title,
+            description,
+            maintainers: vec![],
+            homepage: None,
+            public_key: None,
+            global,
+        })
     }
 
     /// Initializes a new registry file for the Project if one doesn't already exist.
@@ -1275,9 +3323,11 @@ impl RegistryOptions {
     /// Project's reference datasets and metadata. The registry file location is determined
     /// by the `RegistryOptions` configuration, following these rules:
     ///
-    /// 1. User-specified custom path if provided to `RegistryOptions::try_new()`
+    /// 1. User-specified custom path if provided to `RegistryOptions::try_new()`, which is either
+    ///    the registry file itself or a directory holding a `refman.toml`
     /// 2. For global registries (global = true):
     ///    - `$REFMAN_HOME/.refman/refman.toml` if `REFMAN_HOME` is set
+    ///    - `$XDG_DATA_HOME/refman/refman.toml` if `XDG_DATA_HOME` is set (not on Windows)
     ///    - ~/.refman/refman.toml as default global location
     /// 3. For local registries (global = false):
     ///    - ./refman.toml in current directory
@@ -1318,8 +3368,12 @@ impl RegistryOptions {
         } else {
             let mut new_project =
                 Project::new(self.title.clone(), self.description.clone(), self.global);
-            File::create(&self.resolved_path)?;
-
+            new_project
+                .project
+                .maintainers
+                .clone_from(&self.maintainers);
+            new_project.project.homepage.clone_from(&self.homepage);
+            new_project.project.public_key.clone_from(&self.public_key);
             self.write_registry(&mut new_project)?;
             // Otherwise, do nothing except log out that a registry file already exists
         }
@@ -1337,6 +3391,7 @@ impl RegistryOptions {
     /// 1. User-specified custom path if provided
     /// 2. For global registries (global = true):
     ///    - `$REFMAN_HOME/.refman/refman.toml`
+    ///    - `$XDG_DATA_HOME/refman/refman.toml` (not on Windows)
     ///    - ~/.refman/refman.toml (default)
     /// 3. For local registries (global = false):
     ///    - ./refman.toml
@@ -1388,9 +3443,41 @@ impl RegistryOptions {
         // If neither of those conditions were met, read and deserialize the TOML
         // file into a Project struct and return it
         let toml_contents = read_to_string(self.resolved_path.clone())?;
-        let project: Project = toml::from_str(&toml_contents)?;
+        let project: Project = toml_contents.parse().map_err(|e| match e {
+            // name the file being read, since the parse error only gives a line and column in it
+            RegistryError::InvalidInputFormat(e) => {
+                RegistryError::InvalidRegistryFile(self.resolved_path.display().to_string(), e)
+            },
+            e => e,
+        })?;
         Ok(project)
     }
+
+    /// Reads the registry like [`RegistryOptions::read_registry`], but only if the registry file
+    /// exists and isn't empty.
+    ///
+    /// Commands that only make sense for datasets that were already registered, like listing
+    /// or downloading them, use this so that pointing them at the wrong path fails clearly,
+    /// rather than quietly working with an empty registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RegistryError::NoRegistry` if there is no registry file at the resolved path and
+    /// `RegistryError::EmptyRegistry` if the file is empty, as well as any error `read_registry`
+    /// can return.
+    pub fn read_existing_registry(&self) -> Result<Project, RegistryError> {
+        if !self.resolved_path.exists() {
+            return Err(RegistryError::NoRegistry(
+                self.resolved_path.display().to_string(),
+            ));
+        }
+        if fs::metadata(&self.resolved_path)?.len() == 0 {
+            return Err(RegistryError::EmptyRegistry);
+        }
+
+        self.read_registry()
+    }
+
     /// Writes a Project's registry data to the refman.toml file at the resolved registry path.
     ///
     /// This method handles persisting Project state to disk, including:
@@ -1403,6 +3490,7 @@ impl RegistryOptions {
     /// 1. User-specified custom path if provided
     /// 2. For global registries (global = true):
     ///    - `$REFMAN_HOME/.refman/refman.toml`
+    ///    - `$XDG_DATA_HOME/refman/refman.toml` (not on Windows)
     ///    - ~/.refman/refman.toml (default)
     /// 3. For local registries (global = false):
     ///    - ./refman.toml
@@ -1441,111 +3529,243 @@ impl RegistryOptions {
         // update the timestamp
         project.project.last_modified = Timestamp::now();
 
+        // make sure the registry's directory exists, which may not yet be the case for a global registry
+        if let Some(parent) = self.resolved_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
         // serialize and write out the TOML file
-        let toml_text = toml::to_string_pretty(project)?;
-        fs::write(&self.resolved_path, toml_text)?;
+        fs::write(&self.resolved_path, project.to_toml()?)?;
 
         Ok(())
     }
 }
 
+/// Resolves where the registry lives, returning refman's home directory for this run along with
+/// the path to the registry file. The home is the directory the user requested, or the one
+/// holding the requested registry file, then `$REFMAN_HOME` or the default global directory for
+/// global registries, and otherwise the current directory.
 fn resolve_registry_path(
     maybe_path: Option<PathBuf>,
     global: bool,
-) -> Result<PathBuf, RegistryError> {
+) -> Result<(PathBuf, PathBuf), RegistryError> {
     // to resolve a registry path, a fair amount of control flow needs to happen to unwrap a few conditions.
     // First, we prioritize a directory the user requests we place the registry in, if provided. This is the simplest
     // branch and comes first.
-    let registry_path = match maybe_path {
+    let resolved = match maybe_path {
+        // A path to an existing file or to a TOML file names the registry file itself, which lets a directory hold
+        // more than one registry. Any other path names the directory to keep a `refman.toml` in.
+        Some(valid_path) if names_registry_file(&valid_path) => {
+            let home = match valid_path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                _ => current_dir()?,
+            };
+            (home, valid_path)
+        },
         Some(valid_path) => {
-            if let Some(path_str) = valid_path.to_str() {
-                debug!("Setting the refman home to '{path_str}'");
-                set_refman_home(path_str);
-            }
-            valid_path.join("refman.toml")
+            let registry_path = valid_path.join("refman.toml");
+            (valid_path, registry_path)
         },
 
         // If the user did not request a particular directory, we then check if a global registry was requested.
+        None if global => resolve_global_home()?,
+
         // If not, this is the next simplest case; just place the registry in the current working directory (ideally,
-        // the project root).
+        // the project root), and use it as the refman home.
         None => {
-            // If not global, use the current directory as the refman home and return the full path.
-            if !global {
-                let current_dir = current_dir()?;
-                if let Some(current_dir_string) = current_dir.to_str() {
-                    debug!("Setting the refman home to '{current_dir_string}'");
-                    set_refman_home(current_dir_string);
-                }
-
-                return Ok(current_dir.join("refman.toml"));
-            }
-
-            // If no desired directory was provided, but the user also requested that the registry is global, first
-            // check the environment variable REFMAN_HOME for the registry's location.
-            let refman_home: Option<PathBuf> = match env::var("REFMAN_HOME") {
-                Ok(path_str) => {
-                    debug!(
-                        "Desired file path detected in the REFMAN_HOME environment variable: '{}'. A global registry will be placed there.",
-                        path_str
-                    );
-                    let path = PathBuf::from(path_str);
-                    Some(path)
-                },
-                // If that environment variable isn't set, place it in the home directory.
-                Err(_) => {
-                    debug!(
-                        "The REFMAN_HOME variable is not set. The registry will thus be placed in its default location in the user's home directory."
-                    );
-                    dirs::home_dir()
-                },
-            };
-
-            // Finally, whether the home directory is being used or the current directory as a fallback, join on
-            // a subdirectory called ".refman" and then "refman.toml" onto that.
-            if let Some(dir) = refman_home {
-                let resolved_home = dir.join(".refman");
-                debug!("setting the refman home to '{:?}'", resolved_home);
-                resolved_home
-            } else {
-                warn!("unable to access home directory, so `refman `will place its registry in the current working directory. unless this path is provided in the next `refman` run, `refman` may be unable to pick up where it leaves off during the current run.");
-                let current_dir = current_dir()?;
-                if let Some(current_dir_string) = current_dir.to_str() {
-                    debug!("setting the refman home to '{current_dir_string}'");
-                    set_refman_home(current_dir_string);
-                }
-                let resolved_home = current_dir.join(".refman");
-                debug!("setting the refman home to '{:?}'", resolved_home);
-                resolved_home
-            }.join("refman.toml")
-        }, // TODO: Eventually, it would be cool to have a global dotfile config for refman so the user doesn't have
-           // to tell it to operate globally every time.
+            let current_dir = current_dir()?;
+            let registry_path = current_dir.join("refman.toml");
+            (current_dir, registry_path)
+        },
     };
+    debug!("Using '{}' as the refman home", resolved.0.display());
 
-    Ok(registry_path)
+    Ok(resolved)
 }
 
-fn set_refman_home(desired_dir: &str) {
-    // If REFMAN_HOME is set,
-    if let Ok(old_home) = env::var("REFMAN_HOME") {
-        warn!(
-            "The environment variable $REFMAN_HOME was previously set to {}, but a new location at {} was requested. `refman` will overwrite the old $REFMAN_HOME value and proceed.",
-            old_home, desired_dir
-        );
-        unsafe { env::set_var("REFMAN_HOME", desired_dir) }
-    } else {
+/// Resolves the home and registry path of the global registry, when no path was requested.
+fn resolve_global_home() -> Result<(PathBuf, PathBuf), RegistryError> {
+    // If no desired directory was provided, but the user also requested that the registry is global, first check the
+    // environment variable REFMAN_HOME for the registry's location.
+    // The variable is read as an `OsString`, so that a path that isn't valid UTF-8 is used as is rather than being
+    // mistaken for an unset variable.
+    if let Some(path_str) = env::var_os("REFMAN_HOME") {
+        let path = PathBuf::from(path_str);
         debug!(
-            "The REFMAN_HOME environment variable has not previously been set. Now setting it to the requested directory, {}",
-            desired_dir
+            "Desired file path detected in the REFMAN_HOME environment variable: '{}'. A global registry will be placed there.",
+            path.display()
         );
-        unsafe { env::set_var("REFMAN_HOME", desired_dir) }
+        let registry_path = path.join(".refman").join("refman.toml");
+        return Ok((path, registry_path));
+    }
+
+    // If that environment variable isn't set, place it in the XDG data directory or the home directory.
+    debug!(
+        "The REFMAN_HOME variable is not set. The registry will thus be placed in its default location in the user's data or home directory."
+    );
+    if let Some(global_home) = default_global_home() {
+        let registry_path = global_home.join("refman.toml");
+        return Ok((global_home, registry_path));
+    }
+
+    // Finally, fall back to a ".refman" subdirectory of the current directory if no global directory could be found
+    warn!(
+        "unable to access home directory, so `refman `will place its registry in the current working directory. unless this path is provided in the next `refman` run, `refman` may be unable to pick up where it leaves off during the current run."
+    );
+    let current_dir = current_dir()?;
+    let registry_path = current_dir.join(".refman").join("refman.toml");
+    Ok((current_dir, registry_path))
+}
+
+/// Whether a registry path requested by the user names the registry file itself, rather than a
+/// directory to keep a `refman.toml` in: either it's an existing file, or it doesn't exist yet
+/// and has a `.toml` extension.
+fn names_registry_file(path: &Path) -> bool {
+    path.is_file()
+        || (!path.is_dir()
+            && path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("toml")))
+}
+
+/// Finds the default directory for the global registry when `REFMAN_HOME` is not set.
+///
+/// On platforms other than Windows, `$XDG_DATA_HOME/refman` is preferred when `XDG_DATA_HOME` is
+/// set to an absolute path, per the XDG Base Directory spec. The one exception is when a global
+/// registry already exists at the legacy `~/.refman/refman.toml` but not in the XDG location, in
+/// which case the legacy location keeps being used so that existing registries aren't orphaned.
+/// Otherwise, and on Windows, the registry lives in `~/.refman`.
+fn default_global_home() -> Option<PathBuf> {
+    let legacy_home = dirs::home_dir().map(|home| home.join(".refman"));
+
+    let xdg_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| !cfg!(windows) && path.is_absolute())
+        .map(|path| path.join("refman"));
+
+    match (xdg_home, legacy_home) {
+        (Some(xdg), Some(legacy))
+            if !xdg.join("refman.toml").exists() && legacy.join("refman.toml").exists() =>
+        {
+            debug!(
+                "XDG_DATA_HOME is set, but an existing global registry was found in '{}', so it will continue to be used there.",
+                legacy.display()
+            );
+            Some(legacy)
+        },
+        (Some(xdg), _) => Some(xdg),
+        (None, legacy) => legacy,
     }
 }
 
 fn is_likely_url(url: &str) -> bool {
-    url.starts_with("http") || url.starts_with("ftp") || url.starts_with("sftp")
+    url.starts_with("http")
+        || url.starts_with("ftp")
+        || url.starts_with("sftp")
+        || url.starts_with("s3")
 }
 
+/// Makes sure that no two of the files about to be downloaded would be saved to the same path,
+/// e.g. because two datasets, or two formats of one dataset, have URLs ending in the same
+/// filename and are downloaded into the same directory.
+///
+/// Paths are worked out from each URL the way [`download_path`] does, so files that are named
+/// by their server's `Content-Disposition` header can't be checked ahead of time.
+///
+/// # Errors
+///
+/// Returns `DownloadError::DestinationCollision` listing every path that more than one file would
+/// be saved to, along with the label, format, and URL of each of those files.
+fn check_destination_collisions(
+    dataset_files: &[(RefDataset, Vec<UnvalidatedFile>)],
+    target_dir: &Path,
+    options: &DownloadOptions,
+) -> Result<(), DownloadError> {
+    if options.checksum_only {
+        return Ok(());
+    }
+
+    let mut destinations: BTreeMap<PathBuf, Vec<(&str, FileFormat, &str)>> = BTreeMap::new();
+    for (dataset, files) in dataset_files {
+        let dir = dataset.download_dir(target_dir, options.per_label_dirs);
+        for file in files {
+            if let Some(path) = download_path(file.url(), &dir, options.mirror_structure) {
+                destinations.entry(path).or_default().push((
+                    &dataset.label,
+                    file.format(),
+                    file.url(),
+                ));
+            }
+        }
+    }
+
+    let collisions: Vec<String> = destinations
+        .iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|(path, files)| {
+            let files: Vec<String> = files
+                .iter()
+                .map(|(label, format, url)| format!("    {label} ({format}) {url}"))
+                .collect();
+            format!("  {}:\n{}", path.display(), files.join("\n"))
+        })
+        .collect();
+    if collisions.is_empty() {
+        Ok(())
+    } else {
+        Err(DownloadError::DestinationCollision(collisions.join("\n")))
+    }
+}
+
+/// Returns the files for a dataset that still need to be downloaded into `target_dir`, skipping
+/// any that were previously downloaded there and still pass their checksums.
 #[inline]
+fn pending_downloads(dataset: &RefDataset, target_dir: &Path) -> Vec<UnvalidatedFile> {
+    let fasta = dataset.get_fasta_download(target_dir);
+    let genbank = dataset.get_genbank_download(target_dir);
+    let gfa = dataset.get_gfa_download(target_dir);
+    let gtf = dataset.get_gtf_download(target_dir);
+    let gff = dataset.get_gff_download(target_dir);
+    let bed = dataset.get_bed_download(target_dir);
+    let vcf = dataset.get_vcf_download(target_dir);
+    let tar = dataset.get_tar_download(target_dir);
+
+    // the same URL registered under more than one format only needs to be fetched once; the download is recorded
+    // for all of those formats once it finishes
+    let mut requested_urls = HashSet::new();
+    [fasta, genbank, gfa, gff, gtf, bed, vcf, tar]
+        .into_iter()
+        .flatten()
+        .filter(|file| requested_urls.insert(file.url().to_string()))
+        .collect()
+}
+
+/// Unpacks the dataset's tarball into a directory next to it, once it has been downloaded and
+/// recorded, and records the files it was unpacked into. See [`extract_tarball`].
+fn extract_downloaded_tarball(
+    dataset: &mut RefDataset,
+    strip_components: usize,
+) -> Result<(), ValidationError> {
+    if let Some(DownloadStatus::Downloaded(tarball)) = &dataset.tar {
+        let (destination, files) = extract_tarball(&tarball.local_path, strip_components)?;
+        info!(
+            "Extracted {} file(s) from the tarball for '{}' into {}",
+            files.len(),
+            dataset.label,
+            destination.display()
+        );
+        dataset.extracted = files;
+    }
+
+    Ok(())
+}
+
+/// Resolves a path to its canonical form if it exists, so that paths spelled differently can be
+/// compared, falling back to the path as provided.
+fn resolve_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
 fn count_downloads(dataset_files: &[(RefDataset, Vec<UnvalidatedFile>)]) -> usize {
     // count the files to generate a message to inform the user of what will be downloaded
     let mut num_to_download = 0;
@@ -1556,18 +3776,28 @@ fn count_downloads(dataset_files: &[(RefDataset, Vec<UnvalidatedFile>)]) -> usiz
     num_to_download
 }
 
+/// Whether `dataset` is one of the datasets with the provided labels, or any dataset when
+/// `labels` is empty.
+fn is_selected(labels: &[&str], dataset: &RefDataset) -> bool {
+    labels.is_empty() || labels.contains(&dataset.label.as_str())
+}
+
 #[allow(clippy::expect_used)]
 fn setup_progress_tracking(
-    label: Option<&str>,
+    labels: &[&str],
     num_to_download: usize,
 ) -> (ProgressBar, Arc<MultiProgress>) {
-    // generate a message based on whether a particular dataset was requested as well as on the number
+    // generate a message based on whether particular datasets were requested as well as on the number
     // of files to be downloaded.
-    let message = match label {
-        Some(label_str) => {
+    let message = match labels {
+        [] => format!("Downloading all {num_to_download} files listed in the refman registry..."),
+        [label_str] => {
             format!("Downloading {num_to_download} files for project labeled '{label_str}'...")
         },
-        None => format!("Downloading all {num_to_download} files listed in the refman registry..."),
+        _ => format!(
+            "Downloading {num_to_download} files for the projects labeled '{}'...",
+            labels.join("', '")
+        ),
     };
 
     // Create a shared MultiProgress container.
@@ -1588,88 +3818,276 @@ fn setup_progress_tracking(
     (toplevel_pb, multi_pb)
 }
 
+/// What the server of a previously downloaded file says about whether it changed since.
+enum Freshness {
+    Unchanged,
+    Changed,
+    /// The file was downloaded before the headers that tell whether it changed were recorded, so
+    /// the server couldn't be asked. These are the headers it sends now.
+    Unrecorded(CacheValidators),
+}
+
+/// Asks the server of a previously downloaded `file` whether it changed since it was downloaded.
+async fn check_freshness(client: &Client, file: &ValidatedFile) -> color_eyre::Result<Freshness> {
+    let recorded = CacheValidators {
+        etag: file.etag.clone(),
+        last_modified: file.last_modified.clone(),
+    };
+    if recorded.is_empty() {
+        return fetch_validators(client, &file.uri)
+            .await
+            .map(Freshness::Unrecorded);
+    }
+    if remote_changed(client, &file.uri, &recorded).await? {
+        Ok(Freshness::Changed)
+    } else {
+        Ok(Freshness::Unchanged)
+    }
+}
+
 fn submit_download_requests(
     dataset_files: Vec<(RefDataset, Vec<UnvalidatedFile>)>,
     shared_client: &Client,
     target_dir: &Path,
     mp: &Arc<MultiProgress>,
-) -> Vec<JoinHandle<Result<(RefDataset, MultiDownloadResults), ColorError>>> {
-    // count the number of files to download
-    let num_to_download = dataset_files.len();
-
-    // use that count to initialize a vector of exactly the right length, each item of which will be a deeply
-    // nested result of vectors of results. This is because tasks will be spawned at two levels: one task per
-    // request `RefDataset`, and all the registered files per `RefDataset`.
-    let mut dataset_task_handles: Vec<
-        JoinHandle<Result<(RefDataset, MultiDownloadResults), ColorError>>,
-    > = Vec::with_capacity(num_to_download);
+    options: &DownloadOptions,
+    journal: &Arc<ProgressJournal>,
+) -> (JoinSet<DatasetDownloadResult>, HashMap<Id, String>) {
+    let DownloadOptions {
+        fail_fast,
+        mirror_structure,
+        per_label_dirs,
+        redirects,
+        heartbeat,
+        ..
+    } = *options;
+    let retries = options.retry_count();
+    // Each item in the join set will be a deeply nested result of vectors of results. This is because tasks will
+    // be spawned at two levels: one task per request `RefDataset`, and all the registered files per `RefDataset`.
+    // Join sets abort their tasks when dropped, which is what lets a fail-fast run cancel downloads in flight.
+    let mut dataset_tasks: JoinSet<DatasetDownloadResult> = JoinSet::new();
+    // If a task panics or is cancelled, its output is lost, so the label of each dataset and the file of each
+    // download are kept by task ID to say what was lost.
+    let mut dataset_labels: HashMap<Id, String> = HashMap::with_capacity(dataset_files.len());
+    // Every file download, across all datasets, waits for a permit before connecting, which caps how many run at
+    // once. Permits are released when they're dropped, so a download that fails still frees its slot.
+    let permits = Arc::new(Semaphore::new(options.download_jobs()));
 
     // Go through each dataset and its registered files and request them. This design can be thought of somewhat like
     // actors, where each dataset task supervises each file download task
     for (dataset, files) in dataset_files {
         let shared_client = shared_client.clone();
         let mp = mp.clone();
-        let target_dir = Arc::new(target_dir.to_path_buf());
+        let permits = permits.clone();
+        let journal = journal.clone();
+        let target_dir = Arc::new(dataset.download_dir(target_dir, per_label_dirs));
 
         // Spawn a task per dataset
-        let handle: JoinHandle<_> = tokio::spawn(async move {
+        let label = dataset.label.clone();
+        let handle = dataset_tasks.spawn(async move {
             // Inside this task: spawn parallel tasks for each file
-            let file_task_handles = files.into_iter().map(|file| {
+            let mut file_tasks = JoinSet::new();
+            let mut requested_files: HashMap<Id, UnvalidatedFile> = HashMap::new();
+            for file in files {
                 let client = shared_client.clone();
                 let dir = target_dir.clone();
                 let mp = mp.clone();
+                let permits = permits.clone();
+                let journal = journal.clone();
+                let label = dataset.label.clone();
 
-                tokio::spawn(async move { request_dataset(file, client, dir, mp).await })
-            });
+                let signature_url = dataset.signatures.get(&file.format()).cloned();
+                let range = dataset.byte_ranges.get(&file.format()).copied();
+                let requested =
+                    UnvalidatedFile::new(file.format(), file.url().to_string(), PathBuf::new());
+                let lost = requested.clone();
+                let handle = file_tasks.spawn(async move {
+                    let download = async {
+                        // a file that already has a path was finished by an interrupted run
+                        if !file.get_path().as_os_str().is_empty() {
+                            return Ok((file, CacheValidators::default()));
+                        }
+                        let _permit = permits.acquire_owned().await?;
+                        let (file, validators) = request_dataset(
+                            file,
+                            client.clone(),
+                            dir.clone(),
+                            mp.clone(),
+                            range,
+                            mirror_structure,
+                            redirects,
+                            heartbeat,
+                            retries,
+                        )
+                        .await?;
+                        // signatures are kept alongside the files they sign, wherever those ended up
+                        if let Some(signature_url) = signature_url {
+                            let signature_dir = file
+                                .get_path()
+                                .parent()
+                                .map_or(dir, |parent| Arc::new(parent.to_path_buf()));
+                            request_signature(signature_url, client, signature_dir, mp, redirects)
+                                .await?;
+                        }
+                        journal.record(&label, file.url(), file.get_path());
+                        Ok((file, validators))
+                    };
+                    download.await.map_err(|error| FailedDownload {
+                        file: requested,
+                        error,
+                    })
+                });
+                requested_files.insert(handle.id(), lost);
+            }
 
-            // Await all file download tasks for this dataset
-            let file_results = try_join_all(file_task_handles).await?;
+            // Await all file download tasks for this dataset. In fail-fast mode, returning early drops the
+            // join set, which aborts any of this dataset's downloads that are still running.
+            let mut file_results: MultiDownloadResults = Vec::with_capacity(file_tasks.len());
+            while let Some(joined) = file_tasks.join_next_with_id().await {
+                // a download task that panicked is a failed download like any other, so it doesn't cost the
+                // dataset the results of its other downloads
+                let file_result = match joined {
+                    Ok((id, file_result)) => {
+                        requested_files.remove(&id);
+                        file_result
+                    },
+                    Err(join_error) => match requested_files.remove(&join_error.id()) {
+                        Some(file) => Err(FailedDownload {
+                            error: eyre!(
+                                "The download task for {} failed: {join_error}",
+                                file.url()
+                            ),
+                            file,
+                        }),
+                        None => return Err(join_error.into()),
+                    },
+                };
+                match file_result {
+                    Err(failure) if fail_fast => return Err(failure.error),
+                    file_result => file_results.push(file_result),
+                }
+            }
 
             Ok((dataset, file_results))
         });
-
-        // collect all the dataset "supervisor" tasks
-        dataset_task_handles.push(handle);
+        dataset_labels.insert(handle.id(), label);
     }
 
-    dataset_task_handles
+    (dataset_tasks, dataset_labels)
 }
 
 async fn update_project_datasets(
-    dataset_task_handles: Vec<JoinHandle<Result<(RefDataset, MultiDownloadResults), ColorError>>>,
+    (mut dataset_tasks, dataset_labels): (JoinSet<DatasetDownloadResult>, HashMap<Id, String>),
     toplevel_pb: &mut ProgressBar,
+    options: &DownloadOptions,
+    registry_key: Option<&str>,
 ) -> color_eyre::Result<Vec<RefDataset>> {
-    // await all tasks in all threads, collecting them into a vec after the transformations below
-    let updated_datasets: Vec<RefDataset> = try_join_all(dataset_task_handles)
-        .await?
-        // no need to reference each item; consuming them is fine here
-        .into_iter()
-        // take each attempted download, and, if successful, increment the progress bar, and then keep the
-        // successful unvalidated downloads in a vector
-        .filter_map(|dataset_result| {
-            toplevel_pb.inc(1);
-            match dataset_result {
-                Ok((dataset, file_results)) => {
-                    match file_results.into_iter().collect::<Result<Vec<_>, _>>() {
-                        Ok(successful_files) => Some((dataset, successful_files)),
-                        Err(msg) => {
-                            warn!("Failed to download files because of this error: {}", msg);
-                            None
-                        }
-                    }
+    // await all tasks in all threads as they finish, keeping the successful unvalidated downloads for each dataset
+    let mut successful_downloads: Vec<(RefDataset, Vec<DownloadedFile>)> =
+        Vec::with_capacity(dataset_tasks.len());
+    // datasets with files that failed to download aren't updated with their other downloads, but the failures are
+    // recorded so that they're visible across runs
+    let mut failed_datasets: Vec<RefDataset> = Vec::new();
+    // the labels of datasets whose tasks were lost entirely, which doesn't stop the other datasets from being updated
+    // unless the run is failing fast
+    let mut lost_datasets: Vec<&str> = Vec::new();
+    while let Some(joined) = dataset_tasks.join_next_with_id().await {
+        toplevel_pb.inc(1);
+        let dataset_result = match joined {
+            Ok((_, dataset_result)) => dataset_result,
+            Err(join_error) => {
+                let label = dataset_labels
+                    .get(&join_error.id())
+                    .map_or("unknown", String::as_str);
+                lost_datasets.push(label);
+                Err(eyre!(
+                    "The download task for dataset '{label}' failed: {join_error}"
+                ))
+            },
+        };
+        match dataset_result {
+            Ok((mut dataset, file_results)) => {
+                let (successful_files, failures): (Vec<_>, Vec<_>) =
+                    file_results.into_iter().partition(Result::is_ok);
+                if failures.is_empty() {
+                    successful_downloads
+                        .push((dataset, successful_files.into_iter().flatten().collect()));
+                    continue;
                 }
-                Err(msg) => {
-                    warn!("Failed to download files because of this error: {}", msg);
-                    None
+                for FailedDownload { file, error } in failures.into_iter().filter_map(Result::err) {
+                    warn!(
+                        "Failed to download {} because of this error: {error}",
+                        file.url()
+                    );
+                    dataset.record_failed_download(&file, &error.to_string());
                 }
-            }
-        })
-        // now use each successful download to update its associated dataset, returning an owned updated dataset or
-        // a validation error (the update performs validation under the hood)
+                failed_datasets.push(dataset);
+            },
+            // in fail-fast mode, bail out with the first error. Dropping the join set on the way out aborts the
+            // other datasets' tasks along with the file downloads they supervise.
+            Err(msg) if options.fail_fast => {
+                toplevel_pb.abandon_with_message("Aborting remaining downloads after a failure.");
+                return Err(msg);
+            },
+            Err(msg) => {
+                warn!("Failed to download files because of this error: {}", msg);
+            },
+        }
+    }
+    if !lost_datasets.is_empty() {
+        warn!(
+            "{} of {} datasets could not be downloaded and were left as they were: {}. The others will still be updated.",
+            lost_datasets.len(),
+            dataset_labels.len(),
+            lost_datasets.join(", ")
+        );
+    }
+
+    // now use each successful download to update its associated dataset, returning an owned updated dataset or
+    // a validation error (the update performs validation under the hood). If requested, files that fail validation
+    // are instead kept and recorded as unvalidated.
+    let updated_datasets: Vec<RefDataset> = successful_downloads
+        .into_iter()
         .map(
             |(mut dataset, files)| -> Result<RefDataset, ValidationError> {
-                for file in files {
-                    dataset.update_with_download(&file)?;
+                // a file whose URL is registered under more than one format was only downloaded once, so record
+                // the download for each of those formats
+                let files: Vec<DownloadedFile> = files
+                    .into_iter()
+                    .flat_map(|(file, validators)| {
+                        let mut sharing: Vec<DownloadedFile> = dataset
+                            .files_sharing_url(&file)
+                            .into_iter()
+                            .map(|shared| (shared, validators.clone()))
+                            .collect();
+                        sharing.insert(0, (file, validators));
+                        sharing
+                    })
+                    .collect();
+                for (file, validators) in files {
+                    // a file that doesn't match its signature can't be trusted, so it's never recorded, even when
+                    // validation errors are otherwise tolerated
+                    dataset.verify_signature(&file, registry_key)?;
+                    match dataset.update_with_sampled_download(&file, options.validation_sample) {
+                        Ok(()) => {},
+                        // files that don't match their published checksums are never kept, like those that don't match
+                        // their signatures
+                        Err(error @ ValidationError::ChecksumMismatch(..)) => return Err(error),
+                        Err(error) if options.continue_on_validation_error => {
+                            warn!(
+                                "The file downloaded from {} failed validation, but it will be kept at {} and recorded as unvalidated. Here's the validation error: {error}",
+                                file.url(),
+                                file.get_path().display()
+                            );
+                            dataset.update_with_unvalidated_download(&file)?;
+                        },
+                        Err(error) => return Err(error),
+                    }
+                    dataset.set_hash_algorithm(file.format(), options.hash_algorithm);
+                    dataset.set_cache_validators(file.format(), validators);
+                    if options.extract && file.format() == FileFormat::Tar {
+                        extract_downloaded_tarball(&mut dataset, options.strip_components)?;
+                    }
                 }
                 Ok(dataset)
             },
@@ -1677,7 +4095,10 @@ async fn update_project_datasets(
         .collect::<Result<Vec<RefDataset>, ValidationError>>()?;
 
     // return the vector of updated `RefDataset` instances
-    Ok(updated_datasets)
+    Ok(updated_datasets
+        .into_iter()
+        .chain(failed_datasets)
+        .collect())
 }
 
 #[cfg(test)]
@@ -1687,6 +4108,24 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    use crate::ValidatedFile;
+
+    #[test]
+    fn test_with_metadata() {
+        let project = Project::new(
+            Some("Old title".to_string()),
+            Some("Notes".to_string()),
+            false,
+        )
+        .with_metadata(Some("New title".to_string()), None);
+        assert_eq!(project.project.title.as_deref(), Some("New title"));
+        assert_eq!(project.project.description.as_deref(), Some("Notes"));
+
+        let project = project.with_metadata(None, Some(String::new()));
+        assert_eq!(project.project.title.as_deref(), Some("New title"));
+        assert!(project.project.description.is_none());
+    }
+
     #[test]
     fn test_new_project() {
         let title = Some("Test Project".to_string());
@@ -1702,32 +4141,78 @@ mod tests {
     #[test]
     fn test_registry_options_new() {
         let temp_dir = tempdir().unwrap();
-        let dir_path = temp_dir.path().to_str().unwrap();
+        let dir_path = temp_dir.path().to_path_buf();
 
         let options = RegistryOptions::try_new(
             Some("Test Registry".to_string()),
             Some("Test Description".to_string()),
-            &Some(dir_path.to_string()),
+            &Some(dir_path.clone()),
             false,
         )
         .unwrap();
 
-        assert_eq!(
-            options.resolved_path,
-            PathBuf::from(dir_path).join("refman.toml")
-        );
+        assert_eq!(options.resolved_path, dir_path.join("refman.toml"));
         assert_eq!(options.title, Some("Test Registry".to_string()));
         assert_eq!(options.description, Some("Test Description".to_string()));
         assert!(!options.global);
     }
 
     #[test]
-    fn test_read_write_registry() {
+    fn test_registry_options_file_path() {
+        let temp_dir = tempdir().unwrap();
+        let refman_home = env::var_os("REFMAN_HOME");
+        let resolve = |path: PathBuf| {
+            let options = RegistryOptions::try_new(None, None, &Some(path), false).unwrap();
+            (options.home().to_path_buf(), options.resolved_path)
+        };
+
+        // registry files with other names are used as is, whether or not they exist yet, with the
+        // directory holding them as the refman home
+        let custom = temp_dir.path().join("refs.toml");
+        assert_eq!(
+            resolve(custom.clone()),
+            (temp_dir.path().to_path_buf(), custom)
+        );
+        let existing = temp_dir.path().join("registry");
+        fs::write(&existing, "").unwrap();
+        assert_eq!(resolve(existing.clone()).1, existing);
+
+        // directories hold a `refman.toml`, and are the refman home themselves
+        assert_eq!(
+            resolve(temp_dir.path().to_path_buf()),
+            (
+                temp_dir.path().to_path_buf(),
+                temp_dir.path().join("refman.toml")
+            )
+        );
+
+        // resolving the home leaves the environment alone
+        assert_eq!(env::var_os("REFMAN_HOME"), refman_home);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_registry_in_non_utf8_dir() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
         let temp_dir = tempdir().unwrap();
-        let dir_path = temp_dir.path().to_str().unwrap();
+        let dir_path = temp_dir.path().join(OsStr::from_bytes(b"refs-\xff"));
+        fs::create_dir(&dir_path).unwrap();
+
+        let options = RegistryOptions::try_new(None, None, &Some(dir_path.clone()), false).unwrap();
+        assert_eq!(options.resolved_path, dir_path.join("refman.toml"));
+        options
+            .write_registry(&mut Project::new(None, None, false))
+            .unwrap();
+        assert!(dir_path.join("refman.toml").exists());
+    }
 
+    #[test]
+    fn test_read_write_registry() {
+        let temp_dir = tempdir().unwrap();
         let options =
-            RegistryOptions::try_new(None, None, &Some(dir_path.to_string()), false).unwrap();
+            RegistryOptions::try_new(None, None, &Some(temp_dir.path().to_path_buf()), false)
+                .unwrap();
 
         // Test writing
         let mut project = Project::new(None, None, false);
@@ -1738,4 +4223,1163 @@ mod tests {
         let read_project = options.read_registry().unwrap();
         assert_eq!(read_project.datasets().len(), 0);
     }
+
+    #[test]
+    fn test_read_registry_reports_parse_errors() {
+        let temp_dir = tempdir().unwrap();
+        let options =
+            RegistryOptions::try_new(None, None, &Some(temp_dir.path().to_path_buf()), false)
+                .unwrap();
+        let mut project = Project::new(None, None, false);
+        options.write_registry(&mut project).unwrap();
+        let registry = fs::read_to_string(&options.resolved_path).unwrap();
+        fs::write(
+            &options.resolved_path,
+            format!("{registry}[[project.datasets]\nlabel = \"hg38\"\n"),
+        )
+        .unwrap();
+        let typo_line = registry.lines().count() + 1;
+
+        let error = options.read_registry().unwrap_err();
+        assert!(matches!(error, RegistryError::InvalidRegistryFile(..)));
+        let message = error.to_string();
+        assert!(message.contains(&options.resolved_path.display().to_string()));
+        assert!(message.contains(&format!("line {typo_line}")), "{message}");
+    }
+
+    #[test]
+    fn test_validate_structure_rejects_duplicate_labels() {
+        let dataset = RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new("genome.fasta".to_string())),
+            ..RefDataset::default()
+        };
+        let mut project = Project::new(None, None, false);
+        project.project.datasets.push(dataset.clone());
+        assert!(project.validate_structure().is_ok());
+
+        project.project.datasets.push(dataset);
+        assert!(matches!(
+            project.validate_structure(),
+            Err(EntryError::DuplicateLabel(label)) if label == "genome"
+        ));
+    }
+
+    #[test]
+    fn test_unrecorded_existing_files() {
+        let dir = tempdir().unwrap();
+        let url = "https://example.com/genome.fasta";
+        let existing = dir.path().join("genome.fasta");
+        fs::write(&existing, ">unrelated\nACGT\n").unwrap();
+
+        let mut project = Project::new(None, None, false);
+        project.project.datasets.push(RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new(url.to_string())),
+            ..RefDataset::default()
+        });
+        assert_eq!(
+            project.unrecorded_existing_files(
+                &[],
+                dir.path(),
+                false,
+                false,
+                &FormatSelection::default()
+            ),
+            vec![existing.clone()]
+        );
+        assert!(
+            project
+                .unrecorded_existing_files(
+                    &["other"],
+                    dir.path(),
+                    false,
+                    false,
+                    &FormatSelection::default()
+                )
+                .is_empty()
+        );
+
+        // once the registry records downloading the file (here with a stale hash, so it would be re-downloaded), it's
+        // no longer considered unrelated
+        project.project.datasets[0].fasta = Some(DownloadStatus::new_downloaded(ValidatedFile {
+            uri: url.to_string(),
+            local_path: existing,
+            hash: Some("stale".to_string()),
+            ..ValidatedFile::default()
+        }));
+        assert!(
+            project
+                .unrecorded_existing_files(
+                    &[],
+                    dir.path(),
+                    false,
+                    false,
+                    &FormatSelection::default()
+                )
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_plan_counts_shared_urls_once() {
+        let dir = tempdir().unwrap();
+        let shared = "https://example.com/genome.gbff";
+        let mut project = Project::new(None, None, false);
+        project.project.datasets.push(RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new(shared.to_string())),
+            gff: Some(DownloadStatus::new(shared.to_string())),
+            bed: Some(DownloadStatus::new(
+                "https://example.com/peaks.bed".to_string(),
+            )),
+            // sizes come from the byte ranges, so nothing is requested
+            byte_ranges: [
+                (
+                    FileFormat::Fasta,
+                    ByteRange {
+                        start: 0,
+                        end: Some(99),
+                    },
+                ),
+                (
+                    FileFormat::Bed,
+                    ByteRange {
+                        start: 0,
+                        end: Some(49),
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            ..RefDataset::default()
+        });
+
+        let plan = project
+            .download_plan(&[], dir.path(), &DownloadOptions::default())
+            .await
+            .unwrap();
+        let sizes: Vec<String> = (1..plan.table.len())
+            .map(|row| plan.table[row][3].get_content())
+            .collect();
+        assert_eq!(
+            sizes,
+            [humanize_bytes(100), humanize_bytes(100), humanize_bytes(50)]
+        );
+        assert_eq!(plan.num_to_download, 3);
+        assert_eq!(plan.num_unknown, 0);
+        assert_eq!(plan.total_bytes, 150);
+    }
+
+    #[test]
+    fn test_collect_downloads_for_labels() {
+        let mut project = Project::new(None, None, false);
+        for label in ["hg38", "mm39", "dm6"] {
+            project.project.datasets.push(RefDataset {
+                label: label.to_string(),
+                fasta: Some(DownloadStatus::new(format!(
+                    "https://example.com/{label}.fasta"
+                ))),
+                ..RefDataset::default()
+            });
+        }
+        let collected_labels = |labels: &[&str]| {
+            project
+                .collect_downloads(labels, Path::new("refs"), false)
+                .into_iter()
+                .map(|(dataset, _)| dataset.label)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(collected_labels(&[]), ["hg38", "mm39", "dm6"]);
+        assert_eq!(collected_labels(&["dm6", "hg38"]), ["hg38", "dm6"]);
+    }
+
+    #[test]
+    fn test_collect_downloads_per_label_dirs() {
+        let dir = tempdir().unwrap();
+        let flat = dir.path().join("genome.fasta");
+        let nested = dir.path().join("mm39").join("genome.fasta");
+        fs::create_dir(dir.path().join("mm39")).unwrap();
+        fs::write(&flat, ">seq\nACGT\n").unwrap();
+        fs::write(&nested, ">seq\nACGT\n").unwrap();
+        let downloaded = |label: &str, local_path: &Path| RefDataset {
+            label: label.to_string(),
+            fasta: Some(DownloadStatus::new_downloaded(ValidatedFile {
+                uri: format!("https://example.com/{label}/genome.fasta"),
+                local_path: local_path.to_path_buf(),
+                hash: Some(hash_valid_download(local_path).unwrap()),
+                validated: true,
+                ..ValidatedFile::default()
+            })),
+            ..RefDataset::default()
+        };
+
+        let mut project = Project::new(None, None, false);
+        project.project.datasets.push(downloaded("hg38", &flat));
+        project.project.datasets.push(downloaded("mm39", &nested));
+        let pending_labels = |per_label_dirs| {
+            project
+                .collect_downloads(&[], dir.path(), per_label_dirs)
+                .into_iter()
+                .filter(|(_, files)| !files.is_empty())
+                .map(|(dataset, _)| dataset.label)
+                .collect::<Vec<_>>()
+        };
+
+        // files already in their label's directory are recognized, while ones downloaded flat
+        // are fetched again into theirs
+        assert_eq!(pending_labels(true), ["hg38"]);
+        // and files in a label's directory are still within the destination without one
+        assert!(pending_labels(false).is_empty());
+    }
+
+    #[test]
+    fn test_destination_collisions() {
+        let mut project = Project::new(None, None, false);
+        for (label, fasta, gff) in [
+            (
+                "hg38",
+                "https://example.com/hg38/genome.fa",
+                "https://example.com/hg38/genes.gff",
+            ),
+            (
+                "mm39",
+                "https://example.com/mm39/genome.fa",
+                "https://example.com/mm39/genes.gtf",
+            ),
+        ] {
+            project.project.datasets.push(RefDataset {
+                label: label.to_string(),
+                fasta: Some(DownloadStatus::new(fasta.to_string())),
+                gff: Some(DownloadStatus::new(gff.to_string())),
+                ..RefDataset::default()
+            });
+        }
+        let target_dir = Path::new("refs");
+        let dataset_files = project.collect_downloads(&[], target_dir, false);
+
+        let Err(DownloadError::DestinationCollision(message)) =
+            check_destination_collisions(&dataset_files, target_dir, &DownloadOptions::default())
+        else {
+            panic!("the two genome.fa files should collide");
+        };
+        assert!(message.contains(&target_dir.join("genome.fa").display().to_string()));
+        assert!(message.contains("hg38 (FASTA) https://example.com/hg38/genome.fa"));
+        assert!(message.contains("mm39 (FASTA) https://example.com/mm39/genome.fa"));
+        assert!(!message.contains("genes"));
+
+        // separate directories for each dataset, or for each URL's path, keep the files apart
+        for options in [
+            DownloadOptions {
+                per_label_dirs: true,
+                ..DownloadOptions::default()
+            },
+            DownloadOptions {
+                mirror_structure: true,
+                ..DownloadOptions::default()
+            },
+        ] {
+            let dataset_files = project.collect_downloads(&[], target_dir, options.per_label_dirs);
+            assert!(check_destination_collisions(&dataset_files, target_dir, &options).is_ok());
+        }
+
+        // and so does only downloading one of the datasets
+        let dataset_files = project.collect_downloads(&["mm39"], target_dir, false);
+        assert!(
+            check_destination_collisions(&dataset_files, target_dir, &DownloadOptions::default())
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_humanize_age() {
+        assert_eq!(humanize_age(SignedDuration::from_secs(-5)), "in the future");
+        assert_eq!(humanize_age(SignedDuration::from_secs(30)), "just now");
+        assert_eq!(humanize_age(SignedDuration::from_mins(1)), "1 minute ago");
+        assert_eq!(humanize_age(SignedDuration::from_hours(5)), "5 hours ago");
+        assert_eq!(
+            humanize_age(SignedDuration::from_hours(24 * 3)),
+            "3 days ago"
+        );
+    }
+
+    #[test]
+    fn test_humanize_bytes() {
+        assert_eq!(humanize_bytes(0), "0 B");
+        assert_eq!(humanize_bytes(1023), "1023 B");
+        assert_eq!(humanize_bytes(1536), "1.5 KiB");
+        assert_eq!(humanize_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+
+    #[test]
+    fn test_contact_metadata_round_trip() {
+        // registries written before maintainers and homepages were tracked still parse
+        let legacy: Project = "[project]\nlast_modified = \"2025-03-19T17:24:04.673076Z\"\nglobal = false\ndatasets = []\n"
+            .parse()
+            .unwrap();
+        assert!(legacy.maintainers().is_empty());
+        assert!(legacy.homepage().is_none());
+
+        let mut project = Project::new(Some("Shared".to_string()), None, false);
+        project.project.maintainers = vec!["Ada".to_string()];
+        project.project.homepage = Some("https://example.com".to_string());
+        let reparsed: Project = toml::to_string_pretty(&project).unwrap().parse().unwrap();
+        assert_eq!(reparsed.maintainers(), ["Ada".to_string()]);
+        assert_eq!(reparsed.homepage(), Some("https://example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_from_urls_rejects_ambiguous_formats() {
+        let unrecognized = Project::from_urls("quick", &["https://example.com/reads.bam"]).await;
+        assert!(matches!(
+            unrecognized,
+            Err(EntryError::UnrecognizedFormat(url)) if url.ends_with("reads.bam")
+        ));
+
+        let duplicated = Project::from_urls(
+            "quick",
+            &["https://example.com/a.fasta", "https://example.com/b.fa.gz"],
+        )
+        .await;
+        assert!(matches!(
+            duplicated,
+            Err(EntryError::DuplicateFormat(format, ..)) if format == "FASTA"
+        ));
+    }
+
+    #[test]
+    fn test_get_all_urls_rejects_malformed_urls() {
+        let mut project = Project::new(None, None, false);
+        project.project.datasets.push(RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new(
+                "https://example.com/genome.fasta".to_string(),
+            )),
+            ..RefDataset::default()
+        });
+        assert_eq!(
+            project.get_all_urls().unwrap(),
+            ["https://example.com/genome.fasta"]
+        );
+
+        for url in ["", "sftp://example.com/annotation.gff"] {
+            let mut malformed = project.clone();
+            malformed.project.datasets[0].gff = Some(DownloadStatus::new(url.to_string()));
+            assert!(matches!(
+                malformed.get_all_urls(),
+                Err(EntryError::MalformedUrl(label, bad_url)) if label == "genome" && bad_url == url
+            ));
+        }
+
+        // a hand-edited registry with an empty URL, here for a tarball
+        let hand_edited: Project = toml::from_str(
+            r#"
+            [project]
+            last_modified = "2025-01-01T00:00:00Z"
+            global = false
+
+            [[project.datasets]]
+            label = "genome"
+            fasta = "https://example.com/genome.fasta"
+            tar = ""
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(
+            hand_edited.get_all_urls(),
+            Err(EntryError::MalformedUrl(label, bad_url)) if label == "genome" && bad_url.is_empty()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_register_modes() {
+        let dir = tempdir().unwrap();
+        let fasta = dir.path().join("genome.fasta");
+        fs::write(&fasta, ">seq\nACGT\n").unwrap();
+        let dataset = || RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new(fasta.display().to_string())),
+            ..RefDataset::default()
+        };
+
+        let project = Project::new(None, None, false);
+        let update_missing = project
+            .clone()
+            .register_with_mode(dataset(), RegisterMode::UpdateOnly)
+            .await;
+        assert!(
+            matches!(update_missing, Err(EntryError::LabelNotFound(label)) if label == "genome")
+        );
+
+        let project = project
+            .register_with_mode(dataset(), RegisterMode::CreateOnly)
+            .await
+            .unwrap();
+        let create_existing = project
+            .clone()
+            .register_with_mode(dataset(), RegisterMode::CreateOnly)
+            .await;
+        assert!(
+            matches!(create_existing, Err(EntryError::LabelAlreadyExists(label)) if label == "genome")
+        );
+
+        let project = project
+            .register_with_mode(dataset(), RegisterMode::UpdateOnly)
+            .await
+            .unwrap();
+        assert_eq!(project.datasets().len(), 1);
+    }
+
+    #[test]
+    fn test_read_batch_file() {
+        let dir = tempdir().unwrap();
+        let tsv = dir.path().join("datasets.tsv");
+        fs::write(
+            &tsv,
+            "label\tfasta\tgenbank\tgfa\tgff\n\
+             # a comment\n\
+             \n\
+             genome\thttps://example.com/genome.fa\t\t\thttps://example.com/genes.gff\n\
+             \thttps://example.com/unlabeled.fa\n\
+             wide\ta\tb\tc\td\te\tf\tg\th\ti\n",
+        )
+        .unwrap();
+
+        let rows = read_batch_file(&tsv).unwrap();
+        assert_eq!(rows.len(), 3);
+        let row = rows[0].as_ref().unwrap();
+        assert_eq!(row.line, 4);
+        assert_eq!(row.label, "genome");
+        assert_eq!(
+            row.urls[0].as_deref(),
+            Some("https://example.com/genome.fa")
+        );
+        assert_eq!(row.urls[1], None);
+        assert_eq!(
+            row.urls[3].as_deref(),
+            Some("https://example.com/genes.gff")
+        );
+        assert!(row.urls[4..].iter().all(Option::is_none));
+        let no_label = rows[1].as_ref().unwrap_err().to_string();
+        assert!(no_label.contains("Line 5"), "{no_label}");
+        let too_wide = rows[2].as_ref().unwrap_err().to_string();
+        assert!(too_wide.contains("Line 6"), "{too_wide}");
+
+        // CSV files are split on commas, and the header row is optional
+        let csv = dir.path().join("datasets.CSV");
+        fs::write(&csv, "genome, https://example.com/genome.fa\n").unwrap();
+        let rows = read_batch_file(&csv).unwrap();
+        let row = rows[0].as_ref().unwrap();
+        assert_eq!((row.line, row.label.as_str()), (1, "genome"));
+        assert_eq!(
+            row.urls[0].as_deref(),
+            Some("https://example.com/genome.fa")
+        );
+
+        assert!(read_batch_file(&dir.path().join("missing.tsv")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_register_batch() {
+        let row = |line: usize, label: &str, fasta: &str, gff: Option<&str>| {
+            let mut urls: [Option<String>; 8] = Default::default();
+            urls[0] = Some(fasta.to_string());
+            urls[3] = gff.map(str::to_string);
+            Ok(BatchRow {
+                line,
+                label: label.to_string(),
+                urls,
+            })
+        };
+        let rows = || {
+            vec![
+                row(1, "genome", "https://example.com/genome.fa", None),
+                Err(eyre!("Line 2 of the batch file has no label")),
+                // registers the same URL twice, which strict registration refuses
+                row(
+                    3,
+                    "duplicated",
+                    "https://example.com/other.fa",
+                    Some("https://example.com/other.fa"),
+                ),
+                row(4, "plasmid", "https://example.com/plasmid.fa", None),
+            ]
+        };
+
+        let mut project = Project::new(None, None, false);
+        let summary = Box::pin(project.register_batch(
+            rows(),
+            RegisterMode::default(),
+            UrlChecks::Offline,
+            None,
+            true,
+            false,
+        ))
+        .await
+        .unwrap();
+        assert_eq!(summary.registered, ["genome", "plasmid"]);
+        assert_eq!(summary.failures.len(), 2);
+        assert!(summary.failures[1].to_string().contains("Line 3"));
+        // the refused row was left out of the registry
+        assert_eq!(project.datasets().len(), 2);
+        assert!(project.get_dataset("duplicated").is_err());
+
+        let mut project = Project::new(None, None, false);
+        let aborted = Box::pin(project.register_batch(
+            rows(),
+            RegisterMode::default(),
+            UrlChecks::Offline,
+            None,
+            true,
+            true,
+        ))
+        .await;
+        assert!(aborted.unwrap_err().to_string().contains("Line 2"));
+    }
+
+    #[tokio::test]
+    async fn test_register_checks_only_sequences() {
+        let dir = tempdir().unwrap();
+        let fasta = dir.path().join("genome.fasta");
+        fs::write(&fasta, ">seq\nACGT\n").unwrap();
+        let project = Project::new(None, None, false)
+            .register(RefDataset {
+                label: "genome".to_string(),
+                fasta: Some(DownloadStatus::new(fasta.display().to_string())),
+                ..RefDataset::default()
+            })
+            .await
+            .unwrap();
+
+        // nothing listens on the discard port, so the annotation's URL would fail a check
+        assert!(!UrlChecks::SequencesOnly.checks(FileFormat::Gff));
+        let project = project
+            .register(RefDataset {
+                label: "genome".to_string(),
+                gff: Some(DownloadStatus::new(
+                    "http://127.0.0.1:9/genes.gff".to_string(),
+                )),
+                url_checks: UrlChecks::SequencesOnly,
+                ..RefDataset::default()
+            })
+            .await
+            .unwrap();
+        assert!(project.datasets()[0].gff.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_register_rejects_annotations_without_sequence() {
+        let dir = tempdir().unwrap();
+        let fasta = dir.path().join("genome.fasta");
+        fs::write(&fasta, ">seq\nACGT\n").unwrap();
+        let annotations = || RefDataset {
+            label: "genome".to_string(),
+            gff: Some(DownloadStatus::new(
+                dir.path().join("genome.gff").display().to_string(),
+            )),
+            ..RefDataset::default()
+        };
+
+        let project = Project::new(None, None, false);
+        let annotation_only = project.clone().register(annotations()).await;
+        assert!(
+            matches!(annotation_only, Err(EntryError::AnnotationsButNoSequence(label)) if label == "genome")
+        );
+
+        // variants are only meaningful against the sequence they were called on, too
+        let variants_only = project
+            .clone()
+            .register(RefDataset {
+                label: "genome".to_string(),
+                vcf: Some(DownloadStatus::new(
+                    dir.path().join("genome.vcf").display().to_string(),
+                )),
+                ..RefDataset::default()
+            })
+            .await;
+        assert!(matches!(
+            variants_only,
+            Err(EntryError::AnnotationsButNoSequence(_))
+        ));
+
+        let project = project
+            .register(RefDataset {
+                label: "genome".to_string(),
+                fasta: Some(DownloadStatus::new(fasta.display().to_string())),
+                ..RefDataset::default()
+            })
+            .await
+            .unwrap()
+            .register(annotations())
+            .await
+            .unwrap();
+        assert!(project.datasets()[0].has_sequence());
+        assert!(project.datasets()[0].has_annotation());
+    }
+
+    #[test]
+    fn test_resume_from_files() {
+        let dir = tempdir().unwrap();
+        let copy = dir.path().join("genome.fasta");
+        let stray = dir.path().join("notes.txt");
+        fs::write(&copy, ">seq\nACGT\n").unwrap();
+        fs::write(&stray, "not a reference\n").unwrap();
+
+        let mut project = Project::new(None, None, false);
+        project.project.datasets.push(RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new_downloaded(ValidatedFile {
+                uri: "https://example.com/genome.fasta".to_string(),
+                local_path: PathBuf::from("/elsewhere/genome.fasta"),
+                validated: true,
+                hash: hash_valid_download(&copy).ok(),
+                ..ValidatedFile::default()
+            })),
+            ..RefDataset::default()
+        });
+
+        let project = project
+            .resume_from_files(&[], &[copy.clone(), stray])
+            .unwrap();
+        let Some(DownloadStatus::Downloaded(file)) = &project.datasets()[0].fasta else {
+            panic!("the copy should be recorded as downloaded");
+        };
+        assert_eq!(file.local_path, copy);
+        assert!(file.validated);
+
+        assert!(matches!(
+            project.resume_from_files(&["other"], &[copy]),
+            Err(EntryError::LabelNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_all() {
+        let dir = tempdir().unwrap();
+        let fasta = dir.path().join("genome.fasta");
+        let bed = dir.path().join("regions.bed");
+        fs::write(&fasta, ">seq\nACGT\n").unwrap();
+        fs::write(&bed, "not a bed file\n").unwrap();
+        let downloaded = |uri: &str, local_path: &Path| {
+            Some(DownloadStatus::new_downloaded(ValidatedFile {
+                uri: uri.to_string(),
+                local_path: local_path.to_path_buf(),
+                validated: true,
+                ..ValidatedFile::default()
+            }))
+        };
+
+        let mut project = Project::new(None, None, false);
+        project.project.datasets.push(RefDataset {
+            label: "genome".to_string(),
+            fasta: downloaded("https://example.com/genome.fasta", &fasta),
+            bed: downloaded("https://example.com/regions.bed", &bed),
+            ..RefDataset::default()
+        });
+        project.project.datasets.push(RefDataset {
+            label: "pending".to_string(),
+            fasta: Some(DownloadStatus::new(
+                "https://example.com/pending.fasta".to_string(),
+            )),
+            ..RefDataset::default()
+        });
+
+        let report = project.validate_all(None, false, None).unwrap();
+        assert_eq!(report.file_count(), 2);
+        // capping the threads doesn't change the result
+        let capped = project.validate_all(Some(1), false, None).unwrap();
+        assert_eq!(capped.file_count(), 2);
+        assert_eq!(capped.failures().count(), 1);
+        assert!(!report.is_valid());
+        // files that haven't been downloaded have nothing to validate
+        assert!(!report.datasets.contains_key("pending"));
+        assert!(report.datasets["genome"][&FileFormat::Fasta].is_ok());
+        let failures: Vec<_> = report.failures().collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, "genome");
+        assert_eq!(failures[0].1, FileFormat::Bed);
+
+        // sampling records whether each file was cut short, and a file with fewer records than the
+        // sample was read in full
+        let sampled_records = |project: &Project| match &project.datasets()[0].fasta {
+            Some(DownloadStatus::Downloaded(file)) => file.sampled_records,
+            _ => None,
+        };
+        fs::write(&fasta, ">seq\nACGT\n>seq2\nACGT\n").unwrap();
+        project.validate_all(None, false, Some(1)).unwrap();
+        assert_eq!(sampled_records(&project), Some(1));
+        project.validate_all(None, false, Some(5)).unwrap();
+        assert_eq!(sampled_records(&project), None);
+
+        // a file whose checksum still matches the one recorded when it was validated isn't parsed again,
+        // unless revalidation is forced
+        let hash = HashAlgorithm::Md5.hash_file(&bed).unwrap();
+        if let Some(DownloadStatus::Downloaded(file)) = project.project.datasets[0].bed.as_mut() {
+            file.hash = Some(hash);
+        }
+        let last_validated = |project: &Project, format: FileFormat| {
+            project.datasets()[0]
+                .file_fields()
+                .into_iter()
+                .find_map(|(field_format, status)| match status {
+                    Some(DownloadStatus::Downloaded(file)) if field_format == format => {
+                        file.last_validated
+                    },
+                    _ => None,
+                })
+        };
+        // so far, only the file that passed has been marked as validated
+        let fasta_validated = last_validated(&project, FileFormat::Fasta);
+        assert!(fasta_validated.is_some());
+        assert_eq!(last_validated(&project, FileFormat::Bed), None);
+        assert!(project.validate_all(None, false, None).unwrap().is_valid());
+        // a cache hit still counts as passing validation, so it refreshes the timestamp
+        let refreshed = last_validated(&project, FileFormat::Bed);
+        assert!(refreshed.is_some());
+        assert!(last_validated(&project, FileFormat::Fasta) > fasta_validated);
+        assert!(!project.validate_all(None, true, None).unwrap().is_valid());
+        // ...while a failure leaves it as it was
+        assert_eq!(last_validated(&project, FileFormat::Bed), refreshed);
+    }
+
+    #[tokio::test]
+    async fn test_verify_urls_checks_local_paths() {
+        let dir = tempdir().unwrap();
+        let fasta = dir.path().join("genome.fasta");
+        fs::write(&fasta, ">seq\nACGT\n").unwrap();
+        let mut project = Project::new(None, None, false);
+        project.project.datasets.push(RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new(fasta.to_string_lossy().into_owned())),
+            bed: Some(DownloadStatus::new(
+                dir.path()
+                    .join("missing.bed")
+                    .to_string_lossy()
+                    .into_owned(),
+            )),
+            ..RefDataset::default()
+        });
+        project.project.datasets.push(RefDataset {
+            label: "other".to_string(),
+            fasta: Some(DownloadStatus::new(
+                dir.path()
+                    .join("other.fasta")
+                    .to_string_lossy()
+                    .into_owned(),
+            )),
+            ..RefDataset::default()
+        });
+
+        let checks = project
+            .verify_urls(&["genome"], 2, Duration::from_secs(1))
+            .await;
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].format, FileFormat::Fasta);
+        assert_eq!(checks[0].status, LinkStatus::Ok);
+        assert_eq!(checks[1].format, FileFormat::Bed);
+        assert!(checks[1].status.is_broken());
+    }
+
+    #[test]
+    fn test_lifecycle_counts() {
+        let validated_at: Timestamp = "2024-03-01T12:00:00Z".parse().unwrap();
+        let dataset = RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new_downloaded(ValidatedFile {
+                uri: "https://example.com/genome.fasta".to_string(),
+                local_path: PathBuf::from("genome.fasta"),
+                validated: true,
+                last_validated: Some(validated_at),
+                size_bytes: Some(1024),
+                ..ValidatedFile::default()
+            })),
+            gff: Some(DownloadStatus::new_downloaded(ValidatedFile {
+                uri: "https://example.com/genes.gff".to_string(),
+                local_path: PathBuf::from("genes.gff"),
+                size_bytes: Some(2048),
+                ..ValidatedFile::default()
+            })),
+            bed: Some(DownloadStatus::new(
+                "https://example.com/regions.bed".to_string(),
+            )),
+            ..RefDataset::default()
+        };
+
+        let counts = LifecycleCounts::of(&dataset);
+        assert_eq!(
+            counts,
+            LifecycleCounts {
+                files: 3,
+                downloaded: 2,
+                validated: 1,
+                last_validated: Some(validated_at),
+                size_bytes: Some(3072),
+            }
+        );
+
+        let untouched = LifecycleCounts::of(&RefDataset {
+            label: "plasmid".to_string(),
+            genbank: Some(DownloadStatus::new(
+                "https://example.com/plasmid.gbk".to_string(),
+            )),
+            ..RefDataset::default()
+        });
+        assert_eq!(untouched.last_validated, None);
+        assert_eq!(
+            counts.combine(untouched),
+            LifecycleCounts { files: 4, ..counts }
+        );
+    }
+
+    #[test]
+    fn test_subset() {
+        let mut project = Project::new(Some("Shared references".to_string()), None, false);
+        for label in ["human", "mouse"] {
+            project.project.datasets.push(RefDataset {
+                label: label.to_string(),
+                fasta: Some(DownloadStatus::new(format!(
+                    "https://example.com/{label}.fasta"
+                ))),
+                ..RefDataset::default()
+            });
+        }
+
+        assert_eq!(
+            project.dataset_labels().collect::<Vec<_>>(),
+            ["human", "mouse"]
+        );
+
+        let subset = project.clone().subset(Some("mouse")).unwrap();
+        let reparsed: Project = subset.to_toml().unwrap().parse().unwrap();
+        assert_eq!(reparsed.project.title.as_deref(), Some("Shared references"));
+        assert_eq!(reparsed.datasets().len(), 1);
+        assert_eq!(reparsed.datasets()[0].label, "mouse");
+
+        let listed: serde_json::Value =
+            serde_json::from_str(&subset.datasets_to_json().unwrap()).unwrap();
+        assert_eq!(listed.as_array().map(Vec::len), Some(1));
+        assert_eq!(listed[0]["fasta"]["status"], "not_yet_downloaded");
+
+        // YAML exports carry the same structure as JSON ones
+        let json: serde_json::Value = serde_json::from_str(&subset.to_json().unwrap()).unwrap();
+        let yaml: serde_json::Value = serde_yaml::from_str(&subset.to_yaml().unwrap()).unwrap();
+        assert_eq!(yaml, json);
+        assert_eq!(
+            yaml["project"]["datasets"][0]["fasta"]["status"],
+            "not_yet_downloaded"
+        );
+
+        assert_eq!(project.clone().subset(None).unwrap().datasets().len(), 2);
+        assert!(matches!(
+            project.subset(Some("rat")),
+            Err(EntryError::LabelNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_rename() {
+        let mut project = Project::new(None, None, false);
+        project.project.datasets.push(RefDataset {
+            label: "hg38".to_string(),
+            fasta: Some(DownloadStatus::new_downloaded(ValidatedFile {
+                uri: "https://example.com/hg38.fasta".to_string(),
+                local_path: PathBuf::from("refs/hg38.fasta"),
+                hash: Some("aaaa".to_string()),
+                ..ValidatedFile::default()
+            })),
+            ..RefDataset::default()
+        });
+        project.project.datasets.push(RefDataset {
+            label: "mm39".to_string(),
+            fasta: Some(DownloadStatus::new(
+                "https://example.com/mm39.fasta".to_string(),
+            )),
+            ..RefDataset::default()
+        });
+
+        let renamed = project.clone().rename("hg38", "GRCh38").unwrap();
+        assert_eq!(
+            renamed.dataset_labels().collect::<Vec<_>>(),
+            ["GRCh38", "mm39"]
+        );
+        // the file keeps its download state, so it isn't downloaded again
+        assert_eq!(renamed.datasets()[0].fasta, project.datasets()[0].fasta);
+
+        assert!(matches!(
+            project.clone().rename("hg19", "GRCh37"),
+            Err(EntryError::LabelNotFound(label)) if label == "hg19"
+        ));
+        assert!(matches!(
+            project.clone().rename("hg38", "mm39"),
+            Err(EntryError::RenameTargetExists(_, label)) if label == "mm39"
+        ));
+        assert!(matches!(
+            project.rename("hg38", " "),
+            Err(EntryError::EmptyLabel)
+        ));
+    }
+
+    #[test]
+    fn test_diff() {
+        let dataset = |label: &str, fasta: &str, bed: Option<&str>| RefDataset {
+            label: label.to_string(),
+            fasta: Some(DownloadStatus::new(fasta.to_string())),
+            bed: bed.map(|url| DownloadStatus::new(url.to_string())),
+            ..RefDataset::default()
+        };
+        let mut old = Project::new(None, None, false);
+        old.project.datasets.extend([
+            dataset("mm39", "https://example.com/mm39.fasta", None),
+            dataset(
+                "hg38",
+                "https://example.com/hg38.fasta",
+                Some("https://example.com/hg38.bed"),
+            ),
+            dataset("hg19", "https://example.com/hg19.fasta", None),
+        ]);
+        let mut new = Project::new(None, None, false);
+        new.project.datasets.extend([
+            dataset("dm6", "https://example.com/dm6.fasta", None),
+            dataset("hg38", "https://mirror.example.com/hg38.fasta", None),
+            dataset(
+                "mm39",
+                "https://example.com/mm39.fasta",
+                Some("https://example.com/mm39.bed"),
+            ),
+        ]);
+        // download state doesn't count as a difference
+        new.project.datasets[2].fasta = Some(DownloadStatus::new_downloaded(ValidatedFile {
+            uri: "https://example.com/mm39.fasta".to_string(),
+            local_path: PathBuf::from("refs/mm39.fasta"),
+            ..ValidatedFile::default()
+        }));
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, ["dm6"]);
+        assert_eq!(diff.removed, ["hg19"]);
+        assert_eq!(diff.changed.keys().collect::<Vec<_>>(), ["hg38", "mm39"]);
+        assert_eq!(
+            diff.changed["hg38"],
+            [
+                UrlChange {
+                    format: FileFormat::Fasta,
+                    old: Some("https://example.com/hg38.fasta".to_string()),
+                    new: Some("https://mirror.example.com/hg38.fasta".to_string()),
+                },
+                UrlChange {
+                    format: FileFormat::Bed,
+                    old: Some("https://example.com/hg38.bed".to_string()),
+                    new: None,
+                },
+            ]
+        );
+        assert_eq!(
+            diff.changed["mm39"],
+            [UrlChange {
+                format: FileFormat::Bed,
+                old: None,
+                new: Some("https://example.com/mm39.bed".to_string()),
+            }]
+        );
+        assert!(
+            diff.to_string()
+                .contains("    BED: (none) → https://example.com/mm39.bed")
+        );
+
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn test_import() {
+        let downloaded_fasta = Some(DownloadStatus::new_downloaded(ValidatedFile {
+            uri: "https://example.com/hg38.fasta".to_string(),
+            local_path: PathBuf::from("refs/hg38.fasta"),
+            hash: Some("aaaa".to_string()),
+            ..ValidatedFile::default()
+        }));
+        let mut project = Project::new(None, None, false);
+        project.project.datasets.push(RefDataset {
+            label: "hg38".to_string(),
+            fasta: downloaded_fasta.clone(),
+            gff: Some(DownloadStatus::new(
+                "https://example.com/hg38.gff".to_string(),
+            )),
+            ..RefDataset::default()
+        });
+
+        let mut source = Project::new(None, None, false);
+        source.project.datasets.push(RefDataset {
+            label: "hg38".to_string(),
+            fasta: Some(DownloadStatus::new(
+                "https://example.com/hg38.fasta".to_string(),
+            )),
+            gff: Some(DownloadStatus::new(
+                "https://mirror.example.com/hg38.gff".to_string(),
+            )),
+            bed: Some(DownloadStatus::new(
+                "https://example.com/hg38.bed".to_string(),
+            )),
+            ..RefDataset::default()
+        });
+        source.project.datasets.push(RefDataset {
+            label: "mm39".to_string(),
+            fasta: Some(DownloadStatus::new(
+                "https://example.com/mm39.fasta".to_string(),
+            )),
+            ..RefDataset::default()
+        });
+
+        let (skipped, summary) = project
+            .clone()
+            .import(source.clone(), ImportConflict::Skip)
+            .unwrap();
+        assert_eq!(summary.added, ["mm39"]);
+        assert_eq!(summary.skipped, ["hg38"]);
+        assert_eq!(skipped.datasets()[0].gff, project.datasets()[0].gff);
+        assert_eq!(
+            skipped.dataset_labels().collect::<Vec<_>>(),
+            ["hg38", "mm39"]
+        );
+
+        let (overwritten, summary) = project
+            .clone()
+            .import(source.clone(), ImportConflict::Overwrite)
+            .unwrap();
+        assert_eq!(summary.overwritten, ["hg38"]);
+        assert_eq!(overwritten.datasets()[0].fasta, source.datasets()[0].fasta);
+
+        let (merged, summary) = project
+            .clone()
+            .import(source.clone(), ImportConflict::MergeFields)
+            .unwrap();
+        assert_eq!(summary.merged, ["hg38"]);
+        let hg38 = &merged.datasets()[0];
+        // the FASTA's URL didn't change, so it keeps its download state
+        assert_eq!(hg38.fasta, downloaded_fasta);
+        // while the source's GFF replaces the registered one, and its BED is added
+        assert_eq!(
+            hg38.gff.as_ref().map(DownloadStatus::url),
+            Some("https://mirror.example.com/hg38.gff")
+        );
+        assert_eq!(
+            hg38.bed.as_ref().map(DownloadStatus::url),
+            Some("https://example.com/hg38.bed")
+        );
+
+        // a source that's structurally unsound isn't imported at all
+        source.project.datasets.push(RefDataset {
+            label: "mm39".to_string(),
+            ..source.datasets()[1].clone()
+        });
+        assert!(matches!(
+            project.import(source, ImportConflict::Skip),
+            Err(EntryError::DuplicateLabel(label)) if label == "mm39"
+        ));
+    }
+
+    #[test]
+    fn test_remove_final_dataset() {
+        let dir = tempdir().unwrap();
+        let options = RegistryOptions::try_new(
+            Some("Genomes".to_string()),
+            None,
+            &Some(dir.path().join("refs.toml")),
+            false,
+        )
+        .unwrap();
+        let mut project = Project::new(Some("Genomes".to_string()), None, false);
+        project.project.datasets.push(RefDataset {
+            label: "hg38".to_string(),
+            fasta: Some(DownloadStatus::new(
+                "https://example.com/hg38.fasta".to_string(),
+            )),
+            ..RefDataset::default()
+        });
+
+        let mut emptied = project.remove("hg38").unwrap();
+        assert!(emptied.datasets().is_empty());
+        options.write_registry(&mut emptied).unwrap();
+
+        // the empty registry reads back with its metadata intact
+        let read_back = options.read_registry().unwrap();
+        assert!(read_back.datasets().is_empty());
+        assert_eq!(read_back.project.title.as_deref(), Some("Genomes"));
+        assert!(matches!(
+            read_back.remove("hg38"),
+            Err(EntryError::LabelNotFound(label)) if label == "hg38"
+        ));
+    }
+
+    #[test]
+    fn test_structural_problems() {
+        let mut project = Project::new(None, None, false);
+        project.project.datasets.push(RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new_downloaded(ValidatedFile {
+                uri: "https://example.com/genome.fasta".to_string(),
+                local_path: PathBuf::from("/nonexistent/genome.fasta"),
+                ..ValidatedFile::default()
+            })),
+            gff: Some(DownloadStatus::new(
+                "ftp://ftp.example.com/genome.gff".to_string(),
+            )),
+            ..RefDataset::default()
+        });
+        let problems = project.structural_problems();
+        assert_eq!(problems.len(), 1, "{problems:?}");
+
+        project.project.datasets.push(RefDataset {
+            label: "genome".to_string(),
+            gtf: Some(DownloadStatus::new("example.com/genes.gtf".to_string())),
+            ..RefDataset::default()
+        });
+        project.project.datasets.push(RefDataset {
+            label: "empty".to_string(),
+            ..RefDataset::default()
+        });
+
+        let problems = project.structural_problems();
+        assert_eq!(problems.len(), 5, "{problems:?}");
+        assert!(matches!(problems[0], EntryError::MissingLocalFile(..)));
+        assert!(matches!(&problems[1], EntryError::DuplicateLabel(label) if label == "genome"));
+        assert!(matches!(
+            problems[2],
+            EntryError::AnnotationsButNoSequence(_)
+        ));
+        assert!(
+            matches!(&problems[3], EntryError::MalformedUrl(_, url) if url == "example.com/genes.gtf")
+        );
+        assert!(matches!(problems[4], EntryError::LabelButNoFiles));
+    }
+
+    #[test]
+    fn test_dataset_dest_overrides_target_dir() {
+        let dir = tempdir().unwrap();
+        let scratch = dir.path().join("scratch");
+        fs::create_dir(&scratch).unwrap();
+        fs::write(scratch.join("pangenome.gfa"), "H\tVN:Z:1.0\n").unwrap();
+        fs::write(dir.path().join("pangenome.gfa"), "H\tVN:Z:1.0\n").unwrap();
+
+        let mut project = Project::new(None, None, false);
+        project.project.datasets.push(
+            RefDataset {
+                label: "pangenome".to_string(),
+                gfa: Some(DownloadStatus::new(
+                    "https://example.com/pangenome.gfa".to_string(),
+                )),
+                ..RefDataset::default()
+            }
+            .with_dest(Some(scratch.clone())),
+        );
+
+        let existing = project.unrecorded_existing_files(
+            &[],
+            dir.path(),
+            false,
+            false,
+            &FormatSelection::default(),
+        );
+        assert_eq!(existing, vec![scratch.join("pangenome.gfa")]);
+    }
 }
@@ -0,0 +1,217 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use color_eyre::eyre::{Result, eyre};
+use log::{debug, info, warn};
+use reqwest::Client;
+use url::Url;
+
+use crate::{
+    data::{DownloadStatus, RefDataset},
+    headers::with_headers,
+    validate::{HashAlgorithm, UnvalidatedFile},
+};
+
+/// A provider's published index of the checksums of the files in a release, like the
+/// `MD5SUMS`, `md5checksums.txt`, or `SHA256SUMS` files that accompany many reference releases.
+///
+/// Comparing the published checksums against the ones recorded in the registry tells which
+/// files changed upstream, so a periodic sync only has to download those, without checking
+/// every file's URL along the way.
+pub(crate) struct RemoteIndex {
+    algorithm: HashAlgorithm,
+    checksums: HashMap<String, String>,
+}
+
+impl RemoteIndex {
+    /// Fetches and parses the index at `url`, whose checksums were computed with `algorithm`.
+    ///
+    /// Both the output of `md5sum` and `sha256sum`, i.e. `<checksum>  <path>` lines, and the
+    /// BSD-style output of `md5sum --tag` and `shasum --tag`, i.e. `MD5 (<path>) = <checksum>`
+    /// lines, are understood. Paths are resolved relative to the index's URL, and lines in
+    /// neither style are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `url` isn't a valid URL, the index can't be fetched, or it lists no
+    /// checksums.
+    pub(crate) async fn fetch(
+        client: &Client,
+        url: &str,
+        algorithm: HashAlgorithm,
+    ) -> Result<Self> {
+        let base = Url::parse(url)?;
+        let body = with_headers(client.get(url), url)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| eyre!("Unable to fetch the remote index at {url}: {e}"))?
+            .text()
+            .await
+            .map_err(|e| eyre!("Unable to read the remote index at {url}: {e}"))?;
+
+        let index = Self::parse(&body, &base, algorithm);
+        if index.checksums.is_empty() {
+            return Err(eyre!(
+                "No {algorithm} checksums were found in the remote index at {url}"
+            ));
+        }
+        debug!(
+            "Found {} published checksums in the remote index at {url}",
+            index.checksums.len()
+        );
+
+        Ok(index)
+    }
+
+    fn parse(body: &str, base: &Url, algorithm: HashAlgorithm) -> Self {
+        let checksums = body
+            .lines()
+            .filter_map(parse_index_line)
+            .filter_map(|(checksum, path)| {
+                let path = path.strip_prefix("./").unwrap_or(path);
+                base.join(path)
+                    .map(|url| (url.to_string(), checksum.to_ascii_lowercase()))
+                    .inspect_err(|e| warn!("Skipping {path} in the remote index: {e}"))
+                    .ok()
+            })
+            .collect();
+
+        Self {
+            algorithm,
+            checksums,
+        }
+    }
+
+    /// Returns the downloaded files of a dataset that have changed upstream, i.e. whose
+    /// published checksums differ from the ones recorded when they were downloaded, leaving out
+    /// any already in `pending`.
+    ///
+    /// Files that aren't listed in the index, or weren't downloaded, are left to the usual
+    /// checks, and files whose published checksums match are skipped without being requested.
+    pub(crate) fn changed_files(
+        &self,
+        dataset: &RefDataset,
+        pending: &[UnvalidatedFile],
+    ) -> Vec<UnvalidatedFile> {
+        let mut changed: Vec<UnvalidatedFile> = Vec::new();
+        for (format, status) in dataset.file_fields() {
+            let Some(DownloadStatus::Downloaded(file)) = status else {
+                continue;
+            };
+            // registered URLs are normalized the same way as the paths in the index
+            let Some(published) = Url::parse(&file.uri)
+                .ok()
+                .and_then(|url| self.checksums.get(url.as_str()))
+            else {
+                continue;
+            };
+            if file.checksum(self.algorithm) == Some(published.as_str()) {
+                debug!(
+                    "{} is unchanged according to the remote index, so it won't be downloaded again",
+                    file.uri
+                );
+                continue;
+            }
+            if pending
+                .iter()
+                .chain(&changed)
+                .any(|requested| requested.url() == file.uri)
+            {
+                continue;
+            }
+            info!(
+                "{} has changed according to the remote index, so it will be downloaded again",
+                file.uri
+            );
+            changed.push(UnvalidatedFile::new(
+                format,
+                file.uri.clone(),
+                PathBuf::new(),
+            ));
+        }
+
+        changed
+    }
+}
+
+/// Splits a line of a checksum index into its checksum and path, understanding both the
+/// `<checksum>  <path>` lines written by `md5sum` and the `ALGO (<path>) = <checksum>` lines
+/// written with `--tag`.
+fn parse_index_line(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    // BSD-style lines, e.g. `SHA256 (genome.fa.gz) = 0123...`
+    if let Some((name, checksum)) = line.rsplit_once(") = ") {
+        let (_, path) = name.split_once(" (")?;
+        return Some((checksum.trim(), path));
+    }
+
+    // GNU-style lines, where the path may be marked with `*` for files hashed in binary mode
+    let (checksum, path) = line.split_once(char::is_whitespace)?;
+    let path = path.trim_start();
+    let path = path.strip_prefix('*').unwrap_or(path);
+    if checksum.is_empty() || path.is_empty() || !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    Some((checksum, path))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+    use crate::{FileFormat, ValidatedFile};
+
+    #[test]
+    fn test_changed_files() {
+        let base = Url::parse("https://example.com/release-110/MD5SUMS").unwrap();
+        let index = RemoteIndex::parse(
+            "# checksums for release 110\n\
+             aaaa  ./genome.fa.gz\n\
+             BBBB *genes.gff3.gz\n\
+             MD5 (annotation/peaks.bed) = cccc\n",
+            &base,
+            HashAlgorithm::Md5,
+        );
+        assert_eq!(index.checksums.len(), 3);
+        assert_eq!(
+            index
+                .checksums
+                .get("https://example.com/release-110/annotation/peaks.bed")
+                .map(String::as_str),
+            Some("cccc")
+        );
+
+        let downloaded = |url: &str, hash: &str| {
+            Some(DownloadStatus::new_downloaded(ValidatedFile {
+                uri: url.to_string(),
+                hash: Some(hash.to_string()),
+                ..ValidatedFile::default()
+            }))
+        };
+        let dataset = RefDataset {
+            label: "release-110".to_string(),
+            fasta: downloaded("https://example.com/release-110/genome.fa.gz", "aaaa"),
+            gff: downloaded("https://example.com/release-110/genes.gff3.gz", "old"),
+            bed: downloaded(
+                "https://example.com/release-110/annotation/peaks.bed",
+                "old",
+            ),
+            ..RefDataset::default()
+        };
+
+        let changed = index.changed_files(&dataset, &[]);
+        let formats: Vec<FileFormat> = changed.iter().map(UnvalidatedFile::format).collect();
+        assert_eq!(formats, [FileFormat::Gff, FileFormat::Bed]);
+
+        // files that are already going to be downloaded aren't requested twice
+        let changed = index.changed_files(&dataset, &changed[..1]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].format(), FileFormat::Bed);
+    }
+}
@@ -0,0 +1,137 @@
+use std::{collections::HashSet, fs};
+
+use serde::Serialize;
+
+use crate::data::{DownloadStatus, FileFormat, RefDataset};
+
+/// What happened to a registered file during a download run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum FileOutcome {
+    /// The file was downloaded and recorded in the registry.
+    Downloaded,
+    /// The file wasn't downloaded, e.g. because a previous download still passes its checksum.
+    Skipped,
+    /// The file was requested but didn't end up downloaded.
+    Failed,
+}
+
+/// A machine-readable summary of a download run, printed to stdout as JSON with
+/// `--summary-format json` so that pipelines don't have to parse the registry to find out what
+/// just happened.
+#[derive(Debug, Serialize)]
+pub(crate) struct DownloadReport {
+    files: Vec<ReportedFile>,
+}
+
+/// One registered file, as reported in a [`DownloadReport`].
+#[derive(Debug, Serialize)]
+struct ReportedFile {
+    label: String,
+    format: FileFormat,
+    url: String,
+    local_path: Option<String>,
+    size: Option<u64>,
+    hash: Option<String>,
+    status: FileOutcome,
+}
+
+impl DownloadReport {
+    /// Describes every file of the provided datasets after a download run. `requested` holds the
+    /// label and URL of each file the run tried to download; the others are reported as skipped.
+    pub(crate) fn new<'a>(
+        datasets: impl IntoIterator<Item = &'a RefDataset>,
+        requested: &HashSet<(String, String)>,
+    ) -> Self {
+        let mut files = Vec::new();
+        for dataset in datasets {
+            for (format, status) in dataset.file_fields() {
+                let Some(status) = status else {
+                    continue;
+                };
+                let url = status.url_owned();
+                let outcome = match status {
+                    _ if !requested.contains(&(dataset.label.clone(), url.clone())) => {
+                        FileOutcome::Skipped
+                    },
+                    DownloadStatus::Downloaded(_) if status.is_downloaded() => {
+                        FileOutcome::Downloaded
+                    },
+                    _ => FileOutcome::Failed,
+                };
+                let (local_path, size, hash) = match status {
+                    DownloadStatus::Downloaded(file) if status.is_downloaded() => (
+                        Some(file.local_path.display().to_string()),
+                        fs::metadata(&file.local_path).ok().map(|meta| meta.len()),
+                        file.hash.clone(),
+                    ),
+                    _ => (None, None, None),
+                };
+                files.push(ReportedFile {
+                    label: dataset.label.clone(),
+                    format,
+                    url,
+                    local_path,
+                    size,
+                    hash,
+                    status: outcome,
+                });
+            }
+        }
+
+        Self { files }
+    }
+
+    /// Prints the report to stdout as pretty-printed JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report can't be serialized.
+    pub(crate) fn print_json(&self) -> serde_json::Result<()> {
+        println!("{}", serde_json::to_string_pretty(self)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_report_outcomes() {
+        let dataset = RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::Failed {
+                url: "https://example.com/genome.fasta".to_string(),
+                last_error: "404 Not Found".to_string(),
+                attempts: 1,
+                last_attempt: jiff::Timestamp::now(),
+            }),
+            gff: Some(DownloadStatus::new(
+                "https://example.com/genes.gff".to_string(),
+            )),
+            ..RefDataset::default()
+        };
+        let requested = HashSet::from([(
+            "genome".to_string(),
+            "https://example.com/genome.fasta".to_string(),
+        )]);
+
+        let report = DownloadReport::new([&dataset], &requested);
+        let outcomes: Vec<_> = report
+            .files
+            .iter()
+            .map(|file| (file.format, file.status))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                (FileFormat::Fasta, FileOutcome::Failed),
+                (FileFormat::Gff, FileOutcome::Skipped)
+            ]
+        );
+        assert!(report.files.iter().all(|file| file.local_path.is_none()));
+    }
+}
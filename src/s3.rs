@@ -0,0 +1,227 @@
+use std::error::Error;
+
+use aws_config::{BehaviorVersion, meta::region::RegionProviderChain};
+use aws_sdk_s3::{
+    Client,
+    config::{Builder, http::HttpResponse},
+    error::{DisplayErrorContext, ProvideErrorMetadata, SdkError},
+    operation::head_object::HeadObjectOutput,
+    primitives::{ByteStream, DateTime, DateTimeFormat},
+};
+use color_eyre::{Result, eyre::eyre};
+use log::{debug, warn};
+use tokio::sync::OnceCell;
+use url::Url;
+
+use crate::{
+    data::ByteRange,
+    downloads::{CacheValidators, percent_decode},
+};
+
+/// The region buckets are looked for in when neither the environment nor an AWS profile
+/// configures one.
+const FALLBACK_REGION: &str = "us-east-1";
+
+/// The S3 client shared by every request, which is only set up once so that credentials are
+/// only looked up once per run.
+static CLIENT: OnceCell<Client> = OnceCell::const_new();
+
+/// Whether `url` points to an object in an S3 bucket, which is requested through the S3 API
+/// rather than over HTTP.
+pub(crate) fn is_s3(url: &Url) -> bool {
+    url.scheme() == "s3"
+}
+
+/// An S3 object being downloaded, whose bytes arrive in chunks as the bucket sends them.
+pub(crate) struct S3Transfer {
+    /// How many bytes the transfer will send, if the bucket reported it
+    pub(crate) size: Option<u64>,
+    /// The object's bytes, in the order they arrive
+    pub(crate) body: ByteStream,
+    /// The object's `ETag` and last modification time, which can later tell whether it changed
+    pub(crate) validators: CacheValidators,
+}
+
+/// Checks that the object `url` points to exists and can be read with the available
+/// credentials, returning its size.
+///
+/// # Errors
+///
+/// Returns an error if the URL doesn't name a bucket and key, or if the bucket can't be reached,
+/// doesn't have the object, or refuses to share it.
+pub(crate) async fn check(url: &Url) -> Result<u64> {
+    let head = head(url).await?;
+
+    Ok(head
+        .content_length()
+        .and_then(|length| u64::try_from(length).ok())
+        .unwrap_or_default())
+}
+
+/// Fetches the `ETag` and last modification time of the object `url` points to, without
+/// downloading it.
+///
+/// # Errors
+///
+/// Returns an error if the URL doesn't name a bucket and key, or if the object can't be found.
+pub(crate) async fn fetch_validators(url: &Url) -> Result<CacheValidators> {
+    let head = head(url).await?;
+
+    Ok(validators(head.e_tag(), head.last_modified()))
+}
+
+/// Starts downloading the object `url` points to, or only the bytes of it in `range` when one
+/// is provided. With an `if_match` `ETag`, the bucket refuses to send the object if it has
+/// changed since it had that `ETag`.
+///
+/// # Errors
+///
+/// Returns an error if the URL doesn't name a bucket and key, or if the bucket can't be reached,
+/// doesn't have the object, or refuses to send it.
+pub(crate) async fn open_transfer(
+    url: &Url,
+    range: Option<ByteRange>,
+    if_match: Option<&str>,
+) -> Result<S3Transfer> {
+    let (bucket, key) = bucket_and_key(url)?;
+    let request = client()
+        .await
+        .get_object()
+        .bucket(bucket)
+        .key(key)
+        .set_range(range.map(|range| format!("bytes={range}")))
+        .set_if_match(if_match.map(str::to_string));
+    let object = Box::pin(request.send())
+        .await
+        .map_err(|e| eyre!("Unable to download the S3 object {url}: {}", describe(&e)))?;
+
+    Ok(S3Transfer {
+        size: object
+            .content_length()
+            .and_then(|length| u64::try_from(length).ok()),
+        validators: validators(object.e_tag(), object.last_modified()),
+        body: object.body,
+    })
+}
+
+/// Returns the client shared by every S3 request, setting it up on first use.
+///
+/// Credentials are found the way the AWS CLI finds them, i.e. in the `AWS_ACCESS_KEY_ID` and
+/// `AWS_SECRET_ACCESS_KEY` environment variables, `~/.aws/credentials` and `~/.aws/config`, or
+/// the instance profile of the machine `refman` runs on. When an endpoint is configured, e.g.
+/// with `AWS_ENDPOINT_URL` for an S3-compatible store like `MinIO`, buckets are addressed by path
+/// rather than by subdomain, since such stores rarely serve a subdomain per bucket.
+async fn client() -> &'static Client {
+    CLIENT
+        .get_or_init(|| async {
+            let region = RegionProviderChain::default_provider().or_else(FALLBACK_REGION);
+            let config = Box::pin(
+                aws_config::defaults(BehaviorVersion::latest())
+                    .region(region)
+                    .load(),
+            )
+            .await;
+            let custom_endpoint = config.endpoint_url().is_some();
+            debug!(
+                "Set up an S3 client for the {} region",
+                config
+                    .region()
+                    .map_or(FALLBACK_REGION, |region| region.as_ref())
+            );
+            Client::from_conf(
+                Builder::from(&config)
+                    .force_path_style(custom_endpoint)
+                    .build(),
+            )
+        })
+        .await
+}
+
+/// Asks the bucket `url` points to for the metadata of the object at the URL's key.
+async fn head(url: &Url) -> Result<HeadObjectOutput> {
+    let (bucket, key) = bucket_and_key(url)?;
+    let request = client().await.head_object().bucket(bucket).key(key);
+    // the SDK's futures are large, so they're kept on the heap rather than in every future awaiting them
+    Box::pin(request.send())
+        .await
+        .map_err(|e| eyre!("Unable to find the S3 object {url}: {}", describe(&e)))
+}
+
+/// Splits an `s3://bucket/key` URL into the bucket and the decoded key of the object it points
+/// to.
+fn bucket_and_key(url: &Url) -> Result<(&str, String)> {
+    let bucket = url
+        .host_str()
+        .ok_or_else(|| eyre!("The S3 URL {url} doesn't name a bucket"))?;
+    let key = percent_decode(url.path().trim_start_matches('/'))
+        .ok_or_else(|| eyre!("The key of the S3 URL {url} isn't valid UTF-8"))?;
+    if key.is_empty() {
+        return Err(eyre!(
+            "The S3 URL {url} doesn't name an object in its bucket"
+        ));
+    }
+
+    Ok((bucket, key))
+}
+
+/// Describes why an S3 request failed, naming the error code and HTTP status the bucket responded
+/// with, e.g. `NoSuchKey (HTTP 404)`, or the whole chain of causes when the bucket couldn't be
+/// reached at all.
+fn describe<E>(error: &SdkError<E, HttpResponse>) -> String
+where
+    E: ProvideErrorMetadata + Error + 'static,
+{
+    let Some(code) = error
+        .as_service_error()
+        .and_then(ProvideErrorMetadata::code)
+    else {
+        return DisplayErrorContext(error).to_string();
+    };
+    let message = error
+        .as_service_error()
+        .and_then(ProvideErrorMetadata::message)
+        .map(|message| format!(": {message}"))
+        .unwrap_or_default();
+    match error.raw_response() {
+        Some(response) => format!("{code} (HTTP {}){message}", response.status().as_u16()),
+        None => format!("{code}{message}"),
+    }
+}
+
+/// Records an object's `ETag` and last modification time in the form HTTP servers send them.
+fn validators(etag: Option<&str>, last_modified: Option<&DateTime>) -> CacheValidators {
+    CacheValidators {
+        etag: etag.map(str::to_string),
+        last_modified: last_modified.and_then(|modified| {
+            modified
+                .fmt(DateTimeFormat::HttpDate)
+                .inspect_err(|e| warn!("Unable to record when an S3 object was modified: {e}"))
+                .ok()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_bucket_and_key() {
+        let url = Url::parse("s3://refs-mirror/genomes/GRCh38%20p14/genome.fa.gz").unwrap();
+        assert!(is_s3(&url));
+        assert_eq!(
+            bucket_and_key(&url).unwrap(),
+            ("refs-mirror", "genomes/GRCh38 p14/genome.fa.gz".to_string())
+        );
+
+        for url in ["s3://refs-mirror", "s3://refs-mirror/"] {
+            let url = Url::parse(url).unwrap();
+            assert!(bucket_and_key(&url).is_err(), "{url}");
+        }
+        assert!(!is_s3(
+            &Url::parse("https://refs-mirror.s3.amazonaws.com/genome.fa").unwrap()
+        ));
+    }
+}
@@ -1,23 +1,41 @@
-use flate2::read::GzDecoder;
+use flate2::read::{GzDecoder, MultiGzDecoder};
 use gb_io::reader::SeqReader as GbkReader;
 use gfa::parser::GFAParserBuilder;
 use jiff::Timestamp;
+use log::{debug, warn};
 use md5::{Context, Digest};
-use noodles::{bed, fasta, gff, gtf};
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use minisign_verify::{Error as MinisignError, PublicKey, Signature};
+use noodles::{
+    bed,
+    bgzf::{self, gzi},
+    fasta::{self, fai},
+    gff, gtf,
+    vcf::io::Reader as VcfReader,
+};
+use rayon::{
+    ThreadPoolBuilder,
+    iter::{IntoParallelIterator, ParallelIterator},
+};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
 use std::{
+    collections::BTreeMap,
     fmt::{self, Display, Formatter},
-    fs::File,
-    io::{BufReader, Read},
-    path::{Path, PathBuf},
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read},
+    path::{Component, Path, PathBuf},
     result::Result,
     string::ToString,
 };
+use tar::Archive;
 
-use crate::{data::DownloadStatus, RefDataset, ValidationError};
+use crate::{
+    RefDataset, ValidationError,
+    data::{DownloadStatus, FileFormat},
+    project::humanize_bytes,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum UnvalidatedFile {
     Fasta { uri: String, local_path: PathBuf },
     Genbank { uri: String, local_path: PathBuf },
@@ -25,10 +43,39 @@ pub enum UnvalidatedFile {
     Gff { uri: String, local_path: PathBuf },
     Gtf { uri: String, local_path: PathBuf },
     Bed { uri: String, local_path: PathBuf },
+    Vcf { uri: String, local_path: PathBuf },
     Tar { uri: String, local_path: PathBuf },
 }
 
 impl UnvalidatedFile {
+    /// Creates an `UnvalidatedFile` of the variant matching `format`.
+    pub(crate) fn new(format: FileFormat, uri: String, local_path: PathBuf) -> Self {
+        match format {
+            FileFormat::Fasta => UnvalidatedFile::Fasta { uri, local_path },
+            FileFormat::Genbank => UnvalidatedFile::Genbank { uri, local_path },
+            FileFormat::Gfa => UnvalidatedFile::Gfa { uri, local_path },
+            FileFormat::Gff => UnvalidatedFile::Gff { uri, local_path },
+            FileFormat::Gtf => UnvalidatedFile::Gtf { uri, local_path },
+            FileFormat::Bed => UnvalidatedFile::Bed { uri, local_path },
+            FileFormat::Vcf => UnvalidatedFile::Vcf { uri, local_path },
+            FileFormat::Tar => UnvalidatedFile::Tar { uri, local_path },
+        }
+    }
+
+    /// Returns the format this file was registered as.
+    pub(crate) fn format(&self) -> FileFormat {
+        match self {
+            UnvalidatedFile::Fasta { .. } => FileFormat::Fasta,
+            UnvalidatedFile::Genbank { .. } => FileFormat::Genbank,
+            UnvalidatedFile::Gfa { .. } => FileFormat::Gfa,
+            UnvalidatedFile::Gff { .. } => FileFormat::Gff,
+            UnvalidatedFile::Gtf { .. } => FileFormat::Gtf,
+            UnvalidatedFile::Bed { .. } => FileFormat::Bed,
+            UnvalidatedFile::Vcf { .. } => FileFormat::Vcf,
+            UnvalidatedFile::Tar { .. } => FileFormat::Tar,
+        }
+    }
+
     pub(crate) fn url(&self) -> &str {
         match self {
             UnvalidatedFile::Fasta { uri, .. }
@@ -37,6 +84,7 @@ impl UnvalidatedFile {
             | UnvalidatedFile::Gff { uri, .. }
             | UnvalidatedFile::Gtf { uri, .. }
             | UnvalidatedFile::Bed { uri, .. }
+            | UnvalidatedFile::Vcf { uri, .. }
             | UnvalidatedFile::Tar { uri, .. } => uri,
         }
     }
@@ -49,6 +97,7 @@ impl UnvalidatedFile {
             | UnvalidatedFile::Gff { local_path, .. }
             | UnvalidatedFile::Gtf { local_path, .. }
             | UnvalidatedFile::Bed { local_path, .. }
+            | UnvalidatedFile::Vcf { local_path, .. }
             | UnvalidatedFile::Tar { local_path, .. } => *local_path = path,
         }
     }
@@ -80,6 +129,10 @@ impl UnvalidatedFile {
                 uri,
                 local_path: path,
             },
+            UnvalidatedFile::Vcf { uri, .. } => UnvalidatedFile::Vcf {
+                uri,
+                local_path: path,
+            },
             UnvalidatedFile::Tar { uri, .. } => UnvalidatedFile::Tar {
                 uri,
                 local_path: path,
@@ -96,6 +149,7 @@ impl UnvalidatedFile {
             | UnvalidatedFile::Gff { local_path, .. }
             | UnvalidatedFile::Gtf { local_path, .. }
             | UnvalidatedFile::Bed { local_path, .. }
+            | UnvalidatedFile::Vcf { local_path, .. }
             | UnvalidatedFile::Tar { local_path, .. } => local_path,
         }
     }
@@ -104,23 +158,123 @@ impl UnvalidatedFile {
 #[derive(Debug, Serialize, Deserialize, Default, PartialEq, Eq, Clone)]
 pub struct ValidatedFile {
     pub uri: String,
+    /// Where the file was downloaded to. Registries written before the path was recorded leave it
+    /// empty, which marks the file as needing to be downloaded again.
+    #[serde(default)]
     pub local_path: PathBuf,
     pub validated: bool,
     pub hash: Option<String>,
     pub last_validated: Option<Timestamp>,
+    /// The number of leading records that were validated, when only a sample of the file was
+    /// checked rather than the whole thing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampled_records: Option<usize>,
+    /// The SHA-256 checksum of the file, recorded alongside its MD5 `hash`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Which of the recorded checksums is compared to decide whether the local copy is still
+    /// intact. Registries written before this was recorded use MD5.
+    #[serde(default, skip_serializing_if = "HashAlgorithm::is_md5")]
+    pub algorithm: HashAlgorithm,
+    /// The `ETag` the server sent with the file, which lets `refman update` ask the server whether
+    /// the file has changed without downloading it again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// The `Last-Modified` date the server sent with the file, used like `etag` for servers that
+    /// don't send one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    /// How many bytes the file takes up on disk, as of when it was last validated or recorded.
+    /// Registries written before sizes were recorded leave it out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+}
+
+/// The digest algorithms `refman` can checksum downloaded files with.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    /// MD5, which is what `refman` has always used, and what most providers still publish
+    #[default]
+    Md5,
+    /// SHA-256, for providers that publish SHA-256 manifests, or when MD5 is too weak
+    Sha256,
+}
+
+impl HashAlgorithm {
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub(crate) fn is_md5(&self) -> bool {
+        *self == HashAlgorithm::Md5
+    }
+
+    /// The number of hexadecimal digits in a checksum computed with this algorithm.
+    #[must_use]
+    pub fn hex_digits(self) -> usize {
+        match self {
+            HashAlgorithm::Md5 => 32,
+            HashAlgorithm::Sha256 => 64,
+        }
+    }
+
+    /// Computes the checksum of a file on disk with this algorithm, returning it as a lowercase
+    /// hexadecimal string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ValidationError::InaccessibleFile` if the file cannot be opened or read.
+    pub fn hash_file(self, file: impl AsRef<Path>) -> Result<String, ValidationError> {
+        match self {
+            HashAlgorithm::Md5 => hash_valid_download(file),
+            HashAlgorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                read_in_chunks(file.as_ref(), |chunk| hasher.update(chunk))?;
+                Ok(format!("{:x}", hasher.finalize()))
+            },
+        }
+    }
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            HashAlgorithm::Md5 => write!(f, "MD5"),
+            HashAlgorithm::Sha256 => write!(f, "SHA-256"),
+        }
+    }
+}
+
+impl ValidatedFile {
+    /// Returns the checksum recorded with the file's [`HashAlgorithm`], if there is one.
+    #[must_use]
+    pub fn recorded_checksum(&self) -> Option<&str> {
+        self.checksum(self.algorithm)
+    }
+
+    /// Returns the checksum recorded for the file with the provided algorithm, if there is one.
+    #[must_use]
+    pub fn checksum(&self, algorithm: HashAlgorithm) -> Option<&str> {
+        match algorithm {
+            HashAlgorithm::Md5 => self.hash.as_deref(),
+            HashAlgorithm::Sha256 => self.sha256.as_deref(),
+        }
+    }
 }
 
 impl Display for ValidatedFile {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "ValidatedFile {{ uri: {}, validated: {}, hash: {}, last_validated: {} }}",
+            "ValidatedFile {{ uri: {}, validated: {}, hash: {}, last_validated: {}, size: {} }}",
             self.uri,
             self.validated,
             self.hash.as_deref().unwrap_or("None"),
             self.last_validated
                 .as_ref()
-                .map_or_else(|| "None".to_string(), ToString::to_string)
+                .map_or_else(|| "None".to_string(), ToString::to_string),
+            self.size_bytes
+                .map_or_else(|| "None".to_string(), humanize_bytes)
         )
     }
 }
@@ -159,7 +313,7 @@ impl UnvalidatedFile {
     ///
     /// # Examples
     ///
-    /// ```no_run
+    /// ```ignore
     /// let unvalidated = UnvalidatedFile::Fasta {
     ///     uri: "http://example.com/genome.fa".to_string(),
     ///     local_path: "/tmp/genome.fa".into()
@@ -171,35 +325,141 @@ impl UnvalidatedFile {
     /// };
     /// ```
     pub fn try_validate(&self) -> Result<ValidatedFile, ValidationError> {
-        let (uri, local_path) = match self {
-            UnvalidatedFile::Fasta { uri, local_path } => {
-                try_parse_fasta(local_path)?;
-                (uri, local_path)
+        self.try_validate_sample(None)
+    }
+
+    /// Validates the current `UnvalidatedFile` like [`UnvalidatedFile::try_validate`], but when
+    /// `sample` is provided, only parses that many records from the start of the file.
+    ///
+    /// Fully parsing a multi-gigabyte reference is expensive, and checking its first records
+    /// catches most of the problems that matter in practice, like an HTML error page or a
+    /// mislabeled format, so this offers a fast but weaker integrity check. Files that have more
+    /// records than the sample record how many were checked in `sampled_records`, while files
+    /// that were read to the end are recorded as fully validated. FASTA, Genbank, GFF, GTF, and
+    /// VCF files can be sampled; other formats are validated as usual.
+    ///
+    /// Only the leading records are sampled. Checking the last records as well isn't supported,
+    /// since finding where they start in a compressed file means decompressing everything before
+    /// them anyway, which is most of the cost sampling saves.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`UnvalidatedFile::try_validate`], for the sampled records.
+    pub fn try_validate_sample(
+        &self,
+        sample: Option<usize>,
+    ) -> Result<ValidatedFile, ValidationError> {
+        self.validate_and_count(sample)
+            .map(|(validated, _)| validated)
+    }
+
+    /// Validates the file like [`UnvalidatedFile::try_validate_sample`], also returning how many
+    /// records it contains.
+    ///
+    /// The count is only returned for formats whose records are parsed one by one, i.e. FASTA,
+    /// Genbank, GFF, GTF, and VCF, and only when the whole file was read rather than a sample of
+    /// it.
+    ///
+    /// Servers occasionally send an uncompressed file under a `.gz` name, or a gzipped file
+    /// without one. When parsing fails and the file's contents don't match the compression its
+    /// name implies, the file is renamed to add or drop the `.gz` extension and parsed once more.
+    /// If that succeeds, the file keeps its corrected name, which is recorded as its local path;
+    /// otherwise it's given its old name back and the original error is returned.
+    pub(crate) fn validate_and_count(
+        &self,
+        sample: Option<usize>,
+    ) -> Result<(ValidatedFile, Option<usize>), ValidationError> {
+        let error = match self.parse_and_count(sample) {
+            Ok(validated) => return Ok(validated),
+            Err(error) => error,
+        };
+
+        let path = self.get_path();
+        if has_gzip_magic(path) == is_gzipped(path) {
+            return Err(error);
+        }
+        let corrected = toggle_gzip_extension(path);
+        if corrected.exists() || fs::rename(path, &corrected).is_err() {
+            return Err(error);
+        }
+
+        let renamed = UnvalidatedFile::new(self.format(), self.url().to_string(), corrected);
+        let Ok(validated) = renamed.parse_and_count(sample) else {
+            if let Err(e) = fs::rename(renamed.get_path(), path) {
+                warn!(
+                    "Unable to restore the name of {} after it failed validation: {e}",
+                    renamed.get_path().display()
+                );
             }
-            UnvalidatedFile::Genbank { uri, local_path } => {
-                try_parse_genbank(local_path)?;
-                (uri, local_path)
+            return Err(error);
+        };
+        warn!(
+            "The file at {} failed validation because its compression didn't match its name, so \
+            it was renamed to {}. Here's the original validation error: {error}",
+            path.display(),
+            renamed.get_path().display()
+        );
+
+        Ok(validated)
+    }
+
+    /// Parses the file according to its format, assuming that it's compressed if and only if its
+    /// name says so, and hashes it once it passes.
+    fn parse_and_count(
+        &self,
+        sample: Option<usize>,
+    ) -> Result<(ValidatedFile, Option<usize>), ValidationError> {
+        // make sure compressed files decompress cleanly first, so that a damaged download isn't
+        // blamed on the format inside it
+        let path = self.get_path();
+        if is_gzipped(path) {
+            check_gzip_integrity(path)?;
+            if is_tabix_indexable(self.format()) && !is_bgzf(path) {
+                warn!(
+                    "The file at {} is compressed with plain gzip rather than bgzip, so it can't \
+                    be indexed with tabix. Recompress it with `bgzip` if it's meant for random \
+                    access.",
+                    path.display()
+                );
             }
+        }
+
+        let (uri, local_path, records) = match self {
+            UnvalidatedFile::Fasta { uri, local_path } => {
+                (uri, local_path, Some(try_parse_fasta(local_path, sample)?))
+            },
+            UnvalidatedFile::Genbank { uri, local_path } => (
+                uri,
+                local_path,
+                Some(try_parse_genbank(local_path, sample)?),
+            ),
             UnvalidatedFile::Gfa { uri, local_path } => {
                 try_parse_gfa(local_path)?;
-                (uri, local_path)
-            }
+                (uri, local_path, None)
+            },
             UnvalidatedFile::Gff { uri, local_path } => {
-                try_parse_gff(local_path)?;
-                (uri, local_path)
-            }
+                (uri, local_path, Some(try_parse_gff(local_path, sample)?))
+            },
             UnvalidatedFile::Gtf { uri, local_path } => {
-                try_parse_gtf(local_path)?;
-                (uri, local_path)
-            }
+                (uri, local_path, Some(try_parse_gtf(local_path, sample)?))
+            },
             UnvalidatedFile::Bed { uri, local_path } => {
                 try_parse_bed(local_path)?;
-                (uri, local_path)
-            }
-            // Currently no validation is performed for tarballs
-            UnvalidatedFile::Tar { uri, local_path } => (uri, local_path),
+                (uri, local_path, None)
+            },
+            UnvalidatedFile::Vcf { uri, local_path } => {
+                (uri, local_path, Some(try_parse_vcf(local_path, sample)?))
+            },
+            UnvalidatedFile::Tar { uri, local_path } => {
+                try_parse_tar(local_path)?;
+                (uri, local_path, None)
+            },
         };
-        let hash = hash_valid_download(local_path)?;
+        // a sample that was used up may have stopped short of the end of the file, so the file is
+        // only recorded as sampled, and the count only covers the whole file, if it wasn't
+        let sampled_records = records.and_then(|records| sampled_at(sample, records));
+        let records = records.filter(|_| sampled_records.is_none());
+        let (hash, sha256) = hash_download_digests(local_path)?;
         let timestamp = Timestamp::now();
         let validated = ValidatedFile {
             uri: uri.clone(),
@@ -207,9 +467,45 @@ impl UnvalidatedFile {
             validated: true,
             hash: Some(hash),
             last_validated: Some(timestamp),
+            sampled_records,
+            sha256: Some(sha256),
+            algorithm: HashAlgorithm::default(),
+            etag: None,
+            last_modified: None,
+            size_bytes: file_size(local_path),
         };
 
-        Ok(validated)
+        Ok((validated, records))
+    }
+
+    /// Records the current `UnvalidatedFile` as downloaded without checking that its contents
+    /// parse as the declared format.
+    ///
+    /// This is the lenient counterpart to [`UnvalidatedFile::try_validate`], used to keep a file
+    /// that failed validation so that it can be inspected. The file is still hashed, but the
+    /// returned [`ValidatedFile`] has `validated` set to `false` and no `last_validated`
+    /// timestamp, so the registry never mistakes it for a file that passed validation.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ValidationError::InaccessibleFile` if the file cannot be read for hashing.
+    pub fn record_without_validation(&self) -> Result<ValidatedFile, ValidationError> {
+        let (hash, sha256) = hash_download_digests(self.get_path())?;
+        let unvalidated = ValidatedFile {
+            uri: self.url().to_string(),
+            local_path: self.get_path().to_path_buf(),
+            validated: false,
+            hash: Some(hash),
+            last_validated: None,
+            sampled_records: None,
+            sha256: Some(sha256),
+            algorithm: HashAlgorithm::default(),
+            etag: None,
+            last_modified: None,
+            size_bytes: file_size(self.get_path()),
+        };
+
+        Ok(unvalidated)
     }
 
     /// Updates a [`RefDataset`] with a newly validated file, updating the appropriate file type field
@@ -237,7 +533,7 @@ impl UnvalidatedFile {
     ///
     /// # Example
     ///
-    /// ```no_run
+    /// ```ignore
     /// let mut dataset = RefDataset::new();
     /// let unvalidated = UnvalidatedFile::Fasta {
     ///     uri: "path/to/file.fa".to_string(),
@@ -254,37 +550,42 @@ impl UnvalidatedFile {
                 let validated = self.try_validate()?;
                 let complete_download = DownloadStatus::new_downloaded(validated);
                 dataset.fasta = Some(complete_download);
-            }
+            },
             UnvalidatedFile::Genbank { .. } => {
                 let validated = self.try_validate()?;
                 let complete_download = DownloadStatus::new_downloaded(validated);
                 dataset.genbank = Some(complete_download);
-            }
+            },
             UnvalidatedFile::Gfa { .. } => {
                 let validated = self.try_validate()?;
                 let complete_download = DownloadStatus::new_downloaded(validated);
                 dataset.gfa = Some(complete_download);
-            }
+            },
             UnvalidatedFile::Gff { .. } => {
                 let validated = self.try_validate()?;
                 let complete_download = DownloadStatus::new_downloaded(validated);
                 dataset.gff = Some(complete_download);
-            }
+            },
             UnvalidatedFile::Gtf { .. } => {
                 let validated = self.try_validate()?;
                 let complete_download = DownloadStatus::new_downloaded(validated);
                 dataset.gtf = Some(complete_download);
-            }
+            },
             UnvalidatedFile::Bed { .. } => {
                 let validated = self.try_validate()?;
                 let complete_download = DownloadStatus::new_downloaded(validated);
                 dataset.bed = Some(complete_download);
-            }
+            },
+            UnvalidatedFile::Vcf { .. } => {
+                let validated = self.try_validate()?;
+                let complete_download = DownloadStatus::new_downloaded(validated);
+                dataset.vcf = Some(complete_download);
+            },
             UnvalidatedFile::Tar { .. } => {
                 let validated = self.try_validate()?;
                 let complete_download = DownloadStatus::new_downloaded(validated);
                 dataset.tar = Some(complete_download);
-            }
+            },
         }
 
         Ok(())
@@ -321,7 +622,7 @@ impl UnvalidatedFile {
     ///
     /// Basic usage:
     ///
-    /// ```rust,no_run
+    /// ```rust,ignore
     /// let file = UnvalidatedFile::Fasta {
     ///     uri: "example.fa".to_string(),
     ///     local_path: "path/to/file.fa".into()
@@ -376,41 +677,135 @@ impl UnvalidatedFile {
 ///
 /// # Examples
 ///
-/// ```no_run
+/// ```ignore
 /// use std::path::Path;
 /// let hash = hash_valid_download(Path::new("path/to/file.txt"))?;
 /// println!("File MD5: {}", hash);
 /// ```
-#[allow(clippy::large_stack_arrays)]
 pub fn hash_valid_download(download: impl AsRef<Path>) -> Result<String, ValidationError> {
-    let Ok(file) = File::open(download.as_ref()) else {
+    let mut context = Context::new();
+    read_in_chunks(download.as_ref(), |chunk| context.consume(chunk))?;
+
+    let computed: Digest = context.compute();
+    let computed_hex = format!("{computed:x}");
+
+    Ok(computed_hex)
+}
+
+/// Computes both the MD5 and SHA-256 hashes of a file on disk in a single pass, returned in that
+/// order, as lowercase hexadecimal strings.
+///
+/// # Errors
+///
+/// Returns `ValidationError::InaccessibleFile` if the file cannot be opened or read.
+pub(crate) fn hash_download_digests(
+    download: impl AsRef<Path>,
+) -> Result<(String, String), ValidationError> {
+    let mut context = Context::new();
+    let mut hasher = Sha256::new();
+    read_in_chunks(download.as_ref(), |chunk| {
+        context.consume(chunk);
+        hasher.update(chunk);
+    })?;
+
+    let computed: Digest = context.compute();
+    Ok((format!("{computed:x}"), format!("{:x}", hasher.finalize())))
+}
+
+/// Returns how many bytes the file at `path` takes up, or `None` if its metadata can't be read.
+pub(crate) fn file_size(path: impl AsRef<Path>) -> Option<u64> {
+    fs::metadata(path.as_ref())
+        .inspect_err(|e| {
+            debug!(
+                "Unable to check the size of {}: {e}",
+                path.as_ref().display()
+            );
+        })
+        .ok()
+        .map(|metadata| metadata.len())
+}
+
+/// Reads a file from start to finish, handing each chunk of its bytes to `consume`.
+#[allow(clippy::large_stack_arrays)]
+fn read_in_chunks(path: &Path, mut consume: impl FnMut(&[u8])) -> Result<(), ValidationError> {
+    // the error names the file, since a download that became inaccessible between writing and
+    // hashing, e.g. because another process moved it, is otherwise hard to track down
+    let Ok(file) = File::open(path) else {
         return Err(ValidationError::InaccessibleFile(
-            "Unable to access downloaded file, indicating that file permissions may have changed."
-                .to_string(),
+            path.display().to_string(),
         ));
     };
     let mut reader = BufReader::new(file);
-    let mut context = Context::new();
 
     let mut buffer = [0u8; 64 * 1024]; // 64 KB buffer size, adjust as needed
 
     loop {
         let Ok(bytes_read) = reader.read(&mut buffer) else {
             return Err(ValidationError::InaccessibleFile(
-                "Unable to access downloaded file, indicating that file permissions may have changed."
-                    .to_string(),
+                path.display().to_string(),
             ));
         };
         if bytes_read == 0 {
             break; // EOF reached
         }
-        context.consume(&buffer[..bytes_read]);
+        consume(&buffer[..bytes_read]);
     }
 
-    let computed: Digest = context.compute();
-    let computed_hex = format!("{computed:x}");
+    Ok(())
+}
 
-    Ok(computed_hex)
+/// Verifies the minisign signature at `signature` for the file at `file` with the provided
+/// base64-encoded public key.
+///
+/// Files are streamed through the verifier so that large references don't need to fit in memory.
+/// Signatures made by older versions of minisign, which sign the file's contents directly rather
+/// than a hash of them, can't be streamed, so those files are read in full instead.
+pub(crate) fn verify_signature(
+    file: &Path,
+    signature: &Path,
+    public_key: &str,
+) -> Result<(), ValidationError> {
+    let invalid =
+        |reason: String| ValidationError::SignatureInvalid(file.display().to_string(), reason);
+
+    let public_key = PublicKey::from_base64(public_key)
+        .map_err(|e| invalid(format!("the public key could not be decoded ({e})")))?;
+    let signature = Signature::from_file(signature).map_err(|e| {
+        invalid(format!(
+            "the signature at {} could not be read ({e})",
+            signature.display()
+        ))
+    })?;
+
+    let Ok(mut reader) = File::open(file).map(BufReader::new) else {
+        return Err(ValidationError::InaccessibleFile(
+            file.display().to_string(),
+        ));
+    };
+    match public_key.verify_stream(&signature) {
+        Ok(mut verifier) => {
+            let mut buffer = [0u8; 8 * 1024];
+            loop {
+                let bytes_read = reader
+                    .read(&mut buffer)
+                    .map_err(|_| ValidationError::InaccessibleFile(file.display().to_string()))?;
+                if bytes_read == 0 {
+                    break;
+                }
+                verifier.update(&buffer[..bytes_read]);
+            }
+            verifier.finalize()
+        },
+        Err(MinisignError::UnsupportedLegacyMode) => {
+            let mut contents = Vec::new();
+            reader
+                .read_to_end(&mut contents)
+                .map_err(|_| ValidationError::InaccessibleFile(file.display().to_string()))?;
+            public_key.verify(&contents, &signature, true)
+        },
+        Err(e) => Err(e),
+    }
+    .map_err(|e| invalid(e.to_string()))
 }
 
 /// Validates all downloaded files in a `RefDataset` to ensure they exist, are accessible, and
@@ -426,10 +821,19 @@ pub fn hash_valid_download(download: impl AsRef<Path>) -> Result<String, Validat
 /// The validation is done in parallel using rayon's parallel iterator to improve performance
 /// when validating multiple files.
 ///
+/// Parsing a large reference is expensive, so files that were validated before and still match
+/// their recorded checksums are not parsed again, since hashing them is much faster than
+/// parsing them and any change to their contents would change their checksums. Set
+/// `force_revalidate` to parse every file regardless, for a thorough check.
+///
 /// # Arguments
 ///
 /// * `dataset` - A reference to a `RefDataset` containing the file metadata and download statuses
 ///   to validate
+/// * `threads` - The most threads to validate files with at once, e.g. to limit resource usage on
+///   a shared machine. All cores are used when `None`.
+/// * `force_revalidate` - Whether to parse files even if they're unchanged since they were last
+///   validated
 ///
 /// # Returns
 ///
@@ -456,137 +860,275 @@ pub fn hash_valid_download(download: impl AsRef<Path>) -> Result<String, Validat
 ///
 /// Basic usage:
 ///
-/// ```no_run
-/// use your_crate::RefDataset;
+/// ```ignore
+/// use refman::RefDataset;
 ///
 /// let dataset = RefDataset::new();
-/// match validate_files(&dataset) {
+/// match validate_files(&dataset, None, false) {
 ///     Ok(()) => println!("All files validated successfully"),
 ///     Err(e) => eprintln!("Validation failed: {}", e)
 /// }
 /// ```
-#[allow(clippy::similar_names)]
-pub fn validate_files(dataset: &RefDataset) -> Result<(), ValidationError> {
-    #[inline]
-    fn fasta_callback(dataset_fasta: Option<&DownloadStatus>) -> Result<(), ValidationError> {
-        match dataset_fasta {
-            Some(status) => match status {
-                DownloadStatus::NotYetDownloaded(_) => Ok(()),
-                DownloadStatus::Downloaded(validated_file) => try_parse_fasta(&validated_file.uri),
-            },
-            None => Ok(()),
-        }
+pub fn validate_files(
+    dataset: &RefDataset,
+    threads: Option<usize>,
+    force_revalidate: bool,
+) -> Result<(), ValidationError> {
+    let errors: Vec<ValidationError> = check_files(dataset, threads, force_revalidate, None)
+        .into_values()
+        .filter_map(Result::err)
+        .collect();
+
+    if !errors.is_empty() {
+        return Err(ValidationError::MultipleErrors(
+            crate::MultipleValidationErrors(errors),
+        ));
     }
-    #[inline]
-    fn genbank_callback(dataset_genbank: Option<&DownloadStatus>) -> Result<(), ValidationError> {
-        match dataset_genbank {
-            Some(status) => match status {
-                DownloadStatus::NotYetDownloaded(_) => Ok(()),
-                DownloadStatus::Downloaded(validated_file) => {
-                    try_parse_genbank(&validated_file.uri)
+
+    Ok(())
+}
+
+/// Parses each of a dataset's downloaded files in its registered format, like [`validate_files`],
+/// but returns the result for each format that was checked rather than collapsing any errors
+/// into one. Files that are unchanged since they were last validated pass without being parsed
+/// again unless `force_revalidate` is set, and formats with nothing on disk to parse are left
+/// out.
+///
+/// When `sample` is provided, only that many records from the start of each file that can be
+/// sampled are parsed, as with [`UnvalidatedFile::try_validate_sample`]. Each file that passes
+/// comes with the number of records it was sampled at, or `None` if it was checked in full.
+pub(crate) fn check_files(
+    dataset: &RefDataset,
+    threads: Option<usize>,
+    force_revalidate: bool,
+    sample: Option<usize>,
+) -> BTreeMap<FileFormat, Result<Option<usize>, ValidationError>> {
+    let validate = || {
+        dataset
+            .file_fields()
+            .into_par_iter()
+            // files that were only checksummed, or whose path predates the registry recording one, have
+            // nothing on disk to parse, and tarballs are checked when they're downloaded
+            .filter(|(format, _)| *format != FileFormat::Tar)
+            .filter_map(|(format, status)| match status {
+                Some(status @ DownloadStatus::Downloaded(file)) if status.is_downloaded() => {
+                    Some((format, status, file))
+                },
+                _ => None,
+            })
+            .map(|(format, status, file)| {
+                if !force_revalidate && unchanged_since_validation(status) {
+                    return (format, Ok(None));
                 }
-            },
-            None => Ok(()),
-        }
+                (format, parse_sample(format, &file.local_path, sample))
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+
+    with_thread_cap(threads, validate)
+}
+
+/// Parses the file at `path` in `format`, or only its first `sample` records if a sample is
+/// requested and the format can be sampled, returning the number of records it was sampled at,
+/// if it was cut short.
+fn parse_sample(
+    format: FileFormat,
+    path: &Path,
+    sample: Option<usize>,
+) -> Result<Option<usize>, ValidationError> {
+    let records = match format {
+        FileFormat::Fasta => try_parse_fasta(path, sample)?,
+        FileFormat::Genbank => try_parse_genbank(path, sample)?,
+        FileFormat::Gff => try_parse_gff(path, sample)?,
+        FileFormat::Gtf => try_parse_gtf(path, sample)?,
+        FileFormat::Vcf => try_parse_vcf(path, sample)?,
+        FileFormat::Gfa => return try_parse_gfa(path).map(|()| None),
+        FileFormat::Bed => return try_parse_bed(path).map(|()| None),
+        FileFormat::Tar => return try_parse_tar(path).map(|()| None),
+    };
+
+    Ok(sampled_at(sample, records))
+}
+
+/// Returns the sample size if parsing `records` records used it up, i.e. if the parse may have
+/// stopped short of the end of the file, and `None` if the whole file was read.
+fn sampled_at(sample: Option<usize>, records: usize) -> Option<usize> {
+    sample.filter(|&sample| records >= sample)
+}
+
+/// Runs `work` in a pool of its own with `threads` threads if the number of threads was capped,
+/// so that any parallel iterators inside it use at most that many, and otherwise in rayon's
+/// global pool, which uses every core.
+pub(crate) fn with_thread_cap<T, F>(threads: Option<usize>, work: F) -> T
+where
+    T: Send,
+    F: FnOnce() -> T + Send,
+{
+    let pool = threads.and_then(|num_threads| {
+        ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .inspect_err(|e| {
+                warn!("Unable to cap validation at {num_threads} threads, so all cores will be used: {e}");
+            })
+            .ok()
+    });
+    match pool {
+        Some(pool) => pool.install(work),
+        None => work(),
     }
-    #[inline]
-    fn gfa_callback(dataset_gfa: Option<&DownloadStatus>) -> Result<(), ValidationError> {
-        match dataset_gfa {
-            Some(status) => match status {
-                DownloadStatus::NotYetDownloaded(_) => Ok(()),
-                DownloadStatus::Downloaded(validated_file) => try_parse_gfa(&validated_file.uri),
-            },
-            None => Ok(()),
-        }
+}
+
+/// The outcome of validating every downloaded file in a registry with
+/// [`Project::validate_all`](crate::project::Project::validate_all), itemized by dataset and
+/// format so that callers can report on each file however they like.
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    /// The result of validating each downloaded file, keyed by the label of its dataset and then
+    /// by its format. Datasets and formats with nothing downloaded to validate are left out.
+    pub datasets: BTreeMap<String, BTreeMap<FileFormat, Result<(), ValidationError>>>,
+}
+
+impl ValidationReport {
+    /// Whether every file that was checked passed validation.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.failures().next().is_none()
     }
-    #[inline]
-    fn gff_callback(dataset_gff: Option<&DownloadStatus>) -> Result<(), ValidationError> {
-        match dataset_gff {
-            Some(status) => match status {
-                DownloadStatus::NotYetDownloaded(_) => Ok(()),
-                DownloadStatus::Downloaded(validated_file) => try_parse_gff(&validated_file.uri),
-            },
-            None => Ok(()),
-        }
+
+    /// How many files were checked, whether or not they passed.
+    #[must_use]
+    pub fn file_count(&self) -> usize {
+        self.datasets.values().map(BTreeMap::len).sum()
     }
-    #[inline]
-    fn gtf_callback(dataset_gtf: Option<&DownloadStatus>) -> Result<(), ValidationError> {
-        match dataset_gtf {
-            Some(status) => match status {
-                DownloadStatus::NotYetDownloaded(_) => Ok(()),
-                DownloadStatus::Downloaded(validated_file) => try_parse_gtf(&validated_file.uri),
-            },
-            None => Ok(()),
-        }
+
+    /// Returns the label, format, and error of each file that failed validation.
+    pub fn failures(&self) -> impl Iterator<Item = (&str, FileFormat, &ValidationError)> {
+        self.datasets.iter().flat_map(|(label, formats)| {
+            formats.iter().filter_map(move |(format, result)| {
+                result
+                    .as_ref()
+                    .err()
+                    .map(|error| (label.as_str(), *format, error))
+            })
+        })
     }
-    #[inline]
-    fn bed_callback(dataset_bed: Option<&DownloadStatus>) -> Result<(), ValidationError> {
-        match dataset_bed {
-            Some(status) => match status {
-                DownloadStatus::NotYetDownloaded(_) => Ok(()),
-                DownloadStatus::Downloaded(validated_file) => try_parse_bed(&validated_file.uri),
-            },
-            None => Ok(()),
-        }
-    }
-    let validation_callbacks = vec![
-        fasta_callback(dataset.fasta.as_ref()),
-        genbank_callback(dataset.genbank.as_ref()),
-        gfa_callback(dataset.gfa.as_ref()),
-        gff_callback(dataset.gff.as_ref()),
-        gtf_callback(dataset.gtf.as_ref()),
-        bed_callback(dataset.bed.as_ref()),
-    ]
-    .into_par_iter()
-    .filter_map(Result::err)
-    .collect::<Vec<ValidationError>>();
-
-    if !validation_callbacks.is_empty() {
-        return Err(ValidationError::MultipleErrors(
-            crate::MultipleValidationErrors(validation_callbacks),
-        ));
+}
+
+/// Whether a file was fully validated when it was downloaded and still matches the checksum
+/// recorded then, in which case parsing it again would only repeat the same result.
+fn unchanged_since_validation(status: &DownloadStatus) -> bool {
+    let DownloadStatus::Downloaded(file) = status else {
+        return false;
+    };
+    if !file.validated || file.sampled_records.is_some() {
+        return false;
     }
+    let Some(recorded_hash) = file.recorded_checksum() else {
+        return false;
+    };
 
-    Ok(())
+    match file.algorithm.hash_file(&file.local_path) {
+        Ok(hash) if hash == recorded_hash => {
+            debug!(
+                "{} is unchanged since it was last validated, so it won't be parsed again.",
+                file.local_path.display()
+            );
+            true
+        },
+        _ => false,
+    }
 }
 
-fn try_parse_fasta(file: impl AsRef<Path>) -> Result<(), ValidationError> {
-    if file.as_ref().ends_with(".fasta") || file.as_ref().extension().is_some_and(|ext| ext == "fa")
-    {
-        let Ok(mut fa_reader) = File::open(file.as_ref())
-            .map(BufReader::new)
-            .map(fasta::Reader::new)
-        else {
-            return Err(ValidationError::InaccessibleFile(
-                file.as_ref().to_string_lossy().into_owned(),
-            ));
-        };
-        while let Some(record) = fa_reader.records().next() {
-            if let Err(msg) = record {
-                return Err(ValidationError::InvalidFasta(format!("{msg}")));
-            }
+/// Parses the records of a FASTA file, returning how many were parsed.
+///
+/// Files are decompressed if their names end in `.gz` or `.bgz`. A file without a known FASTA
+/// extension underneath, e.g. `.fasta`, `.fa`, `.fna`, or `.faa`, is still parsed, but with a
+/// warning, since it may have been registered under the wrong format.
+fn try_parse_fasta(
+    file: impl AsRef<Path>,
+    sample: Option<usize>,
+) -> Result<usize, ValidationError> {
+    let file = file.as_ref();
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    if FileFormat::from_uri(&name) != Some(FileFormat::Fasta) {
+        warn!(
+            "{} doesn't have a FASTA file extension, but it will be validated as a FASTA file.",
+            file.display()
+        );
+    }
+
+    let Ok(mut fa_reader) = open_text(file).map(fasta::Reader::new) else {
+        return Err(ValidationError::InaccessibleFile(
+            file.to_string_lossy().into_owned(),
+        ));
+    };
+    let mut records = 0;
+    for record in fa_reader.records().take(sample.unwrap_or(usize::MAX)) {
+        if let Err(msg) = record {
+            return Err(ValidationError::InvalidFasta(format!("{msg}")));
         }
-    } else if file.as_ref().extension().is_some_and(|ext| ext == "gz") {
-        let Ok(mut fa_reader) = File::open(file.as_ref())
-            .map(BufReader::new)
-            .map(GzDecoder::new)
-            .map(BufReader::new)
-            .map(fasta::Reader::new)
-        else {
-            return Err(ValidationError::InaccessibleFile(
-                file.as_ref().to_string_lossy().into_owned(),
+        records += 1;
+    }
+
+    check_fasta_indices(file, sample.is_none().then_some(records))?;
+
+    Ok(records)
+}
+
+/// Makes sure that any `.fai` and `.gzi` indices sitting next to a FASTA file parse, and that
+/// they can be used with it. A FASTA index lists every sequence in the file, so when all of its
+/// records were parsed, i.e. `records` is `Some`, the index must list as many. Indices only work
+/// with compressed files written by `bgzip`, so a plain gzipped FASTA with indices next to it is
+/// an error rather than something to find out about partway through a pipeline.
+fn check_fasta_indices(file: &Path, records: Option<usize>) -> Result<(), ValidationError> {
+    let fai = with_appended_extension(file, "fai");
+    let gzi = with_appended_extension(file, "gzi");
+    if !fai.is_file() && !gzi.is_file() {
+        return Ok(());
+    }
+    if is_gzipped(file) && !is_bgzf(file) {
+        return Err(ValidationError::FastaNotBgzf(
+            file.to_string_lossy().into_owned(),
+        ));
+    }
+
+    let invalid_index = |index: &Path, msg: String| {
+        ValidationError::InvalidFastaIndex(index.to_string_lossy().into_owned(), msg)
+    };
+    if fai.is_file() {
+        let index = fai::fs::read(&fai).map_err(|e| invalid_index(&fai, e.to_string()))?;
+        let indexed = index.as_ref().len();
+        if let Some(records) = records.filter(|records| *records != indexed) {
+            return Err(invalid_index(
+                &fai,
+                format!("it lists {indexed} sequences, but the FASTA file has {records}"),
             ));
-        };
-        while let Some(record) = fa_reader.records().next() {
-            if let Err(msg) = record {
-                return Err(ValidationError::InvalidFasta(format!("{msg}")));
-            }
         }
+        debug!("The FASTA index at {} parsed cleanly", fai.display());
+    }
+    if gzi.is_file() {
+        gzi::fs::read(&gzi).map_err(|e| invalid_index(&gzi, e.to_string()))?;
+        debug!("The bgzip index at {} parsed cleanly", gzi.display());
     }
+
     Ok(())
 }
 
-fn try_parse_genbank(file: impl AsRef<Path>) -> Result<(), ValidationError> {
+/// Returns `file`'s path with `extension` added after its existing extensions, e.g. the path of
+/// the `.fai` index of `genome.fa.gz` is `genome.fa.gz.fai`.
+fn with_appended_extension(file: &Path, extension: &str) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+/// Parses the records of a Genbank file, returning how many were parsed.
+fn try_parse_genbank(
+    file: impl AsRef<Path>,
+    sample: Option<usize>,
+) -> Result<usize, ValidationError> {
     let Ok(gbk_reader) = File::open(file.as_ref())
         .map(BufReader::new)
         .map(GbkReader::new)
@@ -596,13 +1138,15 @@ fn try_parse_genbank(file: impl AsRef<Path>) -> Result<(), ValidationError> {
         ));
     };
 
-    for record in gbk_reader {
+    let mut records = 0;
+    for record in gbk_reader.take(sample.unwrap_or(usize::MAX)) {
         if let Err(msg) = record {
             return Err(ValidationError::InvalidGenbank(format!("{msg}")));
         }
+        records += 1;
     }
 
-    Ok(())
+    Ok(records)
 }
 
 fn try_parse_gfa(file: impl AsRef<Path>) -> Result<(), ValidationError> {
@@ -620,38 +1164,331 @@ fn try_parse_gfa(file: impl AsRef<Path>) -> Result<(), ValidationError> {
     Ok(())
 }
 
-fn try_parse_gff(file: impl AsRef<Path>) -> Result<(), ValidationError> {
-    let Ok(mut gff_reader) = File::open(file.as_ref())
-        .map(BufReader::new)
-        .map(gff::Reader::new)
-    else {
+/// Reads through every entry of a tarball, which may be gzipped, to make sure the archive is
+/// complete and that its entries can be listed.
+fn try_parse_tar(file: &Path) -> Result<(), ValidationError> {
+    let invalid = |e: io::Error| {
+        ValidationError::InvalidTar(file.to_string_lossy().into_owned(), e.to_string())
+    };
+    let mut archive = open_tarball(file)
+        .map_err(|_| ValidationError::InaccessibleFile(file.to_string_lossy().into_owned()))?;
+    for entry in archive.entries().map_err(invalid)? {
+        entry
+            .and_then(|entry| entry.path().map(|_| ()))
+            .map_err(invalid)?;
+    }
+
+    Ok(())
+}
+
+/// Unpacks a downloaded tarball into a directory next to it named after the archive, e.g.
+/// `bundle/` for `bundle.tar.gz`, returning the directory's path along with the paths of the
+/// files written into it.
+///
+/// Like `tar --strip-components`, the first `strip_components` components of each entry's path
+/// are dropped before it's written, which does away with the top-level directory many providers
+/// wrap their archives in. Entries with no more components than that are skipped. So are entries
+/// whose paths would land outside of the directory, e.g. because they're absolute or climb out of
+/// it with `..`, and hard links, which name their targets by paths that may have been stripped.
+/// Files already in the directory are overwritten by entries of the same name, and left alone
+/// otherwise.
+///
+/// # Errors
+///
+/// Returns `ValidationError::InaccessibleFile` if the tarball can't be opened, or
+/// `ValidationError::ExtractionFailed` if any of its entries can't be unpacked.
+pub(crate) fn extract_tarball(
+    file: &Path,
+    strip_components: usize,
+) -> Result<(PathBuf, Vec<PathBuf>), ValidationError> {
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    let lowercase = name.to_ascii_lowercase();
+    let stem_len = [".tar.gz", ".tar.bgz", ".tgz", ".tar"]
+        .iter()
+        .find(|extension| lowercase.ends_with(*extension))
+        .map_or(name.len(), |extension| name.len() - extension.len());
+    let destination = file.with_file_name(&name[..stem_len]);
+
+    let failed = |e: io::Error| {
+        ValidationError::ExtractionFailed(file.to_string_lossy().into_owned(), e.to_string())
+    };
+    let mut archive = open_tarball(file)
+        .map_err(|_| ValidationError::InaccessibleFile(file.to_string_lossy().into_owned()))?;
+    fs::create_dir_all(&destination).map_err(failed)?;
+    let root = destination.canonicalize().map_err(failed)?;
+
+    let mut extracted = Vec::new();
+    for entry in archive.entries().map_err(failed)? {
+        let mut entry = entry.map_err(failed)?;
+        let path = entry.path().map_err(failed)?.into_owned();
+        let entry_type = entry.header().entry_type();
+        let Some(relative) = strip_entry_path(&path, strip_components) else {
+            debug!(
+                "Skipping {} while extracting {}",
+                path.display(),
+                file.display()
+            );
+            continue;
+        };
+        if entry_type.is_hard_link() {
+            warn!(
+                "Skipping the hard link {} while extracting {}",
+                path.display(),
+                file.display()
+            );
+            continue;
+        }
+
+        // an earlier entry may have been a symlink to somewhere outside of the directory
+        let target = destination.join(&relative);
+        let parent = target.parent().unwrap_or(&destination);
+        fs::create_dir_all(parent).map_err(failed)?;
+        if !parent.canonicalize().map_err(failed)?.starts_with(&root) {
+            debug!(
+                "Skipping {} while extracting {}",
+                path.display(),
+                file.display()
+            );
+            continue;
+        }
+
+        entry.unpack(&target).map_err(failed)?;
+        if entry_type.is_file() {
+            extracted.push(target);
+        }
+    }
+    debug!(
+        "Extracted {} into {}",
+        file.display(),
+        destination.display()
+    );
+
+    Ok((destination, extracted))
+}
+
+/// Drops the first `strip_components` components of a tarball entry's path, returning what's
+/// left, or `None` if nothing is, or if the path isn't a plain relative one.
+fn strip_entry_path(path: &Path, strip_components: usize) -> Option<PathBuf> {
+    let components = path
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .map(|component| match component {
+            Component::Normal(part) => Some(part),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let stripped: PathBuf = components.into_iter().skip(strip_components).collect();
+
+    (!stripped.as_os_str().is_empty()).then_some(stripped)
+}
+
+/// Opens a tarball for reading its entries, decompressing it on the way if it starts with the
+/// gzip magic bytes, whatever its name says.
+fn open_tarball(file: &Path) -> io::Result<Archive<Box<dyn Read>>> {
+    let reader = BufReader::new(File::open(file)?);
+    let reader: Box<dyn Read> = if has_gzip_magic(file) {
+        Box::new(MultiGzDecoder::new(reader))
+    } else {
+        Box::new(reader)
+    };
+
+    Ok(Archive::new(reader))
+}
+
+/// Parses the records of a GFF file, returning how many were parsed.
+fn try_parse_gff(file: impl AsRef<Path>, sample: Option<usize>) -> Result<usize, ValidationError> {
+    // catch GTF files that were registered as GFF before handing them to a parser that will
+    // either choke confusingly or, worse, partially parse them
+    if sniff_attribute_style(file.as_ref()) == Some(AttributeStyle::Gtf) {
+        return Err(ValidationError::GtfProvidedAsGff(
+            file.as_ref().to_string_lossy().into_owned(),
+        ));
+    }
+
+    let Ok(mut gff_reader) = open_text(file.as_ref()).map(gff::Reader::new) else {
         return Err(ValidationError::InaccessibleFile(
             file.as_ref().to_string_lossy().into_owned(),
         ));
     };
-    while let Some(record) = gff_reader.record_bufs().next() {
+    let mut records = 0;
+    for record in gff_reader.record_bufs().take(sample.unwrap_or(usize::MAX)) {
         if let Err(msg) = record {
             return Err(ValidationError::InvalidGFF(format!("{msg}")));
         }
+        records += 1;
     }
-    Ok(())
+    Ok(records)
 }
 
-fn try_parse_gtf(file: impl AsRef<Path>) -> Result<(), ValidationError> {
-    let Ok(mut gff_reader) = File::open(file.as_ref())
-        .map(BufReader::new)
-        .map(gtf::Reader::new)
-    else {
+/// Parses the records of a GTF file, returning how many were parsed.
+fn try_parse_gtf(file: impl AsRef<Path>, sample: Option<usize>) -> Result<usize, ValidationError> {
+    // likewise, catch GFF files that were registered as GTF
+    if sniff_attribute_style(file.as_ref()) == Some(AttributeStyle::Gff) {
+        return Err(ValidationError::GffProvidedAsGtf(
+            file.as_ref().to_string_lossy().into_owned(),
+        ));
+    }
+
+    let Ok(mut gff_reader) = open_text(file.as_ref()).map(gtf::Reader::new) else {
         return Err(ValidationError::InaccessibleFile(
             file.as_ref().to_string_lossy().into_owned(),
         ));
     };
-    while let Some(record) = gff_reader.record_bufs().next() {
+    let mut records = 0;
+    for record in gff_reader.record_bufs().take(sample.unwrap_or(usize::MAX)) {
         if let Err(msg) = record {
             return Err(ValidationError::InvalidGTF(format!("{msg}")));
         }
+        records += 1;
+    }
+    Ok(records)
+}
+
+/// Whether a file is gzip-compressed, going by its `.gz` or `.bgz` extension.
+fn is_gzipped(file: &Path) -> bool {
+    file.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz") || ext.eq_ignore_ascii_case("bgz"))
+}
+
+/// Decompresses a gzipped file all the way to its end, discarding its contents, to make sure it
+/// isn't truncated or corrupted. Reading to the end checks the CRC32 and size recorded at the end
+/// of each gzip member, and every member of multi-member files like those from `bgzip` is read.
+fn check_gzip_integrity(file: &Path) -> Result<(), ValidationError> {
+    let Ok(compressed) = File::open(file).map(BufReader::new) else {
+        return Err(ValidationError::InaccessibleFile(
+            file.to_string_lossy().into_owned(),
+        ));
+    };
+    io::copy(&mut MultiGzDecoder::new(compressed), &mut io::sink())
+        .map(|_| ())
+        .map_err(|e| {
+            ValidationError::CorruptGzip(file.to_string_lossy().into_owned(), e.to_string())
+        })
+}
+
+/// Whether files of a format can be bgzip-compressed and indexed with tabix for random access.
+fn is_tabix_indexable(format: FileFormat) -> bool {
+    matches!(
+        format,
+        FileFormat::Gff | FileFormat::Gtf | FileFormat::Bed | FileFormat::Vcf
+    )
+}
+
+/// Whether a file starts with the two magic bytes of a gzip stream, regardless of its name.
+fn has_gzip_magic(file: &Path) -> bool {
+    let mut magic = [0_u8; 2];
+    File::open(file)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .is_ok_and(|()| magic == [0x1f, 0x8b])
+}
+
+/// Returns the path a file would have with its `.gz` or `.bgz` extension dropped if it has one,
+/// or with `.gz` added if it doesn't.
+fn toggle_gzip_extension(file: &Path) -> PathBuf {
+    if is_gzipped(file) {
+        file.with_extension("")
+    } else {
+        let mut name = file.as_os_str().to_owned();
+        name.push(".gz");
+        PathBuf::from(name)
+    }
+}
+
+/// Whether a gzipped file is in the blocked gzip format written by `bgzip`, going by the `BC`
+/// subfield that BGZF stores in the extra field of every block's gzip header. Plain gzip streams,
+/// e.g. from recompressing a file with `gzip`, lack it and can't be indexed with tabix.
+fn is_bgzf(file: &Path) -> bool {
+    let Ok(mut reader) = File::open(file).map(BufReader::new) else {
+        return false;
+    };
+    let mut header = [0_u8; 12];
+    if reader.read_exact(&mut header).is_err() {
+        return false;
+    }
+    // gzip magic bytes, the deflate method, and the FEXTRA flag
+    if header[..3] != [0x1f, 0x8b, 8] || header[3] & 0x04 == 0 {
+        return false;
+    }
+
+    let mut extra = vec![0_u8; usize::from(u16::from_le_bytes([header[10], header[11]]))];
+    if reader.read_exact(&mut extra).is_err() {
+        return false;
+    }
+
+    // the extra field is a series of subfields, each with two identifier bytes and a two-byte
+    // little-endian length
+    let mut subfields = extra.as_slice();
+    while let [id1, id2, len_lo, len_hi, rest @ ..] = subfields {
+        if (*id1, *id2) == (b'B', b'C') {
+            return true;
+        }
+        let len = usize::from(u16::from_le_bytes([*len_lo, *len_hi]));
+        subfields = rest.get(len..).unwrap_or_default();
+    }
+    false
+}
+
+/// Opens a text-based file for buffered reading, transparently decompressing it if it is
+/// gzipped, so that e.g. `.gff3` and `.gff3.gz` files can be validated the same way.
+fn open_text(file: &Path) -> io::Result<Box<dyn BufRead>> {
+    // BGZF files are read block by block with noodles rather than as a single gzip stream, which
+    // would end at the first of their many blocks
+    if is_bgzf(file) {
+        return Ok(Box::new(bgzf::Reader::new(File::open(file)?)));
+    }
+    let reader = BufReader::new(File::open(file)?);
+    if is_gzipped(file) {
+        Ok(Box::new(BufReader::new(GzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// The two textual conventions for the ninth, attribute column of GFF-family files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AttributeStyle {
+    /// GFF3 writes attributes as `key=value` pairs, e.g. `ID=gene0;Name=ORF1ab`
+    Gff,
+    /// GTF writes attributes as space-separated, quoted pairs, e.g. `gene_id "ORF1ab";`
+    Gtf,
+}
+
+/// The number of feature lines to inspect before giving up on sniffing an attribute style.
+const ATTRIBUTE_SNIFF_LINES: usize = 100;
+
+/// Peeks at the attribute column of the first feature lines in a GFF or GTF file to work out
+/// which of the two formats the file is actually written in.
+///
+/// GTF and GFF3 are nearly identical in their first eight columns, which makes it easy to
+/// register one under the other's flag. Their attribute columns differ, however, and this is
+/// used to give users an actionable error instead of a confusing parser failure. Returns `None`
+/// if the file can't be read as text or no feature line settles the question, in which case
+/// the regular parser gets the final say.
+fn sniff_attribute_style(file: &Path) -> Option<AttributeStyle> {
+    open_text(file)
+        .ok()?
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .take(ATTRIBUTE_SNIFF_LINES)
+        .find_map(|line| line.split('\t').nth(8).and_then(classify_attributes))
+}
+
+fn classify_attributes(attributes: &str) -> Option<AttributeStyle> {
+    let first_pair = attributes
+        .split(';')
+        .map(str::trim)
+        .find(|pair| !pair.is_empty())?;
+
+    match (first_pair.split_once('='), first_pair.split_once(' ')) {
+        // a `key=value` pair with no whitespace in the key is GFF3
+        (Some((key, _)), _) if !key.contains(char::is_whitespace) => Some(AttributeStyle::Gff),
+        // a `key "value"` or `key value` pair is GTF
+        (_, Some((key, value))) if !key.is_empty() && !value.trim().is_empty() => {
+            Some(AttributeStyle::Gtf)
+        },
+        _ => None,
     }
-    Ok(())
 }
 
 fn try_parse_bed(file: impl AsRef<Path>) -> Result<(), ValidationError> {
@@ -669,3 +1506,487 @@ fn try_parse_bed(file: impl AsRef<Path>) -> Result<(), ValidationError> {
         Err(msg) => Err(ValidationError::InvalidBED(format!("{msg}"))),
     }
 }
+
+/// Parses the header and records of a VCF file, returning how many records were parsed. Each
+/// record is checked against the header, so e.g. `INFO` fields it doesn't define are caught.
+fn try_parse_vcf(file: impl AsRef<Path>, sample: Option<usize>) -> Result<usize, ValidationError> {
+    let Ok(mut vcf_reader) = open_text(file.as_ref()).map(VcfReader::new) else {
+        return Err(ValidationError::InaccessibleFile(
+            file.as_ref().to_string_lossy().into_owned(),
+        ));
+    };
+    let header = vcf_reader
+        .read_header()
+        .map_err(|msg| ValidationError::InvalidVCF(format!("{msg}")))?;
+    let mut records = 0;
+    for record in vcf_reader
+        .record_bufs(&header)
+        .take(sample.unwrap_or(usize::MAX))
+    {
+        if let Err(msg) = record {
+            return Err(ValidationError::InvalidVCF(format!("{msg}")));
+        }
+        records += 1;
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+    use flate2::{Compression, GzBuilder, write::GzEncoder};
+    use std::{fmt::Write as _, fs, io::Write};
+    use tempfile::{NamedTempFile, tempdir};
+
+    const GFF_LINE: &str =
+        "MN908947.3\tGenbank\tgene\t266\t21555\t.\t+\t.\tID=gene-ORF1ab;Name=ORF1ab\n";
+    const GTF_LINE: &str = "MN908947.3\tGenbank\tgene\t266\t21555\t.\t+\t.\tgene_id \"ORF1ab\"; gene_name \"ORF1ab\";\n";
+
+    fn write_gzipped(path: &Path, contents: &str) {
+        let mut encoder = GzEncoder::new(File::create(path).unwrap(), Compression::default());
+        encoder.write_all(contents.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+    }
+
+    fn annotation_file(header: &str, line: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{header}{line}").unwrap();
+        file
+    }
+
+    #[test]
+    fn test_sniff_attribute_style() {
+        let gff = annotation_file("##gff-version 3\n", GFF_LINE);
+        let gtf = annotation_file("#!genome-build MN908947.3\n", GTF_LINE);
+
+        assert_eq!(sniff_attribute_style(gff.path()), Some(AttributeStyle::Gff));
+        assert_eq!(sniff_attribute_style(gtf.path()), Some(AttributeStyle::Gtf));
+    }
+
+    #[test]
+    fn test_parse_gff_extensions() {
+        let contents = format!("##gff-version 3\n{GFF_LINE}");
+        let dir = tempdir().unwrap();
+
+        for name in ["genes.gff", "genes.gff3"] {
+            let path = dir.path().join(name);
+            fs::write(&path, &contents).unwrap();
+            assert!(try_parse_gff(&path, None).is_ok(), "{name}");
+        }
+
+        for name in ["genes.gff.gz", "genes.gff3.gz"] {
+            let path = dir.path().join(name);
+            write_gzipped(&path, &contents);
+            assert!(try_parse_gff(&path, None).is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_parse_gtf_extensions() {
+        let dir = tempdir().unwrap();
+
+        let path = dir.path().join("genes.gtf");
+        fs::write(&path, GTF_LINE).unwrap();
+        assert!(try_parse_gtf(&path, None).is_ok());
+
+        let path = dir.path().join("genes.gtf.gz");
+        write_gzipped(&path, GTF_LINE);
+        assert!(try_parse_gtf(&path, None).is_ok());
+    }
+
+    #[test]
+    fn test_parse_vcf() {
+        const VCF: &str = "##fileformat=VCFv4.3\n\
+            ##contig=<ID=MN908947.3>\n\
+            #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+            MN908947.3\t241\t.\tC\tT\t.\tPASS\t.\n\
+            MN908947.3\t3037\t.\tC\tT\t.\tPASS\t.\n";
+        let dir = tempdir().unwrap();
+
+        let path = dir.path().join("variants.vcf");
+        fs::write(&path, VCF).unwrap();
+        assert_eq!(try_parse_vcf(&path, None).unwrap(), 2);
+        assert_eq!(try_parse_vcf(&path, Some(1)).unwrap(), 1);
+
+        let path = dir.path().join("variants.vcf.gz");
+        write_gzipped(&path, VCF);
+        assert_eq!(try_parse_vcf(&path, None).unwrap(), 2);
+
+        // variant records without the header that describes them
+        let path = dir.path().join("headerless.vcf");
+        fs::write(&path, VCF.lines().skip(3).collect::<Vec<_>>().join("\n")).unwrap();
+        assert!(matches!(
+            try_parse_vcf(&path, None),
+            Err(ValidationError::InvalidVCF(_))
+        ));
+
+        let path = dir.path().join("bad_position.vcf");
+        fs::write(&path, VCF.replace("\t241\t", "\tposition\t")).unwrap();
+        assert!(matches!(
+            try_parse_vcf(&path, None),
+            Err(ValidationError::InvalidVCF(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_and_extract_tar() {
+        let dir = tempdir().unwrap();
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, contents) in [
+            ("bundle/genome.fa", ">seq\nACGT\n"),
+            ("bundle/README", "hi\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_path(path).unwrap();
+            header.set_cksum();
+            builder.append(&header, contents.as_bytes()).unwrap();
+        }
+        // an entry that tries to climb out of the directory it's extracted into
+        let mut header = tar::Header::new_old();
+        header.as_old_mut().name[..13].copy_from_slice(b"../escape.txt");
+        header.set_size(0);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, io::empty()).unwrap();
+        let tarball = builder.into_inner().unwrap();
+
+        let plain = dir.path().join("bundle.tar");
+        fs::write(&plain, &tarball).unwrap();
+        assert!(try_parse_tar(&plain).is_ok());
+
+        let gzipped = dir.path().join("refs.tgz");
+        let mut encoder = GzEncoder::new(File::create(&gzipped).unwrap(), Compression::default());
+        encoder.write_all(&tarball).unwrap();
+        encoder.finish().unwrap();
+        assert!(try_parse_tar(&gzipped).is_ok());
+
+        let (extracted, files) = extract_tarball(&gzipped, 0).unwrap();
+        assert_eq!(extracted, dir.path().join("refs"));
+        assert_eq!(
+            fs::read_to_string(extracted.join("bundle/genome.fa")).unwrap(),
+            ">seq\nACGT\n"
+        );
+        assert_eq!(
+            files,
+            [
+                extracted.join("bundle/genome.fa"),
+                extracted.join("bundle/README")
+            ]
+        );
+        assert!(!dir.path().join("escape.txt").exists());
+
+        // stripping the wrapping directory writes its contents straight into the destination,
+        // and an entry that climbs out with `..` isn't rescued by losing a component
+        let (extracted, files) = extract_tarball(&plain, 1).unwrap();
+        assert_eq!(extracted, dir.path().join("bundle"));
+        assert_eq!(
+            files,
+            [extracted.join("genome.fa"), extracted.join("README")]
+        );
+        assert_eq!(
+            fs::read_to_string(extracted.join("genome.fa")).unwrap(),
+            ">seq\nACGT\n"
+        );
+        assert!(!extracted.join("escape.txt").exists());
+        assert!(!dir.path().join("escape.txt").exists());
+
+        // entries with no components left are skipped
+        let (_, files) = extract_tarball(&plain, 2).unwrap();
+        assert!(files.is_empty());
+
+        let truncated = dir.path().join("truncated.tar");
+        fs::write(&truncated, &tarball[..700]).unwrap();
+        assert!(matches!(
+            try_parse_tar(&truncated),
+            Err(ValidationError::InvalidTar(..))
+        ));
+    }
+
+    #[test]
+    fn test_gff_registered_as_gtf() {
+        let gff = annotation_file("##gff-version 3\n", GFF_LINE);
+        assert!(matches!(
+            try_parse_gtf(gff.path(), None),
+            Err(ValidationError::GffProvidedAsGtf(_))
+        ));
+    }
+
+    #[test]
+    fn test_gtf_registered_as_gff() {
+        let gtf = annotation_file("", GTF_LINE);
+        assert!(matches!(
+            try_parse_gff(gtf.path(), None),
+            Err(ValidationError::GtfProvidedAsGff(_))
+        ));
+    }
+
+    #[test]
+    fn test_record_without_validation() {
+        let gtf = annotation_file("", GTF_LINE);
+        let file = UnvalidatedFile::Gff {
+            uri: "https://example.com/annotation.gff".to_string(),
+            local_path: gtf.path().to_path_buf(),
+        };
+        assert!(file.try_validate().is_err());
+
+        let recorded = file.record_without_validation().unwrap();
+        assert!(!recorded.validated);
+        assert!(recorded.hash.is_some());
+        assert!(recorded.last_validated.is_none());
+        assert_eq!(recorded.local_path, gtf.path());
+    }
+
+    #[test]
+    fn test_moved_download_names_path() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("genomes.tar");
+        fs::write(&path, "not parsed").unwrap();
+        // tarballs aren't parsed, so the file is first opened to hash it
+        let file = UnvalidatedFile::new(
+            FileFormat::Tar,
+            "https://example.com/genomes.tar".to_string(),
+            path.clone(),
+        );
+        fs::rename(&path, dir.path().join("moved.tar")).unwrap();
+
+        let error = file.try_validate().unwrap_err();
+        let expected = path.display().to_string();
+        assert!(
+            matches!(&error, ValidationError::InaccessibleFile(inaccessible) if *inaccessible == expected),
+            "{error}"
+        );
+        assert!(file.record_without_validation().is_err());
+    }
+
+    #[test]
+    fn test_validate_files_reads_local_path() {
+        let gff = annotation_file("##gff-version 3\n", GFF_LINE);
+        let validated = UnvalidatedFile::Gff {
+            uri: "https://example.com/annotation.gff".to_string(),
+            local_path: gff.path().to_path_buf(),
+        }
+        .try_validate()
+        .unwrap();
+        let mut dataset = RefDataset {
+            label: "annotation".to_string(),
+            gff: Some(DownloadStatus::new_downloaded(validated)),
+            ..RefDataset::default()
+        };
+        assert!(validate_files(&dataset, None, true).is_ok());
+
+        // registries written before local paths were recorded still load, with the file marked
+        // as needing to be downloaded again
+        let old: ValidatedFile = toml::from_str(
+            r#"
+            uri = "https://example.com/annotation.gff"
+            validated = true
+            hash = "d41d8cd98f00b204e9800998ecf8427e"
+            "#,
+        )
+        .unwrap();
+        dataset.gff = Some(DownloadStatus::new_downloaded(old));
+        assert!(!dataset.gff.as_ref().unwrap().is_downloaded());
+        assert!(validate_files(&dataset, None, true).is_ok());
+    }
+
+    #[test]
+    fn test_try_validate_sample() {
+        let gff = annotation_file(
+            "##gff-version 3\n",
+            &format!("{GFF_LINE}MN908947.3\tGenbank\tgene\tnot-a-position\n"),
+        );
+        let file = UnvalidatedFile::Gff {
+            uri: "https://example.com/annotation.gff".to_string(),
+            local_path: gff.path().to_path_buf(),
+        };
+        assert!(file.try_validate().is_err());
+
+        let sampled = file.try_validate_sample(Some(1)).unwrap();
+        assert!(sampled.validated);
+        assert_eq!(sampled.sampled_records, Some(1));
+
+        // a file with fewer records than the sample was read to the end, so it wasn't sampled
+        let short = annotation_file("##gff-version 3\n", GFF_LINE);
+        let file = UnvalidatedFile::Gff {
+            uri: "https://example.com/annotation.gff".to_string(),
+            local_path: short.path().to_path_buf(),
+        };
+        let (validated, records) = file.validate_and_count(Some(1000)).unwrap();
+        assert_eq!(validated.sampled_records, None);
+        assert!(records.is_some());
+    }
+
+    #[test]
+    fn test_corrupt_gzip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("genome.fasta.gz");
+        write_gzipped(&path, ">seq\nACGTACGTACGTACGT\n");
+        let file = UnvalidatedFile::Fasta {
+            uri: "https://example.com/genome.fasta.gz".to_string(),
+            local_path: path.clone(),
+        };
+        assert!(file.try_validate().is_ok());
+
+        // chop off the end of the file, like an interrupted download would
+        let compressed = fs::read(&path).unwrap();
+        fs::write(&path, &compressed[..compressed.len() - 6]).unwrap();
+        assert!(matches!(
+            file.try_validate(),
+            Err(ValidationError::CorruptGzip(..))
+        ));
+    }
+
+    #[test]
+    fn test_parse_fasta_extensions() {
+        let dir = tempdir().unwrap();
+        for name in ["test.fasta", "test.fa", "test.fna", "test.faa"] {
+            let path = dir.path().join(name);
+            fs::write(&path, ">seq\nACGT\n>seq2\nACGT\n").unwrap();
+            assert_eq!(try_parse_fasta(&path, None).unwrap(), 2, "{name}");
+
+            // invalid files have to be caught rather than skipped
+            fs::write(&path, "ACGT\n").unwrap();
+            assert!(
+                matches!(
+                    try_parse_fasta(&path, None),
+                    Err(ValidationError::InvalidFasta(_))
+                ),
+                "{name}"
+            );
+        }
+
+        let path = dir.path().join("test.fna.gz");
+        write_gzipped(&path, ">seq\nACGT\n");
+        assert_eq!(try_parse_fasta(&path, None).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_compression_fallback() {
+        let dir = tempdir().unwrap();
+
+        // gzipped contents under an uncompressed name
+        let path = dir.path().join("genome.fa");
+        write_gzipped(&path, ">seq\nACGTACGTACGTACGT\n");
+        let validated = UnvalidatedFile::Fasta {
+            uri: "https://example.com/genome.fa".to_string(),
+            local_path: path.clone(),
+        }
+        .try_validate()
+        .unwrap();
+        assert_eq!(validated.local_path, dir.path().join("genome.fa.gz"));
+        assert!(!path.exists());
+
+        // uncompressed contents under a gzipped name
+        let path = dir.path().join("genes.gff3.gz");
+        fs::write(&path, format!("##gff-version 3\n{GFF_LINE}")).unwrap();
+        let validated = UnvalidatedFile::Gff {
+            uri: "https://example.com/genes.gff3.gz".to_string(),
+            local_path: path.clone(),
+        }
+        .try_validate()
+        .unwrap();
+        assert_eq!(validated.local_path, dir.path().join("genes.gff3"));
+
+        // files that are invalid either way keep their names
+        let path = dir.path().join("broken.gff3.gz");
+        fs::write(&path, "not a gff file\n").unwrap();
+        let file = UnvalidatedFile::Gff {
+            uri: "https://example.com/broken.gff3.gz".to_string(),
+            local_path: path.clone(),
+        };
+        assert!(matches!(
+            file.try_validate(),
+            Err(ValidationError::CorruptGzip(..))
+        ));
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_is_bgzf() {
+        let dir = tempdir().unwrap();
+        let plain = dir.path().join("genes.gff3.gz");
+        write_gzipped(&plain, "##gff-version 3\n");
+        assert!(!is_bgzf(&plain));
+
+        // a BGZF block is an ordinary gzip member with a `BC` subfield holding the block size
+        let blocked = dir.path().join("genes.bgzf.gff3.gz");
+        let mut encoder = GzBuilder::new()
+            .extra(vec![b'B', b'C', 2, 0, 0, 0])
+            .write(File::create(&blocked).unwrap(), Compression::default());
+        encoder.write_all(b"##gff-version 3\n").unwrap();
+        encoder.finish().unwrap();
+        assert!(is_bgzf(&blocked));
+
+        assert!(is_tabix_indexable(FileFormat::Gff));
+        assert!(!is_tabix_indexable(FileFormat::Fasta));
+    }
+
+    #[test]
+    fn test_bgzf_fasta_and_indices() {
+        let dir = tempdir().unwrap();
+        let (mut fasta, mut index) = (String::new(), String::new());
+        for i in 0..2000 {
+            writeln!(fasta, ">seq{i}\n{}", "ACGT".repeat(25)).unwrap();
+            writeln!(index, "seq{i}\t100\t{}\t100\t101", i * 108 + 7).unwrap();
+        }
+
+        // enough sequence to span several BGZF blocks, all of which must be read
+        let blocked = dir.path().join("genome.fa.gz");
+        let mut writer = bgzf::Writer::new(File::create(&blocked).unwrap());
+        writer.write_all(fasta.as_bytes()).unwrap();
+        writer.finish().unwrap();
+        assert!(is_bgzf(&blocked));
+        assert_eq!(try_parse_fasta(&blocked, None).unwrap(), 2000);
+
+        let fai = dir.path().join("genome.fa.gz.fai");
+        fs::write(&fai, &index).unwrap();
+        assert_eq!(try_parse_fasta(&blocked, None).unwrap(), 2000);
+        assert_eq!(try_parse_fasta(&blocked, Some(10)).unwrap(), 10);
+
+        fs::write(&fai, index.lines().take(5).collect::<Vec<_>>().join("\n")).unwrap();
+        assert!(matches!(
+            try_parse_fasta(&blocked, None),
+            Err(ValidationError::InvalidFastaIndex(..))
+        ));
+        fs::write(&fai, "seq0\tnot a length\n").unwrap();
+        assert!(matches!(
+            try_parse_fasta(&blocked, Some(10)),
+            Err(ValidationError::InvalidFastaIndex(..))
+        ));
+
+        // an index next to a plain gzipped FASTA can't be used with it
+        let plain = dir.path().join("plain.fa.gz");
+        write_gzipped(&plain, &fasta);
+        fs::write(dir.path().join("plain.fa.gz.fai"), &index).unwrap();
+        assert!(matches!(
+            try_parse_fasta(&plain, None),
+            Err(ValidationError::FastaNotBgzf(..))
+        ));
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        const PUBLIC_KEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+        const SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1556193335\tfile:test
+y/rUw2y8/hOUYjZU71eHp/Wo1KZ40fGy2VJEDl34XMJM+TX48Ss/17u3IvIfbVR1FkZZSNCisQbuQY+bHwhEBg==
+";
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("test");
+        let signature = dir.path().join("test.minisig");
+        fs::write(&file, "test").unwrap();
+        fs::write(&signature, SIGNATURE).unwrap();
+        assert!(verify_signature(&file, &signature, PUBLIC_KEY).is_ok());
+
+        fs::write(&file, "tampered").unwrap();
+        assert!(matches!(
+            verify_signature(&file, &signature, PUBLIC_KEY),
+            Err(ValidationError::SignatureInvalid(..))
+        ));
+    }
+}
@@ -0,0 +1,158 @@
+use std::fs;
+
+use log::{debug, warn};
+use reqwest::{Client, header::CONTENT_TYPE};
+use serde::Serialize;
+
+use crate::data::{DownloadStatus, FileFormat, RefDataset};
+
+/// Whether a dataset's download finished with every requested file in hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DownloadOutcome {
+    Completed,
+    Failed,
+}
+
+/// The JSON payload posted to a webhook when a dataset's download completes or fails.
+#[derive(Debug, Serialize)]
+pub(crate) struct DownloadEvent {
+    label: String,
+    outcome: DownloadOutcome,
+    files: Vec<EventFile>,
+    total_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// One of a dataset's files, as reported in a [`DownloadEvent`].
+#[derive(Debug, Serialize)]
+struct EventFile {
+    format: FileFormat,
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    local_path: Option<String>,
+    downloaded: bool,
+}
+
+impl DownloadEvent {
+    /// Describes the state of a dataset after a download run. The outcome is `Failed` if `error`
+    /// is provided or any of the dataset's files failed to download, and `total_bytes` adds up
+    /// the sizes of the dataset's files that are on disk.
+    pub(crate) fn for_dataset(dataset: &RefDataset, error: Option<String>) -> Self {
+        let mut failed = error.is_some();
+        let mut total_bytes = 0;
+        let mut files = Vec::new();
+        for (format, status) in dataset.file_fields() {
+            let Some(status) = status else {
+                continue;
+            };
+            failed |= matches!(status, DownloadStatus::Failed { .. });
+            let local_path = match status {
+                DownloadStatus::Downloaded(file) if status.is_downloaded() => {
+                    total_bytes += fs::metadata(&file.local_path).map_or(0, |meta| meta.len());
+                    Some(file.local_path.display().to_string())
+                },
+                _ => None,
+            };
+            files.push(EventFile {
+                format,
+                url: status.url_owned(),
+                local_path,
+                downloaded: status.is_downloaded(),
+            });
+        }
+
+        Self {
+            label: dataset.label.clone(),
+            outcome: if failed {
+                DownloadOutcome::Failed
+            } else {
+                DownloadOutcome::Completed
+            },
+            files,
+            total_bytes,
+            error,
+        }
+    }
+}
+
+/// Posts a download event to a webhook as JSON, authenticating with a bearer token if one is
+/// provided.
+///
+/// Notifications are a courtesy to whatever is listening, so failing to deliver one never fails
+/// the download; the problem is logged instead.
+pub(crate) async fn notify_webhook(
+    client: &Client,
+    url: &str,
+    token: Option<&str>,
+    event: &DownloadEvent,
+) {
+    let payload = match serde_json::to_string(event) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!(
+                "Unable to serialize the download event for {}: {e}",
+                event.label
+            );
+            return;
+        },
+    };
+
+    let mut request = client
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(payload);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+
+    match request
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+    {
+        Ok(_) => debug!(
+            "Notified {url} that the download of {} finished",
+            event.label
+        ),
+        Err(e) => warn!(
+            "Unable to notify the webhook at {url} about {}: {e}",
+            event.label
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_event_outcome() {
+        let mut dataset = RefDataset {
+            label: "genome".to_string(),
+            fasta: Some(DownloadStatus::new(
+                "https://example.com/genome.fasta".to_string(),
+            )),
+            ..RefDataset::default()
+        };
+        let event = DownloadEvent::for_dataset(&dataset, None);
+        assert_eq!(event.outcome, DownloadOutcome::Completed);
+        assert_eq!(event.files.len(), 1);
+
+        let aborted = DownloadEvent::for_dataset(&dataset, Some("aborted".to_string()));
+        assert_eq!(aborted.outcome, DownloadOutcome::Failed);
+
+        dataset.fasta = Some(DownloadStatus::Failed {
+            url: "https://example.com/genome.fasta".to_string(),
+            last_error: "404 Not Found".to_string(),
+            attempts: 1,
+            last_attempt: jiff::Timestamp::now(),
+        });
+        let event = DownloadEvent::for_dataset(&dataset, None);
+        assert_eq!(event.outcome, DownloadOutcome::Failed);
+        assert!(!event.files[0].downloaded);
+    }
+}